@@ -0,0 +1,5 @@
+-- SPDX-License-Identifier: AGPL-3.0-or-later
+
+ALTER TABLE operations_v1 ADD COLUMN received_at BIGINT NOT NULL DEFAULT 0;
+
+ALTER TABLE document_views ADD COLUMN materialized_at BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+-- SPDX-License-Identifier: AGPL-3.0-or-later
+
+CREATE INDEX IF NOT EXISTS idx_operations_v1_received_at ON operations_v1 (schema_id, received_at);
+
+-- Number operations in the order they were received
+ALTER TABLE operations_v1 ADD COLUMN received_index BIGSERIAL;
+
+CREATE INDEX IF NOT EXISTS idx_operations_v1_received_index ON operations_v1 (schema_id, received_index);
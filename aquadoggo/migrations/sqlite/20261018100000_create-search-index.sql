@@ -0,0 +1,34 @@
+-- SPDX-License-Identifier: AGPL-3.0-or-later
+
+-- Full-text search index over the string fields of the latest view of every document
+CREATE VIRTUAL TABLE IF NOT EXISTS document_search USING fts5(
+    document_id UNINDEXED,
+    schema_id UNINDEXED,
+    content
+);
+
+-- Add all already materialized documents, the content of every document is the concatenation of
+-- all its string field values
+INSERT INTO
+    document_search (document_id, schema_id, content)
+SELECT
+    documents.document_id,
+    documents.schema_id,
+    GROUP_CONCAT(operation_fields_v1.value, ' ')
+FROM
+    documents
+    JOIN document_view_fields
+        ON documents.document_view_id = document_view_fields.document_view_id
+    JOIN operation_fields_v1
+        ON
+            document_view_fields.operation_id = operation_fields_v1.operation_id
+        AND
+            document_view_fields.name = operation_fields_v1.name
+WHERE
+    operation_fields_v1.field_type = 'str'
+    AND documents.is_deleted = false
+    AND documents.document_id NOT IN (
+        SELECT document_search.document_id FROM document_search
+    )
+GROUP BY
+    documents.document_id, documents.schema_id;
@@ -0,0 +1,26 @@
+-- SPDX-License-Identifier: AGPL-3.0-or-later
+
+CREATE INDEX IF NOT EXISTS idx_operations_v1_received_at ON operations_v1 (schema_id, received_at);
+
+-- Number operations in the order they were received, counting from a separate sequence makes sure
+-- numbers of purged operations are never handed out again
+ALTER TABLE operations_v1 ADD COLUMN received_index BIGINT;
+
+CREATE TABLE IF NOT EXISTS received_index_sequence (
+    value                   BIGINT NOT NULL
+);
+
+UPDATE operations_v1 SET received_index = rowid;
+
+INSERT INTO received_index_sequence (value) SELECT COALESCE(MAX(rowid), 0) FROM operations_v1;
+
+CREATE TRIGGER IF NOT EXISTS set_received_index AFTER INSERT ON operations_v1
+WHEN NEW.received_index IS NULL
+BEGIN
+    UPDATE received_index_sequence SET value = value + 1;
+    UPDATE operations_v1
+        SET received_index = (SELECT value FROM received_index_sequence)
+        WHERE rowid = NEW.rowid;
+END;
+
+CREATE INDEX IF NOT EXISTS idx_operations_v1_received_index ON operations_v1 (schema_id, received_index);
@@ -30,12 +30,16 @@ impl NodeInterface {
     }
 
     pub async fn migrate(&self, lock_file: LockFile) -> Result<bool> {
-        let committed_operations = migrate(
+        // Report running migration, so the node does not appear to be ready in the meantime
+        self.context.status.set_migration_running(true);
+        let result = migrate(
             &self.context.store,
             &self.context.schema_provider,
             lock_file,
         )
-        .await?;
+        .await;
+        self.context.status.set_migration_running(false);
+        let committed_operations = result?;
 
         let did_migration_happen = !committed_operations.is_empty();
 
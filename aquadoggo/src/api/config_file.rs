@@ -1,17 +1,22 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::OnceLock;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use http::Uri;
 use libp2p::{pnet::PreSharedKey, PeerId};
+use p2panda_rs::identity::PublicKey;
 use p2panda_rs::schema::SchemaId;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tempfile::TempDir;
 
-use crate::{AllowList, Configuration, NetworkConfiguration, Transport};
+use crate::{
+    AllowList, ApiScope, ApiToken, Configuration, NetworkConfiguration, TlsConfiguration, Transport,
+};
 
 const WILDCARD: &str = "*";
 
@@ -21,12 +26,22 @@ const DEFAULT_MAX_DATABASE_CONNECTIONS: u32 = 32;
 
 const DEFAULT_HTTP_PORT: u16 = 2020;
 
+const DEFAULT_HTTP_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
 const DEFAULT_NODE_PORT: u16 = 2022;
 
 const DEFAULT_WORKER_POOL_SIZE: u32 = 16;
 
+const DEFAULT_QUERY_CACHE_SIZE: usize = 1000;
+
 const DEFAULT_MDNS: bool = true;
 
+const DEFAULT_MAX_REQUEST_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+const DEFAULT_MAX_OPERATION_SIZE: usize = 1024 * 1024;
+
+const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
 static TMP_DIR: OnceLock<TempDir> = OnceLock::new();
 
 fn default_log_level() -> String {
@@ -41,6 +56,10 @@ fn default_http_port() -> u16 {
     DEFAULT_HTTP_PORT
 }
 
+fn default_http_bind_address() -> IpAddr {
+    DEFAULT_HTTP_BIND_ADDRESS
+}
+
 fn default_node_port() -> u16 {
     DEFAULT_NODE_PORT
 }
@@ -64,10 +83,26 @@ fn default_worker_pool_size() -> u32 {
     DEFAULT_WORKER_POOL_SIZE
 }
 
+fn default_query_cache_size() -> usize {
+    DEFAULT_QUERY_CACHE_SIZE
+}
+
 fn default_mdns() -> bool {
     DEFAULT_MDNS
 }
 
+fn default_max_request_body_size() -> usize {
+    DEFAULT_MAX_REQUEST_BODY_SIZE
+}
+
+fn default_max_operation_size() -> usize {
+    DEFAULT_MAX_OPERATION_SIZE
+}
+
+fn default_max_batch_size() -> usize {
+    DEFAULT_MAX_BATCH_SIZE
+}
+
 /// Node configuration which can be de/serialized from a config file.
 ///
 /// See https://github.com/p2panda/aquadoggo/blob/main/aquadoggo_cli/config.toml for example
@@ -116,6 +151,31 @@ pub struct ConfigFile {
     #[serde(default = "default_http_port")]
     pub http_port: u16,
 
+    /// IP address the HTTP server binds to, for example "::" to listen on IPv6 interfaces.
+    /// Defaults to "0.0.0.0".
+    #[serde(default = "default_http_bind_address")]
+    pub http_bind_address: IpAddr,
+
+    /// Fail when the HTTP port is already taken instead of falling back to a random, free port.
+    /// Disabled by default.
+    #[serde(default)]
+    pub http_strict_port: bool,
+
+    /// List of origins which are allowed to make cross-origin requests to the HTTP API, for
+    /// example "https://example.com". Defaults to allow requests from _any_ origin ("*").
+    #[serde(default)]
+    pub http_allow_origins: UncheckedAllowList,
+
+    /// Path to PEM file containing the certificate chain to serve the HTTP API via TLS. Needs to be
+    /// set together with `http_tls_key_path`.
+    #[serde(default)]
+    pub http_tls_certificate_path: Option<PathBuf>,
+
+    /// Path to PEM file containing the private key to serve the HTTP API via TLS. Needs to be set
+    /// together with `http_tls_certificate_path`.
+    #[serde(default)]
+    pub http_tls_key_path: Option<PathBuf>,
+
     /// Protocol (TCP/QUIC) used for node-node communication and data replication. Defaults to QUIC.
     #[serde(default)]
     pub transport: Transport,
@@ -222,6 +282,50 @@ pub struct ConfigFile {
     /// Worker pool size, defaults to 16.
     #[serde(default = "default_worker_pool_size")]
     pub worker_pool_size: u32,
+
+    /// Maximum number of document query results kept in memory, defaults to 1000. Set to 0 to
+    /// disable the query cache.
+    #[serde(default = "default_query_cache_size")]
+    pub query_cache_size: usize,
+
+    /// List of tokens which give clients access to the HTTP and GraphQL API. When empty no
+    /// authentication is required.
+    ///
+    /// Clients authenticate by sending a token in an "Authorization: Bearer <token>" header. Every
+    /// token carries scopes ("read", "publish") and can restrict publishing to certain schema ids
+    /// and public keys.
+    #[serde(default)]
+    pub api_tokens: Vec<UncheckedApiToken>,
+
+    /// Path to a .toml file containing further `api_tokens`, for example to keep secrets out of
+    /// the main configuration file.
+    ///
+    /// The tokens of this file need to be added to `api_tokens` and the path removed before
+    /// converting into a `Configuration`, otherwise the conversion fails.
+    #[serde(default)]
+    pub api_tokens_path: Option<PathBuf>,
+
+    /// Maximum number of entries which can be published per minute by a single client, identified
+    /// by its IP address. Disabled by default (0).
+    #[serde(default)]
+    pub publish_rate_limit_per_ip: u32,
+
+    /// Maximum number of entries which can be published per minute by a single author, identified
+    /// by its public key. Disabled by default (0).
+    #[serde(default)]
+    pub publish_rate_limit_per_public_key: u32,
+
+    /// Maximum size in bytes of request bodies sent to the GraphQL API, defaults to 16 MiB.
+    #[serde(default = "default_max_request_body_size")]
+    pub max_request_body_size: usize,
+
+    /// Maximum size in bytes of encoded operations which can be published, defaults to 1 MiB.
+    #[serde(default = "default_max_operation_size")]
+    pub max_operation_size: usize,
+
+    /// Maximum number of entries which can be published at once in a batch, defaults to 100.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
 }
 
 impl Default for ConfigFile {
@@ -234,6 +338,11 @@ impl Default for ConfigFile {
             database_url: default_database_url(),
             database_max_connections: default_max_database_connections(),
             http_port: default_http_port(),
+            http_bind_address: default_http_bind_address(),
+            http_strict_port: false,
+            http_allow_origins: UncheckedAllowList::default(),
+            http_tls_certificate_path: None,
+            http_tls_key_path: None,
             node_port: default_node_port(),
             blobs_base_path: None,
             mdns: default_mdns(),
@@ -244,6 +353,14 @@ impl Default for ConfigFile {
             relay_addresses: vec![],
             relay_mode: false,
             worker_pool_size: default_worker_pool_size(),
+            query_cache_size: default_query_cache_size(),
+            api_tokens: vec![],
+            api_tokens_path: None,
+            publish_rate_limit_per_ip: 0,
+            publish_rate_limit_per_public_key: 0,
+            max_request_body_size: default_max_request_body_size(),
+            max_operation_size: default_max_operation_size(),
+            max_batch_size: default_max_batch_size(),
         }
     }
 }
@@ -288,6 +405,53 @@ impl TryFrom<ConfigFile> for Configuration {
             }
         };
 
+        // Check if given origins are valid and normalize them to the format browsers use
+        let http_allow_origins = match value.http_allow_origins {
+            UncheckedAllowList::Wildcard => AllowList::<String>::Wildcard,
+            UncheckedAllowList::Set(str_values) => {
+                let origins: Result<Vec<String>, anyhow::Error> = str_values
+                    .iter()
+                    .map(|str_value| {
+                        parse_origin(str_value).ok_or_else(|| {
+                            anyhow!(
+                                "Invalid origin '{str_value}' found in 'http_allow_origins' list"
+                            )
+                        })
+                    })
+                    .collect();
+
+                AllowList::Set(origins?)
+            }
+        };
+
+        // Check if both certificate and private key are given when TLS should be enabled
+        let http_tls = match (value.http_tls_certificate_path, value.http_tls_key_path) {
+            (Some(certificate_path), Some(private_key_path)) => Some(TlsConfiguration {
+                certificate_path,
+                private_key_path,
+            }),
+            (None, None) => None,
+            _ => bail!(
+                "Both 'http_tls_certificate_path' and 'http_tls_key_path' need to be set to enable TLS"
+            ),
+        };
+
+        // Make sure we never start without the tokens of a separate file, this would give anyone
+        // access to the API
+        if let Some(path) = value.api_tokens_path {
+            bail!(
+                "API tokens file '{}' was not loaded into 'api_tokens'",
+                path.display()
+            );
+        }
+
+        // Check if given api tokens are valid
+        let api_tokens = value
+            .api_tokens
+            .into_iter()
+            .map(ApiToken::try_from)
+            .collect::<Result<Vec<ApiToken>>>()?;
+
         // Create a temporary blobs directory when none was given
         let blobs_base_path = match value.blobs_base_path {
             Some(path) => path,
@@ -321,8 +485,19 @@ impl TryFrom<ConfigFile> for Configuration {
             database_url: value.database_url,
             database_max_connections: value.database_max_connections,
             http_port: value.http_port,
+            http_bind_address: value.http_bind_address,
+            http_strict_port: value.http_strict_port,
+            http_allow_origins,
+            http_tls,
             blobs_base_path,
             worker_pool_size: value.worker_pool_size,
+            query_cache_size: value.query_cache_size,
+            api_tokens,
+            publish_rate_limit_per_ip: value.publish_rate_limit_per_ip,
+            publish_rate_limit_per_public_key: value.publish_rate_limit_per_public_key,
+            max_request_body_size: value.max_request_body_size,
+            max_operation_size: value.max_operation_size,
+            max_batch_size: value.max_batch_size,
             network: NetworkConfiguration {
                 transport: value.transport,
                 psk,
@@ -339,6 +514,90 @@ impl TryFrom<ConfigFile> for Configuration {
     }
 }
 
+/// Parses an origin like "https://example.com:8080", returns `None` if it contains a path or is
+/// missing the scheme or host.
+fn parse_origin(value: &str) -> Option<String> {
+    let uri: Uri = value.parse().ok()?;
+
+    let scheme = uri.scheme_str()?;
+    let authority = uri.authority()?;
+    if !matches!(uri.path(), "" | "/") || uri.query().is_some() {
+        return None;
+    }
+
+    Some(format!("{scheme}://{authority}"))
+}
+
+/// Token giving a client access to the HTTP and GraphQL API, as found in the config file.
+///
+/// Schema ids and public keys are not checked yet and need to be validated in a succeeding step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UncheckedApiToken {
+    /// Secret value clients send as a bearer token.
+    pub token: String,
+
+    /// Actions which can be performed with this token.
+    pub scopes: Vec<ApiScope>,
+
+    /// Schema ids of operations which can be published with this token. Defaults to any schema.
+    #[serde(default)]
+    pub publish_schema_ids: UncheckedAllowList,
+
+    /// Public keys of authors whose entries can be published with this token. Defaults to any
+    /// author.
+    #[serde(default)]
+    pub publish_public_keys: UncheckedAllowList,
+}
+
+impl TryFrom<UncheckedApiToken> for ApiToken {
+    type Error = anyhow::Error;
+
+    fn try_from(value: UncheckedApiToken) -> Result<Self, Self::Error> {
+        if value.token.is_empty() {
+            return Err(anyhow!("Empty token found in 'api_tokens' list"));
+        }
+
+        let publish_schema_ids = match value.publish_schema_ids {
+            UncheckedAllowList::Wildcard => AllowList::<SchemaId>::Wildcard,
+            UncheckedAllowList::Set(str_values) => {
+                let schema_ids: Result<Vec<SchemaId>, anyhow::Error> = str_values
+                    .iter()
+                    .map(|str_value| {
+                        SchemaId::from_str(str_value).map_err(|_| {
+                            anyhow!("Invalid schema id '{str_value}' found in 'publish_schema_ids' list")
+                        })
+                    })
+                    .collect();
+
+                AllowList::Set(schema_ids?)
+            }
+        };
+
+        let publish_public_keys = match value.publish_public_keys {
+            UncheckedAllowList::Wildcard => AllowList::<PublicKey>::Wildcard,
+            UncheckedAllowList::Set(str_values) => {
+                let public_keys: Result<Vec<PublicKey>, anyhow::Error> = str_values
+                    .iter()
+                    .map(|str_value| {
+                        PublicKey::new(str_value).map_err(|_| {
+                            anyhow!("Invalid public key '{str_value}' found in 'publish_public_keys' list")
+                        })
+                    })
+                    .collect();
+
+                AllowList::Set(public_keys?)
+            }
+        };
+
+        Ok(ApiToken {
+            token: value.token,
+            scopes: value.scopes,
+            publish_schema_ids,
+            publish_public_keys,
+        })
+    }
+}
+
 /// Helper struct to deserialize from either a wildcard string "*" or a list of string values.
 ///
 /// These string values are not checked yet and need to be validated in a succeeding step.
@@ -387,3 +646,25 @@ impl<'de> Deserialize<'de> for UncheckedAllowList {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::path::PathBuf;
+
+    use crate::Configuration;
+
+    use super::ConfigFile;
+
+    #[test]
+    fn unloaded_api_tokens_path() {
+        let config_file = ConfigFile {
+            api_tokens_path: Some(PathBuf::from("tokens.toml")),
+            ..ConfigFile::default()
+        };
+        assert!(Configuration::try_from(config_file).is_err());
+
+        let config_file = ConfigFile::default();
+        assert!(Configuration::try_from(config_file).is_ok());
+    }
+}
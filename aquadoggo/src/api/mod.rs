@@ -7,6 +7,6 @@ mod lock_file;
 mod migration;
 
 pub use api::{NodeEvent, NodeInterface};
-pub use config_file::ConfigFile;
+pub use config_file::{ConfigFile, UncheckedApiToken};
 pub use lock_file::LockFile;
 pub use migration::migrate;
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Authentication of clients accessing the HTTP and GraphQL API with bearer tokens.
+//!
+//! Tokens are defined in the node configuration. When none are configured, authentication is
+//! disabled and every client has full access.
+use p2panda_rs::identity::PublicKey;
+use p2panda_rs::schema::SchemaId;
+use thiserror::Error;
+
+use crate::config::{ApiScope, ApiToken};
+
+/// Authentication scheme expected in "Authorization" headers.
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Access level of a client.
+#[derive(Debug, Clone)]
+pub enum Access {
+    /// Authentication is disabled, the client can perform any action.
+    Unrestricted,
+
+    /// Client authenticated with a configured token.
+    Token(Box<ApiToken>),
+
+    /// Authentication is enabled but the client did not send a token.
+    Anonymous,
+}
+
+impl Access {
+    /// Returns true if the client was granted the given scope.
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        match self {
+            Access::Unrestricted => true,
+            Access::Token(token) => token.has_scope(scope),
+            Access::Anonymous => false,
+        }
+    }
+
+    /// Returns an error if the client was not granted the given scope.
+    pub fn require(&self, scope: ApiScope) -> Result<(), AuthError> {
+        match self {
+            Access::Anonymous => Err(AuthError::MissingToken),
+            _ if self.has_scope(scope) => Ok(()),
+            _ => Err(AuthError::Forbidden),
+        }
+    }
+
+    /// Returns an error if the client is not allowed to publish an entry of the given author
+    /// containing an operation of the given schema.
+    pub fn check_publish(
+        &self,
+        schema_id: &SchemaId,
+        public_key: &PublicKey,
+    ) -> Result<(), AuthError> {
+        self.require(ApiScope::Publish)?;
+
+        match self {
+            Access::Token(token) if !token.can_publish(schema_id, public_key) => Err(
+                AuthError::PublishForbidden(schema_id.to_string(), public_key.to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Determines the access level of a client based on the value of its "Authorization" header.
+pub fn authenticate(
+    api_tokens: &[ApiToken],
+    authorization: Option<&str>,
+) -> Result<Access, AuthError> {
+    if api_tokens.is_empty() {
+        return Ok(Access::Unrestricted);
+    }
+
+    let authorization = match authorization {
+        Some(value) => value,
+        None => return Ok(Access::Anonymous),
+    };
+
+    let token = authorization
+        .strip_prefix(BEARER_PREFIX)
+        .ok_or(AuthError::InvalidToken)?
+        .trim();
+
+    api_tokens
+        .iter()
+        .find(|api_token| constant_time_eq(api_token.token.as_bytes(), token.as_bytes()))
+        .map(|api_token| Access::Token(Box::new(api_token.clone())))
+        .ok_or(AuthError::InvalidToken)
+}
+
+/// Compares two byte strings in constant time to not leak information about valid tokens through
+/// response timings.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// Authentication is enabled but no token was given.
+    #[error("Missing bearer token")]
+    MissingToken,
+
+    /// Given token is malformed or unknown.
+    #[error("Invalid bearer token")]
+    InvalidToken,
+
+    /// Token is valid but was not granted the required scope.
+    #[error("Token is not allowed to perform this action")]
+    Forbidden,
+
+    /// Token is not allowed to publish this entry.
+    #[error("Token is not allowed to publish operations of schema {0} authored by {1}")]
+    PublishForbidden(String, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::schema::SchemaId;
+    use rstest::rstest;
+
+    use crate::config::{AllowList, ApiScope, ApiToken};
+
+    use super::{authenticate, Access, AuthError};
+
+    fn api_token(token: &str, scopes: Vec<ApiScope>) -> ApiToken {
+        ApiToken {
+            token: token.into(),
+            scopes,
+            publish_schema_ids: AllowList::Wildcard,
+            publish_public_keys: AllowList::Wildcard,
+        }
+    }
+
+    #[rstest]
+    #[case(None, Ok(None))]
+    #[case(Some("Bearer reader"), Ok(Some("reader")))]
+    #[case(Some("Bearer  publisher "), Ok(Some("publisher")))]
+    #[case(Some("Bearer unknown"), Err(AuthError::InvalidToken))]
+    #[case(Some("Bearer reade"), Err(AuthError::InvalidToken))]
+    #[case(Some("Basic reader"), Err(AuthError::InvalidToken))]
+    #[case(Some("reader"), Err(AuthError::InvalidToken))]
+    fn authenticate_with_tokens(
+        #[case] authorization: Option<&str>,
+        #[case] expected: Result<Option<&str>, AuthError>,
+    ) {
+        let api_tokens = vec![
+            api_token("reader", vec![ApiScope::Read]),
+            api_token("publisher", vec![ApiScope::Publish]),
+        ];
+
+        let result = authenticate(&api_tokens, authorization).map(|access| match access {
+            Access::Token(token) => Some(token.token),
+            Access::Anonymous => None,
+            Access::Unrestricted => panic!("Authentication should be enabled"),
+        });
+
+        assert_eq!(result, expected.map(|token| token.map(String::from)));
+    }
+
+    #[test]
+    fn unrestricted_without_tokens() {
+        let access = authenticate(&[], Some("Bearer anything")).unwrap();
+        assert!(matches!(access, Access::Unrestricted));
+        assert!(access.require(ApiScope::Read).is_ok());
+        assert!(access.require(ApiScope::Publish).is_ok());
+    }
+
+    #[test]
+    fn scopes_and_publish_restrictions() {
+        let allowed_key_pair = KeyPair::new();
+        let other_key_pair = KeyPair::new();
+
+        let access = Access::Token(Box::new(ApiToken {
+            token: "publisher".into(),
+            scopes: vec![ApiScope::Publish],
+            publish_schema_ids: AllowList::Set(vec![SchemaId::Blob(1)]),
+            publish_public_keys: AllowList::Set(vec![allowed_key_pair.public_key()]),
+        }));
+
+        assert_eq!(access.require(ApiScope::Read), Err(AuthError::Forbidden));
+        assert!(access.require(ApiScope::Publish).is_ok());
+
+        assert!(access
+            .check_publish(&SchemaId::Blob(1), &allowed_key_pair.public_key())
+            .is_ok());
+        assert!(access
+            .check_publish(&SchemaId::BlobPiece(1), &allowed_key_pair.public_key())
+            .is_err());
+        assert!(access
+            .check_publish(&SchemaId::Blob(1), &other_key_pair.public_key())
+            .is_err());
+
+        let reader = Access::Token(Box::new(api_token("reader", vec![ApiScope::Read])));
+        assert_eq!(
+            reader.check_publish(&SchemaId::Blob(1), &allowed_key_pair.public_key()),
+            Err(AuthError::Forbidden)
+        );
+
+        assert_eq!(
+            Access::Anonymous.require(ApiScope::Read),
+            Err(AuthError::MissingToken)
+        );
+    }
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use p2panda_rs::document::DocumentId;
 use p2panda_rs::operation::OperationId;
+use p2panda_rs::schema::SchemaId;
 
 use crate::manager::Sender;
 use crate::network::{Peer, PeerMessage};
@@ -14,6 +16,9 @@ pub enum ServiceMessage {
     /// A new operation arrived at the node.
     NewOperation(OperationId),
 
+    /// The latest view of a document of the given schema was materialized by the node.
+    DocumentMaterialized(SchemaId, DocumentId),
+
     /// Node established a bi-directional connection to another node.
     PeerConnected(Peer),
 
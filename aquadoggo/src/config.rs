@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 
+use p2panda_rs::identity::PublicKey;
 use p2panda_rs::schema::SchemaId;
+use serde::{Deserialize, Serialize};
 
 use crate::network::NetworkConfiguration;
 
@@ -38,6 +41,22 @@ pub struct Configuration {
     /// 2020.
     pub http_port: u16,
 
+    /// IP address the HTTP server binds to. Defaults to 0.0.0.0 to listen on all IPv4 interfaces.
+    ///
+    /// Use an IPv6 address like :: to listen on IPv6 interfaces.
+    pub http_bind_address: IpAddr,
+
+    /// Fail when the HTTP port is already taken instead of falling back to a random, free port.
+    pub http_strict_port: bool,
+
+    /// Origins which are allowed to make cross-origin requests to the HTTP API, for example
+    /// "https://example.com". Defaults to allow requests from _any_ origin.
+    pub http_allow_origins: AllowList<String>,
+
+    /// Certificate and private key to serve the HTTP API via TLS. When not set the API is served
+    /// without encryption.
+    pub http_tls: Option<TlsConfiguration>,
+
     /// Path to folder where blobs (binary files) are kept and served from.
     ///
     /// **Warning**: When set to a temporary directory, make sure that also the database itself is
@@ -51,6 +70,38 @@ pub struct Configuration {
     /// number for low-energy devices with limited resources.
     pub worker_pool_size: u32,
 
+    /// Maximum number of document query results which are kept in memory. Results get removed
+    /// from the cache as soon as documents of their schemas change.
+    ///
+    /// Set to 0 to disable the query cache.
+    pub query_cache_size: usize,
+
+    /// List of tokens which give clients access to the HTTP and GraphQL API.
+    ///
+    /// Clients authenticate by sending a token in an "Authorization: Bearer <token>" header. When
+    /// the list is empty no authentication is required and anyone who can reach the HTTP port can
+    /// query and publish data.
+    pub api_tokens: Vec<ApiToken>,
+
+    /// Maximum number of entries which can be published per minute by a single client, identified
+    /// by its IP address. Set to 0 to disable this limit.
+    ///
+    /// When the node runs behind a reverse proxy all clients share the IP address of the proxy.
+    pub publish_rate_limit_per_ip: u32,
+
+    /// Maximum number of entries which can be published per minute by a single author, identified
+    /// by its public key. Set to 0 to disable this limit.
+    pub publish_rate_limit_per_public_key: u32,
+
+    /// Maximum size in bytes of request bodies sent to the GraphQL API.
+    pub max_request_body_size: usize,
+
+    /// Maximum size in bytes of encoded operations which can be published.
+    pub max_operation_size: usize,
+
+    /// Maximum number of entries which can be published at once in a batch.
+    pub max_batch_size: usize,
+
     /// Network configuration.
     pub network: NetworkConfiguration,
 }
@@ -62,13 +113,34 @@ impl Default for Configuration {
             database_url: "sqlite::memory:".into(),
             database_max_connections: 32,
             http_port: 2020,
+            http_bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            http_strict_port: false,
+            http_allow_origins: AllowList::Wildcard,
+            http_tls: None,
             blobs_base_path: PathBuf::new(),
             worker_pool_size: 16,
+            query_cache_size: 1000,
+            api_tokens: Vec::new(),
+            publish_rate_limit_per_ip: 0,
+            publish_rate_limit_per_public_key: 0,
+            max_request_body_size: 16 * 1024 * 1024,
+            max_operation_size: 1024 * 1024,
+            max_batch_size: 100,
             network: NetworkConfiguration::default(),
         }
     }
 }
 
+/// Paths to the files required to serve the HTTP API via TLS.
+#[derive(Debug, Clone)]
+pub struct TlsConfiguration {
+    /// Path to PEM file containing the certificate chain.
+    pub certificate_path: PathBuf,
+
+    /// Path to PEM file containing the private key (PKCS#8, PKCS#1 or SEC1).
+    pub private_key_path: PathBuf,
+}
+
 /// Set a configuration value to either allow a defined set of elements or to a wildcard (*).
 #[derive(Debug, Clone)]
 pub enum AllowList<T> {
@@ -84,3 +156,55 @@ impl<T> Default for AllowList<T> {
         Self::Wildcard
     }
 }
+
+impl<T: PartialEq> AllowList<T> {
+    /// Returns true if the given item is allowed.
+    pub fn contains(&self, item: &T) -> bool {
+        match self {
+            AllowList::Wildcard => true,
+            AllowList::Set(items) => items.contains(item),
+        }
+    }
+}
+
+/// Token giving a client access to the HTTP and GraphQL API of the node.
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    /// Secret value clients send as a bearer token.
+    pub token: String,
+
+    /// Actions which can be performed with this token.
+    pub scopes: Vec<ApiScope>,
+
+    /// Schema ids of operations which can be published with this token.
+    pub publish_schema_ids: AllowList<SchemaId>,
+
+    /// Public keys of authors whose entries can be published with this token.
+    pub publish_public_keys: AllowList<PublicKey>,
+}
+
+impl ApiToken {
+    /// Returns true if this token was granted the given scope.
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Returns true if this token allows publishing an entry of the given author containing an
+    /// operation of the given schema.
+    pub fn can_publish(&self, schema_id: &SchemaId, public_key: &PublicKey) -> bool {
+        self.has_scope(ApiScope::Publish)
+            && self.publish_schema_ids.contains(schema_id)
+            && self.publish_public_keys.contains(public_key)
+    }
+}
+
+/// Action which can be performed on the API with a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Query documents, entries and node status, subscribe to changes and download blobs.
+    Read,
+
+    /// Publish entries and request the arguments required to create them.
+    Publish,
+}
@@ -8,7 +8,10 @@ use p2panda_rs::storage_provider::traits::{DocumentStore, EntryStore, LogStore,
 
 use crate::config::Configuration;
 use crate::db::SqlStore;
+use crate::limits::Limits;
+use crate::metrics::Metrics;
 use crate::schema::SchemaProvider;
+use crate::status::NodeStatus;
 
 /// Inner data shared across all services.
 #[derive(Debug)]
@@ -27,6 +30,15 @@ where
 
     /// Schema provider gives access to system and application schemas.
     pub schema_provider: SchemaProvider,
+
+    /// Status of the running node, reported by the services.
+    pub status: NodeStatus,
+
+    /// Metrics of the running node, reported by the services.
+    pub metrics: Metrics,
+
+    /// Rate and size limits for requests of API clients.
+    pub limits: Limits,
 }
 
 impl<S> Data<S>
@@ -39,11 +51,18 @@ where
         config: Configuration,
         schema_provider: SchemaProvider,
     ) -> Self {
+        let status = NodeStatus::new(&key_pair.public_key());
+        let metrics = Metrics::default();
+        let limits = Limits::new(&config, metrics.clone());
+
         Self {
             key_pair,
             config,
             store,
             schema_provider,
+            status,
+            metrics,
+            limits,
         }
     }
 }
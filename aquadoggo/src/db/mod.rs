@@ -4,10 +4,15 @@
 //!
 //! The main interface is [`SqlStore`] which offers an interface onto the database by implementing
 //! the storage traits defined in `p2panda-rs` as well as some implementation specific features.
+use std::borrow::Cow;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use anyhow::{Error, Result};
-use sqlx::any::{Any, AnyPool, AnyPoolOptions};
+use sqlx::any::{Any, AnyKind, AnyPool, AnyPoolOptions};
 use sqlx::migrate;
-use sqlx::migrate::MigrateDatabase;
+use sqlx::migrate::{MigrateDatabase, Migration, Migrator};
+
+use crate::db::stores::{QueryCache, QueryCacheMetrics};
 
 pub mod errors;
 pub mod models;
@@ -19,15 +24,38 @@ pub mod types;
 #[derive(Clone, Debug)]
 pub struct SqlStore {
     pub(crate) pool: Pool,
+
+    /// In-memory cache for results of document queries, shared between all clones of this store.
+    pub(crate) query_cache: QueryCache,
 }
 
 impl SqlStore {
-    /// Create a new `SqlStore` using the provided db `Pool`.
-    pub fn new(pool: Pool) -> Self {
-        Self { pool }
+    /// Create a new `SqlStore` using the provided db `Pool`, caching up to `query_cache_size`
+    /// query results in memory.
+    pub fn new(pool: Pool, query_cache_size: usize) -> Self {
+        Self {
+            pool,
+            query_cache: QueryCache::new(query_cache_size),
+        }
+    }
+
+    /// Returns usage counters of the query cache.
+    pub fn query_cache_metrics(&self) -> QueryCacheMetrics {
+        self.query_cache.metrics()
     }
 }
 
+/// Current time of the node in seconds since UNIX epoch.
+///
+/// Operations carry no timestamps, this is used to record when the node stored an operation or
+/// materialized a document view.
+pub(crate) fn local_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time invalid, operation system time configured before UNIX epoch")
+        .as_secs() as i64
+}
+
 /// Re-export of generic connection pool type.
 pub type Pool = AnyPool;
 
@@ -51,7 +79,31 @@ pub async fn connection_pool(url: &str, max_connections: u32) -> Result<Pool, Er
 }
 
 /// Run any pending database migrations from inside the application.
+///
+/// Most migrations are shared between all databases and live in the `migrations` folder. Features
+/// like the full-text search index need different SQL for SQLite and PostgreSQL, their migrations
+/// live in the `migrations/sqlite` and `migrations/postgres` folders and get applied in the order
+/// of their versions together with the shared ones.
 pub async fn run_pending_migrations(pool: &Pool) -> Result<()> {
-    migrate!().run(pool).await?;
+    let shared_migrator = migrate!();
+    let database_migrator = match pool.any_kind() {
+        AnyKind::Postgres => migrate!("migrations/postgres"),
+        _ => migrate!("migrations/sqlite"),
+    };
+
+    let mut migrations: Vec<Migration> = shared_migrator
+        .iter()
+        .chain(database_migrator.iter())
+        .cloned()
+        .collect();
+    migrations.sort_by_key(|migration| migration.version);
+
+    Migrator {
+        migrations: Cow::Owned(migrations),
+        ..shared_migrator
+    }
+    .run(pool)
+    .await?;
+
     Ok(())
 }
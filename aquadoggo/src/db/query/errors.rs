@@ -29,6 +29,10 @@ pub enum QueryError {
     #[error("Can't apply interval filter as field '{0}' is not of type string, float or integer")]
     FilterInvalidInterval(String),
 
+    /// Filters on related documents can only be applied on relation and pinned relation fields.
+    #[error("Can't filter related documents as field '{0}' is not a relation or pinned relation")]
+    FilterInvalidRelation(String),
+
     /// Search filters can only be applied on strings.
     #[error("Can't apply search filter as field '{0}' is not of type string")]
     FilterInvalidSearch(String),
@@ -22,6 +22,15 @@ pub enum MetaField {
 
     /// Flag indicating if document was deleted.
     Deleted,
+
+    /// Relevance of the document for the given full-text search query.
+    Relevance,
+
+    /// Local time when the node received the operation which created the document.
+    ReceivedAt,
+
+    /// Local time when the node materialized the latest version of the document.
+    UpdatedAt,
 }
 
 impl TryFrom<&str> for MetaField {
@@ -34,6 +43,9 @@ impl TryFrom<&str> for MetaField {
             "owner" => Ok(MetaField::Owner),
             "edited" => Ok(MetaField::Edited),
             "deleted" => Ok(MetaField::Deleted),
+            "relevance" => Ok(MetaField::Relevance),
+            "receivedAt" => Ok(MetaField::ReceivedAt),
+            "updatedAt" => Ok(MetaField::UpdatedAt),
             _ => bail!("Unknown meta field"),
         }
     }
@@ -47,6 +59,9 @@ impl std::fmt::Display for MetaField {
             MetaField::Owner => "owner",
             MetaField::Edited => "edited",
             MetaField::Deleted => "deleted",
+            MetaField::Relevance => "relevance",
+            MetaField::ReceivedAt => "receivedAt",
+            MetaField::UpdatedAt => "updatedAt",
         })
     }
 }
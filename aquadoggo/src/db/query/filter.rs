@@ -5,6 +5,7 @@ use std::convert::TryInto;
 use std::slice::Iter;
 
 use p2panda_rs::operation::OperationValue;
+use p2panda_rs::schema::Schema;
 
 #[cfg(test)]
 use crate::db::query::test_utils::parse_str;
@@ -92,34 +93,72 @@ impl FilterSetting {
     }
 }
 
+/// Group of nested filters combined with a boolean operator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterGroup {
+    /// Match when all of the filters match.
+    And(Vec<Filter>),
+
+    /// Match when at least one of the filters matches.
+    Or(Vec<Filter>),
+
+    /// Match when the filter does not match.
+    Not(Box<Filter>),
+
+    /// Match when the document the (pinned) relation field with the given name points at matches
+    /// the filter. The filter is applied on fields of the related document's schema.
+    Related(String, Box<Schema>, Box<Filter>),
+}
+
 /// Collection of filter settings which can be used further to construct a database query.
 ///
+/// All filter settings and groups of a filter need to match. Groups can contain further nested
+/// filters to express alternatives or negations.
+///
 /// Internally this struct merges or extends added filter settings as some of them can be optimized
 /// in simple ways.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Filter(Vec<FilterSetting>);
+pub struct Filter {
+    settings: Vec<FilterSetting>,
+    groups: Vec<FilterGroup>,
+}
 
 impl Filter {
     /// Returns a new `Filter` instance.
     pub fn new() -> Self {
-        Self(Vec::new())
+        Self {
+            settings: Vec::new(),
+            groups: Vec::new(),
+        }
     }
 
     /// Returns the total number of filter settings.
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.settings.len()
     }
 
     /// Returns a filter setting from a given index.
     #[allow(dead_code)]
     pub fn get(&self, index: usize) -> Option<&FilterSetting> {
-        self.0.get(index)
+        self.settings.get(index)
     }
 
     /// Returns an iterator over all filter settings.
+    ///
+    /// Filter settings of nested groups are not included.
     pub fn iter(&self) -> Iter<FilterSetting> {
-        self.0.iter()
+        self.settings.iter()
+    }
+
+    /// Returns an iterator over all nested filter groups.
+    pub fn groups(&self) -> Iter<'_, FilterGroup> {
+        self.groups.iter()
+    }
+
+    /// Add a group of nested filters.
+    pub fn add_group(&mut self, group: FilterGroup) {
+        self.groups.push(group);
     }
 
     /// Helper method to merge or extend existing filterings.
@@ -132,17 +171,20 @@ impl Filter {
     fn upsert_filter_item(&mut self, new_item: FilterSetting) {
         // Check if a field exists we potentially can extend. For this the field needs to:
         // - Have the same field name
-        let index = self.0.iter().position(|item| item.field == new_item.field);
+        let index = self
+            .settings
+            .iter()
+            .position(|item| item.field == new_item.field);
 
         // We haven't found anything matching, just add it to the array
         if index.is_none() {
-            self.0.push(new_item);
+            self.settings.push(new_item);
             return;
         }
 
         // Get a mutable reference to the current field, unwrap since we know that both the index
         // and the element exists at this point
-        let current_item = self.0.get_mut(index.unwrap()).unwrap();
+        let current_item = self.settings.get_mut(index.unwrap()).unwrap();
 
         // Boolean values for the same field we can always easily overwrite
         if let FilterBy::Element(OperationValue::Boolean(_)) = current_item.by {
@@ -153,7 +195,7 @@ impl Filter {
         // We don't merge other fields with different exclusivity, in this case just add it and
         // return early
         if current_item.exclusive != new_item.exclusive {
-            self.0.push(new_item);
+            self.settings.push(new_item);
             return;
         }
 
@@ -207,7 +249,7 @@ impl Filter {
                 current_item.by = filter;
             }
             None => {
-                self.0.push(new_item);
+                self.settings.push(new_item);
             }
         }
     }
@@ -221,6 +263,19 @@ impl Filter {
         ));
     }
 
+    /// Add an equality (eq) filter setting matching a value without merging it with other filter
+    /// settings of the same field.
+    ///
+    /// This is useful to further restrict an existing filter, as all other filter settings still
+    /// need to match as well.
+    pub fn restrict(&mut self, field: &Field, value: &OperationValue) {
+        self.settings.push(FilterSetting::new(
+            field,
+            FilterBy::Element(value.to_owned()),
+            false,
+        ));
+    }
+
     /// Add a negated equality (not eq) filter setting not matching a value.
     pub fn add_not(&mut self, field: &Field, value: &OperationValue) {
         self.upsert_filter_item(FilterSetting::new(
@@ -318,6 +373,35 @@ impl Filter {
         ));
     }
 
+    /// Add a filter (contains any) to match all relation lists which contain at least one of the
+    /// given values.
+    ///
+    /// This filter is not merged with other filter settings of the same field, as all of them need
+    /// to match.
+    pub fn add_list_contains_any(&mut self, field: &Field, values: &[OperationValue]) {
+        let by = if values.len() == 1 {
+            FilterBy::Element(values[0].to_owned())
+        } else {
+            FilterBy::Set(values.to_owned())
+        };
+
+        self.settings.push(FilterSetting::new(field, by, false));
+    }
+
+    /// Add a filter (contains all) to match all relation lists which contain every one of the
+    /// given values.
+    pub fn add_list_contains_all(&mut self, field: &Field, values: &[OperationValue]) {
+        // Every value becomes a separate filter setting, each of them checks if the list contains
+        // that value
+        for value in values {
+            self.settings.push(FilterSetting::new(
+                field,
+                FilterBy::Element(value.to_owned()),
+                false,
+            ));
+        }
+    }
+
     /// Add a negated filter (not contains) to match all items which do not contain the given
     /// search string.
     pub fn add_not_contains(&mut self, field: &Field, value: &str) {
@@ -434,6 +518,23 @@ mod tests {
         assert_eq!(filter.get(0).unwrap().by, FilterBy::Element(panda));
     }
 
+    #[test]
+    fn restrict_element_filters() {
+        let mut filter = Filter::new();
+        let field: Field = "animal".into();
+
+        let panda: OperationValue = "panda".into();
+        let turtle: OperationValue = "turtle".into();
+
+        // Restricting the filter does not merge the elements into a set
+        filter.add(&field, &panda);
+        filter.restrict(&field, &turtle);
+
+        assert_eq!(filter.len(), 2);
+        assert_eq!(filter.get(0).unwrap().by, FilterBy::Element(panda));
+        assert_eq!(filter.get(1).unwrap().by, FilterBy::Element(turtle));
+    }
+
     #[test]
     fn merge_element_filters() {
         let mut filter = Filter::new();
@@ -11,7 +11,7 @@ mod test_utils;
 mod validate;
 
 pub use field::{Field, MetaField};
-pub use filter::{Filter, FilterBy, FilterSetting, LowerBound, UpperBound};
+pub use filter::{Filter, FilterBy, FilterGroup, FilterSetting, LowerBound, UpperBound};
 pub use order::{Direction, Order};
 pub use pagination::{Cursor, Pagination, PaginationField};
 pub use select::{ApplicationFields, Select};
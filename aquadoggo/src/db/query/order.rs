@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::slice::Iter;
+
 use crate::db::query::Field;
 
 /// Options to determine the direction of the ordering.
@@ -12,31 +14,73 @@ pub enum Direction {
     Descending,
 }
 
+/// Field to order by together with the direction in which its values are arranged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderClause {
+    pub field: Field,
+    pub direction: Direction,
+}
+
 /// Ordering settings which can be used further to construct a database query.
 ///
-/// An ordering determines in which direction and based on what field the results are sorted.
-#[derive(Debug, Clone, PartialEq)]
+/// An ordering determines in which direction and based on what fields the results are sorted.
+/// When multiple fields are given, the following ones are only considered when all previous field
+/// values are equal.
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Order {
-    pub field: Option<Field>,
-    pub direction: Direction,
+    pub clauses: Vec<OrderClause>,
 }
 
 impl Order {
     /// Returns a new instance of ordering settings.
     #[allow(dead_code)]
     pub fn new(field: &Field, direction: &Direction) -> Self {
-        Self {
-            field: Some(field.clone()),
+        Self::default().then(field, direction)
+    }
+
+    /// Adds another field to order by when the values of all previous fields are equal.
+    pub fn then(mut self, field: &Field, direction: &Direction) -> Self {
+        self.clauses.push(OrderClause {
+            field: field.clone(),
             direction: direction.clone(),
-        }
+        });
+
+        self
+    }
+
+    /// Returns true if no ordering was chosen.
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Returns an iterator over all fields to order by.
+    pub fn iter(&self) -> Iter<'_, OrderClause> {
+        self.clauses.iter()
+    }
+
+    /// Returns true if the results are ordered by the given field.
+    pub fn contains(&self, field: &Field) -> bool {
+        self.iter().any(|clause| &clause.field == field)
     }
 }
 
-impl Default for Order {
-    fn default() -> Self {
-        Self {
-            field: None,
-            direction: Direction::Ascending,
-        }
+#[cfg(test)]
+mod tests {
+    use crate::db::query::{Field, MetaField};
+
+    use super::{Direction, Order};
+
+    #[test]
+    fn create_order() {
+        let order = Order::new(&"priority".into(), &Direction::Descending)
+            .then(&"title".into(), &Direction::Ascending);
+
+        assert_eq!(order.clauses.len(), 2);
+        assert_eq!(order.clauses[0].field, Field::new("priority"));
+        assert_eq!(order.clauses[0].direction, Direction::Descending);
+        assert_eq!(order.clauses[1].field, Field::new("title"));
+        assert!(order.contains(&"title".into()));
+        assert!(!order.contains(&Field::Meta(MetaField::DocumentId)));
+        assert!(Order::default().is_empty());
     }
 }
@@ -65,6 +65,10 @@ impl From<&str> for PaginationField {
 ///
 /// This object represents all required values to allow cursor-based pagination, while the cursor
 /// can be externally defined.
+///
+/// Pagination goes forwards using `first` and `after` by default. As soon as `last` is set the
+/// results are paginated backwards instead, starting from the item identified by the `before`
+/// cursor or from the end of the collection if no cursor was given.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Pagination<C>
 where
@@ -72,6 +76,8 @@ where
 {
     pub first: NonZeroU64,
     pub after: Option<C>,
+    pub last: Option<NonZeroU64>,
+    pub before: Option<C>,
     pub fields: Vec<PaginationField>,
 }
 
@@ -86,6 +92,42 @@ where
             first: *first,
             after: after.cloned(),
             fields: fields.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a new instance of pagination settings, paginating backwards.
+    #[allow(dead_code)]
+    pub fn new_backward(
+        last: &NonZeroU64,
+        before: Option<&C>,
+        fields: &Vec<PaginationField>,
+    ) -> Self {
+        Self {
+            last: Some(*last),
+            before: before.cloned(),
+            fields: fields.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if results are paginated backwards.
+    pub fn is_backward(&self) -> bool {
+        self.last.is_some()
+    }
+
+    /// Returns the number of items requested for one page.
+    pub fn page_size(&self) -> NonZeroU64 {
+        self.last.unwrap_or(self.first)
+    }
+
+    /// Returns the cursor pointing at the item the page starts from, depending on the direction
+    /// we're paginating in.
+    pub fn cursor(&self) -> Option<&C> {
+        if self.is_backward() {
+            self.before.as_ref()
+        } else {
+            self.after.as_ref()
         }
     }
 }
@@ -99,6 +141,8 @@ where
             // Unwrap here because we know that the default is non-zero
             first: NonZeroU64::new(DEFAULT_PAGE_SIZE).unwrap(),
             after: None,
+            last: None,
+            before: None,
             fields: vec![],
         }
     }
@@ -125,6 +169,27 @@ mod tests {
         )
     }
 
+    #[test]
+    fn backward_pagination() {
+        let cursor = "cursor".to_string();
+
+        let forward =
+            Pagination::<String>::new(&NonZeroU64::new(10).unwrap(), Some(&cursor), &Vec::new());
+        assert!(!forward.is_backward());
+        assert_eq!(forward.page_size().get(), 10);
+        assert_eq!(forward.cursor(), Some(&cursor));
+
+        let backward = Pagination::<String>::new_backward(
+            &NonZeroU64::new(5).unwrap(),
+            Some(&cursor),
+            &Vec::new(),
+        );
+        assert!(backward.is_backward());
+        assert_eq!(backward.page_size().get(), 5);
+        assert_eq!(backward.cursor(), Some(&cursor));
+        assert_eq!(backward.after, None);
+    }
+
     #[test]
     fn cursor_encoding() {
         #[derive(Clone)]
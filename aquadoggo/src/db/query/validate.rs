@@ -6,7 +6,7 @@ use p2panda_rs::operation::OperationValue;
 use p2panda_rs::schema::{FieldName, FieldType, Schema};
 
 use crate::db::query::errors::QueryError;
-use crate::db::query::{Field, Filter, FilterBy, MetaField, Order, Select};
+use crate::db::query::{Field, Filter, FilterBy, FilterGroup, MetaField, Order, Select};
 
 /// Helper method to make sure that the chosen type in the query value matches the schema's field
 /// type.
@@ -54,17 +54,19 @@ pub fn validate_query(
         }
     }
 
-    // Make sure field to order actually exists in schema
-    match &order.field {
-        None | Some(Field::Meta(_)) => {
-            // Ordering any meta field or none is always okay
-        }
-        Some(Field::Field(field_name)) => {
-            if !schema_fields.contains_key(field_name) {
-                return Err(QueryError::OrderFieldUnknown(field_name.clone()));
+    // Make sure fields to order actually exist in schema
+    for clause in order.iter() {
+        match &clause.field {
+            Field::Meta(_) => {
+                // Ordering any meta field is always okay
+            }
+            Field::Field(field_name) => {
+                if !schema_fields.contains_key(field_name) {
+                    return Err(QueryError::OrderFieldUnknown(field_name.clone()));
+                }
             }
         }
-    };
+    }
 
     // Make sure field to filter exists and filtering value is of correct type
     for field in filter.iter() {
@@ -100,13 +102,29 @@ pub fn validate_query(
                     validate_type(&meta_field.to_string(), element, &FieldType::Boolean)?;
                 }
 
+                // Make sure that "receivedAt" and "updatedAt" filter values are integers
+                (FilterBy::Element(element), MetaField::ReceivedAt)
+                | (FilterBy::Element(element), MetaField::UpdatedAt) => {
+                    validate_type(&meta_field.to_string(), element, &FieldType::Integer)?;
+                }
+                (FilterBy::Set(elements), MetaField::ReceivedAt)
+                | (FilterBy::Set(elements), MetaField::UpdatedAt) => {
+                    for element in elements {
+                        validate_type(&meta_field.to_string(), element, &FieldType::Integer)?;
+                    }
+                }
+
+                // Filtering over an interval is only permitted for timestamps
+                (FilterBy::Interval(_, _), MetaField::ReceivedAt)
+                | (FilterBy::Interval(_, _), MetaField::UpdatedAt) => (),
+
                 // Filtering over multiple values of "edited" and "deleted" flag is not permitted,
                 // for all other meta fields this is okay
                 (FilterBy::Set(_), MetaField::Edited) | (FilterBy::Set(_), MetaField::Deleted) => {
                     return Err(QueryError::FilterInvalidSet(meta_field.to_string()));
                 }
 
-                // Filtering over an interval for meta fields is not permitted
+                // Filtering over an interval for all other meta fields is not permitted
                 (FilterBy::Interval(_, _), _) => {
                     return Err(QueryError::FilterInvalidInterval(meta_field.to_string()))
                 }
@@ -169,6 +187,37 @@ pub fn validate_query(
         }
     }
 
+    // Make sure all nested filters are valid as well
+    for group in filter.groups() {
+        let nested_filters: Vec<&Filter> = match group {
+            FilterGroup::And(filters) | FilterGroup::Or(filters) => filters.iter().collect(),
+            FilterGroup::Not(filter) => vec![filter.as_ref()],
+            FilterGroup::Related(field_name, related_schema, related_filter) => {
+                // Filters on related documents need to point at a relation to documents of the
+                // given schema and are validated against that schema
+                match schema_fields.get(field_name) {
+                    Some(FieldType::Relation(schema_id))
+                    | Some(FieldType::PinnedRelation(schema_id))
+                        if schema_id == related_schema.id() => {}
+                    Some(_) => return Err(QueryError::FilterInvalidRelation(field_name.clone())),
+                    None => return Err(QueryError::FilterFieldUnknown(field_name.clone())),
+                }
+
+                validate_query(
+                    &Select::default(),
+                    related_filter,
+                    &Order::default(),
+                    related_schema,
+                )?;
+                continue;
+            }
+        };
+
+        for nested_filter in nested_filters {
+            validate_query(&Select::default(), nested_filter, &Order::default(), schema)?;
+        }
+    }
+
     Ok(())
 }
 
@@ -176,7 +225,7 @@ pub fn validate_query(
 mod tests {
     use rstest::rstest;
 
-    use crate::db::query::{Direction, Filter, Order, Select};
+    use crate::db::query::{Direction, Field, Filter, FilterGroup, MetaField, Order, Select};
     use crate::test_utils::doggo_schema;
 
     use super::validate_query;
@@ -199,6 +248,17 @@ mod tests {
             &Direction::Descending
         )
     )]
+    #[case::timestamps(
+        Select::default(),
+        Filter::new().meta_fields(&[
+            ("receivedAt_gte", &[1687265969.into()]),
+            ("updatedAt_in", &[1687266014.into(), 1687266032.into()]),
+        ]),
+        Order::new(
+            &Field::Meta(MetaField::UpdatedAt),
+            &Direction::Descending
+        )
+    )]
     fn valid_queries(#[case] select: Select, #[case] filter: Filter, #[case] order: Order) {
         if let Err(err) = validate_query(&select, &filter, &order, &doggo_schema()) {
             panic!("{}", err)
@@ -226,6 +286,13 @@ mod tests {
         Order::new(&"message".into(), &Direction::Ascending),
         "Can't apply ordering on unknown field 'message'"
     )]
+    #[case::order_unknown_second_field(
+        Select::default(),
+        Filter::default(),
+        Order::new(&"username".into(), &Direction::Ascending)
+            .then(&"message".into(), &Direction::Descending),
+        "Can't apply ordering on unknown field 'message'"
+    )]
     #[case::invalid_meta_field_type(
         Select::default(),
         Filter::new().meta_fields(&[
@@ -266,6 +333,28 @@ mod tests {
         Order::default(),
         "Filter type 'int' for field 'username' is not matching schema type 'str'"
     )]
+    #[case::invalid_related_field(
+        Select::default(),
+        {
+            let mut filter = Filter::new();
+            filter.add_group(FilterGroup::Related(
+                "username".into(),
+                Box::new(doggo_schema()),
+                Box::new(Filter::new()),
+            ));
+            filter
+        },
+        Order::default(),
+        "Can't filter related documents as field 'username' is not a relation or pinned relation"
+    )]
+    #[case::invalid_timestamp_type(
+        Select::default(),
+        Filter::new().meta_fields(&[
+            ("receivedAt", &["yesterday".into()])
+        ]),
+        Order::default(),
+        "Filter type 'str' for field 'receivedAt' is not matching schema type 'int'"
+    )]
     fn invalid_queries(
         #[case] select: Select,
         #[case] filter: Filter,
@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Aggregations over filtered collections of documents, computed by the database.
+use std::collections::BTreeMap;
+
+use p2panda_rs::operation::OperationValue;
+use p2panda_rs::schema::{FieldType, Schema};
+use p2panda_rs::storage_provider::error::DocumentStorageError;
+use sqlx::{query, Row};
+
+use crate::db::query::{Field, Filter, MetaField};
+use crate::db::stores::query::{
+    from_sql, typecast_field_sql, where_filter_sql, where_sql, BindArgument,
+};
+use crate::db::SqlStore;
+
+/// Aggregated values of one numeric field over a group of documents.
+///
+/// All values are `None` when the group does not contain any documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldAggregate {
+    /// Sum of all values, with the same type as the field.
+    pub sum: Option<OperationValue>,
+
+    /// Average of all values.
+    pub avg: Option<f64>,
+
+    /// Smallest value, with the same type as the field.
+    pub min: Option<OperationValue>,
+
+    /// Largest value, with the same type as the field.
+    pub max: Option<OperationValue>,
+}
+
+/// Aggregation results for a group of documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateGroup {
+    /// Value of the field the documents were grouped by, `None` when they were not grouped.
+    pub group: Option<String>,
+
+    /// Number of documents in this group.
+    pub count: u64,
+
+    /// Aggregated values of all integer and float fields of the schema, indexed by field name.
+    pub fields: BTreeMap<String, FieldAggregate>,
+}
+
+/// Returns names and types of all fields of a schema which can be aggregated.
+fn numeric_fields(schema: &Schema) -> Vec<(String, FieldType)> {
+    schema
+        .fields()
+        .iter()
+        .filter(|(_, field_type)| matches!(field_type, FieldType::Integer | FieldType::Float))
+        .map(|(name, field_type)| (name.to_owned(), field_type.to_owned()))
+        .collect()
+}
+
+/// Returns SQL sub-query selecting the value of an application field of the current document.
+fn field_value_sql(field_name: &str) -> String {
+    format!(
+        r#"
+        (
+            SELECT
+                operation_fields_v1.value
+            FROM
+                document_view_fields AS document_view_fields_aggregate
+                JOIN operation_fields_v1
+                    ON
+                        document_view_fields_aggregate.operation_id = operation_fields_v1.operation_id
+                    AND
+                        document_view_fields_aggregate.name = operation_fields_v1.name
+            WHERE
+                document_view_fields_aggregate.document_view_id = documents.document_view_id
+                AND operation_fields_v1.name = '{field_name}'
+                AND operation_fields_v1.list_index = 0
+        )
+        "#
+    )
+}
+
+/// Returns SQL sub-query selecting the value documents get grouped by.
+///
+/// Documents can only be grouped by their owner or by application fields.
+fn group_value_sql(group_by: &Field) -> Result<String, DocumentStorageError> {
+    match group_by {
+        Field::Meta(MetaField::Owner) => Ok(r#"
+            (
+                SELECT
+                    operations_v1.public_key
+                FROM
+                    operations_v1
+                WHERE
+                    operations_v1.operation_id = documents.document_id
+            )
+            "#
+        .to_string()),
+        Field::Field(field_name) => Ok(field_value_sql(field_name)),
+        Field::Meta(meta_field) => Err(DocumentStorageError::Custom(format!(
+            "Documents can not be grouped by meta field '{}'",
+            meta_field
+        ))),
+    }
+}
+
+impl SqlStore {
+    /// Returns the number of documents and aggregated values of all integer and float fields of a
+    /// filtered collection.
+    ///
+    /// When passing a field to group by, one result is returned for every distinct value of that
+    /// field, ordered by that value. Otherwise exactly one result is returned for the whole
+    /// collection.
+    pub async fn aggregate(
+        &self,
+        schema: &Schema,
+        filter: &Filter,
+        group_by: Option<&Field>,
+    ) -> Result<Vec<AggregateGroup>, DocumentStorageError> {
+        let fields = numeric_fields(schema);
+
+        let from = from_sql(None, None);
+        let where_ = where_sql(schema, &Vec::new(), None);
+        let (and_filters, bind_args) = where_filter_sql(filter, None, schema, self.pool.any_kind());
+
+        // Select the value of every numeric field of every filtered document first ..
+        let mut inner_select = vec!["documents.document_id".to_string()];
+
+        if let Some(group_by) = group_by {
+            inner_select.push(format!("{} AS group_value", group_value_sql(group_by)?));
+        }
+
+        for (index, (field_name, _)) in fields.iter().enumerate() {
+            inner_select.push(format!("{} AS field_{index}", field_value_sql(field_name)));
+        }
+
+        // .. and aggregate them afterwards
+        let mut outer_select = Vec::new();
+
+        if group_by.is_some() {
+            outer_select.push("group_value".to_string());
+        }
+
+        outer_select.push("COUNT(filtered_documents.document_id)".to_string());
+
+        for (index, (field_name, field_type)) in fields.iter().enumerate() {
+            let value_sql = typecast_field_sql(&format!("field_{index}"), field_name, schema, true);
+
+            let result_type = match field_type {
+                FieldType::Integer => "BIGINT",
+                _ => "DOUBLE PRECISION",
+            };
+
+            outer_select.push(format!("CAST(SUM({value_sql}) AS {result_type})"));
+            outer_select.push(format!("CAST(AVG({value_sql}) AS DOUBLE PRECISION)"));
+            outer_select.push(format!("CAST(MIN({value_sql}) AS {result_type})"));
+            outer_select.push(format!("CAST(MAX({value_sql}) AS {result_type})"));
+        }
+
+        let group_sql = match group_by {
+            Some(Field::Field(field_name)) => {
+                let order_sql = typecast_field_sql("group_value", field_name, schema, true);
+                format!("GROUP BY group_value ORDER BY {order_sql} ASC")
+            }
+            Some(_) => "GROUP BY group_value ORDER BY group_value ASC".to_string(),
+            None => "".to_string(),
+        };
+
+        let inner_select = inner_select.join(",\n");
+        let outer_select = outer_select.join(",\n");
+
+        let sql = format!(
+            r#"
+            SELECT
+                {outer_select}
+
+            FROM (
+                SELECT
+                    {inner_select}
+
+                FROM
+                    {from}
+
+                    JOIN operation_fields_v1
+                        ON
+                            document_view_fields.operation_id = operation_fields_v1.operation_id
+                            AND
+                                document_view_fields.name = operation_fields_v1.name
+
+                WHERE
+                    {where_}
+                    {and_filters}
+            ) AS filtered_documents
+
+            {group_sql}
+            "#
+        );
+
+        let mut query = query(&sql);
+
+        // Bind untrusted user arguments to query
+        for arg in &bind_args {
+            query = match arg {
+                BindArgument::String(value) => query.bind(value),
+                BindArgument::Integer(value) => query.bind(value),
+                BindArgument::Float(value) => query.bind(value),
+            };
+        }
+
+        let rows = query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+        let to_storage_error =
+            |err: sqlx::Error| DocumentStorageError::FatalStorageError(err.to_string());
+
+        let mut groups = Vec::new();
+
+        for row in rows {
+            let mut index = 0;
+
+            let group = match group_by {
+                Some(_) => {
+                    index += 1;
+                    row.try_get::<Option<String>, _>(0)
+                        .map_err(to_storage_error)?
+                }
+                None => None,
+            };
+
+            let count = row.try_get::<i64, _>(index).map_err(to_storage_error)? as u64;
+            index += 1;
+
+            let mut aggregates = BTreeMap::new();
+
+            for (field_name, field_type) in fields.iter() {
+                let value = |index: usize| -> Result<Option<OperationValue>, sqlx::Error> {
+                    let value = match field_type {
+                        FieldType::Integer => row
+                            .try_get::<Option<i64>, _>(index)?
+                            .map(OperationValue::Integer),
+                        _ => row
+                            .try_get::<Option<f64>, _>(index)?
+                            .map(OperationValue::Float),
+                    };
+
+                    Ok(value)
+                };
+
+                let aggregate = FieldAggregate {
+                    sum: value(index).map_err(to_storage_error)?,
+                    avg: row
+                        .try_get::<Option<f64>, _>(index + 1)
+                        .map_err(to_storage_error)?,
+                    min: value(index + 2).map_err(to_storage_error)?,
+                    max: value(index + 3).map_err(to_storage_error)?,
+                };
+                index += 4;
+
+                aggregates.insert(field_name.to_owned(), aggregate);
+            }
+
+            groups.push(AggregateGroup {
+                group,
+                count,
+                fields: aggregates,
+            });
+        }
+
+        Ok(groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::operation::OperationValue;
+    use p2panda_rs::schema::Schema;
+    use p2panda_rs::test_utils::fixtures::key_pair;
+    use rstest::rstest;
+
+    use crate::db::query::{Field, Filter, MetaField};
+    use crate::test_utils::{add_schema_and_documents, test_runner, TestNode};
+
+    use super::FieldAggregate;
+
+    async fn create_votes_test_data(node: &mut TestNode, key_pair: &KeyPair) -> Schema {
+        let (schema, _) = add_schema_and_documents(
+            node,
+            "votes",
+            vec![
+                vec![
+                    ("option", "bamboo".into(), None),
+                    ("points", 3.into(), None),
+                    ("weight", 0.5.into(), None),
+                ],
+                vec![
+                    ("option", "bamboo".into(), None),
+                    ("points", 5.into(), None),
+                    ("weight", 1.5.into(), None),
+                ],
+                vec![
+                    ("option", "leaves".into(), None),
+                    ("points", 1.into(), None),
+                    ("weight", 1.0.into(), None),
+                ],
+            ],
+            key_pair,
+        )
+        .await;
+
+        schema
+    }
+
+    #[rstest]
+    fn aggregate_collection(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let schema = create_votes_test_data(&mut node, &key_pair).await;
+
+            let result = node
+                .context
+                .store
+                .aggregate(&schema, &Filter::default(), None)
+                .await
+                .unwrap();
+
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].group, None);
+            assert_eq!(result[0].count, 3);
+            assert_eq!(
+                result[0].fields.get("points").unwrap(),
+                &FieldAggregate {
+                    sum: Some(OperationValue::Integer(9)),
+                    avg: Some(3.0),
+                    min: Some(OperationValue::Integer(1)),
+                    max: Some(OperationValue::Integer(5)),
+                }
+            );
+            assert_eq!(
+                result[0].fields.get("weight").unwrap(),
+                &FieldAggregate {
+                    sum: Some(OperationValue::Float(3.0)),
+                    avg: Some(1.0),
+                    min: Some(OperationValue::Float(0.5)),
+                    max: Some(OperationValue::Float(1.5)),
+                }
+            );
+            assert!(!result[0].fields.contains_key("option"));
+
+            // Aggregating an empty collection still returns a result
+            let filter = Filter::new().fields(&[("option", &["grass".into()])]);
+            let result = node
+                .context
+                .store
+                .aggregate(&schema, &filter, None)
+                .await
+                .unwrap();
+
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].count, 0);
+            assert_eq!(result[0].fields.get("points").unwrap().sum, None);
+        });
+    }
+
+    #[rstest]
+    fn aggregate_groups(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let schema = create_votes_test_data(&mut node, &key_pair).await;
+
+            let result = node
+                .context
+                .store
+                .aggregate(&schema, &Filter::default(), Some(&Field::new("option")))
+                .await
+                .unwrap();
+
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0].group, Some("bamboo".to_string()));
+            assert_eq!(result[0].count, 2);
+            assert_eq!(
+                result[0].fields.get("points").unwrap().sum,
+                Some(OperationValue::Integer(8))
+            );
+            assert_eq!(result[1].group, Some("leaves".to_string()));
+            assert_eq!(result[1].count, 1);
+            assert_eq!(
+                result[1].fields.get("points").unwrap().sum,
+                Some(OperationValue::Integer(1))
+            );
+
+            // Group by owner and only aggregate filtered documents
+            let filter = Filter::new().fields(&[("points_gt", &[2.into()])]);
+            let result = node
+                .context
+                .store
+                .aggregate(&schema, &filter, Some(&Field::Meta(MetaField::Owner)))
+                .await
+                .unwrap();
+
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].group, Some(key_pair.public_key().to_string()));
+            assert_eq!(result[0].count, 2);
+            assert_eq!(result[0].fields.get("weight").unwrap().avg, Some(1.0));
+
+            // Documents can not be grouped by other meta fields
+            let result = node
+                .context
+                .store
+                .aggregate(
+                    &schema,
+                    &Filter::default(),
+                    Some(&Field::Meta(MetaField::DocumentId)),
+                )
+                .await;
+            assert!(result.is_err());
+        });
+    }
+}
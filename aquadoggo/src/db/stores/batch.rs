@@ -0,0 +1,434 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use p2panda_rs::document::DocumentId;
+use p2panda_rs::entry::traits::{AsEncodedEntry, AsEntry};
+use p2panda_rs::entry::{EncodedEntry, Entry, LogId, SeqNum};
+use p2panda_rs::hash::Hash;
+use p2panda_rs::identity::PublicKey;
+use p2panda_rs::operation::traits::AsOperation;
+use p2panda_rs::operation::{EncodedOperation, Operation, OperationId};
+use p2panda_rs::schema::SchemaId;
+use p2panda_rs::storage_provider::error::{
+    EntryStorageError, LogStorageError, OperationStorageError,
+};
+use p2panda_rs::storage_provider::traits::{EntryStore, LogStore, OperationStore};
+
+use crate::db::errors::SqlStoreError;
+use crate::db::stores::entry::insert_entry;
+use crate::db::stores::log::insert_log;
+use crate::db::stores::operation::insert_operation;
+use crate::db::types::{StorageEntry, StorageOperation};
+use crate::db::SqlStore;
+
+/// Log, entry or operation which got inserted into a `BatchStore` but not into the database yet.
+#[derive(Debug, Clone)]
+enum PendingInsert {
+    Log {
+        log_id: LogId,
+        public_key: PublicKey,
+        schema_id: SchemaId,
+        document_id: DocumentId,
+    },
+    Entry(StorageEntry),
+    Operation(StorageOperation),
+}
+
+/// Store collecting logs, entries and operations in memory on top of the database.
+///
+/// All reads see both the pending inserts and everything which is already persisted, this allows
+/// validating a batch of entries where later ones depend on earlier ones, for example when they
+/// are part of the same log. Nothing is written to the database until `commit` is called, which
+/// inserts all pending data in a single transaction.
+#[derive(Debug)]
+pub struct BatchStore<'a> {
+    store: &'a SqlStore,
+    pending: Mutex<Vec<PendingInsert>>,
+}
+
+impl<'a> BatchStore<'a> {
+    /// Returns a new `BatchStore` on top of the given database.
+    pub fn new(store: &'a SqlStore) -> Self {
+        Self {
+            store,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Write all pending inserts to the database, in the order they have been inserted.
+    ///
+    /// Either all or none of the inserts are persisted.
+    pub async fn commit(self) -> Result<(), SqlStoreError> {
+        let pending = self
+            .pending
+            .into_inner()
+            .expect("Acquire lock on pending inserts");
+
+        // Start a transaction, any db insertions after this point, and before the `commit()` will
+        // be rolled back in the event of an error.
+        let mut tx = self
+            .store
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStoreError::Transaction(e.to_string()))?;
+
+        for insert in pending {
+            match insert {
+                PendingInsert::Log {
+                    log_id,
+                    public_key,
+                    schema_id,
+                    document_id,
+                } => {
+                    insert_log(&mut tx, &log_id, &public_key, &schema_id, &document_id)
+                        .await
+                        .map_err(|e| SqlStoreError::Transaction(e.to_string()))?;
+                }
+                PendingInsert::Entry(entry) => {
+                    insert_entry(&mut tx, &entry, &entry.encoded_entry, entry.payload())
+                        .await
+                        .map_err(|e| SqlStoreError::Transaction(e.to_string()))?;
+                }
+                PendingInsert::Operation(operation) => {
+                    insert_operation(
+                        &mut tx,
+                        &operation.id,
+                        &operation.public_key,
+                        &operation,
+                        &operation.document_id,
+                        None,
+                    )
+                    .await
+                    .map_err(|e| SqlStoreError::Transaction(e.to_string()))?;
+                }
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SqlStoreError::Transaction(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns all pending entries matching the given filter.
+    fn pending_entries<F>(&self, filter: F) -> Vec<StorageEntry>
+    where
+        F: Fn(&StorageEntry) -> bool,
+    {
+        self.pending
+            .lock()
+            .expect("Acquire lock on pending inserts")
+            .iter()
+            .filter_map(|insert| match insert {
+                PendingInsert::Entry(entry) if filter(entry) => Some(entry.to_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns all pending operations matching the given filter.
+    fn pending_operations<F>(&self, filter: F) -> Vec<StorageOperation>
+    where
+        F: Fn(&StorageOperation) -> bool,
+    {
+        self.pending
+            .lock()
+            .expect("Acquire lock on pending inserts")
+            .iter()
+            .filter_map(|insert| match insert {
+                PendingInsert::Operation(operation) if filter(operation) => {
+                    Some(operation.to_owned())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the ids of all pending logs of this public key, matching the given filter.
+    fn pending_log_ids<F>(&self, public_key: &PublicKey, filter: F) -> Vec<LogId>
+    where
+        F: Fn(&DocumentId) -> bool,
+    {
+        self.pending
+            .lock()
+            .expect("Acquire lock on pending inserts")
+            .iter()
+            .filter_map(|insert| match insert {
+                PendingInsert::Log {
+                    log_id,
+                    public_key: log_public_key,
+                    document_id,
+                    ..
+                } if log_public_key == public_key && filter(document_id) => Some(log_id.to_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<'a> EntryStore for BatchStore<'a> {
+    type Entry = StorageEntry;
+
+    async fn insert_entry(
+        &self,
+        entry: &Entry,
+        encoded_entry: &EncodedEntry,
+        encoded_operation: Option<&EncodedOperation>,
+    ) -> Result<(), EntryStorageError> {
+        let entry = StorageEntry {
+            public_key: entry.public_key().to_owned(),
+            log_id: entry.log_id().to_owned(),
+            seq_num: entry.seq_num().to_owned(),
+            skiplink: entry.skiplink().cloned(),
+            backlink: entry.backlink().cloned(),
+            payload_size: entry.payload_size(),
+            payload_hash: entry.payload_hash().to_owned(),
+            signature: entry.signature().to_owned(),
+            encoded_entry: encoded_entry.to_owned(),
+            payload: encoded_operation.cloned(),
+        };
+
+        self.pending
+            .lock()
+            .expect("Acquire lock on pending inserts")
+            .push(PendingInsert::Entry(entry));
+
+        Ok(())
+    }
+
+    async fn get_entry(&self, hash: &Hash) -> Result<Option<StorageEntry>, EntryStorageError> {
+        match self.pending_entries(|entry| &entry.hash() == hash).pop() {
+            Some(entry) => Ok(Some(entry)),
+            None => self.store.get_entry(hash).await,
+        }
+    }
+
+    async fn get_entry_at_seq_num(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        seq_num: &SeqNum,
+    ) -> Result<Option<StorageEntry>, EntryStorageError> {
+        match self
+            .pending_entries(|entry| {
+                entry.public_key() == public_key
+                    && entry.log_id() == log_id
+                    && entry.seq_num() == seq_num
+            })
+            .pop()
+        {
+            Some(entry) => Ok(Some(entry)),
+            None => {
+                self.store
+                    .get_entry_at_seq_num(public_key, log_id, seq_num)
+                    .await
+            }
+        }
+    }
+
+    async fn get_latest_entry(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+    ) -> Result<Option<StorageEntry>, EntryStorageError> {
+        // Pending entries always come after the persisted ones in the same log
+        match self
+            .pending_entries(|entry| entry.public_key() == public_key && entry.log_id() == log_id)
+            .into_iter()
+            .max_by_key(|entry| entry.seq_num().as_u64())
+        {
+            Some(entry) => Ok(Some(entry)),
+            None => self.store.get_latest_entry(public_key, log_id).await,
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> LogStore for BatchStore<'a> {
+    async fn insert_log(
+        &self,
+        log_id: &LogId,
+        public_key: &PublicKey,
+        schema: &SchemaId,
+        document: &DocumentId,
+    ) -> Result<bool, LogStorageError> {
+        if self.pending_log_ids(public_key, |_| true).contains(log_id) {
+            return Ok(false);
+        }
+
+        self.pending
+            .lock()
+            .expect("Acquire lock on pending inserts")
+            .push(PendingInsert::Log {
+                log_id: log_id.to_owned(),
+                public_key: public_key.to_owned(),
+                schema_id: schema.to_owned(),
+                document_id: document.to_owned(),
+            });
+
+        Ok(true)
+    }
+
+    async fn get_log_id(
+        &self,
+        public_key: &PublicKey,
+        document_id: &DocumentId,
+    ) -> Result<Option<LogId>, LogStorageError> {
+        match self
+            .pending_log_ids(public_key, |log_document_id| log_document_id == document_id)
+            .pop()
+        {
+            Some(log_id) => Ok(Some(log_id)),
+            None => self.store.get_log_id(public_key, document_id).await,
+        }
+    }
+
+    async fn latest_log_id(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<LogId>, LogStorageError> {
+        let pending_log_id = self.pending_log_ids(public_key, |_| true).into_iter().max();
+        let persisted_log_id = self.store.latest_log_id(public_key).await?;
+        Ok(pending_log_id.max(persisted_log_id))
+    }
+}
+
+#[async_trait]
+impl<'a> OperationStore for BatchStore<'a> {
+    type Operation = StorageOperation;
+
+    async fn insert_operation(
+        &self,
+        id: &OperationId,
+        public_key: &PublicKey,
+        operation: &Operation,
+        document_id: &DocumentId,
+    ) -> Result<(), OperationStorageError> {
+        let operation = StorageOperation {
+            document_id: document_id.to_owned(),
+            id: id.to_owned(),
+            version: operation.version(),
+            action: operation.action(),
+            schema_id: operation.schema_id(),
+            previous: operation.previous(),
+            fields: operation.fields(),
+            public_key: public_key.to_owned(),
+            sorted_index: None,
+        };
+
+        self.pending
+            .lock()
+            .expect("Acquire lock on pending inserts")
+            .push(PendingInsert::Operation(operation));
+
+        Ok(())
+    }
+
+    async fn get_operation(
+        &self,
+        id: &OperationId,
+    ) -> Result<Option<StorageOperation>, OperationStorageError> {
+        match self
+            .pending_operations(|operation| &operation.id == id)
+            .pop()
+        {
+            Some(operation) => Ok(Some(operation)),
+            None => self.store.get_operation(id).await,
+        }
+    }
+
+    async fn get_document_id_by_operation_id(
+        &self,
+        id: &OperationId,
+    ) -> Result<Option<DocumentId>, OperationStorageError> {
+        match self
+            .pending_operations(|operation| &operation.id == id)
+            .pop()
+        {
+            Some(operation) => Ok(Some(operation.document_id)),
+            None => self.store.get_document_id_by_operation_id(id).await,
+        }
+    }
+
+    async fn get_operations_by_document_id(
+        &self,
+        id: &DocumentId,
+    ) -> Result<Vec<StorageOperation>, OperationStorageError> {
+        let mut operations = self.store.get_operations_by_document_id(id).await?;
+        operations.extend(self.pending_operations(|operation| &operation.document_id == id));
+        Ok(operations)
+    }
+
+    async fn get_operations_by_schema_id(
+        &self,
+        id: &SchemaId,
+    ) -> Result<Vec<StorageOperation>, OperationStorageError> {
+        let mut operations = self.store.get_operations_by_schema_id(id).await?;
+        operations.extend(self.pending_operations(|operation| &operation.schema_id == id));
+        Ok(operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p2panda_rs::entry::traits::{AsEncodedEntry, AsEntry};
+    use p2panda_rs::entry::{EncodedEntry, Entry};
+    use p2panda_rs::operation::EncodedOperation;
+    use p2panda_rs::storage_provider::traits::EntryStore;
+    use p2panda_rs::test_utils::fixtures::{encoded_entry, encoded_operation, entry};
+    use rstest::rstest;
+
+    use crate::test_utils::{test_runner, TestNode};
+
+    use super::BatchStore;
+
+    #[rstest]
+    fn insert_on_commit(
+        encoded_entry: EncodedEntry,
+        entry: Entry,
+        encoded_operation: EncodedOperation,
+    ) {
+        test_runner(|node: TestNode| async move {
+            let batch = BatchStore::new(&node.context.store);
+            batch
+                .insert_entry(&entry, &encoded_entry, Some(&encoded_operation))
+                .await
+                .unwrap();
+
+            // The pending entry is visible in the batch but not in the database yet
+            let pending_entry = batch
+                .get_latest_entry(entry.public_key(), entry.log_id())
+                .await
+                .unwrap();
+            assert_eq!(
+                pending_entry.map(|entry| entry.hash()),
+                Some(encoded_entry.hash())
+            );
+            let persisted_entry = node
+                .context
+                .store
+                .get_entry(&encoded_entry.hash())
+                .await
+                .unwrap();
+            assert!(persisted_entry.is_none());
+
+            // After committing the entry got persisted
+            batch.commit().await.unwrap();
+            let persisted_entry = node
+                .context
+                .store
+                .get_entry(&encoded_entry.hash())
+                .await
+                .unwrap();
+            assert_eq!(
+                persisted_entry.map(|entry| entry.hash()),
+                Some(encoded_entry.hash())
+            );
+        });
+    }
+}
@@ -10,12 +10,14 @@ use p2panda_rs::document::{DocumentId, DocumentViewId};
 use p2panda_rs::operation::OperationValue;
 use p2panda_rs::schema::validate::MAX_BLOB_PIECE_LENGTH;
 use p2panda_rs::schema::{Schema, SchemaId};
+use p2panda_rs::storage_provider::error::DocumentStorageError;
 use p2panda_rs::storage_provider::traits::DocumentStore;
-use sqlx::{query_scalar, AnyPool};
+use sqlx::{query_as, query_scalar, AnyPool};
 
 use crate::db::errors::{BlobStoreError, SqlStoreError};
 use crate::db::query::{Filter, Order, Pagination, PaginationField, Select};
 use crate::db::stores::query::{PaginationCursor, Query, RelationList};
+use crate::db::stores::OperationCursor;
 use crate::db::SqlStore;
 
 /// Number of blob pieces requested per database query iteration.
@@ -149,6 +151,84 @@ impl BlobStream {
             }
         }
     }
+
+    /// Returns the length of the blob in bytes as claimed by the blob document.
+    pub fn expected_length(&self) -> usize {
+        self.expected_length
+    }
+
+    /// Moves the stream to the piece containing the given byte position and returns the position
+    /// at which the data of that piece starts.
+    async fn seek(&mut self, position: usize) -> Result<usize, BlobStoreError> {
+        let pieces = piece_lengths(&self.store.pool, &self.document_view_id).await?;
+
+        let mut piece_start = 0;
+        let mut skipped_cursor = None;
+        for (list_cursor, length) in pieces {
+            if piece_start + length > position {
+                break;
+            }
+
+            piece_start += length;
+            skipped_cursor = Some(list_cursor);
+        }
+
+        // Continue after the last skipped piece. When paginating over a relation list without
+        // ordering only the cursor of the list item is looked at
+        self.pagination_cursor = skipped_cursor.map(|cursor| {
+            PaginationCursor::new(
+                cursor.clone(),
+                Some(cursor),
+                Some(self.document_view_id.clone()),
+            )
+        });
+
+        Ok(piece_start)
+    }
+
+    /// Establishes a data stream of the blob data between the `start` and `end` byte positions
+    /// (end exclusive).
+    ///
+    /// Pieces before the range are skipped and no further pieces are requested from the database
+    /// once the end of the range was reached. The blob data only gets validated when the range
+    /// covers the whole blob.
+    pub fn read_range(
+        mut self,
+        start: usize,
+        end: usize,
+    ) -> impl Stream<Item = Result<BlobData, BlobStoreError>> {
+        try_stream! {
+            let mut position = if start > 0 { self.seek(start).await? } else { 0 };
+
+            while position < end {
+                let blob_data = self.next_chunk().await?;
+
+                if blob_data.is_empty() {
+                    break;
+                }
+
+                let chunk_start = position;
+                position += blob_data.len();
+
+                // Skip chunks which end before the requested range
+                if position <= start {
+                    continue;
+                }
+
+                let from = start.saturating_sub(chunk_start);
+                let to = blob_data.len().min(end - chunk_start);
+                yield blob_data[from..to].to_vec();
+            }
+
+            // We stop reading as soon as the range was served, so the empty chunk marking the end
+            // of the blob is never reached and we need to validate the whole blob explicitly
+            if start == 0 && end == self.expected_length {
+                self.validate()?;
+            } else if position < end {
+                Err(BlobStoreError::IncorrectLength)?;
+            }
+        }
+    }
 }
 
 impl SqlStore {
@@ -321,6 +401,54 @@ async fn validate_blob_pieces(
     }
 }
 
+/// Helper for getting the cursors of the relation list items pointing at the pieces of a blob,
+/// together with the length of each piece's data in bytes, ordered like the pieces.
+async fn piece_lengths(
+    pool: &AnyPool,
+    document_view_id: &DocumentViewId,
+) -> Result<Vec<(OperationCursor, usize)>, BlobStoreError> {
+    let rows: Vec<(String, i64)> = query_as(
+        "
+        SELECT
+            operation_fields_v1_list.cursor,
+            -- Bytes are stored hex-encoded
+            CAST(LENGTH(operation_fields_v1.value) / 2 AS BIGINT)
+        FROM
+            document_view_fields AS document_view_fields_list
+        JOIN operation_fields_v1 AS operation_fields_v1_list
+            ON
+                document_view_fields_list.operation_id = operation_fields_v1_list.operation_id
+            AND
+                document_view_fields_list.name = operation_fields_v1_list.name
+        JOIN document_view_fields
+            ON
+                document_view_fields.document_view_id = operation_fields_v1_list.value
+            AND
+                document_view_fields.name = 'data'
+        JOIN operation_fields_v1
+            ON
+                document_view_fields.operation_id = operation_fields_v1.operation_id
+            AND
+                document_view_fields.name = operation_fields_v1.name
+        WHERE
+            document_view_fields_list.document_view_id = $1
+        AND
+            document_view_fields_list.name = 'pieces'
+        ORDER BY
+            operation_fields_v1_list.list_index ASC
+        ",
+    )
+    .bind(document_view_id.to_string())
+    .fetch_all(pool)
+    .await
+    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(cursor, length)| (OperationCursor::from(cursor.as_str()), length as usize))
+        .collect())
+}
+
 /// Helper for getting the document ids of any document which relates to the specified document.
 ///
 /// Optionally pass in a `SchemaId` to restrict the results to documents of a certain schema.
@@ -432,6 +560,36 @@ mod tests {
         })
     }
 
+    #[rstest]
+    fn read_blob_range(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            // Use small pieces to have the blob data spread across multiple database queries
+            let blob_data = "Hello, World! Hello, Panda!".as_bytes();
+            let blob_view_id = add_blob(&mut node, blob_data, 2, "text/plain", &key_pair).await;
+
+            for (start, end) in [(0, 27), (7, 12), (10, 27), (19, 21), (20, 26), (26, 27)] {
+                let blob_stream = node
+                    .context
+                    .store
+                    .get_blob_by_view_id(&blob_view_id)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(blob_stream.expected_length(), blob_data.len());
+
+                let stream = blob_stream.read_range(start, end);
+                pin_mut!(stream);
+
+                let mut buf = BytesMut::new();
+                while let Some(blob_data) = stream.next().await {
+                    buf.put(blob_data.unwrap().as_slice());
+                }
+
+                assert_eq!(buf.to_vec(), blob_data[start..end]);
+            }
+        })
+    }
+
     #[rstest]
     fn get_blob_errors(key_pair: KeyPair) {
         test_runner(|mut node: TestNode| async move {
@@ -509,7 +667,7 @@ mod tests {
                     ("mime_type", "text/plain".into()),
                     (
                         "pieces",
-                        vec![blob_piece_view_id_1, blob_piece_view_id_2].into(),
+                        vec![blob_piece_view_id_1.clone(), blob_piece_view_id_2.clone()].into(),
                     ),
                 ],
                 &key_pair,
@@ -530,6 +688,40 @@ mod tests {
                 collected_data,
                 Err(BlobStoreError::IncorrectLength)
             ),);
+
+            // Publish a blob with pieces exceeding the claimed byte length.
+            let blob_view_id = add_document(
+                &mut node,
+                &SchemaId::Blob(1),
+                vec![
+                    ("length", 5.into()),
+                    ("mime_type", "text/plain".into()),
+                    (
+                        "pieces",
+                        vec![blob_piece_view_id_1, blob_piece_view_id_2].into(),
+                    ),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            // Reading the whole claimed range validates the blob as well.
+            let blob_stream = node
+                .context
+                .store
+                .get_blob_by_view_id(&blob_view_id)
+                .await
+                .unwrap()
+                .unwrap();
+            let stream = blob_stream.read_range(0, 5);
+            pin_mut!(stream);
+            let mut result = Ok(());
+            while let Some(value) = stream.next().await {
+                if let Err(err) = value {
+                    result = Err(err);
+                }
+            }
+            assert!(matches!(result, Err(BlobStoreError::IncorrectLength)));
         })
     }
 
@@ -0,0 +1,655 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use p2panda_rs::operation::OperationValue;
+use p2panda_rs::schema::SchemaId;
+
+use crate::db::query::{
+    Cursor, Direction, Field, Filter, FilterBy, FilterGroup, LowerBound, PaginationField,
+    UpperBound,
+};
+use crate::db::stores::query::{AsOf, Query, QueryResponse, RelationList, RelationListType};
+
+/// Counters describing the state and usage of the query cache since the node started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCacheMetrics {
+    /// Number of query results currently kept in the cache.
+    pub entries: usize,
+
+    /// Maximum number of query results the cache can hold.
+    pub capacity: usize,
+
+    /// Number of queries which were answered from the cache.
+    pub hits: u64,
+
+    /// Number of queries which needed to be sent to the database.
+    pub misses: u64,
+
+    /// Number of results which were removed to make space for newer ones.
+    pub evictions: u64,
+
+    /// Number of results which were removed because documents of their schemas changed.
+    pub invalidations: u64,
+}
+
+/// Result of a query which can be kept in the cache.
+#[derive(Debug, Clone)]
+pub enum CachedResult {
+    /// Paginated documents returned by `SqlStore::query`.
+    Query(QueryResponse),
+
+    /// Total number of documents returned by `SqlStore::count`.
+    Count(u64),
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    value: CachedResult,
+
+    /// Schemas whose documents were looked at to compute this result.
+    schema_ids: Vec<SchemaId>,
+
+    /// Value of the usage counter when this entry was last read or written.
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct QueryCacheInner {
+    entries: HashMap<String, CacheEntry>,
+
+    /// Counter increased on every cache access, used to find the least recently used entry.
+    tick: u64,
+
+    /// Counter increased on every invalidation, results of queries which started before an
+    /// invalidation might be outdated and are not cached.
+    generation: u64,
+
+    metrics: QueryCacheMetrics,
+}
+
+/// In-memory cache for results of document queries.
+///
+/// Results are keyed by a canonical representation of the query (see `query_cache_key`) and
+/// invalidated per schema whenever a document view of that schema gets written or removed. When the cache is full the least recently used result gets
+/// evicted. A capacity of zero disables the cache.
+#[derive(Debug, Clone)]
+pub struct QueryCache {
+    capacity: usize,
+    inner: Arc<Mutex<QueryCacheInner>>,
+}
+
+impl QueryCache {
+    /// Returns a new, empty cache holding up to `capacity` query results.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Arc::new(Mutex::new(QueryCacheInner::default())),
+        }
+    }
+
+    /// Returns the current generation of the cache.
+    ///
+    /// This needs to be taken before sending a query to the database and passed on when inserting
+    /// its result.
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().unwrap().generation
+    }
+
+    /// Returns a cached result for this key, if any.
+    pub fn get(&self, key: &str) -> Option<CachedResult> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+
+        match inner.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = tick;
+                let value = entry.value.clone();
+                inner.metrics.hits += 1;
+                Some(value)
+            }
+            None => {
+                inner.metrics.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Insert a query result which depends on documents of the given schemas.
+    ///
+    /// The result is dropped if the cache got invalidated since the given generation.
+    pub fn insert(
+        &self,
+        key: String,
+        schema_ids: Vec<SchemaId>,
+        value: CachedResult,
+        generation: u64,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generation != generation {
+            return;
+        }
+
+        // Make space by removing the least recently used entry
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(&key) {
+            let oldest_key = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.to_owned());
+
+            if let Some(oldest_key) = oldest_key {
+                inner.entries.remove(&oldest_key);
+                inner.metrics.evictions += 1;
+            }
+        }
+
+        inner.tick += 1;
+        let last_used = inner.tick;
+        inner.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                schema_ids,
+                last_used,
+            },
+        );
+    }
+
+    /// Remove all results depending on documents of this schema.
+    pub fn invalidate(&self, schema_id: &SchemaId) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+
+        let entries_before = inner.entries.len();
+        inner
+            .entries
+            .retain(|_, entry| !entry.schema_ids.contains(schema_id));
+        inner.metrics.invalidations += (entries_before - inner.entries.len()) as u64;
+    }
+
+    /// Remove all results.
+    pub fn invalidate_all(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+
+        inner.metrics.invalidations += inner.entries.len() as u64;
+        inner.entries.clear();
+    }
+
+    /// Returns usage counters of this cache.
+    pub fn metrics(&self) -> QueryCacheMetrics {
+        let inner = self.inner.lock().unwrap();
+
+        QueryCacheMetrics {
+            entries: inner.entries.len(),
+            capacity: self.capacity,
+            ..inner.metrics
+        }
+    }
+}
+
+/// Returns the ids of all schemas a query on documents of the given schema depends on.
+///
+/// Next to the queried schema itself these are the schemas of related documents used in filters.
+pub fn query_schema_ids(schema_id: &SchemaId, filter: &Filter) -> Vec<SchemaId> {
+    let mut schema_ids = vec![schema_id.to_owned()];
+    collect_related_schema_ids(filter, &mut schema_ids);
+    schema_ids
+}
+
+/// Returns a canonical key identifying the result of a query of the given kind.
+///
+/// All parts of the query are written in a fixed order with every token prefixed by its length,
+/// two different queries can therefore never share a key. Selected fields are sorted as their
+/// order does not change the result.
+pub fn query_cache_key<C>(
+    kind: &str,
+    schema_id: &SchemaId,
+    args: &Query<C>,
+    list: Option<&RelationList>,
+) -> String
+where
+    C: Cursor,
+{
+    let mut key = String::new();
+    push_token(&mut key, kind);
+    push_token(&mut key, &schema_id.to_string());
+
+    // Pagination
+    let pagination = &args.pagination;
+    push_token(&mut key, &pagination.first.to_string());
+    push_option(
+        &mut key,
+        pagination.after.as_ref().map(|cursor| cursor.encode()),
+    );
+    push_option(&mut key, pagination.last.map(|last| last.to_string()));
+    push_option(
+        &mut key,
+        pagination.before.as_ref().map(|cursor| cursor.encode()),
+    );
+    push_token(&mut key, &pagination.fields.len().to_string());
+    for field in &pagination.fields {
+        push_token(&mut key, pagination_field_token(field));
+    }
+
+    // Selected fields
+    let mut fields: Vec<String> = args
+        .select
+        .fields
+        .iter()
+        .map(|field| {
+            let mut token = String::new();
+            push_field(&mut token, field);
+            token
+        })
+        .collect();
+    fields.sort();
+    push_token(&mut key, &fields.len().to_string());
+    for field in fields {
+        key.push_str(&field);
+    }
+
+    push_filter(&mut key, &args.filter);
+
+    // Order
+    push_token(&mut key, &args.order.clauses.len().to_string());
+    for clause in args.order.iter() {
+        push_field(&mut key, &clause.field);
+        push_token(
+            &mut key,
+            match clause.direction {
+                Direction::Ascending => "asc",
+                Direction::Descending => "desc",
+            },
+        );
+    }
+
+    push_option(&mut key, args.search.clone());
+
+    match &args.reverse_relation {
+        Some(reverse_relation) => {
+            push_token(&mut key, "some");
+            push_token(&mut key, reverse_relation.document_id.as_str());
+            push_token(&mut key, &reverse_relation.field);
+        }
+        None => push_token(&mut key, "none"),
+    }
+
+    match &args.as_of {
+        Some(AsOf::Timestamp(timestamp)) => {
+            push_token(&mut key, "timestamp");
+            push_token(&mut key, &timestamp.to_string());
+        }
+        Some(AsOf::OperationIndex(index)) => {
+            push_token(&mut key, "operation_index");
+            push_token(&mut key, &index.to_string());
+        }
+        None => push_token(&mut key, "none"),
+    }
+
+    match list {
+        Some(list) => {
+            push_token(&mut key, "some");
+            push_token(&mut key, &list.root_view_id.to_string());
+            push_token(&mut key, &list.field);
+            push_token(
+                &mut key,
+                match list.list_type {
+                    RelationListType::Pinned => "pinned",
+                    RelationListType::Unpinned => "unpinned",
+                },
+            );
+        }
+        None => push_token(&mut key, "none"),
+    }
+
+    key
+}
+
+fn push_token(key: &mut String, token: &str) {
+    key.push_str(&token.len().to_string());
+    key.push(':');
+    key.push_str(token);
+}
+
+fn push_option(key: &mut String, value: Option<String>) {
+    match value {
+        Some(value) => {
+            push_token(key, "some");
+            push_token(key, &value);
+        }
+        None => push_token(key, "none"),
+    }
+}
+
+fn pagination_field_token(field: &PaginationField) -> &'static str {
+    match field {
+        PaginationField::TotalCount => "totalCount",
+        PaginationField::HasNextPage => "hasNextPage",
+        PaginationField::HasPreviousPage => "hasPreviousPage",
+        PaginationField::StartCursor => "startCursor",
+        PaginationField::EndCursor => "endCursor",
+    }
+}
+
+fn push_field(key: &mut String, field: &Field) {
+    match field {
+        Field::Meta(meta_field) => {
+            push_token(key, "meta");
+            push_token(key, &meta_field.to_string());
+        }
+        Field::Field(field_name) => {
+            push_token(key, "field");
+            push_token(key, field_name);
+        }
+    }
+}
+
+fn push_filter(key: &mut String, filter: &Filter) {
+    push_token(key, &filter.len().to_string());
+    for setting in filter.iter() {
+        push_field(key, &setting.field);
+        push_token(
+            key,
+            if setting.exclusive {
+                "exclusive"
+            } else {
+                "inclusive"
+            },
+        );
+
+        match &setting.by {
+            FilterBy::Element(value) => {
+                push_token(key, "element");
+                push_value(key, value);
+            }
+            FilterBy::Set(values) => {
+                push_token(key, "set");
+                push_token(key, &values.len().to_string());
+                for value in values {
+                    push_value(key, value);
+                }
+            }
+            FilterBy::Interval(lower, upper) => {
+                push_token(key, "interval");
+                match lower {
+                    LowerBound::Unbounded => push_token(key, "unbounded"),
+                    LowerBound::Greater(value) => {
+                        push_token(key, "gt");
+                        push_value(key, value);
+                    }
+                    LowerBound::GreaterEqual(value) => {
+                        push_token(key, "gte");
+                        push_value(key, value);
+                    }
+                }
+                match upper {
+                    UpperBound::Unbounded => push_token(key, "unbounded"),
+                    UpperBound::Lower(value) => {
+                        push_token(key, "lt");
+                        push_value(key, value);
+                    }
+                    UpperBound::LowerEqual(value) => {
+                        push_token(key, "lte");
+                        push_value(key, value);
+                    }
+                }
+            }
+            FilterBy::Contains(value) => {
+                push_token(key, "contains");
+                push_value(key, value);
+            }
+        }
+    }
+
+    let groups: Vec<&FilterGroup> = filter.groups().collect();
+    push_token(key, &groups.len().to_string());
+    for group in groups {
+        match group {
+            FilterGroup::And(filters) => {
+                push_token(key, "and");
+                push_filters(key, filters);
+            }
+            FilterGroup::Or(filters) => {
+                push_token(key, "or");
+                push_filters(key, filters);
+            }
+            FilterGroup::Not(filter) => {
+                push_token(key, "not");
+                push_filter(key, filter);
+            }
+            FilterGroup::Related(field_name, schema, filter) => {
+                push_token(key, "related");
+                push_token(key, field_name);
+                push_token(key, &schema.id().to_string());
+                push_filter(key, filter);
+            }
+        }
+    }
+}
+
+fn push_filters(key: &mut String, filters: &[Filter]) {
+    push_token(key, &filters.len().to_string());
+    for filter in filters {
+        push_filter(key, filter);
+    }
+}
+
+fn push_value(key: &mut String, value: &OperationValue) {
+    push_token(key, value.field_type());
+
+    match value {
+        OperationValue::Boolean(value) => push_token(key, &value.to_string()),
+        OperationValue::Bytes(value) => push_token(key, &hex::encode(value)),
+        OperationValue::Integer(value) => push_token(key, &value.to_string()),
+        // Use the exact bit pattern, the textual representation might be rounded
+        OperationValue::Float(value) => push_token(key, &value.to_bits().to_string()),
+        OperationValue::String(value) => push_token(key, value),
+        OperationValue::Relation(relation) => push_token(key, relation.document_id().as_str()),
+        OperationValue::RelationList(list) => {
+            push_token(key, &list.len().to_string());
+            for document_id in list.iter() {
+                push_token(key, document_id.as_str());
+            }
+        }
+        OperationValue::PinnedRelation(relation) => {
+            push_token(key, &relation.view_id().to_string())
+        }
+        OperationValue::PinnedRelationList(list) => {
+            push_token(key, &list.len().to_string());
+            for view_id in list.iter() {
+                push_token(key, &view_id.to_string());
+            }
+        }
+    }
+}
+
+fn collect_related_schema_ids(filter: &Filter, schema_ids: &mut Vec<SchemaId>) {
+    for group in filter.groups() {
+        match group {
+            FilterGroup::And(filters) | FilterGroup::Or(filters) => {
+                for filter in filters {
+                    collect_related_schema_ids(filter, schema_ids);
+                }
+            }
+            FilterGroup::Not(filter) => collect_related_schema_ids(filter, schema_ids),
+            FilterGroup::Related(_, schema, filter) => {
+                if !schema_ids.contains(schema.id()) {
+                    schema_ids.push(schema.id().to_owned());
+                }
+                collect_related_schema_ids(filter, schema_ids);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p2panda_rs::schema::SchemaId;
+    use p2panda_rs::test_utils::fixtures::schema_id;
+    use rstest::rstest;
+
+    use crate::db::query::{Field, Filter, Order, Pagination, Select};
+    use crate::db::stores::query::{PaginationCursor, Query};
+
+    use super::{query_cache_key, CachedResult, QueryCache};
+
+    #[rstest]
+    fn evict_and_invalidate(
+        #[from(schema_id)]
+        #[with("venues_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b")]
+        venues: SchemaId,
+        #[from(schema_id)]
+        #[with("events_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b")]
+        events: SchemaId,
+    ) {
+        let cache = QueryCache::new(2);
+
+        let generation = cache.generation();
+        cache.insert(
+            "a".into(),
+            vec![venues.clone()],
+            CachedResult::Count(1),
+            generation,
+        );
+        cache.insert(
+            "b".into(),
+            vec![events.clone(), venues.clone()],
+            CachedResult::Count(2),
+            generation,
+        );
+
+        // Reading "a" makes "b" the least recently used entry
+        assert!(matches!(cache.get("a"), Some(CachedResult::Count(1))));
+        cache.insert(
+            "c".into(),
+            vec![events.clone()],
+            CachedResult::Count(3),
+            generation,
+        );
+        assert!(cache.get("b").is_none());
+
+        // Invalidating venues only removes results depending on them
+        cache.invalidate(&venues);
+        assert!(cache.get("a").is_none());
+        assert!(matches!(cache.get("c"), Some(CachedResult::Count(3))));
+
+        // Results of queries which started before an invalidation are not cached
+        cache.insert(
+            "d".into(),
+            vec![events.clone()],
+            CachedResult::Count(4),
+            generation,
+        );
+        assert!(cache.get("d").is_none());
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.entries, 1);
+        assert_eq!(metrics.capacity, 2);
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(metrics.misses, 3);
+        assert_eq!(metrics.evictions, 1);
+        assert_eq!(metrics.invalidations, 1);
+    }
+
+    #[rstest]
+    fn disabled_cache(
+        #[from(schema_id)]
+        #[with("venues_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b")]
+        venues: SchemaId,
+    ) {
+        let cache = QueryCache::new(0);
+        cache.insert(
+            "a".into(),
+            vec![venues],
+            CachedResult::Count(1),
+            cache.generation(),
+        );
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.metrics().entries, 0);
+    }
+
+    #[rstest]
+    fn canonical_query_keys(
+        #[from(schema_id)]
+        #[with("venues_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b")]
+        venues: SchemaId,
+    ) {
+        let query = |fields: &[Field], filter: &Filter| {
+            Query::<PaginationCursor>::new(
+                &Pagination::default(),
+                &Select::new(fields),
+                filter,
+                &Order::default(),
+            )
+        };
+
+        let mut filter = Filter::new();
+        filter.add(&Field::new("name"), &"Panda Café".into());
+
+        // Order of selected fields does not matter
+        let key = query_cache_key(
+            "query",
+            &venues,
+            &query(&[Field::new("name"), Field::new("city")], &filter),
+            None,
+        );
+        assert_eq!(
+            key,
+            query_cache_key(
+                "query",
+                &venues,
+                &query(&[Field::new("city"), Field::new("name")], &filter),
+                None,
+            )
+        );
+
+        // Different filter values, kinds or field names crafted to look alike result in different
+        // keys
+        let mut other_filter = Filter::new();
+        other_filter.add(&Field::new("name"), &"Panda Bar".into());
+        assert_ne!(
+            key,
+            query_cache_key(
+                "query",
+                &venues,
+                &query(&[Field::new("name"), Field::new("city")], &other_filter),
+                None,
+            )
+        );
+        assert_ne!(
+            key,
+            query_cache_key(
+                "count",
+                &venues,
+                &query(&[Field::new("name"), Field::new("city")], &filter),
+                None,
+            )
+        );
+        assert_ne!(
+            query_cache_key(
+                "query",
+                &venues,
+                &query(&[Field::new("a:b")], &Filter::new()),
+                None
+            ),
+            query_cache_key(
+                "query",
+                &venues,
+                &query(&[Field::new("a"), Field::new("b")], &Filter::new()),
+                None
+            )
+        );
+    }
+}
@@ -28,21 +28,28 @@
 //! view if it has already been materialised and stored. Although it is possible to construct a
 //! document at any point in its history if all operations are retained, we use a system of "pinned
 //! relations" to identify and materialise only views we explicitly wish to keep.
+use std::collections::{BTreeMap, HashSet};
+
 use async_trait::async_trait;
 use log::debug;
 use p2panda_rs::document::traits::AsDocument;
-use p2panda_rs::document::{DocumentId, DocumentView, DocumentViewId};
+use p2panda_rs::document::{DocumentBuilder, DocumentId, DocumentView, DocumentViewId};
+use p2panda_rs::operation::OperationId;
 use p2panda_rs::schema::SchemaId;
 use p2panda_rs::storage_provider::error::DocumentStorageError;
-use p2panda_rs::storage_provider::traits::DocumentStore;
+use p2panda_rs::storage_provider::traits::{DocumentStore, OperationStore};
 use sqlx::any::AnyQueryResult;
 use sqlx::{query, query_as, query_scalar, Any, Transaction};
 
 use crate::db::models::utils::parse_document_view_field_rows;
 use crate::db::models::{DocumentRow, DocumentViewFieldRow};
+use crate::db::stores::query::AsOf;
+use crate::db::stores::search::{remove_from_search_index, update_search_index};
 use crate::db::types::StorageDocument;
-use crate::db::Pool;
-use crate::db::SqlStore;
+use crate::db::{local_timestamp, Pool, SqlStore};
+
+/// Id, action, previous operations and local time of receipt of an operation.
+type ReceivedOperation = (String, String, Option<String>, i64);
 
 #[async_trait]
 impl DocumentStore for SqlStore {
@@ -306,10 +313,15 @@ impl SqlStore {
 
         match result {
             // Commit the tx here if no error occurred.
-            Ok(_) => tx
-                .commit()
-                .await
-                .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string())),
+            Ok(_) => {
+                tx.commit()
+                    .await
+                    .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
+
+                // Cached query results for this schema might be outdated now
+                self.query_cache.invalidate(document.schema_id());
+                Ok(())
+            }
             // Rollback here if an error occurred.
             Err(err) => {
                 tx.rollback()
@@ -332,6 +344,24 @@ impl SqlStore {
         document_view: &DocumentView,
         document_id: &DocumentId,
         schema_id: &SchemaId,
+    ) -> Result<(), DocumentStorageError> {
+        self.write_document_view(document_view, document_id, schema_id, local_timestamp())
+            .await?;
+
+        // Cached query results for this schema might be outdated now
+        self.query_cache.invalidate(schema_id);
+
+        Ok(())
+    }
+
+    /// Insert a document view and its fields into the database in one transaction, stating the
+    /// local time when it was materialized.
+    async fn write_document_view(
+        &self,
+        document_view: &DocumentView,
+        document_id: &DocumentId,
+        schema_id: &SchemaId,
+        materialized_at: i64,
     ) -> Result<(), DocumentStorageError> {
         // Start a transaction, any db insertions after this point, and before the `commit()`
         // will be rolled back in the event of an error.
@@ -342,7 +372,15 @@ impl SqlStore {
             .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
 
         // Insert the document view into the `document_views` table. Rollback insertions if an error occurs.
-        match insert_document_view(&mut tx, document_view, document_id, schema_id).await {
+        match insert_document_view(
+            &mut tx,
+            document_view,
+            document_id,
+            schema_id,
+            materialized_at,
+        )
+        .await
+        {
             Ok(_) => (),
             Err(err) => {
                 tx.rollback()
@@ -367,7 +405,9 @@ impl SqlStore {
         // Commit the tx here as no errors occurred.
         tx.commit()
             .await
-            .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))
+            .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
+
+        Ok(())
     }
 
     /// Get the ids for all document views for a document which are currently materialized to the store.
@@ -487,10 +527,27 @@ impl SqlStore {
     ///
     /// This operations only succeeds if the view is "dangling", meaning no other document view
     /// exists which relates to this view, AND it is not the current view of any document.
+    ///
+    /// Cached query results of the view's schema are invalidated when the view got removed.
     pub async fn prune_document_view(
         &self,
         document_view_id: &DocumentViewId,
     ) -> Result<bool, DocumentStorageError> {
+        let schema_id: Option<String> = query_scalar(
+            "
+            SELECT
+                document_views.schema_id
+            FROM
+                document_views
+            WHERE
+                document_views.document_view_id = $1
+            ",
+        )
+        .bind(document_view_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
         // Attempt to delete the view. If it is pinned from an existing view, or it is the current
         // view of a document, the deletion will not go ahead.
         let result = query(
@@ -529,6 +586,14 @@ impl SqlStore {
         // If any rows were affected the deletion went ahead.
         if result.rows_affected() > 0 {
             debug!("Deleted view: {}", document_view_id);
+
+            if let Some(schema_id) = schema_id {
+                let schema_id = schema_id
+                    .parse()
+                    .expect("Schema ids coming from the store should be valid");
+                self.query_cache.invalidate(&schema_id);
+            }
+
             Ok(true)
         } else {
             debug!("Did not delete view: {}", document_view_id);
@@ -559,6 +624,194 @@ impl SqlStore {
         Ok(document_view_id.is_some())
     }
 
+    /// Get the local times when this node received the operation which created the document and
+    /// when it materialized the given view of it, in seconds since UNIX epoch.
+    pub async fn get_document_timestamps(
+        &self,
+        document_view_id: &DocumentViewId,
+    ) -> Result<(i64, i64), DocumentStorageError> {
+        query_as(
+            "
+            SELECT
+                operations_v1.received_at,
+                document_views.materialized_at
+            FROM
+                document_views
+                JOIN operations_v1
+                    ON operations_v1.operation_id = document_views.document_id
+            WHERE
+                document_views.document_view_id = $1
+            ",
+        )
+        .bind(document_view_id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))
+    }
+
+    /// Get the ids of the document views of a schema which were current at the given point in the
+    /// history of this node.
+    ///
+    /// The views are derived from the operations this node received until then. Documents which
+    /// were deleted at that time are not included.
+    ///
+    /// Views which were never materialized or got removed by the garbage collection are
+    /// materialized again, stating the time they became current. This does not invalidate cached
+    /// query results as the latest views did not change. Like any other unpinned historic view
+    /// they get removed again by the next garbage collection of their document.
+    pub async fn get_document_view_ids_as_of(
+        &self,
+        schema_id: &SchemaId,
+        as_of: AsOf,
+    ) -> Result<Vec<DocumentViewId>, DocumentStorageError> {
+        let (received_sql, value) = match as_of {
+            AsOf::Timestamp(timestamp) => ("operations_v1.received_at <= $2", timestamp),
+            AsOf::OperationIndex(index) => ("operations_v1.received_index <= $2", index),
+        };
+
+        let operation_rows: Vec<(String, String, String, Option<String>, i64)> =
+            query_as(&format!(
+                "
+                SELECT
+                    operations_v1.document_id,
+                    operations_v1.operation_id,
+                    operations_v1.action,
+                    operations_v1.previous,
+                    operations_v1.received_at
+                FROM
+                    operations_v1
+                WHERE
+                    operations_v1.schema_id = $1
+                    AND {received_sql}
+                "
+            ))
+            .bind(schema_id.to_string())
+            .bind(value)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+        // Group the received operations by document
+        let mut documents: BTreeMap<String, Vec<ReceivedOperation>> = BTreeMap::new();
+        for (document_id, operation_id, action, previous, received_at) in operation_rows {
+            documents.entry(document_id).or_default().push((
+                operation_id,
+                action,
+                previous,
+                received_at,
+            ));
+        }
+
+        let mut document_view_ids = Vec::new();
+
+        for (document_id, operations) in documents {
+            if operations
+                .iter()
+                .any(|(_, action, _, _)| action == "delete")
+            {
+                continue;
+            }
+
+            // Operations are only accepted when all their previous operations are known, the
+            // graph tips at that time are the operations no other received operation points at
+            let previous_ids: HashSet<&str> = operations
+                .iter()
+                .filter_map(|(_, _, previous, _)| previous.as_deref())
+                .flat_map(|previous| previous.split('_'))
+                .collect();
+            let graph_tips = operations
+                .iter()
+                .filter(|(operation_id, _, _, _)| !previous_ids.contains(operation_id.as_str()))
+                .map(|(operation_id, _, _, _)| {
+                    operation_id
+                        .parse::<OperationId>()
+                        .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))
+                })
+                .collect::<Result<Vec<OperationId>, DocumentStorageError>>()?;
+            let document_view_id = DocumentViewId::new(&graph_tips);
+
+            if !self.document_view_exists(&document_view_id).await? {
+                let document_id = document_id
+                    .parse::<DocumentId>()
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+                let operations_by_document = self
+                    .get_operations_by_document_id(&document_id)
+                    .await
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+                let (document, _) = DocumentBuilder::from(&operations_by_document)
+                    .build_to_view_id(document_view_id.clone())
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+                // The view became current when the last of its operations was received
+                let materialized_at = operations
+                    .iter()
+                    .map(|(_, _, _, received_at)| *received_at)
+                    .max()
+                    .unwrap_or_default();
+
+                match document.view() {
+                    Some(document_view) => {
+                        self.write_document_view(
+                            &document_view,
+                            &document_id,
+                            schema_id,
+                            materialized_at,
+                        )
+                        .await?;
+                        debug!("Materialized historic document view {}", document_view_id);
+                    }
+                    None => continue,
+                }
+            }
+
+            document_view_ids.push(document_view_id);
+        }
+
+        Ok(document_view_ids)
+    }
+
+    /// Get the index of the latest operation this node received, operations are numbered in the
+    /// order they arrived. Returns 0 if no operation was received yet.
+    pub async fn get_latest_operation_index(&self) -> Result<i64, DocumentStorageError> {
+        let index: Option<i64> = query_scalar(
+            "
+            SELECT
+                MAX(operations_v1.received_index)
+            FROM
+                operations_v1
+            ",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+        Ok(index.unwrap_or_default())
+    }
+
+    /// Check if a document view is materialized in the store.
+    async fn document_view_exists(
+        &self,
+        document_view_id: &DocumentViewId,
+    ) -> Result<bool, DocumentStorageError> {
+        let document_view_id: Option<String> = query_scalar(
+            "
+            SELECT
+                document_views.document_view_id
+            FROM
+                document_views
+            WHERE
+                document_views.document_view_id = $1
+            ",
+        )
+        .bind(document_view_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+        Ok(document_view_id.is_some())
+    }
+
     /// Purge a document from the store by its id.
     ///
     /// This removes entries, operations and any materialized documents which exist.
@@ -590,6 +843,9 @@ impl SqlStore {
         .await
         .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
 
+        // Delete document from the full-text search index.
+        remove_from_search_index(&mut tx, document_id).await?;
+
         // Delete rows from `entries` table.
         query(
             "
@@ -623,6 +879,10 @@ impl SqlStore {
             .await
             .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
 
+        // We don't know the schema of the purged document here, so all cached query results
+        // might be outdated now
+        self.query_cache.invalidate_all();
+
         Ok(())
     }
 }
@@ -714,6 +974,7 @@ async fn insert_document_view(
     document_view: &DocumentView,
     document_id: &DocumentId,
     schema_id: &SchemaId,
+    materialized_at: i64,
 ) -> Result<AnyQueryResult, DocumentStorageError> {
     query(
         "
@@ -721,16 +982,18 @@ async fn insert_document_view(
             document_views (
                 document_view_id,
                 document_id,
-                schema_id
+                schema_id,
+                materialized_at
             )
         VALUES
-            ($1, $2, $3)
+            ($1, $2, $3, $4)
         ON CONFLICT DO NOTHING
         ",
     )
     .bind(document_view.id().to_string())
     .bind(document_id.to_string())
     .bind(schema_id.to_string())
+    .bind(materialized_at)
     .execute(tx)
     .await
     .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))
@@ -779,6 +1042,7 @@ async fn insert_document(
             &document_view,
             document.id(),
             document.schema_id(),
+            local_timestamp(),
         )
         .await?;
 
@@ -786,6 +1050,9 @@ async fn insert_document(
         insert_document_fields(&mut *tx, &document_view).await?;
     };
 
+    // Keep the full-text search index in sync with the latest document view
+    update_search_index(tx, document).await?;
+
     Ok(())
 }
 
@@ -1224,6 +1491,7 @@ mod tests {
             assert!(document.is_some());
 
             // Prune the first document view.
+            let generation = node.context.store.query_cache.generation();
             let result = node
                 .context
                 .store
@@ -1233,6 +1501,9 @@ mod tests {
             // Returns `true` when pruning succeeded.
             assert!(result.unwrap());
 
+            // Cached query results are invalidated
+            assert!(node.context.store.query_cache.generation() > generation);
+
             // Get the first document view again, it should no longer be there.
             let document = node
                 .context
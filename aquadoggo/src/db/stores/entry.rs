@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::vec;
 
 use async_trait::async_trait;
@@ -12,7 +12,7 @@ use p2panda_rs::identity::PublicKey;
 use p2panda_rs::operation::EncodedOperation;
 use p2panda_rs::storage_provider::error::EntryStorageError;
 use p2panda_rs::storage_provider::traits::EntryStore;
-use sqlx::{query, query_as};
+use sqlx::{query, query_as, Any, Executor};
 
 use crate::db::models::{EntryRow, LogHeightRow};
 use crate::db::types::StorageEntry;
@@ -36,41 +36,7 @@ impl EntryStore for SqlStore {
         encoded_entry: &EncodedEntry,
         encoded_operation: Option<&EncodedOperation>,
     ) -> Result<(), EntryStorageError> {
-        let insert_entry_result = query(
-            "
-            INSERT INTO
-                entries (
-                    public_key,
-                    entry_bytes,
-                    entry_hash,
-                    log_id,
-                    payload_bytes,
-                    payload_hash,
-                    seq_num
-                )
-            VALUES
-                ($1, $2, $3, $4, $5, $6, $7)
-            ",
-        )
-        .bind(entry.public_key().to_string())
-        .bind(encoded_entry.into_hex())
-        .bind(encoded_entry.hash().as_str())
-        .bind(entry.log_id().as_u64().to_string())
-        .bind(encoded_operation.map(|payload| payload.to_string()))
-        .bind(entry.payload_hash().as_str())
-        .bind(entry.seq_num().as_u64().to_string())
-        .execute(&self.pool)
-        .await
-        .map_err(|e| EntryStorageError::Custom(e.to_string()))?;
-
-        if insert_entry_result.rows_affected() != 1 {
-            return Err(EntryStorageError::Custom(format!(
-                "Unexpected number of inserts occured for entry with id: {}",
-                encoded_entry.hash()
-            )));
-        }
-
-        Ok(())
+        insert_entry(&self.pool, entry, encoded_entry, encoded_operation).await
     }
 
     /// Get an entry from storage by its hash id.
@@ -254,6 +220,143 @@ impl SqlStore {
         Ok(log_heights.into_iter().collect())
     }
 
+    /// Get the sequence number of the latest entry in each of the given logs.
+    ///
+    /// Logs without any entries are not contained in the returned map.
+    pub async fn get_latest_seq_nums(
+        &self,
+        logs: &[(PublicKey, LogId)],
+    ) -> Result<HashMap<(PublicKey, LogId), SeqNum>, EntryStorageError> {
+        if logs.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let public_keys_str = logs
+            .iter()
+            .map(|(public_key, _)| format!("'{public_key}'"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let log_ids_str = logs
+            .iter()
+            .map(|(_, log_id)| format!("'{}'", log_id.as_u64()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let log_height_rows = query_as::<_, LogHeightRow>(&format!(
+            "
+            SELECT
+                entries.public_key,
+                entries.log_id,
+                CAST(MAX(CAST(entries.seq_num AS NUMERIC)) AS TEXT) as seq_num
+            FROM
+                entries
+            WHERE
+                entries.public_key IN ({public_keys_str})
+                AND entries.log_id IN ({log_ids_str})
+            GROUP BY
+                entries.public_key, entries.log_id
+            ",
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| EntryStorageError::Custom(e.to_string()))?;
+
+        // Public keys and log ids were filtered separately, only keep the requested combinations
+        let logs: HashSet<&(PublicKey, LogId)> = logs.iter().collect();
+        Ok(log_height_rows
+            .into_iter()
+            .map(|row| {
+                (
+                    (
+                        row.public_key
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                        row.log_id
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                    ),
+                    row.seq_num
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                )
+            })
+            .filter(|(log, _)| logs.contains(log))
+            .collect())
+    }
+
+    /// Get the hashes of the entries at the given sequence numbers of the given logs.
+    ///
+    /// Entries which were not found are not contained in the returned map.
+    pub async fn get_entry_hashes(
+        &self,
+        entries: &[(PublicKey, LogId, SeqNum)],
+    ) -> Result<HashMap<(PublicKey, LogId, SeqNum), Hash>, EntryStorageError> {
+        if entries.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let public_keys_str = entries
+            .iter()
+            .map(|(public_key, _, _)| format!("'{public_key}'"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let log_ids_str = entries
+            .iter()
+            .map(|(_, log_id, _)| format!("'{}'", log_id.as_u64()))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let seq_nums_str = entries
+            .iter()
+            .map(|(_, _, seq_num)| format!("'{}'", seq_num.as_u64()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let rows: Vec<(String, String, String, String)> = query_as(&format!(
+            "
+            SELECT
+                entries.public_key,
+                entries.log_id,
+                entries.seq_num,
+                entries.entry_hash
+            FROM
+                entries
+            WHERE
+                entries.public_key IN ({public_keys_str})
+                AND entries.log_id IN ({log_ids_str})
+                AND entries.seq_num IN ({seq_nums_str})
+            ",
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| EntryStorageError::Custom(e.to_string()))?;
+
+        // Public keys, log ids and sequence numbers were filtered separately, only keep the
+        // requested combinations
+        let entries: HashSet<&(PublicKey, LogId, SeqNum)> = entries.iter().collect();
+        Ok(rows
+            .into_iter()
+            .map(|(public_key, log_id, seq_num, entry_hash)| {
+                (
+                    (
+                        public_key
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                        log_id
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                        seq_num
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                    ),
+                    entry_hash
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                )
+            })
+            .filter(|(entry, _)| entries.contains(entry))
+            .collect())
+    }
+
     pub async fn get_entries_from(
         &self,
         public_key: &PublicKey,
@@ -289,6 +392,95 @@ impl SqlStore {
 
         Ok(entries.into_iter().map(|row| row.into()).collect())
     }
+
+    /// Get all entries of a log with a sequence number between `from` and `to`, both inclusive.
+    pub async fn get_entries_range(
+        &self,
+        public_key: &PublicKey,
+        log_id: &LogId,
+        from: &SeqNum,
+        to: &SeqNum,
+    ) -> Result<Vec<StorageEntry>, EntryStorageError> {
+        let entries = query_as::<_, EntryRow>(
+            "
+            SELECT
+                public_key,
+                entry_bytes,
+                entry_hash,
+                log_id,
+                payload_bytes,
+                payload_hash,
+                seq_num
+            FROM
+                entries
+            WHERE
+                public_key = $1
+                AND log_id = $2
+                AND CAST(seq_num AS NUMERIC) >= CAST($3 AS NUMERIC)
+                AND CAST(seq_num AS NUMERIC) <= CAST($4 AS NUMERIC)
+            ORDER BY
+                CAST(seq_num AS NUMERIC)
+            ",
+        )
+        .bind(public_key.to_string())
+        .bind(log_id.as_u64().to_string())
+        .bind(from.as_u64().to_string())
+        .bind(to.as_u64().to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| EntryStorageError::Custom(e.to_string()))?;
+
+        Ok(entries.into_iter().map(|row| row.into()).collect())
+    }
+}
+
+// Helper method for inserting an entry into the `entries` table.
+//
+// Returns an error if the insertion doesn't result in exactly one affected row.
+pub(super) async fn insert_entry<'e, E>(
+    executor: E,
+    entry: &impl AsEntry,
+    encoded_entry: &EncodedEntry,
+    encoded_operation: Option<&EncodedOperation>,
+) -> Result<(), EntryStorageError>
+where
+    E: Executor<'e, Database = Any>,
+{
+    let insert_entry_result = query(
+        "
+        INSERT INTO
+            entries (
+                public_key,
+                entry_bytes,
+                entry_hash,
+                log_id,
+                payload_bytes,
+                payload_hash,
+                seq_num
+            )
+        VALUES
+            ($1, $2, $3, $4, $5, $6, $7)
+        ",
+    )
+    .bind(entry.public_key().to_string())
+    .bind(encoded_entry.into_hex())
+    .bind(encoded_entry.hash().as_str())
+    .bind(entry.log_id().as_u64().to_string())
+    .bind(encoded_operation.map(|payload| payload.to_string()))
+    .bind(entry.payload_hash().as_str())
+    .bind(entry.seq_num().as_u64().to_string())
+    .execute(executor)
+    .await
+    .map_err(|e| EntryStorageError::Custom(e.to_string()))?;
+
+    if insert_entry_result.rows_affected() != 1 {
+        return Err(EntryStorageError::Custom(format!(
+            "Unexpected number of inserts occured for entry with id: {}",
+            encoded_entry.hash()
+        )));
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -592,4 +784,32 @@ mod tests {
             assert_eq!(entries.len(), 11);
         });
     }
+
+    #[rstest]
+    fn get_entries_range(
+        #[from(populate_store_config)]
+        #[with(20, 2, vec![KeyPair::new()])]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|node: TestNode| async move {
+            // Populate the store with some entries and operations but DON'T materialise any resulting documents.
+            let _ = populate_store(&node.context.store, &config).await;
+            let public_key = config.authors[0].public_key();
+            let entries = node
+                .context
+                .store
+                .get_entries_range(
+                    &public_key,
+                    &LogId::default(),
+                    &SeqNum::new(5).unwrap(),
+                    &SeqNum::new(10).unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(entries.len(), 6);
+            assert_eq!(entries.first().unwrap().seq_num(), &SeqNum::new(5).unwrap());
+            assert_eq!(entries.last().unwrap().seq_num(), &SeqNum::new(10).unwrap());
+        });
+    }
 }
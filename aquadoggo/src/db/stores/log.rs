@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use p2panda_rs::document::DocumentId;
 use p2panda_rs::entry::LogId;
@@ -7,8 +9,9 @@ use p2panda_rs::identity::PublicKey;
 use p2panda_rs::schema::SchemaId;
 use p2panda_rs::storage_provider::error::LogStorageError;
 use p2panda_rs::storage_provider::traits::LogStore;
-use sqlx::{query, query_scalar};
+use sqlx::{query, query_as, query_scalar, Any, Executor};
 
+use crate::db::models::LogRow;
 use crate::db::SqlStore;
 
 /// Implementation of `LogStore` trait which is required when constructing a
@@ -27,30 +30,7 @@ impl LogStore for SqlStore {
         schema: &SchemaId,
         document: &DocumentId,
     ) -> Result<bool, LogStorageError> {
-        let rows_affected = query(
-            "
-            INSERT INTO
-                logs (
-                    public_key,
-                    log_id,
-                    document,
-                    schema
-                )
-            VALUES
-                ($1, $2, $3, $4)
-            ON CONFLICT DO NOTHING
-            ",
-        )
-        .bind(public_key.to_string())
-        .bind(log_id.as_u64().to_string())
-        .bind(document.as_str())
-        .bind(schema.to_string())
-        .execute(&self.pool)
-        .await
-        .map_err(|e| LogStorageError::Custom(e.to_string()))?
-        .rows_affected();
-
-        Ok(rows_affected == 1)
+        insert_log(&self.pool, log_id, public_key, schema, document).await
     }
 
     /// Get a log from storage
@@ -121,6 +101,202 @@ impl LogStore for SqlStore {
     }
 }
 
+impl SqlStore {
+    /// Get all logs of a public key, ordered by their log id.
+    ///
+    /// Returns the log id together with the id of the document and schema of each log.
+    pub async fn get_logs(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Vec<(LogId, DocumentId, SchemaId)>, LogStorageError> {
+        let log_rows = query_as::<_, LogRow>(
+            "
+            SELECT
+                public_key,
+                log_id,
+                document,
+                schema
+            FROM
+                logs
+            WHERE
+                public_key = $1
+            ORDER BY
+                CAST(log_id AS NUMERIC)
+            ",
+        )
+        .bind(public_key.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| LogStorageError::Custom(e.to_string()))?;
+
+        Ok(log_rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.log_id
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                    row.document
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                    row.schema
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                )
+            })
+            .collect())
+    }
+
+    /// Get the log ids the given public keys use for the given documents.
+    ///
+    /// Public keys which have no log for a document yet are not contained in the returned map.
+    pub async fn get_log_ids(
+        &self,
+        public_keys: &[PublicKey],
+        document_ids: &[DocumentId],
+    ) -> Result<HashMap<(PublicKey, DocumentId), LogId>, LogStorageError> {
+        if public_keys.is_empty() || document_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let public_keys_str = public_keys
+            .iter()
+            .map(|public_key| format!("'{public_key}'"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let document_ids_str = document_ids
+            .iter()
+            .map(|document_id| format!("'{}'", document_id.as_str()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let log_rows = query_as::<_, LogRow>(&format!(
+            "
+            SELECT
+                public_key,
+                log_id,
+                document,
+                schema
+            FROM
+                logs
+            WHERE
+                public_key IN ({public_keys_str})
+                AND document IN ({document_ids_str})
+            ",
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| LogStorageError::Custom(e.to_string()))?;
+
+        Ok(log_rows
+            .into_iter()
+            .map(|row| {
+                (
+                    (
+                        row.public_key
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                        row.document
+                            .parse()
+                            .expect("Values stored in the database are valid"),
+                    ),
+                    row.log_id
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                )
+            })
+            .collect())
+    }
+
+    /// Determines the latest `LogId` of every given public key.
+    ///
+    /// Public keys without any known logs are not contained in the returned map.
+    pub async fn get_latest_log_ids(
+        &self,
+        public_keys: &[PublicKey],
+    ) -> Result<HashMap<PublicKey, LogId>, LogStorageError> {
+        if public_keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let public_keys_str = public_keys
+            .iter()
+            .map(|public_key| format!("'{public_key}'"))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let rows: Vec<(String, String)> = query_as(&format!(
+            "
+            SELECT
+                public_key,
+                CAST(MAX(CAST(log_id AS NUMERIC)) AS TEXT) as log_id
+            FROM
+                logs
+            WHERE
+                public_key IN ({public_keys_str})
+            GROUP BY
+                public_key
+            ",
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| LogStorageError::Custom(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(public_key, log_id)| {
+                (
+                    public_key
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                    log_id
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                )
+            })
+            .collect())
+    }
+}
+
+// Helper method for inserting a log into the `logs` table.
+//
+// Returns `false` if this log already existed.
+pub(super) async fn insert_log<'e, E>(
+    executor: E,
+    log_id: &LogId,
+    public_key: &PublicKey,
+    schema: &SchemaId,
+    document: &DocumentId,
+) -> Result<bool, LogStorageError>
+where
+    E: Executor<'e, Database = Any>,
+{
+    let rows_affected = query(
+        "
+        INSERT INTO
+            logs (
+                public_key,
+                log_id,
+                document,
+                schema
+            )
+        VALUES
+            ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        ",
+    )
+    .bind(public_key.to_string())
+    .bind(log_id.as_u64().to_string())
+    .bind(document.as_str())
+    .bind(schema.to_string())
+    .execute(executor)
+    .await
+    .map_err(|e| LogStorageError::Custom(e.to_string()))?
+    .rows_affected();
+
+    Ok(rows_affected == 1)
+}
+
 #[cfg(test)]
 mod tests {
     use p2panda_rs::document::{DocumentId, DocumentViewId};
@@ -207,4 +383,32 @@ mod tests {
             }
         });
     }
+
+    #[rstest]
+    fn get_logs(#[from(public_key)] public_key: PublicKey, #[from(schema_id)] schema_id: SchemaId) {
+        test_runner(move |node: TestNode| async move {
+            let logs = node.context.store.get_logs(&public_key).await.unwrap();
+            assert!(logs.is_empty());
+
+            let mut document_ids = Vec::new();
+            for n in (0..12).rev() {
+                let document_id = random_document_id();
+                node.context
+                    .store
+                    .insert_log(&LogId::new(n), &public_key, &schema_id, &document_id)
+                    .await
+                    .unwrap();
+                document_ids.insert(0, document_id);
+            }
+
+            // Logs are ordered by their log id
+            let logs = node.context.store.get_logs(&public_key).await.unwrap();
+            assert_eq!(logs.len(), 12);
+            for (n, (log_id, document_id, log_schema_id)) in logs.into_iter().enumerate() {
+                assert_eq!(log_id, LogId::new(n as u64));
+                assert_eq!(document_id, document_ids[n]);
+                assert_eq!(log_schema_id, schema_id);
+            }
+        });
+    }
 }
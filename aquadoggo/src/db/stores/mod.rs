@@ -2,14 +2,21 @@
 
 //! Implementations of all `p2panda-rs` defined storage provider traits and additionally
 //! `aquadoggo` specific interfaces.
+mod aggregate;
+mod batch;
 mod blob;
+mod cache;
 pub mod document;
 mod entry;
 mod log;
 mod operation;
 mod query;
 mod schema;
+mod search;
 mod task;
 
+pub use aggregate::{AggregateGroup, FieldAggregate};
+pub use batch::BatchStore;
+pub use cache::{QueryCache, QueryCacheMetrics};
 pub use operation::OperationCursor;
-pub use query::{PaginationCursor, PaginationData, Query, RelationList};
+pub use query::{AsOf, PaginationCursor, PaginationData, Query, RelationList, ReverseRelation};
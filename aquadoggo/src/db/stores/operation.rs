@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
 use async_trait::async_trait;
@@ -11,12 +12,12 @@ use p2panda_rs::operation::{Operation, OperationId};
 use p2panda_rs::schema::SchemaId;
 use p2panda_rs::storage_provider::error::OperationStorageError;
 use p2panda_rs::storage_provider::traits::OperationStore;
-use sqlx::{query, query_as, query_scalar, Any};
+use sqlx::{query, query_as, query_scalar, Any, Transaction};
 
 use crate::db::models::utils::{parse_operation_rows, parse_value_to_string_vec};
 use crate::db::models::{DocumentViewFieldRow, OperationFieldsJoinedRow};
 use crate::db::types::StorageOperation;
-use crate::db::SqlStore;
+use crate::db::{local_timestamp, SqlStore};
 
 /// Implementation of `OperationStore` trait which is required when constructing a
 /// `StorageProvider`.
@@ -243,6 +244,93 @@ fn group_and_parse_operation_rows(
 }
 
 impl SqlStore {
+    /// Get the ids of the documents the given operations are part of.
+    ///
+    /// Operations which were not found are not contained in the returned map.
+    pub async fn get_document_ids_by_operation_ids(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Result<HashMap<OperationId, DocumentId>, OperationStorageError> {
+        if operation_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let operation_ids_str = operation_ids
+            .iter()
+            .map(|operation_id| format!("'{}'", operation_id.as_str()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let rows: Vec<(String, String)> = query_as(&format!(
+            "
+            SELECT
+                operations_v1.operation_id,
+                operations_v1.document_id
+            FROM
+                operations_v1
+            WHERE
+                operations_v1.operation_id IN ({operation_ids_str})
+            ",
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(operation_id, document_id)| {
+                (
+                    operation_id
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                    document_id
+                        .parse()
+                        .expect("Values stored in the database are valid"),
+                )
+            })
+            .collect())
+    }
+
+    /// Get the ids of the given documents which contain a DELETE operation.
+    pub async fn get_deleted_document_ids(
+        &self,
+        document_ids: &[DocumentId],
+    ) -> Result<HashSet<DocumentId>, OperationStorageError> {
+        if document_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let document_ids_str = document_ids
+            .iter()
+            .map(|document_id| format!("'{}'", document_id.as_str()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let rows: Vec<String> = query_scalar(&format!(
+            "
+            SELECT DISTINCT
+                operations_v1.document_id
+            FROM
+                operations_v1
+            WHERE
+                operations_v1.document_id IN ({document_ids_str})
+                AND operations_v1.action = 'delete'
+            ",
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|document_id| {
+                document_id
+                    .parse()
+                    .expect("Values stored in the database are valid")
+            })
+            .collect())
+    }
+
     /// Returns ids of operations which have not been processed by `reduce` task yet.
     pub async fn get_unindexed_operation_ids(
         &self,
@@ -311,80 +399,15 @@ impl SqlStore {
             .await
             .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
 
-        // Construct query for inserting operation an row, execute it and check exactly one row was
-        // affected.
-        query(
-            "
-            INSERT INTO
-                operations_v1 (
-                    public_key,
-                    document_id,
-                    operation_id,
-                    action,
-                    schema_id,
-                    previous,
-                    sorted_index
-                )
-            VALUES
-                ($1, $2, $3, $4, $5, $6, $7)
-            ",
+        insert_operation(
+            &mut tx,
+            id,
+            public_key,
+            operation,
+            document_id,
+            sorted_index,
         )
-        .bind(public_key.to_string())
-        .bind(document_id.as_str())
-        .bind(id.as_str())
-        .bind(operation.action().as_str())
-        .bind(operation.schema_id().to_string())
-        .bind(
-            operation
-                .previous()
-                .map(|document_view_id| document_view_id.to_string()),
-        )
-        .bind(sorted_index)
-        .execute(&mut tx)
-        .await
-        .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
-
-        let mut results = Vec::new();
-        if let Some(fields) = operation.fields() {
-            for (name, value) in fields.iter() {
-                // If the value is a relation_list or pinned_relation_list we need to insert a new
-                // field row for every item in the list. Here we collect these items and return
-                // them in a vector. If this operation value is anything except for the above list
-                // types, we will return a vec containing a single item.
-                let db_values = parse_value_to_string_vec(value);
-
-                for (index, db_value) in db_values.into_iter().enumerate() {
-                    let cursor = OperationCursor::new(index, name, id);
-
-                    let result = query(
-                        "
-                        INSERT INTO
-                            operation_fields_v1 (
-                                operation_id,
-                                name,
-                                field_type,
-                                value,
-                                list_index,
-                                cursor
-                            )
-                        VALUES
-                            ($1, $2, $3, $4, $5, $6)
-                        ",
-                    )
-                    .bind(id.as_str().to_owned())
-                    .bind(name.to_owned())
-                    .bind(value.field_type().to_string())
-                    .bind(db_value)
-                    .bind(index as i32)
-                    .bind(cursor.to_string())
-                    .execute(&mut tx)
-                    .await
-                    .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
-
-                    results.push(result);
-                }
-            }
-        };
+        .await?;
 
         // Commit the transaction.
         tx.commit()
@@ -395,6 +418,96 @@ impl SqlStore {
     }
 }
 
+// Helper method for inserting an operation into the `operations_v1` table and its fields into the
+// `operation_fields_v1` table.
+pub(super) async fn insert_operation(
+    tx: &mut Transaction<'_, Any>,
+    id: &OperationId,
+    public_key: &PublicKey,
+    operation: &impl AsOperation,
+    document_id: &DocumentId,
+    sorted_index: Option<i32>,
+) -> Result<(), OperationStorageError> {
+    // Construct query for inserting operation an row, execute it and check exactly one row was
+    // affected.
+    query(
+        "
+        INSERT INTO
+            operations_v1 (
+                public_key,
+                document_id,
+                operation_id,
+                action,
+                schema_id,
+                previous,
+                sorted_index,
+                received_at
+            )
+        VALUES
+            ($1, $2, $3, $4, $5, $6, $7, $8)
+        ",
+    )
+    .bind(public_key.to_string())
+    .bind(document_id.as_str())
+    .bind(id.as_str())
+    .bind(operation.action().as_str())
+    .bind(operation.schema_id().to_string())
+    .bind(
+        operation
+            .previous()
+            .map(|document_view_id| document_view_id.to_string()),
+    )
+    .bind(sorted_index)
+    .bind(local_timestamp())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
+
+    let mut results = Vec::new();
+    if let Some(fields) = operation.fields() {
+        for (name, value) in fields.iter() {
+            // If the value is a relation_list or pinned_relation_list we need to insert a new
+            // field row for every item in the list. Here we collect these items and return
+            // them in a vector. If this operation value is anything except for the above list
+            // types, we will return a vec containing a single item.
+            let db_values = parse_value_to_string_vec(value);
+
+            for (index, db_value) in db_values.into_iter().enumerate() {
+                let cursor = OperationCursor::new(index, name, id);
+
+                let result = query(
+                    "
+                    INSERT INTO
+                        operation_fields_v1 (
+                            operation_id,
+                            name,
+                            field_type,
+                            value,
+                            list_index,
+                            cursor
+                        )
+                    VALUES
+                        ($1, $2, $3, $4, $5, $6)
+                    ",
+                )
+                .bind(id.as_str().to_owned())
+                .bind(name.to_owned())
+                .bind(value.field_type().to_string())
+                .bind(db_value)
+                .bind(index as i32)
+                .bind(cursor.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| OperationStorageError::FatalStorageError(e.to_string()))?;
+
+                results.push(result);
+            }
+        }
+    };
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct OperationCursor(String);
 
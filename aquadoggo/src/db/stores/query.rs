@@ -7,19 +7,22 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 use anyhow::bail;
-use p2panda_rs::document::DocumentViewId;
+use p2panda_rs::document::{DocumentId, DocumentViewId};
 use p2panda_rs::operation::OperationValue;
-use p2panda_rs::schema::{FieldName, Schema, SchemaId};
+use p2panda_rs::schema::{FieldName, FieldType, Schema, SchemaId};
 use p2panda_rs::storage_provider::error::DocumentStorageError;
+use sqlx::any::AnyKind;
 use sqlx::query::QueryAs;
 use sqlx::query_as;
 
 use crate::db::models::utils::parse_document_view_field_rows;
 use crate::db::models::{DocumentViewFieldRow, QueryRow};
 use crate::db::query::{
-    ApplicationFields, Cursor, Direction, Field, Filter, FilterBy, FilterSetting, LowerBound,
-    MetaField, Order, Pagination, PaginationField, Select, UpperBound,
+    ApplicationFields, Cursor, Direction, Field, Filter, FilterBy, FilterGroup, FilterSetting,
+    LowerBound, MetaField, Order, Pagination, PaginationField, Select, UpperBound,
 };
+use crate::db::stores::cache::{query_cache_key, query_schema_ids, CachedResult};
+use crate::db::stores::search::{search_match_sql, search_query, search_rank_sql};
 use crate::db::stores::OperationCursor;
 use crate::db::types::StorageDocument;
 use crate::db::{Pool, SqlStore};
@@ -61,6 +64,26 @@ impl RelationList {
     }
 }
 
+/// Configure query to only select documents which relate to another document in one of their
+/// fields.
+#[derive(Debug, Clone)]
+pub struct ReverseRelation {
+    /// Id of the document the selected documents relate to.
+    pub document_id: DocumentId,
+
+    /// Field which contains the (pinned) relation or relation list values.
+    pub field: FieldName,
+}
+
+impl ReverseRelation {
+    pub fn new(document_id: &DocumentId, field: &str) -> Self {
+        Self {
+            document_id: document_id.to_owned(),
+            field: field.to_string(),
+        }
+    }
+}
+
 /// Cursor aiding pagination, represented as a base58-encoded string.
 ///
 /// The encoding ensures that the cursor stays "opaque", API consumers to not read any further
@@ -186,6 +209,27 @@ where
     pub select: Select,
     pub filter: Filter,
     pub order: Order,
+
+    /// Optional full-text search text, only documents containing all of its words are returned.
+    pub search: Option<String>,
+
+    /// Optionally select only documents relating to another document.
+    pub reverse_relation: Option<ReverseRelation>,
+
+    /// Optionally query the document views which were current at this point in the history of
+    /// the node instead of the latest ones. Not supported for relation lists.
+    pub as_of: Option<AsOf>,
+}
+
+/// Point in the history of the node at which document views were current.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsOf {
+    /// Local time of the node in seconds since UNIX epoch.
+    Timestamp(i64),
+
+    /// Index of an operation in the order the node received them, see
+    /// `SqlStore::get_latest_operation_index`.
+    OperationIndex(i64),
 }
 
 impl<C> Query<C>
@@ -203,13 +247,16 @@ where
             select: select.clone(),
             filter: filter.clone(),
             order: order.clone(),
+            search: None,
+            reverse_relation: None,
+            as_of: None,
         }
     }
 }
 
 /// Helper method to determine the field type of the given field by looking at the schema and
 /// derive a SQL type cast function from it.
-fn typecast_field_sql(
+pub(super) fn typecast_field_sql(
     sql_field: &str,
     field_name: &str,
     schema: &Schema,
@@ -230,10 +277,10 @@ fn typecast_field_sql(
         .unwrap_or_else(|| panic!("Field '{}' not given in Schema", field_name));
 
     match field_type {
-        p2panda_rs::schema::FieldType::Integer => {
+        FieldType::Integer => {
             format!("CAST ({sql_field} AS INTEGER)")
         }
-        p2panda_rs::schema::FieldType::Float => {
+        FieldType::Float => {
             format!("CAST ({sql_field} AS REAL)")
         }
         // All other types (booleans, relations, etc.) we keep as strings. We can not convert
@@ -250,7 +297,7 @@ fn typecast_field_sql(
 
 /// Values to bind to SQL query.
 #[derive(Debug)]
-enum BindArgument {
+pub(super) enum BindArgument {
     String(String),
     Integer(i64),
     Float(f64),
@@ -391,106 +438,300 @@ fn concatenate_sql(items: &[Option<String>]) -> String {
         .join(", ")
 }
 
+/// Returns SQL condition for a single filter setting.
+fn filter_setting_sql(
+    filter_setting: &FilterSetting,
+    schema: &Schema,
+    args: &mut Vec<BindArgument>,
+) -> Option<String> {
+    match &filter_setting.field {
+        Field::Meta(MetaField::Owner) => {
+            let filter_cmp = cmp_sql("operations_v1.public_key", filter_setting, args);
+
+            Some(format!(
+                r#"
+                EXISTS (
+                    SELECT
+                        operations_v1.public_key
+                    FROM
+                        operations_v1
+                    WHERE
+                        operations_v1.operation_id = documents.document_id
+                        AND
+                            {filter_cmp}
+                )
+                "#
+            ))
+        }
+        Field::Meta(MetaField::Edited) => {
+            if let FilterBy::Element(OperationValue::Boolean(filter_value)) = filter_setting.by {
+                // Convert the boolean value manually here since we're dealing with native
+                // boolean values instead of operation field value strings
+                let edited_flag = if filter_value { "true" } else { "false" };
+                Some(format!("is_edited = {edited_flag}"))
+            } else {
+                None
+            }
+        }
+        Field::Meta(MetaField::Deleted) => {
+            if let FilterBy::Element(OperationValue::Boolean(filter_value)) = filter_setting.by {
+                // Convert the boolean value manually here since we're dealing with native
+                // boolean values instead of operation field value strings
+                let deleted_flag = if filter_value { "true" } else { "false" };
+                Some(format!("documents.is_deleted = {deleted_flag}"))
+            } else {
+                None
+            }
+        }
+        Field::Meta(MetaField::DocumentId) => {
+            Some(cmp_sql("documents.document_id", filter_setting, args))
+        }
+        // Relevance is only known in the context of a search and can not be filtered
+        Field::Meta(MetaField::Relevance) => None,
+        Field::Meta(MetaField::ReceivedAt) => Some(cmp_sql(
+            &received_at_sql("documents.document_id"),
+            filter_setting,
+            args,
+        )),
+        Field::Meta(MetaField::UpdatedAt) => Some(cmp_sql(
+            &updated_at_sql("documents.document_view_id"),
+            filter_setting,
+            args,
+        )),
+        Field::Meta(MetaField::DocumentViewId) => {
+            Some(cmp_sql("documents.document_view_id", filter_setting, args))
+        }
+        Field::Field(field_name) => {
+            let field_sql =
+                typecast_field_sql("operation_fields_v1.value", field_name, schema, true);
+            let filter_cmp = cmp_sql(&field_sql, filter_setting, args);
+
+            Some(format!(
+                r#"
+                EXISTS (
+                    SELECT
+                        operation_fields_v1.value
+                    FROM
+                        document_view_fields AS document_view_fields_subquery
+                        JOIN operation_fields_v1
+                            ON
+                                document_view_fields_subquery.operation_id = operation_fields_v1.operation_id
+                            AND
+                                document_view_fields_subquery.name = operation_fields_v1.name
+                    WHERE
+                        -- Match document_view_fields of this subquery with the parent one
+                        document_view_fields.document_view_id = document_view_fields_subquery.document_view_id
+
+                        -- Check if this document view fullfils this filter
+                        AND operation_fields_v1.name = '{field_name}'
+                        AND
+                            {filter_cmp}
+                        AND
+                            operation_fields_v1.operation_id = document_view_fields_subquery.operation_id
+                )
+                "#
+            ))
+        }
+    }
+}
+
+/// Returns SQL conditions for all filter settings and nested groups of a filter, all of them need
+/// to match.
+///
+/// Bind arguments are added in the same order as the conditions appear in the query.
+fn filter_conditions_sql(
+    filter: &Filter,
+    schema: &Schema,
+    args: &mut Vec<BindArgument>,
+) -> Vec<String> {
+    let mut conditions: Vec<String> = filter
+        .iter()
+        .filter_map(|filter_setting| filter_setting_sql(filter_setting, schema, args))
+        .collect();
+
+    for group in filter.groups() {
+        let (filters, operator) = match group {
+            FilterGroup::And(filters) => (filters.iter().collect::<Vec<&Filter>>(), "AND"),
+            FilterGroup::Or(filters) => (filters.iter().collect::<Vec<&Filter>>(), "OR"),
+            FilterGroup::Not(filter) => {
+                let condition = filter_group_sql(filter, schema, args);
+                conditions.push(format!("NOT {condition}"));
+                continue;
+            }
+            FilterGroup::Related(field_name, related_schema, related_filter) => {
+                conditions.push(filter_related_sql(
+                    field_name,
+                    schema,
+                    related_schema,
+                    related_filter,
+                    args,
+                ));
+                continue;
+            }
+        };
+
+        // Empty groups don't restrict the results
+        if filters.is_empty() {
+            continue;
+        }
+
+        let group_sql = filters
+            .into_iter()
+            .map(|filter| filter_group_sql(filter, schema, args))
+            .collect::<Vec<String>>()
+            .join(&format!(" {operator} "));
+
+        conditions.push(format!("({group_sql})"));
+    }
+
+    conditions
+}
+
+/// Returns SQL condition matching documents whose (pinned) relation field points at a document
+/// which matches the given filter.
+fn filter_related_sql(
+    field_name: &str,
+    schema: &Schema,
+    related_schema: &Schema,
+    related_filter: &Filter,
+    args: &mut Vec<BindArgument>,
+) -> String {
+    // Relations point at the latest view of a document, pinned relations at a specific view which
+    // is not necessarily the latest one. For the latter we construct a "documents" table with the
+    // pinned views to make sure the nested filter is applied on the right document fields
+    let (documents_sql, relation_sql) = match schema.fields().get(field_name) {
+        Some(FieldType::PinnedRelation(_)) => (
+            r#"
+            (
+                SELECT
+                    document_views.document_id,
+                    document_views.document_view_id,
+                    documents.schema_id,
+                    documents.is_deleted
+                FROM
+                    document_views
+                    JOIN documents
+                        ON document_views.document_id = documents.document_id
+            ) AS documents
+            "#,
+            "documents.document_view_id",
+        ),
+        _ => ("documents", "documents.document_id"),
+    };
+
+    // The related document's fields are selected with the usual table names, nested filter
+    // conditions will refer to them instead of to the parent document
+    let filter_sql = filter_group_sql(related_filter, related_schema, args);
+    let related_schema_id = related_schema.id();
+
+    format!(
+        r#"
+        EXISTS (
+            SELECT
+                operation_fields_v1_related.value
+            FROM
+                document_view_fields AS document_view_fields_related
+                JOIN operation_fields_v1 AS operation_fields_v1_related
+                    ON
+                        document_view_fields_related.operation_id = operation_fields_v1_related.operation_id
+                    AND
+                        document_view_fields_related.name = operation_fields_v1_related.name
+            WHERE
+                -- Match document_view_fields of this subquery with the parent one
+                document_view_fields_related.document_view_id = document_view_fields.document_view_id
+                AND operation_fields_v1_related.name = '{field_name}'
+
+                -- Check if the related document fullfils the nested filter
+                AND EXISTS (
+                    SELECT
+                        documents.document_id
+                    FROM
+                        {documents_sql}
+                        JOIN document_view_fields
+                            ON documents.document_view_id = document_view_fields.document_view_id
+                    WHERE
+                        {relation_sql} = operation_fields_v1_related.value
+                        AND documents.schema_id = '{related_schema_id}'
+                        AND {filter_sql}
+                )
+        )
+        "#
+    )
+}
+
+/// Returns one SQL condition for a nested filter, wrapped in parentheses.
+fn filter_group_sql(filter: &Filter, schema: &Schema, args: &mut Vec<BindArgument>) -> String {
+    let conditions = filter_conditions_sql(filter, schema, args);
+
+    if conditions.is_empty() {
+        // An empty filter matches every document
+        "(1 = 1)".to_string()
+    } else {
+        format!("({})", conditions.join(" AND "))
+    }
+}
+
 /// Returns SQL to filter documents.
 ///
 /// Since filters are the only place which can contain untrusted user values we are building the
 /// SQL query with positional arguments and bind the values to them. This helps sanitization of all
 /// values and prevents potential SQL injection attacks.
-fn where_filter_sql(filter: &Filter, schema: &Schema) -> (String, Vec<BindArgument>) {
+///
+/// A full-text search query is always bound as the first argument, this allows referring to it
+/// in other parts of the query.
+pub(super) fn where_filter_sql(
+    filter: &Filter,
+    search: Option<&str>,
+    schema: &Schema,
+    kind: AnyKind,
+) -> (String, Vec<BindArgument>) {
     let mut args: Vec<BindArgument> = Vec::new();
 
-    let sql = filter
-        .iter()
-        .filter_map(|filter_setting| {
-            match &filter_setting.field {
-                Field::Meta(MetaField::Owner) => {
-                    let filter_cmp = cmp_sql("operations_v1.public_key", filter_setting, &mut args);
+    let search_sql = search.map(|text| {
+        args.push(BindArgument::String(search_query(kind, text)));
+        format!("AND {}", search_match_sql(kind, "$1"))
+    });
 
-                    Some(format!(
-                        r#"
-                        AND EXISTS (
-                            SELECT
-                                operations_v1.public_key
-                            FROM
-                                operations_v1
-                            WHERE
-                                operations_v1.operation_id = documents.document_id
-                                AND
-                                    {filter_cmp}
-                        )
-                        "#
-                    ))
-                }
-                Field::Meta(MetaField::Edited) => {
-                    if let FilterBy::Element(OperationValue::Boolean(filter_value)) =
-                        filter_setting.by
-                    {
-                        // Convert the boolean value manually here since we're dealing with native
-                        // boolean values instead of operation field value strings
-                        let edited_flag = if filter_value { "true" } else { "false" };
-                        Some(format!("AND is_edited = {edited_flag}"))
-                    } else {
-                        None
-                    }
-                }
-                Field::Meta(MetaField::Deleted) => {
-                    if let FilterBy::Element(OperationValue::Boolean(filter_value)) =
-                        filter_setting.by
-                    {
-                        // Convert the boolean value manually here since we're dealing with native
-                        // boolean values instead of operation field value strings
-                        let deleted_flag = if filter_value { "true" } else { "false" };
-                        Some(format!("AND documents.is_deleted = {deleted_flag}"))
-                    } else {
-                        None
-                    }
-                }
-                Field::Meta(MetaField::DocumentId) => Some(format!(
-                    "AND {}",
-                    cmp_sql("documents.document_id", filter_setting, &mut args)
-                )),
-                Field::Meta(MetaField::DocumentViewId) => Some(format!(
-                    "AND {}",
-                    cmp_sql("documents.document_view_id", filter_setting, &mut args)
-                )),
-                Field::Field(field_name) => {
-                    let field_sql = typecast_field_sql("operation_fields_v1.value", field_name, schema, true);
-                    let filter_cmp = cmp_sql(&field_sql, filter_setting, &mut args);
-
-                    Some(format!(
-                        r#"
-                        AND EXISTS (
-                            SELECT
-                                operation_fields_v1.value
-                            FROM
-                                document_view_fields AS document_view_fields_subquery
-                                JOIN operation_fields_v1
-                                    ON
-                                        document_view_fields_subquery.operation_id = operation_fields_v1.operation_id
-                                    AND
-                                        document_view_fields_subquery.name = operation_fields_v1.name
-                            WHERE
-                                -- Match document_view_fields of this subquery with the parent one
-                                document_view_fields.document_view_id = document_view_fields_subquery.document_view_id
-
-                                -- Check if this document view fullfils this filter
-                                AND operation_fields_v1.name = '{field_name}'
-                                AND
-                                    {filter_cmp}
-                                AND
-                                    operation_fields_v1.operation_id = document_view_fields_subquery.operation_id
-                        )
-                        "#
-                    ))
-                }
-            }
-        })
+    let filter_sql = filter_conditions_sql(filter, schema, &mut args)
+        .into_iter()
+        .map(|condition| format!("AND {condition}"))
         .collect::<Vec<String>>()
         .join("\n");
 
+    let sql = match search_sql {
+        Some(search_sql) => format!("{search_sql}\n{filter_sql}"),
+        None => filter_sql,
+    };
+
     (sql, args)
 }
 
+/// Returns the document view id of the document the given operation cursor points at.
+async fn cursor_document_view_id(
+    pool: &Pool,
+    operation_cursor: &OperationCursor,
+) -> Result<String, DocumentStorageError> {
+    // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the same result
+    let document_view_id: (String,) = query_as(&format!(
+        r#"
+        SELECT
+            document_view_fields.document_view_id
+        FROM
+            operation_fields_v1
+            JOIN document_view_fields
+                ON operation_fields_v1.operation_id = document_view_fields.operation_id
+        WHERE
+            operation_fields_v1.cursor = '{operation_cursor}'
+        LIMIT 1
+        "#
+    ))
+    .fetch_one(pool)
+    .await
+    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+    Ok(document_view_id.0)
+}
+
 /// Generate SQL for cursor-based pagination.
 ///
 /// Read more about cursor-based pagination here:
@@ -519,6 +760,7 @@ fn where_filter_sql(filter: &Filter, schema: &Schema) -> (String, Vec<BindArgume
 /// an optimization over the fact that cursors sometimes point at values which stay the same for
 /// each SQL sub-SELECT, so we just do this query once and pass the values over into the "main"
 /// query.
+#[allow(clippy::too_many_arguments)]
 async fn where_pagination_sql(
     pool: &Pool,
     bind_args: &mut Vec<BindArgument>,
@@ -527,27 +769,34 @@ async fn where_pagination_sql(
     list: Option<&RelationList>,
     schema: &Schema,
     order: &Order,
+    search: Option<&str>,
 ) -> Result<String, DocumentStorageError> {
     // No pagination cursor was given
-    if pagination.after.is_none() {
-        return Ok("".to_string());
-    }
+    let cursor = match pagination.cursor() {
+        Some(cursor) => cursor,
+        None => return Ok("".to_string()),
+    };
 
     // Ignore pagination if we're in a relation list query and the cursor does not match the parent
     // document view id
-    if let (Some(relation_list), Some(cursor)) = (list, pagination.after.as_ref()) {
+    if let Some(relation_list) = list {
         if Some(&relation_list.root_view_id) != cursor.root_view_id.as_ref() {
             return Ok("".to_string());
         }
     }
 
-    // Unwrap as we know now that a cursor exists at this point
-    let cursor = pagination
-        .after
-        .as_ref()
-        .expect("Expect cursor to be set at this point");
     let operation_cursor = &cursor.operation_cursor;
 
+    // When paginating backwards we're looking at all items _before_ the cursor, the results are
+    // queried in reversed order and flipped again at the end
+    let (cmp_next, cmp_previous) = if pagination.is_backward() {
+        ("<", ">")
+    } else {
+        (">", "<")
+    };
+
+    // Tie-breaker for documents with the same value in the ordered field, following the same
+    // logic as in `order_sql`
     let cursor_sql = match list {
         Some(_) => {
             let root_cursor = cursor
@@ -555,28 +804,51 @@ async fn where_pagination_sql(
                 .as_ref()
                 .expect("Expect root_operation_cursor to be set when querying relation list");
 
-            format!("operation_fields_v1_list.cursor > '{root_cursor}'")
+            let list_cursor_sql =
+                format!("operation_fields_v1_list.cursor {cmp_next} '{root_cursor}'");
+
+            if fields.len() > 1 {
+                let document_view_id = cursor_document_view_id(pool, operation_cursor).await?;
+
+                format!(
+                    r#"
+                    (
+                        documents.document_view_id {cmp_next} '{document_view_id}'
+                        OR
+                        (
+                            documents.document_view_id = '{document_view_id}'
+                            AND
+                                {list_cursor_sql}
+                        )
+                    )
+                    "#
+                )
+            } else {
+                list_cursor_sql
+            }
         }
         None => {
-            format!("operation_fields_v1.cursor > '{operation_cursor}'")
+            if fields.len() > 1 {
+                // Every document has multiple rows here, we compare the document view ids to not
+                // accidentially select only some fields of the document the cursor points at
+                let document_view_id = cursor_document_view_id(pool, operation_cursor).await?;
+                format!("documents.document_view_id {cmp_next} '{document_view_id}'")
+            } else {
+                format!("operation_fields_v1.cursor {cmp_next} '{operation_cursor}'")
+            }
         }
     };
 
-    let cmp_direction = match order.direction {
-        Direction::Ascending => ">",
-        Direction::Descending => "<",
-    };
-
-    match &order.field {
-        // 1. No ordering has been chosen by the client
-        // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
-        //
-        // We order by default which is either:
-        //
-        // a) Simply paginate over document view ids, from the view where the cursor points at
-        // b) If we're in a relation list, we paginate over the list index values, from where the
-        // root_cursor points at
-        None => match list {
+    // 1. No ordering has been chosen by the client
+    // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
+    //
+    // We order by default which is either:
+    //
+    // a) Simply paginate over document view ids, from the view where the cursor points at
+    // b) If we're in a relation list, we paginate over the list index values, from where the
+    // root_cursor points at
+    if order.is_empty() {
+        return match list {
             None => {
                 // Collection of all documents following a certain schema id:
                 //
@@ -591,30 +863,10 @@ async fn where_pagination_sql(
                 //
                 // -> Select document_view_id at cursor 0xc2
                 // -> Show results from document_view_id > 0x01
-                let cmp_value_pre = format!(
-                    r#"
-                    SELECT
-                        document_view_fields.document_view_id
-                    FROM
-                        operation_fields_v1
-                        JOIN document_view_fields
-                            ON operation_fields_v1.operation_id = document_view_fields.operation_id
-                    WHERE
-                        operation_fields_v1.cursor = '{operation_cursor}'
-                    LIMIT 1
-                    "#
-                );
-
-                // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the same
-                // result
-                let document_view_id: (String,) = query_as(&cmp_value_pre)
-                    .fetch_one(pool)
-                    .await
-                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+                let document_view_id = cursor_document_view_id(pool, operation_cursor).await?;
 
                 Ok(format!(
-                    "AND documents.document_view_id > '{}'",
-                    document_view_id.0
+                    "AND documents.document_view_id {cmp_next} '{document_view_id}'"
                 ))
             }
             Some(_) => {
@@ -661,207 +913,314 @@ async fn where_pagination_sql(
 
                 // List indexes are always unique so we can simply just compare them like that
                 Ok(format!(
-                    "AND operation_fields_v1_list.list_index > {}",
+                    "AND operation_fields_v1_list.list_index {cmp_next} {}",
                     list_index.0
                 ))
             }
-        },
-
-        // 2. Ordering over a meta field
-        // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
-        //
-        // We select the meta data from the document the cursor points at and use it to paginate
-        // over.
-        Some(Field::Meta(meta_field)) => {
-            let (cmp_value_pre, cmp_field) = match meta_field {
-                MetaField::DocumentId => {
-                    // Select document_id of operation where the cursor points at
-                    let cmp_value = format!(
-                        r#"
-                        SELECT
-                            operations_v1.document_id
-                        FROM
-                            operation_fields_v1
-                            JOIN operations_v1
-                                ON operation_fields_v1.operation_id = operations_v1.operation_id
-                        WHERE
-                            operation_fields_v1.cursor = '{operation_cursor}'
-                        LIMIT 1
-                        "#
-                    );
-
-                    (cmp_value, "documents.document_id")
-                }
-                MetaField::DocumentViewId => {
-                    // Select document_view_id of operation where the cursor points at
-                    let cmp_value = format!(
-                        r#"
-                        SELECT
-                            document_view_fields.document_view_id
-                        FROM
-                            operation_fields_v1
-                            JOIN document_view_fields
-                                ON operation_fields_v1.operation_id = document_view_fields.operation_id
-                        WHERE
-                            operation_fields_v1.cursor = '{operation_cursor}'
-                        LIMIT 1
-                        "#
-                    );
-
-                    (cmp_value, "documents.document_view_id")
-                }
-                MetaField::Owner | MetaField::Edited | MetaField::Deleted => {
-                    // @TODO: See issue: https://github.com/p2panda/aquadoggo/issues/326
-                    todo!("Not implemented");
-                }
-            };
+        };
+    }
 
-            // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the same
-            // result
-            let cmp_value: (String,) = query_as(&cmp_value_pre)
-                .fetch_one(pool)
-                .await
-                .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
-            let cmp_value = format!("'{}'", cmp_value.0);
+    // 2. Ordering over one or more fields
+    // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
+    //
+    // For every ordered field we select the value of the document the cursor points at. A
+    // document comes after the cursor if the value of its first ordered field is greater, or if
+    // it is equal and the value of the second ordered field is greater and so on. If all values
+    // are equal, we fall back to the tie-breaker.
+    //
+    // Cursors are always pointing at the last field of a document. In the following example
+    // this is the "message" field. Since we've ordered the results by "timestamp" we need to
+    // manually find out what this value is by tracing back the cursor to the document to that
+    // ordered field.
+    //
+    // Collection of all documents, ordered by "timestamp", following a certain schema id:
+    //
+    // -------------------------------------------------
+    // document_id | view_id | field_name | ... | cursor
+    // -------------------------------------------------
+    // 0x01        | 0x01    | username   | ... | 0xa0
+    // 0x01        | 0x01    | timestamp  | ... | 0x72 <-- Compare
+    // 0x01        | 0x01    | message    | ... | 0xc2 <-- Select
+    // 0x02        | 0x02    | username   | ... | 0x06
+    // 0x02        | 0x02    | timestamp  | ... | 0x8f
+    // 0x02        | 0x02    | message    | ... | 0x8b
+    // -------------------------------------------------
+    //
+    // -> Select document_view_id where cursor points at
+    // -> Find "timestamp" field of that document and use this value
+    // -> Show results from "timestamp" value > other "timestamp" values
+    let kind = pool.any_kind();
+
+    let mut conditions = Vec::new();
+    let mut equal_values = Vec::new();
+
+    for clause in order.iter() {
+        let cmp_value = match &clause.field {
+            // We calculate the relevance of the document the cursor points at
+            Field::Meta(MetaField::Relevance) => {
+                let search = search.ok_or_else(|| {
+                    DocumentStorageError::FatalStorageError(
+                        "Ordering by relevance requires a search query".into(),
+                    )
+                })?;
 
-            if fields.is_empty() && list.is_none() {
-                // If:
-                //
-                // 1. No application fields have been selected by the client
-                // 2. We're not paginating over a relation list
-                //
-                // .. then we can do a simplification of the query, since the results were grouped
-                // by documents and we can be sure that for each row the document id and view are
-                // unique.
-                Ok(format!("AND {cmp_field} {cmp_direction} {cmp_value}"))
-            } else {
-                // Cursor-based pagination
-                Ok(format!(
+                let cmp_value_pre = format!(
                     r#"
-                    AND (
-                        {cmp_field} {cmp_direction} {cmp_value}
-                        OR
-                        (
-                            {cmp_field} = {cmp_value}
-                            AND
-                                {cursor_sql}
+                    SELECT
+                        {}
+                    "#,
+                    search_rank_sql(
+                        kind,
+                        "$1",
+                        &format!(
+                            r#"
+                            (
+                                SELECT
+                                    operations_v1.document_id
+                                FROM
+                                    operation_fields_v1
+                                    JOIN operations_v1
+                                        ON operation_fields_v1.operation_id = operations_v1.operation_id
+                                WHERE
+                                    operation_fields_v1.cursor = '{operation_cursor}'
+                                LIMIT 1
+                            )
+                            "#
                         )
                     )
-                    "#
-                ))
-            }
-        }
-
-        // 3. Ordering over an application field
-        // ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
-        //
-        // Cursors are always pointing at the last field of a document. In the following example
-        // this is the "message" field. Since we've ordered the results by "timestamp" we need to
-        // manually find out what this value is by tracing back the cursor to the document to that
-        // ordered field.
-        //
-        // Collection of all documents, ordered by "timestamp", following a certain schema id:
-        //
-        // -------------------------------------------------
-        // document_id | view_id | field_name | ... | cursor
-        // -------------------------------------------------
-        // 0x01        | 0x01    | username   | ... | 0xa0
-        // 0x01        | 0x01    | timestamp  | ... | 0x72 <-- Compare
-        // 0x01        | 0x01    | message    | ... | 0xc2 <-- Select
-        // 0x02        | 0x02    | username   | ... | 0x06
-        // 0x02        | 0x02    | timestamp  | ... | 0x8f
-        // 0x02        | 0x02    | message    | ... | 0x8b
-        // -------------------------------------------------
-        //
-        // -> Select document_view_id where cursor points at
-        // -> Find "timestamp" field of that document and use this value
-        // -> Show results from "timestamp" value > other "timestamp" values
-        Some(Field::Field(order_field_name)) => {
-            // Select the value we want to compare with from the document the cursor is pointing
-            // at. This is the value which we also order the whole results by
-            let cmp_value_pre = format!(
-                r#"
-                SELECT
-                    operation_fields_v1.value
-
-                FROM
-                    operation_fields_v1
-                    LEFT JOIN
-                        document_view_fields
-                        ON document_view_fields.operation_id = operation_fields_v1.operation_id
-
-                WHERE
-                    document_view_fields.document_view_id = (
-                        SELECT
-                            document_view_fields.document_view_id
-
-                        FROM
-                            operation_fields_v1
-                            LEFT JOIN
-                                document_view_fields
-                                ON document_view_fields.operation_id = operation_fields_v1.operation_id
-
-                        WHERE
-                            operation_fields_v1.cursor = '{operation_cursor}'
-
-                        LIMIT 1
-                    )
-                    AND operation_fields_v1.name = '{order_field_name}'
+                );
 
-                LIMIT 1
-                "#
-            );
+                // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the
+                // same result
+                let cmp_value: (f64,) = query_as(&cmp_value_pre)
+                    .bind(search_query(kind, search))
+                    .fetch_one(pool)
+                    .await
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+                bind_args.push(BindArgument::Float(cmp_value.0));
 
-            // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the same
-            // result.
-            //
-            // The returned value is added to the bindable arguments array since this is untrusted
-            // user content.
-            let operation_fields_value: (String,) = query_as(&cmp_value_pre)
-                .fetch_one(pool)
-                .await
-                .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
-            bind_args.push(BindArgument::String(operation_fields_value.0));
-
-            // Necessary casting for operation values of different type
-            let cmp_field =
-                typecast_field_sql("operation_fields_v1.value", order_field_name, schema, false);
-
-            let bind_arg_marker = typecast_field_sql(
-                &format!("${}", bind_args.len()),
-                order_field_name,
-                schema,
-                false,
-            );
+                format!("${}", bind_args.len())
+            }
 
-            // Cursor-based pagination
-            Ok(format!(
-                r#"
-                AND EXISTS (
+            // We select the meta data from the document the cursor points at
+            Field::Meta(MetaField::DocumentId) => {
+                // Select document_id of operation where the cursor points at
+                let cmp_value_pre = format!(
+                    r#"
                     SELECT
-                        operation_fields_v1.value
+                        operations_v1.document_id
                     FROM
                         operation_fields_v1
-                        LEFT JOIN document_view_fields
-                            ON operation_fields_v1.operation_id = document_view_fields.operation_id
+                        JOIN operations_v1
+                            ON operation_fields_v1.operation_id = operations_v1.operation_id
                     WHERE
-                        operation_fields_v1.name = '{order_field_name}'
-                        AND
-                            document_view_fields.document_view_id = documents.document_view_id
-                        AND
-                            (
-                                {cmp_field} {cmp_direction} {bind_arg_marker}
-                                OR
-                                (
-                                    {cmp_field} = {bind_arg_marker}
-                                    AND
-                                        {cursor_sql}
-                                )
-                            )
-                )
-                "#
-            ))
-        }
+                        operation_fields_v1.cursor = '{operation_cursor}'
+                    LIMIT 1
+                    "#
+                );
+
+                // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the
+                // same result
+                let cmp_value: (String,) = query_as(&cmp_value_pre)
+                    .fetch_one(pool)
+                    .await
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+                format!("'{}'", cmp_value.0)
+            }
+            Field::Meta(MetaField::DocumentViewId) => {
+                let document_view_id = cursor_document_view_id(pool, operation_cursor).await?;
+                format!("'{document_view_id}'")
+            }
+            // We select the timestamp of the document the cursor points at
+            Field::Meta(MetaField::ReceivedAt) | Field::Meta(MetaField::UpdatedAt) => {
+                let document_view_id = cursor_document_view_id(pool, operation_cursor).await?;
+
+                let timestamp_sql = if clause.field == Field::Meta(MetaField::ReceivedAt) {
+                    received_at_sql(&format!(
+                        r#"
+                        (
+                            SELECT
+                                document_views.document_id
+                            FROM
+                                document_views
+                            WHERE
+                                document_views.document_view_id = '{document_view_id}'
+                        )
+                        "#
+                    ))
+                } else {
+                    updated_at_sql(&format!("'{document_view_id}'"))
+                };
+
+                // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the
+                // same result
+                let cmp_value: (i64,) = query_as(&format!("SELECT {timestamp_sql}"))
+                    .fetch_one(pool)
+                    .await
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+
+                cmp_value.0.to_string()
+            }
+            Field::Meta(MetaField::Owner)
+            | Field::Meta(MetaField::Edited)
+            | Field::Meta(MetaField::Deleted) => {
+                // @TODO: See issue: https://github.com/p2panda/aquadoggo/issues/326
+                todo!("Not implemented");
+            }
+
+            // Select the value we want to compare with from the document the cursor is pointing
+            // at. This is the value which we also order the whole results by
+            Field::Field(order_field_name) => {
+                let cmp_value_pre = format!(
+                    r#"
+                    SELECT
+                        operation_fields_v1.value
+
+                    FROM
+                        operation_fields_v1
+                        LEFT JOIN
+                            document_view_fields
+                            ON document_view_fields.operation_id = operation_fields_v1.operation_id
+
+                    WHERE
+                        document_view_fields.document_view_id = (
+                            SELECT
+                                document_view_fields.document_view_id
+
+                            FROM
+                                operation_fields_v1
+                                LEFT JOIN
+                                    document_view_fields
+                                    ON document_view_fields.operation_id = operation_fields_v1.operation_id
+
+                            WHERE
+                                operation_fields_v1.cursor = '{operation_cursor}'
+
+                            LIMIT 1
+                        )
+                        AND operation_fields_v1.name = '{order_field_name}'
+
+                    LIMIT 1
+                    "#
+                );
+
+                // Make a "pre" SQL query to avoid duplicate sub SELECT's always returning the
+                // same result.
+                //
+                // The returned value is added to the bindable arguments array since this is
+                // untrusted user content.
+                let operation_fields_value: (String,) = query_as(&cmp_value_pre)
+                    .fetch_one(pool)
+                    .await
+                    .map_err(|err| DocumentStorageError::FatalStorageError(err.to_string()))?;
+                bind_args.push(BindArgument::String(operation_fields_value.0));
+
+                // Necessary casting for operation values of different type
+                typecast_field_sql(
+                    &format!("${}", bind_args.len()),
+                    order_field_name,
+                    schema,
+                    false,
+                )
+            }
+        };
+
+        // Compare against the same value we're ordering the results by
+        let cmp_field = order_field_sql(&clause.field, schema, kind);
+
+        let cmp_direction = match clause.direction {
+            Direction::Ascending => cmp_next,
+            Direction::Descending => cmp_previous,
+        };
+
+        let mut condition = equal_values.clone();
+        condition.push(format!("{cmp_field} {cmp_direction} {cmp_value}"));
+        conditions.push(condition.join(" AND "));
+
+        equal_values.push(format!("{cmp_field} = {cmp_value}"));
+    }
+
+    // Cursor-based pagination, documents can have the same values in all ordered fields
+    equal_values.push(cursor_sql);
+    conditions.push(equal_values.join(" AND "));
+
+    Ok(format!(
+        r#"
+        AND (
+            ({})
+        )
+        "#,
+        conditions.join(")\n            OR\n            (")
+    ))
+}
+
+/// Returns SQL expression selecting the local time when the node received the operation which
+/// created the given document.
+fn received_at_sql(document_id_sql: &str) -> String {
+    format!(
+        r#"
+        (
+            SELECT
+                operations_v1.received_at
+            FROM
+                operations_v1
+            WHERE
+                operations_v1.operation_id = {document_id_sql}
+        )
+        "#
+    )
+}
+
+/// Returns SQL expression selecting the local time when the node materialized the given document
+/// view.
+fn updated_at_sql(document_view_id_sql: &str) -> String {
+    format!(
+        r#"
+        (
+            SELECT
+                document_views.materialized_at
+            FROM
+                document_views
+            WHERE
+                document_views.document_view_id = {document_view_id_sql}
+        )
+        "#
+    )
+}
+
+/// Returns SQL expression of the value a field is ordered by.
+fn order_field_sql(field: &Field, schema: &Schema, kind: AnyKind) -> String {
+    match field {
+        Field::Meta(MetaField::DocumentId) => "documents.document_id".to_string(),
+        Field::Meta(MetaField::DocumentViewId) => "documents.document_view_id".to_string(),
+        Field::Meta(MetaField::Owner) => "owner".to_string(),
+        Field::Meta(MetaField::Edited) => "is_edited".to_string(),
+        Field::Meta(MetaField::Deleted) => "is_deleted".to_string(),
+        // The search query is always bound as the first argument
+        Field::Meta(MetaField::Relevance) => search_rank_sql(kind, "$1", "documents.document_id"),
+        Field::Meta(MetaField::ReceivedAt) => received_at_sql("documents.document_id"),
+        Field::Meta(MetaField::UpdatedAt) => updated_at_sql("documents.document_view_id"),
+        Field::Field(field_name) => {
+            format!(
+                r#"
+                (
+                    SELECT
+                        {}
+                    FROM
+                        operation_fields_v1
+                        LEFT JOIN document_view_fields
+                            ON operation_fields_v1.operation_id = document_view_fields.operation_id
+                    WHERE
+                        operation_fields_v1.name = '{}'
+                        AND document_view_fields.document_view_id = documents.document_view_id
+                    LIMIT 1
+                )
+                "#,
+                typecast_field_sql("operation_fields_v1.value", field_name, schema, false),
+                field_name,
+            )
+        }
     }
 }
 
@@ -870,50 +1229,36 @@ fn order_sql(
     schema: &Schema,
     list: Option<&RelationList>,
     fields: &ApplicationFields,
+    backward: bool,
+    kind: AnyKind,
 ) -> String {
+    // When paginating backwards we reverse the whole ordering to get the rows right before the
+    // cursor first, the results are brought into the requested order again after the query
+    let (asc, desc) = if backward {
+        ("DESC", "ASC")
+    } else {
+        ("ASC", "DESC")
+    };
+
     // Create custom ordering if query set one
-    let custom = order
-        .field
-        .as_ref()
-        .map(|field| match field {
-            Field::Meta(MetaField::DocumentId) => "documents.document_id".to_string(),
-            Field::Meta(MetaField::DocumentViewId) => "documents.document_view_id".to_string(),
-            Field::Meta(MetaField::Owner) => "owner".to_string(),
-            Field::Meta(MetaField::Edited) => "is_edited".to_string(),
-            Field::Meta(MetaField::Deleted) => "is_deleted".to_string(),
-            Field::Field(field_name) => {
-                format!(
-                    r#"
-                    (
-                        SELECT
-                            {}
-                        FROM
-                            operation_fields_v1
-                            LEFT JOIN document_view_fields
-                                ON operation_fields_v1.operation_id = document_view_fields.operation_id
-                        WHERE
-                            operation_fields_v1.name = '{}'
-                            AND document_view_fields.document_view_id = documents.document_view_id
-                        LIMIT 1
-                    )
-                    "#,
-                    typecast_field_sql("operation_fields_v1.value", field_name, schema, false),
-                    field_name,
-                )
-            }
-        })
-        .map(|field| {
-            let direction = match order.direction {
-                Direction::Ascending => "ASC",
-                Direction::Descending => "DESC",
+    let custom: Vec<Option<String>> = order
+        .iter()
+        .map(|clause| {
+            let direction = match clause.direction {
+                Direction::Ascending => asc,
+                Direction::Descending => desc,
             };
 
-            format!("{field} {direction}")
-        });
+            Some(format!(
+                "{} {direction}",
+                order_field_sql(&clause.field, schema, kind)
+            ))
+        })
+        .collect();
 
     // .. and by relation list index, in case we're querying one
-    let list_sql = match (&order.field, list) {
-        (None, Some(_)) => Some("operation_fields_v1_list.list_index ASC".to_string()),
+    let list_sql = match list {
+        Some(_) if order.is_empty() => Some(format!("operation_fields_v1_list.list_index {asc}")),
         _ => None,
     };
 
@@ -921,9 +1266,10 @@ fn order_sql(
     // the rows to documents correctly at the end
     let id_sql = {
         if fields.len() > 1 {
-            match order.field {
-                Some(Field::Meta(MetaField::DocumentViewId)) => None,
-                _ => Some("documents.document_view_id ASC".to_string()),
+            if order.contains(&Field::Meta(MetaField::DocumentViewId)) {
+                None
+            } else {
+                Some(format!("documents.document_view_id {asc}"))
             }
         } else {
             // Skip this step when only _one_ field was selected for this query to not mess with
@@ -942,11 +1288,13 @@ fn order_sql(
     // On top we sort always by the unique operation cursor in case the previous order value is
     // equal between two rows
     let cursor_sql = match list {
-        Some(_) => Some("operation_fields_v1_list.cursor ASC".to_string()),
-        None => Some("operation_fields_v1.cursor ASC".to_string()),
+        Some(_) => Some(format!("operation_fields_v1_list.cursor {asc}")),
+        None => Some(format!("operation_fields_v1.cursor {asc}")),
     };
 
-    let order = concatenate_sql(&[custom, list_sql, id_sql, cursor_sql]);
+    let mut order_vec = custom;
+    order_vec.append(&mut vec![list_sql, id_sql, cursor_sql]);
+    let order = concatenate_sql(&order_vec);
 
     format!("ORDER BY {order}")
 }
@@ -957,9 +1305,9 @@ where
 {
     // We multiply the value by the number of fields we selected. If no fields have been selected
     // we just take the page size as is
-    let page_size = pagination.first.get() * std::cmp::max(1, fields.len() as u64);
+    let page_size = pagination.page_size().get() * std::cmp::max(1, fields.len() as u64);
 
-    // ... and add + 1 for the "has next page" flag
+    // ... and add + 1 for the "has next page" or "has previous page" flag
     (page_size, format!("LIMIT {page_size} + 1"))
 }
 
@@ -975,6 +1323,47 @@ fn where_fields_sql(fields: &ApplicationFields) -> String {
     }
 }
 
+fn where_reverse_relation_sql(reverse_relation: Option<&ReverseRelation>) -> String {
+    match reverse_relation {
+        Some(reverse_relation) => {
+            let field_name = &reverse_relation.field;
+            let document_id = &reverse_relation.document_id;
+
+            // Relations point at the document id, pinned relations at any view of that document
+            format!(
+                r#"
+                AND EXISTS (
+                    SELECT
+                        operation_fields_v1_relation.value
+                    FROM
+                        document_view_fields AS document_view_fields_relation
+                        JOIN operation_fields_v1 AS operation_fields_v1_relation
+                            ON
+                                document_view_fields_relation.operation_id = operation_fields_v1_relation.operation_id
+                            AND
+                                document_view_fields_relation.name = operation_fields_v1_relation.name
+                    WHERE
+                        document_view_fields_relation.document_view_id = documents.document_view_id
+                        AND operation_fields_v1_relation.name = '{field_name}'
+                        AND (
+                            operation_fields_v1_relation.value = '{document_id}'
+                            OR operation_fields_v1_relation.value IN (
+                                SELECT
+                                    document_views.document_view_id
+                                FROM
+                                    document_views
+                                WHERE
+                                    document_views.document_id = '{document_id}'
+                            )
+                        )
+                )
+                "#
+            )
+        }
+        None => "".to_string(),
+    }
+}
+
 fn select_edited_sql(select: &Select) -> Option<String> {
     if select.fields.contains(&Field::Meta(MetaField::Edited)) {
         let sql = r#"
@@ -1048,7 +1437,11 @@ fn select_cursor_sql(list: Option<&RelationList>) -> Vec<Option<String>> {
     }
 }
 
-fn where_sql(schema: &Schema, fields: &ApplicationFields, list: Option<&RelationList>) -> String {
+pub(super) fn where_sql(
+    schema: &Schema,
+    fields: &ApplicationFields,
+    list: Option<&RelationList>,
+) -> String {
     let schema_id = schema.id();
 
     // Only one row per field: restrict relation lists to first list item
@@ -1099,7 +1492,48 @@ fn where_sql(schema: &Schema, fields: &ApplicationFields, list: Option<&Relation
     }
 }
 
-fn from_sql(list: Option<&RelationList>) -> String {
+pub(super) fn from_sql(
+    list: Option<&RelationList>,
+    document_view_ids: Option<&[DocumentViewId]>,
+) -> String {
+    // When querying historic document views we replace the "documents" table, which only contains
+    // the latest views, with the given views
+    if let (None, Some(document_view_ids)) = (list, document_view_ids) {
+        let document_view_ids_sql = document_view_ids
+            .iter()
+            .map(|document_view_id| format!("'{document_view_id}'"))
+            .collect::<Vec<String>>();
+
+        // Make sure to not return anything when there were no views yet, empty lists are not
+        // supported in SQL
+        let where_sql = if document_view_ids_sql.is_empty() {
+            "1 = 0".to_string()
+        } else {
+            format!(
+                "document_views.document_view_id IN ({})",
+                document_view_ids_sql.join(", ")
+            )
+        };
+
+        return format!(
+            r#"
+            (
+                SELECT
+                    document_views.document_id,
+                    document_views.document_view_id,
+                    document_views.schema_id,
+                    false AS is_deleted
+                FROM
+                    document_views
+                WHERE
+                    {where_sql}
+            ) AS documents
+            JOIN document_view_fields
+                ON documents.document_view_id = document_view_fields.document_view_id
+            "#
+        );
+    }
+
     match list {
         Some(relation_list) => {
             let filter_sql = match relation_list.list_type {
@@ -1142,11 +1576,44 @@ impl SqlStore {
     ///
     /// When passing a `list` configuration the query will run against the documents of a (pinned
     /// and unpinned) relation list instead.
+    ///
+    /// Results are served from the query cache when the same query was sent before and no
+    /// document of the involved schemas changed since.
     pub async fn query(
         &self,
         schema: &Schema,
         args: &Query<PaginationCursor>,
         list: Option<&RelationList>,
+    ) -> Result<QueryResponse, DocumentStorageError> {
+        let key = query_cache_key("query", schema.id(), args, list);
+        let generation = self.query_cache.generation();
+
+        if let Some(CachedResult::Query(response)) = self.query_cache.get(&key) {
+            return Ok(response);
+        }
+
+        // Historic document views are only looked up once and shared with the total count
+        let document_view_ids = self.as_of_document_view_ids(schema, args, list).await?;
+        let response = self
+            .query_uncached(schema, args, list, document_view_ids.as_deref())
+            .await?;
+
+        self.query_cache.insert(
+            key,
+            query_schema_ids(schema.id(), &args.filter),
+            CachedResult::Query(response.clone()),
+            generation,
+        );
+
+        Ok(response)
+    }
+
+    async fn query_uncached(
+        &self,
+        schema: &Schema,
+        args: &Query<PaginationCursor>,
+        list: Option<&RelationList>,
+        document_view_ids: Option<&[DocumentViewId]>,
     ) -> Result<QueryResponse, DocumentStorageError> {
         // Get all selected application fields from query
         let application_fields = args.select.application_fields();
@@ -1174,11 +1641,14 @@ impl SqlStore {
 
         let select = concatenate_sql(&select_vec);
 
-        let from = from_sql(list);
+        let from = from_sql(list, document_view_ids);
 
+        let kind = self.pool.any_kind();
         let where_ = where_sql(schema, &application_fields, list);
         let and_fields = where_fields_sql(&application_fields);
-        let (and_filters, mut bind_args) = where_filter_sql(&args.filter, schema);
+        let and_reverse_relation = where_reverse_relation_sql(args.reverse_relation.as_ref());
+        let (and_filters, mut bind_args) =
+            where_filter_sql(&args.filter, args.search.as_deref(), schema, kind);
         let and_pagination = where_pagination_sql(
             &self.pool,
             &mut bind_args,
@@ -1187,10 +1657,18 @@ impl SqlStore {
             list,
             schema,
             &args.order,
+            args.search.as_deref(),
         )
         .await?;
 
-        let order = order_sql(&args.order, schema, list, &application_fields);
+        let order = order_sql(
+            &args.order,
+            schema,
+            list,
+            &application_fields,
+            args.pagination.is_backward(),
+            kind,
+        );
         let (page_size, limit) = limit_sql(&args.pagination, &application_fields);
 
         let sea_quel = format!(
@@ -1240,6 +1718,9 @@ impl SqlStore {
                 -- .. and further filter the data by custom parameters
                 {and_filters}
 
+                -- .. only select documents relating to another document, if requested
+                {and_reverse_relation}
+
                 -- Lastly we batch all results into smaller chunks via cursor pagination
                 {and_pagination}
 
@@ -1264,7 +1745,7 @@ impl SqlStore {
 
         // We always query one more row than needed to find out if there's more data. This
         // information aids the user during pagination
-        let has_next_page = if rows.len() as u64 > page_size {
+        let has_more_rows = if rows.len() as u64 > page_size {
             // Remove that last row from final results if it exists
             rows.pop();
             true
@@ -1272,13 +1753,25 @@ impl SqlStore {
             false
         };
 
+        // Rows of backwards pagination were queried in reversed order, bring them back into the
+        // requested order. There are always more items on the other side of the cursor
+        let (has_next_page, has_previous_page) = if args.pagination.is_backward() {
+            rows.reverse();
+            (args.pagination.before.is_some(), has_more_rows)
+        } else {
+            (has_more_rows, args.pagination.after.is_some())
+        };
+
         // Calculate the total number of (filtered) documents in this query
         let total_count = if args
             .pagination
             .fields
             .contains(&PaginationField::TotalCount)
         {
-            Some(self.count(schema, args, list).await?)
+            Some(
+                self.count_with_document_view_ids(schema, args, list, Some(document_view_ids))
+                    .await?,
+            )
         } else {
             None
         };
@@ -1306,9 +1799,7 @@ impl SqlStore {
         let pagination_data = PaginationData {
             total_count,
             has_next_page,
-            // @TODO: Implement backwards pagination, see related issue:
-            // https://github.com/p2panda/aquadoggo/issues/325
-            has_previous_page: false,
+            has_previous_page,
             start_cursor,
             end_cursor,
         };
@@ -1322,12 +1813,67 @@ impl SqlStore {
         schema: &Schema,
         args: &Query<PaginationCursor>,
         list: Option<&RelationList>,
+    ) -> Result<u64, DocumentStorageError> {
+        self.count_with_document_view_ids(schema, args, list, None)
+            .await
+    }
+
+    /// Query number of documents in filtered collection, optionally with the already looked up
+    /// historic document views of this query.
+    async fn count_with_document_view_ids(
+        &self,
+        schema: &Schema,
+        args: &Query<PaginationCursor>,
+        list: Option<&RelationList>,
+        document_view_ids: Option<Option<&[DocumentViewId]>>,
+    ) -> Result<u64, DocumentStorageError> {
+        let key = query_cache_key("count", schema.id(), args, list);
+        let generation = self.query_cache.generation();
+
+        if let Some(CachedResult::Count(count)) = self.query_cache.get(&key) {
+            return Ok(count);
+        }
+
+        let count = match document_view_ids {
+            Some(document_view_ids) => {
+                self.count_uncached(schema, args, list, document_view_ids)
+                    .await?
+            }
+            None => {
+                let document_view_ids = self.as_of_document_view_ids(schema, args, list).await?;
+                self.count_uncached(schema, args, list, document_view_ids.as_deref())
+                    .await?
+            }
+        };
+
+        self.query_cache.insert(
+            key,
+            query_schema_ids(schema.id(), &args.filter),
+            CachedResult::Count(count),
+            generation,
+        );
+
+        Ok(count)
+    }
+
+    async fn count_uncached(
+        &self,
+        schema: &Schema,
+        args: &Query<PaginationCursor>,
+        list: Option<&RelationList>,
+        document_view_ids: Option<&[DocumentViewId]>,
     ) -> Result<u64, DocumentStorageError> {
         let application_fields = args.select.application_fields();
 
-        let from = from_sql(list);
+        let from = from_sql(list, document_view_ids);
         let where_ = where_sql(schema, &application_fields, list);
-        let (and_filters, bind_args) = where_filter_sql(&args.filter, schema);
+        let and_reverse_relation = where_reverse_relation_sql(args.reverse_relation.as_ref());
+        let (and_filters, bind_args) = where_filter_sql(
+            &args.filter,
+            args.search.as_deref(),
+            schema,
+            self.pool.any_kind(),
+        );
 
         let count_sql = format!(
             r#"
@@ -1346,6 +1892,7 @@ impl SqlStore {
             WHERE
                 {where_}
                 {and_filters}
+                {and_reverse_relation}
 
             -- Group application fields by name to make sure we get actual number of documents
             GROUP BY operation_fields_v1.name
@@ -1369,6 +1916,25 @@ impl SqlStore {
 
         Ok(count)
     }
+
+    /// Returns the ids of the document views which were current at the point in history given in
+    /// the query, if any.
+    ///
+    /// Relation lists always contain the latest views of their documents, historic views are only
+    /// looked up for root queries.
+    async fn as_of_document_view_ids(
+        &self,
+        schema: &Schema,
+        args: &Query<PaginationCursor>,
+        list: Option<&RelationList>,
+    ) -> Result<Option<Vec<DocumentViewId>>, DocumentStorageError> {
+        match (list, args.as_of) {
+            (None, Some(as_of)) => Ok(Some(
+                self.get_document_view_ids_as_of(schema.id(), as_of).await?,
+            )),
+            _ => Ok(None),
+        }
+    }
 }
 
 /// Merges all operation fields from the database into documents.
@@ -1502,28 +2068,30 @@ mod tests {
     use std::num::NonZeroU64;
 
     use p2panda_rs::document::traits::AsDocument;
-    use p2panda_rs::document::DocumentViewId;
+    use p2panda_rs::document::{DocumentId, DocumentViewId};
     use p2panda_rs::hash::Hash;
     use p2panda_rs::identity::KeyPair;
-    use p2panda_rs::operation::{OperationValue, PinnedRelationList};
+    use p2panda_rs::operation::{OperationValue, PinnedRelationList, Relation};
     use p2panda_rs::schema::{FieldType, Schema, SchemaId};
     use p2panda_rs::storage_provider::traits::DocumentStore;
     use p2panda_rs::test_utils::fixtures::{key_pair, schema_id};
     use rstest::rstest;
+    use sqlx::{query, query_scalar};
 
     use crate::db::models::{OptionalOwner, QueryRow};
     use crate::db::query::{
-        Direction, Field, Filter, MetaField, Order, Pagination, PaginationField, Select,
+        Direction, Field, Filter, FilterGroup, MetaField, Order, Pagination, PaginationField,
+        Select,
     };
     use crate::db::stores::{OperationCursor, RelationList};
     use crate::db::types::StorageDocument;
     use crate::test_utils::{
-        add_document, add_schema, add_schema_and_documents, doggo_fields, doggo_schema,
-        populate_and_materialize, populate_store_config, test_runner, PopulateStoreConfig,
-        TestNode,
+        add_document, add_schema, add_schema_and_documents, delete_document, doggo_fields,
+        doggo_schema, populate_and_materialize, populate_store_config, test_runner,
+        update_document, PopulateStoreConfig, TestNode,
     };
 
-    use super::{convert_rows, PaginationCursor, Query};
+    use super::{convert_rows, AsOf, PaginationCursor, Query};
 
     fn get_document_value(document: &StorageDocument, field: &str) -> OperationValue {
         document
@@ -1817,6 +2385,19 @@ mod tests {
             "Oh, howdy, Pengi!".into(),
         ],
     )]
+    #[case::order_by_username_and_timestamp(
+        Order::new(&"username".into(), &Direction::Ascending)
+            .then(&"timestamp".into(), &Direction::Descending),
+        "message".into(),
+        vec![
+            "I am cute and very hungry".into(),
+            "How are you?".into(),
+            "Oh, howdy, Pengi!".into(),
+            "(°◇°) !!".into(),
+            "I miss Pengolina. How about you?".into(),
+            "Hello, Panda!".into(),
+        ],
+    )]
     fn pagination_over_ordered_fields(
         key_pair: KeyPair,
         #[case] order: Order,
@@ -1896,37 +2477,148 @@ mod tests {
     }
 
     #[rstest]
-    fn pagination_over_ordered_view_ids(key_pair: KeyPair) {
+    #[case::order_by_timestamp_asc(Order::new(&"timestamp".into(), &Direction::Ascending))]
+    #[case::order_by_timestamp_desc(Order::new(&"timestamp".into(), &Direction::Descending))]
+    #[case::order_by_username_with_duplicates(Order::new(
+        &"username".into(),
+        &Direction::Ascending
+    ))]
+    #[case::order_by_document_id(Order::new(
+        &Field::Meta(MetaField::DocumentId),
+        &Direction::Ascending
+    ))]
+    #[case::order_by_view_id_desc(Order::new(
+        &Field::Meta(MetaField::DocumentViewId),
+        &Direction::Descending
+    ))]
+    #[case::order_by_username_and_timestamp(
+        Order::new(&"username".into(), &Direction::Descending)
+            .then(&"timestamp".into(), &Direction::Ascending)
+    )]
+    fn backward_pagination_over_ordered_fields(key_pair: KeyPair, #[case] order: Order) {
         test_runner(|mut node: TestNode| async move {
-            let (schema, mut view_ids) = create_events_test_data(&mut node, &key_pair).await;
-            let view_ids_len = view_ids.len();
+            let (schema, view_ids) = create_chat_test_data(&mut node, &key_pair).await;
 
-            // Sort created documents by document view id, to compare to similarily sorted query
-            // results
-            view_ids.sort();
+            let pagination_fields = vec![
+                PaginationField::StartCursor,
+                PaginationField::EndCursor,
+                PaginationField::HasNextPage,
+                PaginationField::HasPreviousPage,
+            ];
 
-            let mut cursor: Option<PaginationCursor> = None;
+            let select = Select::new(&[
+                Field::Field("message".into()),
+                Field::Field("username".into()),
+                Field::Field("timestamp".into()),
+            ]);
 
-            let mut args = Query::new(
-                &Pagination::new(
-                    &NonZeroU64::new(1).unwrap(),
-                    cursor.as_ref(),
-                    &vec![
-                        PaginationField::TotalCount,
-                        PaginationField::EndCursor,
-                        PaginationField::HasNextPage,
-                    ],
-                ),
-                &Select::new(&[Field::Meta(MetaField::DocumentViewId)]),
+            // Get all documents in the expected order first
+            let args = Query::new(
+                &Pagination::new(&NonZeroU64::new(10).unwrap(), None, &pagination_fields),
+                &select,
                 &Filter::default(),
-                &Order::new(
-                    &Field::Meta(MetaField::DocumentViewId),
-                    &Direction::Ascending,
-                ),
+                &order,
             );
 
-            // Go through all pages, one document at a time
-            for (index, view_id) in view_ids.into_iter().enumerate() {
+            let (_, documents) = node
+                .context
+                .store
+                .query(&schema, &args, None)
+                .await
+                .expect("Query failed");
+
+            let expected: Vec<DocumentViewId> = documents
+                .iter()
+                .map(|(_, document)| document.view_id.clone())
+                .collect();
+            assert_eq!(expected.len(), view_ids.len());
+
+            // Go through all pages backwards, two documents at a time, starting from the end
+            let mut cursor: Option<PaginationCursor> = None;
+            let mut results: Vec<DocumentViewId> = Vec::new();
+
+            let mut args = Query::new(
+                &Pagination::new_backward(&NonZeroU64::new(2).unwrap(), None, &pagination_fields),
+                &select,
+                &Filter::default(),
+                &order,
+            );
+
+            for page in 0..3 {
+                args.pagination.before = cursor;
+
+                let (pagination_data, documents) = node
+                    .context
+                    .store
+                    .query(&schema, &args, None)
+                    .await
+                    .expect("Query failed");
+
+                assert_eq!(documents.len(), 2);
+                assert_eq!(pagination_data.has_previous_page, page < 2);
+                assert_eq!(pagination_data.has_next_page, page > 0);
+                assert_eq!(pagination_data.start_cursor.as_ref(), Some(&documents[0].0));
+                assert_eq!(pagination_data.end_cursor.as_ref(), Some(&documents[1].0));
+
+                // Documents of every page are still in the requested order
+                let page_view_ids = documents
+                    .iter()
+                    .map(|(_, document)| document.view_id.clone());
+                results.splice(0..0, page_view_ids);
+
+                cursor = pagination_data.start_cursor;
+            }
+
+            assert_eq!(results, expected);
+
+            // Query one last time after we paginated through everything
+            args.pagination.before = cursor;
+
+            let (pagination_data, documents) = node
+                .context
+                .store
+                .query(&schema, &args, None)
+                .await
+                .expect("Query failed");
+
+            assert!(!pagination_data.has_previous_page);
+            assert_eq!(pagination_data.start_cursor, None);
+            assert_eq!(documents.len(), 0);
+        });
+    }
+
+    #[rstest]
+    fn pagination_over_ordered_view_ids(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, mut view_ids) = create_events_test_data(&mut node, &key_pair).await;
+            let view_ids_len = view_ids.len();
+
+            // Sort created documents by document view id, to compare to similarily sorted query
+            // results
+            view_ids.sort();
+
+            let mut cursor: Option<PaginationCursor> = None;
+
+            let mut args = Query::new(
+                &Pagination::new(
+                    &NonZeroU64::new(1).unwrap(),
+                    cursor.as_ref(),
+                    &vec![
+                        PaginationField::TotalCount,
+                        PaginationField::EndCursor,
+                        PaginationField::HasNextPage,
+                    ],
+                ),
+                &Select::new(&[Field::Meta(MetaField::DocumentViewId)]),
+                &Filter::default(),
+                &Order::new(
+                    &Field::Meta(MetaField::DocumentViewId),
+                    &Direction::Ascending,
+                ),
+            );
+
+            // Go through all pages, one document at a time
+            for (index, view_id) in view_ids.into_iter().enumerate() {
                 args.pagination.after = cursor;
 
                 let (pagination_data, documents) = node
@@ -2403,6 +3095,418 @@ mod tests {
         });
     }
 
+    #[rstest]
+    fn filter_groups(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = create_events_test_data(&mut node, &key_pair).await;
+
+            let title = Field::new("title");
+            let ticket_price = Field::new("ticket_price");
+
+            // (title = "The Pandadoodle Flute Trio" OR ticket_price > 50) AND NOT (ticket_price < 5)
+            let mut first_alternative = Filter::new();
+            first_alternative.add(&title, &"The Pandadoodle Flute Trio".into());
+            let mut second_alternative = Filter::new();
+            second_alternative.add_gt(&ticket_price, &50.0.into());
+            let mut negated = Filter::new();
+            negated.add_lt(&ticket_price, &5.0.into());
+
+            let mut filter = Filter::default();
+            filter.add_group(FilterGroup::Or(vec![first_alternative, second_alternative]));
+            filter.add_group(FilterGroup::Not(Box::new(negated)));
+
+            let args = Query::new(
+                &Pagination::new(
+                    &NonZeroU64::new(10).unwrap(),
+                    None,
+                    &vec![PaginationField::TotalCount],
+                ),
+                &Select::new(&[title]),
+                &filter,
+                &Order::new(&ticket_price, &Direction::Ascending),
+            );
+
+            let (pagination_data, documents) = node
+                .context
+                .store
+                .query(&schema, &args, None)
+                .await
+                .expect("Query failed");
+
+            assert_eq!(pagination_data.total_count, Some(2));
+            assert_eq!(
+                documents
+                    .iter()
+                    .map(|(_, document)| get_document_value(document, "title"))
+                    .collect::<Vec<OperationValue>>(),
+                vec![
+                    "The Pandadoodle Flute Trio".into(),
+                    "Bamboo-Scrumble Rumba Night - Xmas special".into()
+                ]
+            );
+
+            // NOT (ticket_price < 11 OR ticket_price > 50)
+            let mut cheap = Filter::new();
+            cheap.add_lt(&ticket_price, &11.0.into());
+            let mut expensive = Filter::new();
+            expensive.add_gt(&ticket_price, &50.0.into());
+            let mut alternatives = Filter::new();
+            alternatives.add_group(FilterGroup::Or(vec![cheap, expensive]));
+
+            let mut filter = Filter::default();
+            filter.add_group(FilterGroup::Not(Box::new(alternatives)));
+
+            let args = Query::new(
+                &Pagination::default(),
+                &Select::default(),
+                &filter,
+                &Order::default(),
+            );
+
+            let count = node
+                .context
+                .store
+                .count(&schema, &args, None)
+                .await
+                .expect("Query failed");
+            assert_eq!(count, 2);
+        });
+    }
+
+    #[rstest]
+    fn order_and_filter_by_timestamps(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, view_ids) = create_events_test_data(&mut node, &key_pair).await;
+
+            // Overwrite the recorded timestamps to not depend on the clock during the test
+            for (view_id, received_at) in view_ids.iter().zip([300, 100, 500, 200, 400]) {
+                query("UPDATE operations_v1 SET received_at = $1 WHERE operation_id = $2")
+                    .bind(received_at)
+                    .bind(view_id.graph_tips()[0].as_str())
+                    .execute(&node.context.store.pool)
+                    .await
+                    .unwrap();
+            }
+            query("UPDATE document_views SET materialized_at = 50 WHERE document_view_id = $1")
+                .bind(view_ids[3].to_string())
+                .execute(&node.context.store.pool)
+                .await
+                .unwrap();
+
+            let received_at = Field::Meta(MetaField::ReceivedAt);
+            let mut args = Query::new(
+                &Pagination::new(
+                    &NonZeroU64::new(2).unwrap(),
+                    None,
+                    &vec![PaginationField::EndCursor, PaginationField::HasNextPage],
+                ),
+                &Select::new(&[Field::new("title")]),
+                &Filter::default(),
+                &Order::new(&received_at, &Direction::Descending),
+            );
+
+            // Paginate over all events, newest first
+            let mut titles: Vec<OperationValue> = Vec::new();
+            loop {
+                let (pagination_data, documents) = node
+                    .context
+                    .store
+                    .query(&schema, &args, None)
+                    .await
+                    .expect("Query failed");
+
+                for (_, document) in &documents {
+                    titles.push(get_document_value(document, "title"));
+                }
+
+                if !pagination_data.has_next_page {
+                    break;
+                }
+                args.pagination.after = pagination_data.end_cursor;
+            }
+
+            assert_eq!(
+                titles,
+                vec![
+                    "Eventual Consistent Grapefruit".into(),
+                    "Shoebill - Non-migratory Shoegaze".into(),
+                    "Kids Bits! Chiptune for baby squirrels".into(),
+                    "Bamboo-Scrumble Rumba Night - Xmas special".into(),
+                    "The Pandadoodle Flute Trio".into(),
+                ]
+            );
+
+            // Filter by an interval of timestamps
+            let args = Query::new(
+                &Pagination::default(),
+                &Select::default(),
+                &Filter::new().meta_fields(&[
+                    ("receivedAt_gte", &[200.into()]),
+                    ("receivedAt_lt", &[500.into()]),
+                ]),
+                &Order::default(),
+            );
+            let count = node
+                .context
+                .store
+                .count(&schema, &args, None)
+                .await
+                .expect("Query failed");
+            assert_eq!(count, 3);
+
+            let args = Query::new(
+                &Pagination::default(),
+                &Select::new(&[Field::new("title")]),
+                &Filter::new().meta_fields(&[("updatedAt_lt", &[100.into()])]),
+                &Order::default(),
+            );
+            let (_, documents) = node
+                .context
+                .store
+                .query(&schema, &args, None)
+                .await
+                .expect("Query failed");
+            assert_eq!(documents.len(), 1);
+            assert_eq!(
+                get_document_value(&documents[0].1, "title"),
+                "Bamboo-Scrumble Rumba Night - Xmas special".into()
+            );
+        });
+    }
+
+    #[rstest]
+    fn query_document_views_as_of(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, view_ids) = add_schema_and_documents(
+                &mut node,
+                "notes",
+                vec![
+                    vec![("text", "First draft".into(), None)],
+                    vec![("text", "Shopping list".into(), None)],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let updated_view_id = update_document(
+                &mut node,
+                schema.id(),
+                vec![("text", "Final version".into())],
+                &view_ids[0],
+                &key_pair,
+            )
+            .await;
+            let deleted_view_id =
+                delete_document(&mut node, schema.id(), &view_ids[1], &key_pair).await;
+
+            // Overwrite the recorded timestamps to not depend on the clock during the test
+            for (view_id, received_at) in [
+                (&view_ids[0], 100),
+                (&view_ids[1], 100),
+                (&updated_view_id, 200),
+                (&deleted_view_id, 200),
+            ] {
+                query("UPDATE operations_v1 SET received_at = $1 WHERE operation_id = $2")
+                    .bind(received_at)
+                    .bind(view_id.graph_tips()[0].as_str())
+                    .execute(&node.context.store.pool)
+                    .await
+                    .unwrap();
+            }
+
+            // Historic views removed by the garbage collection get materialized again
+            assert!(node
+                .context
+                .store
+                .prune_document_view(&view_ids[0])
+                .await
+                .unwrap());
+
+            let received_index = |view_id: DocumentViewId| {
+                let pool = node.context.store.pool.clone();
+                async move {
+                    query_scalar::<_, i64>(
+                        "SELECT received_index FROM operations_v1 WHERE operation_id = $1",
+                    )
+                    .bind(view_id.graph_tips()[0].as_str())
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+                }
+            };
+            let created_index = received_index(view_ids[1].clone()).await;
+            let updated_index = received_index(updated_view_id.clone()).await;
+            let generation = node.context.store.query_cache.generation();
+
+            let text = Field::new("text");
+
+            for (as_of, expected_texts) in [
+                (AsOf::Timestamp(50), vec![]),
+                (AsOf::Timestamp(150), vec!["First draft", "Shopping list"]),
+                (AsOf::Timestamp(250), vec!["Final version"]),
+                (
+                    AsOf::OperationIndex(created_index),
+                    vec!["First draft", "Shopping list"],
+                ),
+                (
+                    AsOf::OperationIndex(updated_index),
+                    vec!["Final version", "Shopping list"],
+                ),
+            ] {
+                let mut args = Query::new(
+                    &Pagination::new(
+                        &NonZeroU64::new(10).unwrap(),
+                        None,
+                        &vec![PaginationField::TotalCount],
+                    ),
+                    &Select::new(&[Field::new("text")]),
+                    &Filter::default(),
+                    &Order::new(&text, &Direction::Ascending),
+                );
+                args.as_of = Some(as_of);
+
+                let (pagination_data, documents) = node
+                    .context
+                    .store
+                    .query(&schema, &args, None)
+                    .await
+                    .expect("Query failed");
+
+                assert_eq!(
+                    pagination_data.total_count,
+                    Some(expected_texts.len() as u64)
+                );
+                assert_eq!(
+                    documents
+                        .iter()
+                        .map(|(_, document)| get_document_value(document, "text"))
+                        .collect::<Vec<OperationValue>>(),
+                    expected_texts
+                        .into_iter()
+                        .map(|text| text.into())
+                        .collect::<Vec<OperationValue>>(),
+                    "as of {:?}",
+                    as_of
+                );
+            }
+
+            // Materialized historic views state when they became current and do not invalidate
+            // cached results of the latest views
+            assert_eq!(
+                node.context
+                    .store
+                    .get_document_timestamps(&view_ids[0])
+                    .await
+                    .unwrap(),
+                (100, 100)
+            );
+            assert_eq!(node.context.store.query_cache.generation(), generation);
+        });
+    }
+
+    #[rstest]
+    fn filter_by_related_documents(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (venues_schema, venues_view_ids) =
+                create_venues_test_data(&mut node, &key_pair).await;
+
+            let reviews_schema = add_schema(
+                &mut node,
+                "reviews",
+                vec![
+                    ("rating", FieldType::Integer),
+                    ("venue", FieldType::Relation(venues_schema.id().to_owned())),
+                    (
+                        "pinned_venue",
+                        FieldType::PinnedRelation(venues_schema.id().to_owned()),
+                    ),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            for (rating, venue_view_id) in [
+                (5, &venues_view_ids[0]),
+                (3, &venues_view_ids[0]),
+                (4, &venues_view_ids[1]),
+            ] {
+                add_document(
+                    &mut node,
+                    reviews_schema.id(),
+                    vec![
+                        ("rating", rating.into()),
+                        (
+                            "venue",
+                            OperationValue::Relation(Relation::new(DocumentId::new(
+                                &venue_view_id.graph_tips()[0],
+                            ))),
+                        ),
+                        ("pinned_venue", venue_view_id.clone().into()),
+                    ],
+                    &key_pair,
+                )
+                .await;
+            }
+
+            // Rename the first venue, pinned relations still point at the old view
+            update_document(
+                &mut node,
+                venues_schema.id(),
+                vec![("name", "World Wide Field".into())],
+                &venues_view_ids[0],
+                &key_pair,
+            )
+            .await;
+
+            let name = Field::new("name");
+            let rating = Field::new("rating");
+
+            for (relation_field, venue_name, expected_ratings) in [
+                ("venue", "World Wide Field", vec![3, 5]),
+                ("venue", "World Wide Feld", vec![]),
+                ("pinned_venue", "World Wide Field", vec![]),
+                ("pinned_venue", "World Wide Feld", vec![3, 5]),
+                ("pinned_venue", "Internet Explorer", vec![4]),
+            ] {
+                let mut related_filter = Filter::new();
+                related_filter.add(&name, &venue_name.into());
+
+                let mut filter = Filter::default();
+                filter.add_group(FilterGroup::Related(
+                    relation_field.to_string(),
+                    Box::new(venues_schema.clone()),
+                    Box::new(related_filter),
+                ));
+
+                let args = Query::new(
+                    &Pagination::default(),
+                    &Select::new(&[Field::new("rating")]),
+                    &filter,
+                    &Order::new(&rating, &Direction::Ascending),
+                );
+
+                let (_, documents) = node
+                    .context
+                    .store
+                    .query(&reviews_schema, &args, None)
+                    .await
+                    .expect("Query failed");
+
+                assert_eq!(
+                    documents
+                        .iter()
+                        .map(|(_, document)| get_document_value(document, "rating"))
+                        .collect::<Vec<OperationValue>>(),
+                    expected_ratings
+                        .into_iter()
+                        .map(OperationValue::Integer)
+                        .collect::<Vec<OperationValue>>(),
+                    "{relation_field}: {venue_name}"
+                );
+            }
+        });
+    }
+
     #[rstest]
     #[case::default(Filter::default(), 3)]
     #[case::filtered(Filter::new().fields(&[("name_contains", &["Internet".into()])]), 1)]
@@ -2709,4 +3813,172 @@ mod tests {
             }
         });
     }
+
+    #[rstest]
+    fn query_cache_invalidation(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, view_ids) = add_schema_and_documents(
+                &mut node,
+                "notes",
+                vec![vec![("text", "First draft".into(), None)]],
+                &key_pair,
+            )
+            .await;
+
+            let args = Query::new(
+                &Pagination::default(),
+                &Select::new(&[Field::new("text")]),
+                &Filter::default(),
+                &Order::default(),
+            );
+
+            let metrics_before = node.context.store.query_cache_metrics();
+
+            // Sending the same query twice only hits the database once
+            for _ in 0..2 {
+                let (_, documents) = node
+                    .context
+                    .store
+                    .query(&schema, &args, None)
+                    .await
+                    .expect("Query failed");
+                assert_eq!(
+                    get_document_value(&documents[0].1, "text"),
+                    "First draft".into()
+                );
+            }
+
+            let metrics = node.context.store.query_cache_metrics();
+            assert_eq!(metrics.misses, metrics_before.misses + 1);
+            assert_eq!(metrics.hits, metrics_before.hits + 1);
+
+            // Materializing a new view of the document invalidates the cached result
+            update_document(
+                &mut node,
+                schema.id(),
+                vec![("text", "Final version".into())],
+                &view_ids[0],
+                &key_pair,
+            )
+            .await;
+
+            let (_, documents) = node
+                .context
+                .store
+                .query(&schema, &args, None)
+                .await
+                .expect("Query failed");
+            assert_eq!(
+                get_document_value(&documents[0].1, "text"),
+                "Final version".into()
+            );
+
+            let metrics = node.context.store.query_cache_metrics();
+            assert_eq!(metrics.misses, metrics_before.misses + 2);
+            assert!(metrics.invalidations > metrics_before.invalidations);
+        });
+    }
+
+    #[rstest]
+    fn filter_by_list_membership(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let songs_schema = add_schema(
+                &mut node,
+                "songs",
+                vec![("title", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+            let playlists_schema = add_schema(
+                &mut node,
+                "playlists",
+                vec![
+                    ("name", FieldType::String),
+                    (
+                        "songs",
+                        FieldType::PinnedRelationList(songs_schema.id().to_owned()),
+                    ),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let mut songs = Vec::new();
+            for title in ["Ambush", "Bamboo", "Cactus"] {
+                let view_id = add_document(
+                    &mut node,
+                    songs_schema.id(),
+                    vec![("title", title.into())],
+                    &key_pair,
+                )
+                .await;
+                songs.push(view_id);
+            }
+
+            for (name, indexes) in [
+                ("Chill", vec![1, 2]),
+                ("Mix", vec![0, 1]),
+                ("Solo", vec![2]),
+            ] {
+                let list: Vec<DocumentViewId> =
+                    indexes.iter().map(|index| songs[*index].clone()).collect();
+                add_document(
+                    &mut node,
+                    playlists_schema.id(),
+                    vec![
+                        ("name", name.into()),
+                        (
+                            "songs",
+                            OperationValue::PinnedRelationList(PinnedRelationList::new(list)),
+                        ),
+                    ],
+                    &key_pair,
+                )
+                .await;
+            }
+
+            let name = Field::new("name");
+            let songs_field = Field::new("songs");
+            let song = |index: usize| OperationValue::from(songs[index].clone());
+
+            let mut contains_any = Filter::default();
+            contains_any.add_list_contains_any(&songs_field, &[song(0), song(2)]);
+
+            let mut contains_all = Filter::default();
+            contains_all.add_list_contains_all(&songs_field, &[song(1), song(2)]);
+
+            // Combined with another filter on the same field all of them need to match
+            let mut contains_both = Filter::default();
+            contains_both.add_list_contains_any(&songs_field, &[song(0)]);
+            contains_both.add_list_contains_any(&songs_field, &[song(1)]);
+
+            for (filter, expected_names) in [
+                (contains_any, vec!["Chill", "Mix", "Solo"]),
+                (contains_all, vec!["Chill"]),
+                (contains_both, vec!["Mix"]),
+            ] {
+                let args = Query::new(
+                    &Pagination::default(),
+                    &Select::new(&[Field::new("name")]),
+                    &filter,
+                    &Order::new(&name, &Direction::Ascending),
+                );
+
+                let (_, documents) = node
+                    .context
+                    .store
+                    .query(&playlists_schema, &args, None)
+                    .await
+                    .expect("Query failed");
+
+                let names: Vec<OperationValue> = documents
+                    .iter()
+                    .map(|(_, document)| get_document_value(document, "name"))
+                    .collect();
+                let expected_names: Vec<OperationValue> =
+                    expected_names.into_iter().map(|name| name.into()).collect();
+                assert_eq!(names, expected_names);
+            }
+        });
+    }
 }
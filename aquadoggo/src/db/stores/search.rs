@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Full-text search index over the string fields of the latest view of every document.
+//!
+//! SQLite and PostgreSQL offer different full-text search features which can not be set up by
+//! the same migration: For SQLite we use a FTS5 virtual table, for PostgreSQL a `tsvector` column
+//! with a GIN index. The index gets created by the database specific migrations and is kept
+//! up-to-date whenever a document is materialized or purged.
+use p2panda_rs::document::traits::AsDocument;
+use p2panda_rs::document::DocumentId;
+use p2panda_rs::operation::OperationValue;
+use p2panda_rs::storage_provider::error::DocumentStorageError;
+use sqlx::any::AnyKind;
+use sqlx::{query, Any, Transaction};
+
+/// Replace the indexed content of a document with the string values of its current view.
+///
+/// Deleted documents are removed from the index.
+pub(super) async fn update_search_index(
+    tx: &mut Transaction<'_, Any>,
+    document: &impl AsDocument,
+) -> Result<(), DocumentStorageError> {
+    remove_from_search_index(tx, document.id()).await?;
+
+    if document.is_deleted() {
+        return Ok(());
+    }
+
+    let content = match document.fields() {
+        Some(fields) => fields
+            .iter()
+            .filter_map(
+                |(_, document_view_value)| match document_view_value.value() {
+                    OperationValue::String(value) => Some(value.as_str()),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<&str>>()
+            .join(" "),
+        None => return Ok(()),
+    };
+
+    let insert_sql = match tx.kind() {
+        AnyKind::Postgres => {
+            "
+            INSERT INTO
+                document_search (document_id, schema_id, content)
+            VALUES
+                ($1, $2, TO_TSVECTOR('simple', $3))
+            "
+        }
+        _ => {
+            "
+            INSERT INTO
+                document_search (document_id, schema_id, content)
+            VALUES
+                ($1, $2, $3)
+            "
+        }
+    };
+
+    query(insert_sql)
+        .bind(document.id().as_str())
+        .bind(document.schema_id().to_string())
+        .bind(content)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Remove a document from the full-text search index.
+pub(super) async fn remove_from_search_index(
+    tx: &mut Transaction<'_, Any>,
+    document_id: &DocumentId,
+) -> Result<(), DocumentStorageError> {
+    query(
+        "
+        DELETE FROM
+            document_search
+        WHERE
+            document_id = $1
+        ",
+    )
+    .bind(document_id.as_str())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| DocumentStorageError::FatalStorageError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Convert a user given search text into a query for the full-text search index.
+///
+/// All words of the search text need to be contained in a document for it to match. For SQLite
+/// every word is quoted, this way no FTS5 query syntax can be injected by the user.
+pub(super) fn search_query(kind: AnyKind, text: &str) -> String {
+    match kind {
+        AnyKind::Postgres => text.to_string(),
+        _ => text
+            .split_whitespace()
+            .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+            .collect::<Vec<String>>()
+            .join(" "),
+    }
+}
+
+/// Returns SQL condition to only select documents matching the search query bound to the given
+/// argument marker.
+pub(super) fn search_match_sql(kind: AnyKind, marker: &str) -> String {
+    let match_sql = match kind {
+        AnyKind::Postgres => {
+            format!("document_search.content @@ PLAINTO_TSQUERY('simple', {marker})")
+        }
+        _ => format!("document_search MATCH {marker}"),
+    };
+
+    format!(
+        r#"
+        documents.document_id IN (
+            SELECT
+                document_search.document_id
+            FROM
+                document_search
+            WHERE
+                {match_sql}
+        )
+        "#
+    )
+}
+
+/// Returns SQL expression calculating the relevance of a document for the search query bound to
+/// the given argument marker.
+///
+/// Higher values indicate a higher relevance.
+pub(super) fn search_rank_sql(kind: AnyKind, marker: &str, document_id_sql: &str) -> String {
+    match kind {
+        AnyKind::Postgres => format!(
+            r#"
+            (
+                SELECT
+                    CAST(
+                        TS_RANK(document_search.content, PLAINTO_TSQUERY('simple', {marker}))
+                        AS DOUBLE PRECISION
+                    )
+                FROM
+                    document_search
+                WHERE
+                    document_search.document_id = {document_id_sql}
+            )
+            "#
+        ),
+        // The bm25 function of FTS5 returns lower values for more relevant documents
+        _ => format!(
+            r#"
+            (
+                SELECT
+                    -BM25(document_search)
+                FROM
+                    document_search
+                WHERE
+                    document_search MATCH {marker}
+                    AND document_search.document_id = {document_id_sql}
+            )
+            "#
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::test_utils::fixtures::key_pair;
+    use rstest::rstest;
+    use sqlx::any::AnyKind;
+    use sqlx::query_scalar;
+
+    use crate::db::run_pending_migrations;
+    use crate::test_utils::{add_schema_and_documents, test_runner, TestNode};
+
+    use super::{search_match_sql, search_query, search_rank_sql};
+
+    #[rstest]
+    #[case("hello world", "\"hello\" \"world\"")]
+    #[case("  panda  ", "\"panda\"")]
+    #[case("say \"hi\" OR", "\"say\" \"\"\"hi\"\"\" \"OR\"")]
+    fn sqlite_search_query(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(search_query(AnyKind::Sqlite, text), expected);
+    }
+
+    #[test]
+    fn postgres_search_sql() {
+        // Search text is handled by `PLAINTO_TSQUERY` which does not interpret any query syntax
+        assert_eq!(
+            search_query(AnyKind::Postgres, "say \"hi\" OR"),
+            "say \"hi\" OR"
+        );
+        assert!(search_match_sql(AnyKind::Postgres, "$1")
+            .contains("document_search.content @@ PLAINTO_TSQUERY('simple', $1)"));
+        assert!(
+            search_rank_sql(AnyKind::Postgres, "$1", "documents.document_id")
+                .contains("TS_RANK(document_search.content, PLAINTO_TSQUERY('simple', $1))")
+        );
+    }
+
+    #[rstest]
+    fn search_index(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "lyrics",
+                vec![
+                    vec![("line", "Oh bondage, up yours".into(), None)],
+                    vec![("line", "Bind me, tie me".into(), None)],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            // Running the migrations again does not touch the already populated index
+            let pool = &node.context.store.pool;
+            run_pending_migrations(pool).await.unwrap();
+
+            let indexed: i64 =
+                query_scalar("SELECT COUNT(*) FROM document_search WHERE schema_id = $1")
+                    .bind(schema.id().to_string())
+                    .fetch_one(pool)
+                    .await
+                    .unwrap();
+            assert_eq!(indexed, 2);
+
+            // Matches are found with the index of the database in use
+            let kind = pool.any_kind();
+            let match_sql = format!(
+                "SELECT COUNT(*) FROM documents WHERE documents.schema_id = $2 AND {}",
+                search_match_sql(kind, "$1")
+            );
+            for (text, expected) in [("bondage", 1), ("ME", 1), ("panda", 0)] {
+                let matches: i64 = query_scalar(&match_sql)
+                    .bind(search_query(kind, text))
+                    .bind(schema.id().to_string())
+                    .fetch_one(pool)
+                    .await
+                    .unwrap();
+                assert_eq!(matches, expected, "search for {}", text);
+            }
+        });
+    }
+}
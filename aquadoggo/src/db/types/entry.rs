@@ -46,8 +46,6 @@ pub struct StorageEntry {
 }
 
 impl StorageEntry {
-    // We will need this again as soon as we implement a new replication protocol
-    #[allow(dead_code)]
     pub fn payload(&self) -> Option<&EncodedOperation> {
         self.payload.as_ref()
     }
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Authorization of GraphQL requests based on the access level of the client.
+//!
+//! Root fields of every operation are checked against the scopes of the client before the request
+//! gets executed. Restrictions on which entries can be published are checked by the mutations
+//! themselves.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery};
+use async_graphql::parser::types::{ExecutableDocument, OperationType, Selection, SelectionSet};
+use async_graphql::{Name, ServerError, ServerResult, Variables};
+
+use crate::auth::{Access, AuthError};
+use crate::config::ApiScope;
+use crate::graphql::constants;
+
+/// Extension rejecting GraphQL requests which contain fields the client has no access to.
+///
+/// Requests without any `Access` attached to their data are not restricted.
+pub struct Authorization;
+
+impl ExtensionFactory for Authorization {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AuthorizationExtension)
+    }
+}
+
+struct AuthorizationExtension;
+
+#[async_trait::async_trait]
+impl Extension for AuthorizationExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+
+        if let Some(access) = ctx.data_opt::<Access>() {
+            authorize(access, &document).map_err(|err| ServerError::new(err.to_string(), None))?;
+        }
+
+        Ok(document)
+    }
+}
+
+/// Checks if the client is allowed to request all root fields of all operations in the document.
+fn authorize(access: &Access, document: &ExecutableDocument) -> Result<(), AuthError> {
+    for (_, operation) in document.operations.iter() {
+        let mut root_fields = Vec::new();
+        let mut visited_fragments = HashSet::new();
+        collect_root_fields(
+            document,
+            &operation.node.selection_set.node,
+            &mut visited_fragments,
+            &mut root_fields,
+        );
+
+        for field_name in root_fields {
+            authorize_field(access, operation.node.ty, field_name.as_str())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if the client is allowed to request the given root field.
+fn authorize_field(access: &Access, ty: OperationType, field_name: &str) -> Result<(), AuthError> {
+    // Introspection is always allowed, clients need it to learn about the API
+    if field_name.starts_with("__") {
+        return Ok(());
+    }
+
+    match ty {
+        // Arguments for creating new entries are required for publishing as well
+        OperationType::Query
+            if field_name == constants::NEXT_ARGS_QUERY
+                || field_name == constants::NEXT_ARGS_BATCH_QUERY =>
+        {
+            if access.has_scope(ApiScope::Publish) {
+                Ok(())
+            } else {
+                access.require(ApiScope::Read)
+            }
+        }
+        OperationType::Query | OperationType::Subscription => access.require(ApiScope::Read),
+        OperationType::Mutation => access.require(ApiScope::Publish),
+    }
+}
+
+/// Collects the names of all fields in a selection set, following fragments.
+fn collect_root_fields<'a>(
+    document: &'a ExecutableDocument,
+    selection_set: &'a SelectionSet,
+    visited_fragments: &mut HashSet<&'a Name>,
+    root_fields: &mut Vec<&'a Name>,
+) {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => root_fields.push(&field.node.name.node),
+            Selection::InlineFragment(fragment) => collect_root_fields(
+                document,
+                &fragment.node.selection_set.node,
+                visited_fragments,
+                root_fields,
+            ),
+            Selection::FragmentSpread(spread) => {
+                let fragment_name = &spread.node.fragment_name.node;
+
+                // Skip fragments we've seen already, cyclic fragments get rejected during
+                // validation later
+                if !visited_fragments.insert(fragment_name) {
+                    continue;
+                }
+
+                if let Some(fragment) = document.fragments.get(fragment_name) {
+                    collect_root_fields(
+                        document,
+                        &fragment.node.selection_set.node,
+                        visited_fragments,
+                        root_fields,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::parser::parse_query;
+    use rstest::rstest;
+
+    use crate::auth::Access;
+    use crate::config::{AllowList, ApiScope, ApiToken};
+
+    use super::authorize;
+
+    fn access(scopes: Vec<ApiScope>) -> Access {
+        Access::Token(Box::new(ApiToken {
+            token: "secret".into(),
+            scopes,
+            publish_schema_ids: AllowList::Wildcard,
+            publish_public_keys: AllowList::Wildcard,
+        }))
+    }
+
+    #[rstest]
+    #[case("{ node { id } }", vec![ApiScope::Read], true)]
+    #[case("{ node { id } }", vec![ApiScope::Publish], false)]
+    #[case("{ __schema { types { name } } }", vec![], true)]
+    #[case("{ nextArgs(publicKey: \"abc\") { logId } }", vec![ApiScope::Publish], true)]
+    #[case("{ nextArgs(publicKey: \"abc\") { logId } }", vec![ApiScope::Read], true)]
+    #[case("{ nextArgs(publicKey: \"abc\") { logId } }", vec![], false)]
+    #[case("mutation { publish(entry: \"\", operation: \"\") { logId } }", vec![ApiScope::Publish], true)]
+    #[case("mutation { publish(entry: \"\", operation: \"\") { logId } }", vec![ApiScope::Read], false)]
+    #[case("subscription { node { id } }", vec![ApiScope::Read], true)]
+    #[case("subscription { node { id } }", vec![ApiScope::Publish], false)]
+    #[case("{ ...Fields } fragment Fields on Query { node { id } }", vec![ApiScope::Publish], false)]
+    #[case("{ ... on Query { node { id } } }", vec![ApiScope::Publish], false)]
+    #[case("{ ...A } fragment A on Query { ...B } fragment B on Query { ...A }", vec![], true)]
+    #[case("query A { node { id } } mutation B { validate { valid } }", vec![ApiScope::Read], false)]
+    fn authorize_root_fields(
+        #[case] query: &str,
+        #[case] scopes: Vec<ApiScope>,
+        #[case] expected: bool,
+    ) {
+        let document = parse_query(query).unwrap();
+        assert_eq!(authorize(&access(scopes), &document).is_ok(), expected);
+    }
+}
@@ -8,9 +8,30 @@
 /// GraphQL object representing a documents meta data.
 pub const DOCUMENT_META: &str = "DocumentMeta";
 
+/// GraphQL object representing the history of a document.
+pub const DOCUMENT_HISTORY: &str = "DocumentHistory";
+
+/// GraphQL root object containing all subscriptions.
+pub const SUBSCRIPTION_ROOT: &str = "Subscription";
+
 /// GraphQL object representing next arguments data.
 pub const NEXT_ARGS: &str = "NextArguments";
 
+/// GraphQL input object representing a public key and optional document view id.
+pub const NEXT_ARGS_BATCH_ITEM: &str = "NextArgsBatchItem";
+
+/// GraphQL object representing a signed entry.
+pub const ENTRY: &str = "Entry";
+
+/// GraphQL object representing a log.
+pub const LOG: &str = "Log";
+
+/// GraphQL object representing the status of the node.
+pub const NODE_STATUS: &str = "NodeStatus";
+
+/// GraphQL object representing a schema definition.
+pub const SCHEMA: &str = "Schema";
+
 /// GraphQL scalar type representing a public key.
 pub const PUBLIC_KEY: &str = "PublicKey";
 
@@ -20,14 +41,53 @@ pub const DOCUMENT_ID: &str = "DocumentId";
 /// GraphQL scalar representing a document view id.
 pub const DOCUMENT_VIEW_ID: &str = "DocumentViewId";
 
+/// GraphQL scalar representing an entry hash.
+pub const ENTRY_HASH: &str = "EntryHash";
+
+/// GraphQL scalar representing a log id.
+pub const LOG_ID: &str = "LogId";
+
+/// GraphQL scalar representing a sequence number.
+pub const SEQ_NUM: &str = "SeqNum";
+
 // Query, field and argument names and pre-/suffixes.
 
 /// Prefix for query name where all documents of a particular schema can be retrieved.
 pub const QUERY_ALL_PREFIX: &str = "all_";
 
+/// Prefix for query name where aggregated values of a particular schema can be retrieved.
+pub const QUERY_AGGREGATE_PREFIX: &str = "aggregate_";
+
+/// Prefix for subscription name where a single document of a particular schema can be observed.
+pub const SUBSCRIPTION_DOCUMENT_PREFIX: &str = "document_";
+
 /// Name of query to fetch next entry arguments.
 pub const NEXT_ARGS_QUERY: &str = "nextArgs";
 
+/// Name of query to fetch next entry arguments for many documents at once.
+pub const NEXT_ARGS_BATCH_QUERY: &str = "nextArgsBatch";
+
+/// Name of query to fetch a single entry.
+pub const ENTRY_QUERY: &str = "entry";
+
+/// Name of query to fetch entries of a log.
+pub const ENTRIES_QUERY: &str = "entries";
+
+/// Name of query to fetch logs of a public key.
+pub const LOGS_QUERY: &str = "logs";
+
+/// Name of query to fetch the status of the node.
+pub const NODE_QUERY: &str = "node";
+
+/// Name of query to fetch all schemas supported by the node.
+pub const SCHEMAS_QUERY: &str = "schemas";
+
+/// Name of query to fetch a single schema.
+pub const SCHEMA_QUERY: &str = "schema";
+
+/// Argument string used for passing a schema id into a query.
+pub const SCHEMA_ID_ARG: &str = "id";
+
 /// Argument string used for passing a document id into a query.
 pub const DOCUMENT_ID_ARG: &str = "id";
 
@@ -37,9 +97,36 @@ pub const PUBLIC_KEY_ARG: &str = "publicKey";
 /// Argument string used for passing a document view id into a query.
 pub const DOCUMENT_VIEW_ID_ARG: &str = "viewId";
 
+/// Argument string used for passing a list of public keys and document view ids into a query.
+pub const NEXT_ARGS_BATCH_ARG: &str = "items";
+
+/// Argument string used for passing an entry hash into a query.
+pub const ENTRY_HASH_ARG: &str = "hash";
+
+/// Argument string used for passing a log id into a query.
+pub const LOG_ID_ARG: &str = "logId";
+
+/// Argument string used for passing the lowest requested sequence number into a query.
+pub const SEQ_NUM_FROM_ARG: &str = "from";
+
+/// Argument string used for passing the highest requested sequence number into a query.
+pub const SEQ_NUM_TO_ARG: &str = "to";
+
 /// Argument string used for passing a filter into a query.
 pub const FILTER_ARG: &str = "filter";
 
+/// Name of field on a filter input object combining nested filters which all need to match.
+pub const FILTER_AND_FIELD: &str = "_and";
+
+/// Name of field on a filter input object combining nested filters of which one needs to match.
+pub const FILTER_OR_FIELD: &str = "_or";
+
+/// Name of field on a filter input object containing a nested filter which must not match.
+pub const FILTER_NOT_FIELD: &str = "_not";
+
+/// Name of field on a filter input object containing filters on related documents.
+pub const FILTER_RELATED_FIELD: &str = "_related";
+
 /// Argument string used for passing a filter into a query.
 pub const META_FILTER_ARG: &str = "meta";
 
@@ -49,12 +136,39 @@ pub const PAGINATION_AFTER_ARG: &str = "after";
 /// Argument string used for passing number of paginated items requested to query.
 pub const PAGINATION_FIRST_ARG: &str = "first";
 
+/// Argument string used for passing a pagination cursor into a query, paginating backwards.
+pub const PAGINATION_BEFORE_ARG: &str = "before";
+
+/// Argument string used for passing number of paginated items requested to query, paginating
+/// backwards.
+pub const PAGINATION_LAST_ARG: &str = "last";
+
 /// Argument string used for passing field to order by to query.
 pub const ORDER_BY_ARG: &str = "orderBy";
 
 /// Argument string used for passing ordering direction to query.
 pub const ORDER_DIRECTION_ARG: &str = "orderDirection";
 
+/// Name of field on an ordering input object containing the field to order by.
+pub const ORDER_FIELD_FIELD: &str = "field";
+
+/// Name of field on an ordering input object containing the direction of the ordering.
+pub const ORDER_DIRECTION_FIELD: &str = "direction";
+
+/// Argument string used for passing a full-text search query.
+pub const SEARCH_ARG: &str = "search";
+
+/// Argument string used for passing the local time at which the queried document views were
+/// current.
+pub const AS_OF_ARG: &str = "asOf";
+
+/// Argument string used for passing the index of the last operation received by the node at the
+/// point the queried document views were current.
+pub const AS_OF_OPERATION_ARG: &str = "asOfOperation";
+
+/// Argument string used for passing the field to group aggregations by.
+pub const GROUP_BY_ARG: &str = "groupBy";
+
 /// Name of field where a collection of documents can be accessed.
 pub const DOCUMENTS_FIELD: &str = "documents";
 
@@ -64,12 +178,36 @@ pub const FIELDS_FIELD: &str = "fields";
 /// Name of field on a document where its meta data can be accessed.
 pub const META_FIELD: &str = "meta";
 
+/// Name of field on a document where its history can be accessed.
+pub const HISTORY_FIELD: &str = "history";
+
+/// Name of field on a document where documents relating to it can be accessed.
+pub const REFERENCED_BY_FIELD: &str = "referencedBy";
+
 /// Name of field on a document where pagination cursor can be accessed.
 pub const CURSOR_FIELD: &str = "cursor";
 
 /// Name of field on a paginated response which contains the total count.
 pub const TOTAL_COUNT_FIELD: &str = "totalCount";
 
+/// Name of field on an aggregation containing the value documents were grouped by.
+pub const GROUP_FIELD: &str = "group";
+
+/// Name of field on an aggregation which contains the number of documents.
+pub const COUNT_FIELD: &str = "count";
+
+/// Name of field on an aggregation which contains the sums of all numeric fields.
+pub const SUM_FIELD: &str = "sum";
+
+/// Name of field on an aggregation which contains the averages of all numeric fields.
+pub const AVG_FIELD: &str = "avg";
+
+/// Name of field on an aggregation which contains the smallest values of all numeric fields.
+pub const MIN_FIELD: &str = "min";
+
+/// Name of field on an aggregation which contains the largest values of all numeric fields.
+pub const MAX_FIELD: &str = "max";
+
 /// Name of field on a paginated response which shows if a next page exists.
 pub const HAS_NEXT_PAGE_FIELD: &str = "hasNextPage";
 
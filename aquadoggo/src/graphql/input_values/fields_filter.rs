@@ -10,20 +10,59 @@ use async_graphql::dynamic::{InputObject, InputValue, TypeRef};
 use dynamic_graphql::InputObject;
 use p2panda_rs::schema::{FieldType, Schema};
 
+use crate::graphql::constants;
 use crate::graphql::scalars::{
     DocumentIdScalar, DocumentViewIdScalar, HexBytesScalar, PublicKeyScalar,
 };
-use crate::graphql::utils::filter_name;
+use crate::graphql::utils::{filter_name, related_filter_name};
 
 /// Build a filter input object for a p2panda schema. It can be used to filter collection queries
 /// based on the values each document contains.
 ///
 /// The resulting input objects are used passed to the `filter` argument on a document collection
 /// query or list relation fields.
+///
+/// Filters can be nested and combined with `_and`, `_or` and `_not` fields. The underscore prefix
+/// makes sure they never collide with application field names, as these need to start with a
+/// letter.
+///
+/// Schemas containing `relation` or `pinned_relation` fields additionally offer a `_related` field
+/// to filter by the values of the documents these fields point at.
 pub fn build_filter_input_object(schema: &Schema) -> InputObject {
     // Construct the document fields object which will be named `<schema_id>Filter`
     let schema_field_name = filter_name(schema.id());
-    let mut filter_input = InputObject::new(schema_field_name);
+    let mut filter_input = InputObject::new(&schema_field_name)
+        .field(
+            InputValue::new(
+                constants::FILTER_AND_FIELD,
+                TypeRef::named_nn_list(&schema_field_name),
+            )
+            .description("Match documents for which all of the given filters match"),
+        )
+        .field(
+            InputValue::new(
+                constants::FILTER_OR_FIELD,
+                TypeRef::named_nn_list(&schema_field_name),
+            )
+            .description("Match documents for which at least one of the given filters matches"),
+        )
+        .field(
+            InputValue::new(
+                constants::FILTER_NOT_FIELD,
+                TypeRef::named(&schema_field_name),
+            )
+            .description("Match documents for which the given filter does not match"),
+        );
+
+    if has_related_fields(schema) {
+        filter_input = filter_input.field(
+            InputValue::new(
+                constants::FILTER_RELATED_FIELD,
+                TypeRef::named(related_filter_name(schema.id())),
+            )
+            .description("Match documents by the values of the documents their relations point at"),
+        );
+    }
 
     // For every field in the schema we create a type with a resolver
     for (name, field_type) in schema.fields().iter() {
@@ -74,6 +113,40 @@ pub fn build_filter_input_object(schema: &Schema) -> InputObject {
     filter_input
 }
 
+/// Build an input object containing a filter for every `relation` and `pinned_relation` field of a
+/// p2panda schema. It is named `<schema_id>RelatedFilter` and accepts the filter input objects of
+/// the related schemas.
+///
+/// Returns `None` if the schema does not contain any of these fields.
+pub fn build_related_filter_input_object(schema: &Schema) -> Option<InputObject> {
+    if !has_related_fields(schema) {
+        return None;
+    }
+
+    let mut related_filter_input = InputObject::new(related_filter_name(schema.id()));
+
+    for (name, field_type) in schema.fields().iter() {
+        if let FieldType::Relation(schema_id) | FieldType::PinnedRelation(schema_id) = field_type {
+            related_filter_input = related_filter_input.field(InputValue::new(
+                name,
+                TypeRef::named(filter_name(schema_id)),
+            ));
+        }
+    }
+
+    Some(related_filter_input)
+}
+
+/// Returns true if the schema contains at least one `relation` or `pinned_relation` field.
+fn has_related_fields(schema: &Schema) -> bool {
+    schema.fields().iter().any(|(_, field_type)| {
+        matches!(
+            field_type,
+            FieldType::Relation(_) | FieldType::PinnedRelation(_)
+        )
+    })
+}
+
 /// A filter input type for owner field on meta object.
 #[derive(InputObject)]
 #[allow(dead_code)]
@@ -322,6 +395,17 @@ pub struct RelationListFilter {
     /// Filter by values not in set.
     #[graphql(name = "notIn")]
     not_in: Option<Vec<DocumentIdScalar>>,
+
+    /// Filter lists containing this document.
+    contains: Option<DocumentIdScalar>,
+
+    /// Filter lists containing at least one of these documents.
+    #[graphql(name = "containsAny")]
+    contains_any: Option<Vec<DocumentIdScalar>>,
+
+    /// Filter lists containing all of these documents.
+    #[graphql(name = "containsAll")]
+    contains_all: Option<Vec<DocumentIdScalar>>,
 }
 
 /// A filter input type for pinned relation list field values.
@@ -335,4 +419,15 @@ pub struct PinnedRelationListFilter {
     /// Filter by values not in set.
     #[graphql(name = "notIn")]
     not_in: Option<Vec<DocumentViewIdScalar>>,
+
+    /// Filter lists containing this document view.
+    contains: Option<DocumentViewIdScalar>,
+
+    /// Filter lists containing at least one of these document views.
+    #[graphql(name = "containsAny")]
+    contains_any: Option<Vec<DocumentViewIdScalar>>,
+
+    /// Filter lists containing all of these document views.
+    #[graphql(name = "containsAll")]
+    contains_all: Option<Vec<DocumentViewIdScalar>>,
 }
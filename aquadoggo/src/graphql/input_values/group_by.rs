@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Input value types used when grouping aggregations over collections.
+use async_graphql::dynamic::Enum;
+use p2panda_rs::schema::{FieldType, Schema};
+
+use crate::graphql::utils::group_by_name;
+
+/// Meta fields by which a collection of documents can be grouped.
+pub const META_GROUP_BY_FIELDS: [&str; 1] = ["OWNER"];
+
+/// Dynamically build an enum input value which can be set to meta or application fields which
+/// aggregations over a collection of documents can be grouped by.
+///
+/// Relation list fields contain many values per document and can not be grouped by.
+pub fn build_group_by_enum_value(schema: &Schema) -> Enum {
+    let mut input_values = Enum::new(group_by_name(schema.id()));
+
+    // Add meta fields to grouping enum.
+    //
+    // Meta fields are uppercase formatted strings.
+    for name in META_GROUP_BY_FIELDS {
+        input_values = input_values.item(name)
+    }
+
+    // Add document fields to grouping enum.
+    //
+    // Application fields are lowercase formatted strings.
+    for (name, field_type) in schema.fields().iter() {
+        match field_type {
+            FieldType::RelationList(_) | FieldType::PinnedRelationList(_) => (),
+            _ => input_values = input_values.item(name),
+        }
+    }
+
+    input_values
+}
@@ -3,7 +3,7 @@
 use dynamic_graphql::InputObject;
 
 use crate::graphql::input_values::{
-    BooleanFilter, DocumentIdFilter, DocumentViewIdFilter, OwnerFilter,
+    BooleanFilter, DocumentIdFilter, DocumentViewIdFilter, IntegerFilter, OwnerFilter,
 };
 
 /// Filter input object containing all meta fields a collection of documents can be filtered by.
@@ -27,4 +27,10 @@ pub struct MetaFilterInputObject {
 
     /// Deleted filter.
     deleted: Option<BooleanFilter>,
+
+    /// Received at filter, in seconds since UNIX epoch.
+    received_at: Option<IntegerFilter>,
+
+    /// Updated at filter, in seconds since UNIX epoch.
+    updated_at: Option<IntegerFilter>,
 }
@@ -1,13 +1,20 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 mod fields_filter;
+mod group_by;
 mod meta_filter;
+mod next_args_batch;
 mod order;
+mod publish_batch;
 
 pub use fields_filter::{
-    build_filter_input_object, BooleanFilter, DocumentIdFilter, DocumentViewIdFilter, FloatFilter,
-    HexBytesFilter, IntegerFilter, OwnerFilter, PinnedRelationFilter, PinnedRelationListFilter,
-    RelationFilter, RelationListFilter, StringFilter,
+    build_filter_input_object, build_related_filter_input_object, BooleanFilter, DocumentIdFilter,
+    DocumentViewIdFilter, FloatFilter, HexBytesFilter, IntegerFilter, OwnerFilter,
+    PinnedRelationFilter, PinnedRelationListFilter, RelationFilter, RelationListFilter,
+    StringFilter,
 };
+pub use group_by::build_group_by_enum_value;
 pub use meta_filter::MetaFilterInputObject;
-pub use order::{build_order_enum_value, OrderDirection};
+pub use next_args_batch::NextArgsBatchItem;
+pub use order::{build_order_enum_value, build_order_input_object, OrderDirection};
+pub use publish_batch::PublishBatchItem;
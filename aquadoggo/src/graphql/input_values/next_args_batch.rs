@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use dynamic_graphql::InputObject;
+
+use crate::graphql::scalars::{DocumentViewIdScalar, PublicKeyScalar};
+
+/// Public key and optional document view id next arguments are requested for as part of a batch.
+#[derive(InputObject)]
+#[allow(dead_code)]
+pub struct NextArgsBatchItem {
+    /// The public key of the author next args are being requested for.
+    #[graphql(name = "publicKey")]
+    pub public_key: PublicKeyScalar,
+
+    /// Optional field for specifying an existing document next args are being requested for.
+    #[graphql(name = "viewId")]
+    pub view_id: Option<DocumentViewIdScalar>,
+}
@@ -1,15 +1,22 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 //! Input value types used when specifying ordering parameters on collection queries.
-use async_graphql::dynamic::Enum;
+use async_graphql::dynamic::{Enum, InputObject, InputValue, TypeRef};
 use dynamic_graphql::Enum;
 use p2panda_rs::schema::Schema;
 
-use crate::graphql::utils::order_by_name;
+use crate::graphql::constants;
+use crate::graphql::utils::{order_by_name, order_name};
 
 /// Meta fields by which a collection of documents can be sorted.
 // @TODO: Add more fields, see related issue: https://github.com/p2panda/aquadoggo/issues/326
-pub const META_ORDER_FIELDS: [&str; 2] = ["DOCUMENT_ID", "DOCUMENT_VIEW_ID"];
+pub const META_ORDER_FIELDS: [&str; 5] = [
+    "DOCUMENT_ID",
+    "DOCUMENT_VIEW_ID",
+    "RELEVANCE",
+    "RECEIVED_AT",
+    "UPDATED_AT",
+];
 
 /// Possible ordering direction for collection queries.
 #[derive(Enum, Debug)]
@@ -43,3 +50,32 @@ pub fn build_order_enum_value(schema: &Schema) -> Enum {
     }
     input_values
 }
+
+/// Dynamically build an input object describing one field a collection of documents is ordered by
+/// together with the direction of that ordering.
+///
+/// Each generated object has a type name with the formatting `<schema_id>Order`.
+pub fn build_order_input_object(schema: &Schema) -> InputObject {
+    InputObject::new(order_name(schema.id()))
+        .field(
+            InputValue::new(
+                constants::ORDER_FIELD_FIELD,
+                TypeRef::named_nn(order_by_name(schema.id())),
+            )
+            .description("Field by which items in the collection will be ordered"),
+        )
+        .field(
+            InputValue::new(
+                constants::ORDER_DIRECTION_FIELD,
+                TypeRef::named("OrderDirection"),
+            )
+            .description(
+                "Direction which items will be ordered in, defaults to the `orderDirection` \
+                argument of the query",
+            ),
+        )
+        .description(format!(
+            "Field and direction to order a collection of `{}` documents by.",
+            schema.id().name()
+        ))
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use dynamic_graphql::InputObject;
+
+use crate::graphql::scalars::{EncodedEntryScalar, EncodedOperationScalar};
+
+/// Signed and encoded entry together with its operation, published as part of a batch.
+#[derive(InputObject)]
+pub struct PublishBatchItem {
+    /// Signed and encoded entry to publish.
+    pub entry: EncodedEntryScalar,
+
+    /// p2panda operation representing the entry payload.
+    pub operation: EncodedOperationScalar,
+}
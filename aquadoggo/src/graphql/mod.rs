@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+mod auth;
 pub mod constants;
 pub mod input_values;
 pub mod mutations;
@@ -9,6 +10,7 @@ pub mod resolvers;
 pub mod responses;
 pub mod scalars;
 mod schema;
+pub mod subscriptions;
 #[cfg(test)]
 mod tests;
 pub mod utils;
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 mod publish;
+mod validate;
 
 pub use publish::{MutationRoot, Publish};
+pub use validate::Validate;
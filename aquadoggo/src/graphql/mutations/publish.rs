@@ -1,19 +1,25 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use anyhow::anyhow;
+use async_graphql::ErrorExtensions;
 use dynamic_graphql::{Context, Mutation, MutationFields, MutationRoot, Result};
 use log::debug;
 use p2panda_rs::api::publish;
-use p2panda_rs::entry::traits::AsEncodedEntry;
+use p2panda_rs::entry::decode::decode_entry;
+use p2panda_rs::entry::traits::{AsEncodedEntry, AsEntry};
 use p2panda_rs::entry::EncodedEntry;
 use p2panda_rs::operation::decode::decode_operation;
 use p2panda_rs::operation::traits::Schematic;
 use p2panda_rs::operation::{EncodedOperation, OperationId};
 
+use crate::auth::Access;
 use crate::bus::{ServiceMessage, ServiceSender};
+use crate::db::stores::BatchStore;
 use crate::db::SqlStore;
+use crate::graphql::input_values::PublishBatchItem;
 use crate::graphql::responses::NextArguments;
 use crate::graphql::scalars::{EncodedEntryScalar, EncodedOperationScalar};
+use crate::limits::{ClientAddress, Limits};
 use crate::schema::SchemaProvider;
 
 /// GraphQL mutation root.
@@ -48,25 +54,63 @@ impl Publish {
             encoded_entry.hash()
         );
 
+        // Reject oversized operations before decoding them
+        if let Some(limits) = ctx.data_opt::<Limits>() {
+            limits
+                .check_operation_size(encoded_operation.size())
+                .map_err(|err| err.extend())?;
+        }
+
         let operation = decode_operation(&encoded_operation)?;
 
+        // Reject entries the client is not allowed to publish before anything gets validated
+        if let Some(access) = ctx.data_opt::<Access>() {
+            let entry = decode_entry(&encoded_entry)?;
+            access.check_publish(operation.schema_id(), entry.public_key())?;
+        }
+
         let schema = schema_provider
             .get(operation.schema_id())
             .await
             .ok_or_else(|| anyhow!("Schema not found"))?;
 
+        // Reject clients and authors which published too many entries recently. The entry gets
+        // counted towards their limits right away so parallel requests can not get past them
+        let limits = match ctx.data_opt::<Limits>() {
+            Some(limits) => {
+                let entry = decode_entry(&encoded_entry)?;
+                limits
+                    .try_publish(ctx.data_opt::<ClientAddress>(), &[*entry.public_key()])
+                    .map_err(|err| err.extend())?;
+                Some((limits, *entry.public_key()))
+            }
+            None => None,
+        };
+
         /////////////////////////////////////
         // PUBLISH THE ENTRY AND OPERATION //
         /////////////////////////////////////
 
-        let (backlink, skiplink, seq_num, log_id) = publish(
+        let result = publish(
             store,
             &schema,
             &encoded_entry,
             &operation,
             &encoded_operation,
         )
-        .await?;
+        .await;
+
+        // Entries which did not get published do not count towards the limits
+        let (backlink, skiplink, seq_num, log_id) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                if let Some((limits, public_key)) = limits {
+                    limits.refund_publish(ctx.data_opt::<ClientAddress>(), &[public_key]);
+                }
+
+                return Err(err.into());
+            }
+        };
 
         ////////////////////////////////////////
         // SEND THE OPERATION TO MATERIALIZER //
@@ -89,14 +133,151 @@ impl Publish {
             skiplink: skiplink.map(|hash| hash.into()),
         })
     }
+
+    /// Publish multiple entries at once, for example all pieces of a blob together with the blob
+    /// document itself.
+    ///
+    /// Entries are validated and published in the given order, later entries can build on top of
+    /// earlier ones of the same batch. If any entry is invalid none of them get published. Every
+    /// published entry of the batch counts towards the rate limits. Batches can contain at most
+    /// `max_batch_size` entries.
+    ///
+    /// Returns arguments for publishing the next entry in the same log for every entry.
+    async fn publish_batch(
+        ctx: &Context<'_>,
+        // Ordered list of signed and encoded entries together with their operations
+        entries: Vec<PublishBatchItem>,
+    ) -> Result<Vec<NextArguments>> {
+        let store = ctx.data::<SqlStore>()?;
+        let tx = ctx.data::<ServiceSender>()?;
+        let schema_provider = ctx.data::<SchemaProvider>()?;
+
+        debug!(
+            "Query to publish batch received containing {} entries",
+            entries.len()
+        );
+
+        // Reject too large batches before doing any work on them
+        if let Some(limits) = ctx.data_opt::<Limits>() {
+            limits
+                .check_batch_size(entries.len())
+                .map_err(|err| err.extend())?;
+        }
+
+        // Decode all entries first and reject the ones the client is not allowed to publish
+        let mut items = Vec::with_capacity(entries.len());
+        let mut public_keys = Vec::with_capacity(entries.len());
+
+        for (index, item) in entries.into_iter().enumerate() {
+            let encoded_entry: EncodedEntry = item.entry.into();
+            let encoded_operation: EncodedOperation = item.operation.into();
+
+            if let Some(limits) = ctx.data_opt::<Limits>() {
+                limits
+                    .check_operation_size(encoded_operation.size())
+                    .map_err(|err| {
+                        let mut error = err.extend();
+                        error.message = format!("Rejected entry at index {}: {}", index, err);
+                        error
+                    })?;
+            }
+
+            let operation = decode_operation(&encoded_operation)
+                .map_err(|err| anyhow!("Invalid operation at index {}: {}", index, err))?;
+
+            let entry = decode_entry(&encoded_entry)
+                .map_err(|err| anyhow!("Invalid entry at index {}: {}", index, err))?;
+
+            if let Some(access) = ctx.data_opt::<Access>() {
+                access
+                    .check_publish(operation.schema_id(), entry.public_key())
+                    .map_err(|err| anyhow!("Unauthorized entry at index {}: {}", index, err))?;
+            }
+
+            public_keys.push(*entry.public_key());
+            items.push((encoded_entry, encoded_operation, operation));
+        }
+
+        // Reject clients and authors which published too many entries recently. All entries get
+        // counted towards their limits right away so parallel requests can not get past them
+        let limits = ctx.data_opt::<Limits>();
+        if let Some(limits) = limits {
+            limits
+                .try_publish(ctx.data_opt::<ClientAddress>(), &public_keys)
+                .map_err(|err| err.extend())?;
+        }
+
+        let result: Result<(Vec<OperationId>, Vec<NextArguments>)> = async {
+            // Validate all entries first, nothing gets written to the database yet
+            let batch = BatchStore::new(store);
+            let mut operation_ids = Vec::with_capacity(items.len());
+            let mut next_args = Vec::with_capacity(items.len());
+
+            for (index, (encoded_entry, encoded_operation, operation)) in
+                items.into_iter().enumerate()
+            {
+                let schema = schema_provider
+                    .get(operation.schema_id())
+                    .await
+                    .ok_or_else(|| anyhow!("Schema not found for entry at index {}", index))?;
+
+                let (backlink, skiplink, seq_num, log_id) = publish(
+                    &batch,
+                    &schema,
+                    &encoded_entry,
+                    &operation,
+                    &encoded_operation,
+                )
+                .await
+                .map_err(|err| anyhow!("Invalid entry at index {}: {}", index, err))?;
+
+                operation_ids.push(OperationId::from(encoded_entry.hash()));
+                next_args.push(NextArguments {
+                    log_id: log_id.into(),
+                    seq_num: seq_num.into(),
+                    backlink: backlink.map(|hash| hash.into()),
+                    skiplink: skiplink.map(|hash| hash.into()),
+                });
+            }
+
+            // Persist all entries and operations at once, either all or none of them get stored
+            batch.commit().await?;
+
+            Ok((operation_ids, next_args))
+        }
+        .await;
+
+        // Entries which did not get published do not count towards the limits
+        let (operation_ids, next_args) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                if let Some(limits) = limits {
+                    limits.refund_publish(ctx.data_opt::<ClientAddress>(), &public_keys);
+                }
+
+                return Err(err);
+            }
+        };
+
+        // Send new operations in the same order on service communication bus
+        for operation_id in operation_ids {
+            if tx.send(ServiceMessage::NewOperation(operation_id)).is_err() {
+                // Silently fail here as we don't mind if there are no subscribers
+            }
+        }
+
+        Ok(next_args)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
     use std::str::FromStr;
 
-    use async_graphql::{value, Request, Variables};
+    use async_graphql::{value, Request, Response, Value, Variables};
     use ciborium::cbor;
+    use futures::future::join_all;
     use once_cell::sync::Lazy;
     use p2panda_rs::api::next_args;
     use p2panda_rs::document::{DocumentId, DocumentViewId};
@@ -107,7 +288,7 @@ mod tests {
     use p2panda_rs::identity::KeyPair;
     use p2panda_rs::operation::encode::encode_operation;
     use p2panda_rs::operation::{
-        EncodedOperation, OperationBuilder, OperationValue, PinnedRelationList,
+        EncodedOperation, OperationAction, OperationBuilder, OperationValue, PinnedRelationList,
     };
     use p2panda_rs::schema::{FieldType, Schema, SchemaId};
     use p2panda_rs::serde::serialize_value;
@@ -122,9 +303,12 @@ mod tests {
     use serde_json::json;
     use tokio::sync::broadcast;
 
+    use crate::auth::Access;
     use crate::bus::ServiceMessage;
+    use crate::config::{AllowList, ApiScope, ApiToken, Configuration};
     use crate::graphql::GraphQLSchemaManager;
     use crate::http::HttpServiceContext;
+    use crate::limits::{ClientAddress, Limits};
     use crate::test_utils::{
         add_schema, doggo_fields, doggo_schema, http_test_client, populate_and_materialize,
         populate_store_config, test_runner, PopulateStoreConfig, TestNode,
@@ -234,12 +418,17 @@ mod tests {
                 node.context.store.clone(),
                 tx,
                 node.context.schema_provider.clone(),
+                node.context.status.clone(),
             )
             .await;
             let context = HttpServiceContext::new(
                 node.context.store.clone(),
                 manager,
                 node.context.config.blobs_base_path.to_path_buf(),
+                node.context.status.clone(),
+                node.context.metrics.clone(),
+                node.context.config.api_tokens.clone(),
+                node.context.limits.clone(),
             );
 
             let response = context.schema.execute(publish_request).await;
@@ -299,12 +488,17 @@ mod tests {
                 node.context.store.clone(),
                 tx,
                 node.context.schema_provider.clone(),
+                node.context.status.clone(),
             )
             .await;
             let context = HttpServiceContext::new(
                 node.context.store.clone(),
                 manager,
                 node.context.config.blobs_base_path.to_path_buf(),
+                node.context.status.clone(),
+                node.context.metrics.clone(),
+                node.context.config.api_tokens.clone(),
+                node.context.limits.clone(),
             );
 
             let response = context
@@ -316,6 +510,195 @@ mod tests {
         });
     }
 
+    #[rstest]
+    fn publish_restricted_by_token(
+        #[from(populate_store_config)]
+        #[with(0, 0, vec![], false, test_schema())]
+        config: PopulateStoreConfig,
+        #[from(publish_request)] request_other_author: Request,
+        #[from(publish_request)] request_other_schema: Request,
+        #[from(publish_request)] request_allowed: Request,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Adds the test_schema to the store and schema provider.
+            populate_and_materialize(&mut node, &config).await;
+
+            let (tx, _rx) = broadcast::channel(120);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+
+            let access = |publish_schema_ids, publish_public_keys| {
+                Access::Token(Box::new(ApiToken {
+                    token: "publisher".into(),
+                    scopes: vec![ApiScope::Publish],
+                    publish_schema_ids,
+                    publish_public_keys,
+                }))
+            };
+
+            // Token is only allowed to publish entries of another author
+            let response = manager
+                .execute(request_other_author.data(access(
+                    AllowList::Wildcard,
+                    AllowList::Set(vec![KeyPair::new().public_key()]),
+                )))
+                .await;
+            assert!(response.errors[0]
+                .message
+                .contains("not allowed to publish"));
+
+            // Token is only allowed to publish operations of another schema
+            let response = manager
+                .execute(request_other_schema.data(access(
+                    AllowList::Set(vec![SchemaId::Blob(1)]),
+                    AllowList::Wildcard,
+                )))
+                .await;
+            assert!(response.errors[0]
+                .message
+                .contains("not allowed to publish"));
+
+            // Token is allowed to publish this entry
+            let response = manager
+                .execute(request_allowed.data(access(
+                    AllowList::Set(vec![test_schema().id().to_owned()]),
+                    AllowList::Set(vec![key_pair(PRIVATE_KEY).public_key()]),
+                )))
+                .await;
+            assert!(response.is_ok(), "{:?}", response.errors);
+        });
+    }
+
+    #[rstest]
+    fn publish_limits(
+        #[from(populate_store_config)]
+        #[with(0, 0, vec![], false, test_schema())]
+        config: PopulateStoreConfig,
+        #[from(publish_request)] request_too_large: Request,
+        #[from(publish_request)] request_allowed: Request,
+        #[from(publish_request)] request_rate_limited: Request,
+        #[from(publish_request)] request_replayed: Request,
+        #[from(publish_request)] request_replayed_again: Request,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Adds the test_schema to the store and schema provider.
+            populate_and_materialize(&mut node, &config).await;
+
+            let (tx, _rx) = broadcast::channel(120);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+
+            let limits = |max_operation_size| {
+                Limits::new(
+                    &Configuration {
+                        publish_rate_limit_per_ip: 1,
+                        max_operation_size,
+                        ..Configuration::default()
+                    },
+                    node.context.metrics.clone(),
+                )
+            };
+            let client_address = ClientAddress(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+            let error_code = |response: &Response| {
+                response.errors[0]
+                    .extensions
+                    .as_ref()
+                    .and_then(|extensions| extensions.get("code"))
+                    .cloned()
+            };
+
+            // Operation exceeds the maximum size
+            let response = manager
+                .execute(request_too_large.data(limits(10)).data(client_address))
+                .await;
+            assert_eq!(
+                error_code(&response),
+                Some(Value::from("OPERATION_TOO_LARGE"))
+            );
+
+            // First entry of this client is allowed, the next one is rate limited
+            let limits = limits(1024);
+            let response = manager
+                .execute(request_allowed.data(limits.clone()).data(client_address))
+                .await;
+            assert!(response.is_ok(), "{:?}", response.errors);
+
+            let response = manager
+                .execute(request_rate_limited.data(limits).data(client_address))
+                .await;
+            assert_eq!(
+                error_code(&response),
+                Some(Value::from("RATE_LIMITED_CLIENT"))
+            );
+
+            // Entries which fail validation, like replayed ones, do not count towards the limits
+            let limits = Limits::new(
+                &Configuration {
+                    publish_rate_limit_per_ip: 1,
+                    ..Configuration::default()
+                },
+                node.context.metrics.clone(),
+            );
+            for request in [request_replayed, request_replayed_again] {
+                let response = manager
+                    .execute(request.data(limits.clone()).data(client_address))
+                    .await;
+                assert!(!response.is_ok());
+                assert_eq!(error_code(&response), None);
+            }
+        });
+    }
+
+    #[rstest]
+    fn publish_limits_concurrent_requests(
+        #[from(populate_store_config)]
+        #[with(0, 0, vec![], false, test_schema())]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Adds the test_schema to the store and schema provider.
+            populate_and_materialize(&mut node, &config).await;
+
+            let (tx, _rx) = broadcast::channel(120);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+            let limits = Limits::new(
+                &Configuration {
+                    publish_rate_limit_per_ip: 1,
+                    ..Configuration::default()
+                },
+                node.context.metrics.clone(),
+            );
+            let client_address = ClientAddress(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+            // The same client sends valid entries of different authors all at once
+            let requests = (0..5).map(|_| {
+                let (entry, operation) = create_and_update(&KeyPair::new()).remove(0);
+                let request = publish_request(&entry.to_string(), &operation.to_string());
+                manager.execute(request.data(limits.clone()).data(client_address))
+            });
+            let responses = join_all(requests).await;
+
+            let published = responses.iter().filter(|response| response.is_ok()).count();
+            assert_eq!(published, 1);
+        });
+    }
+
     #[rstest]
     fn sends_message_on_communication_bus(
         #[from(populate_store_config)]
@@ -331,12 +714,17 @@ mod tests {
                 node.context.store.clone(),
                 tx,
                 node.context.schema_provider.clone(),
+                node.context.status.clone(),
             )
             .await;
             let context = HttpServiceContext::new(
                 node.context.store.clone(),
                 manager,
                 node.context.config.blobs_base_path.to_path_buf(),
+                node.context.status.clone(),
+                node.context.metrics.clone(),
+                node.context.config.api_tokens.clone(),
+                node.context.limits.clone(),
             );
 
             context.schema.execute(publish_request).await;
@@ -909,4 +1297,243 @@ mod tests {
             }
         });
     }
+
+    // Prepare GraphQL mutation publishing a batch of entries.
+    fn publish_batch_request(items: &[(EncodedEntry, EncodedOperation)]) -> Request {
+        let entries: Vec<serde_json::Value> = items
+            .iter()
+            .map(|(entry, operation)| {
+                json!({
+                    "entry": entry.to_string(),
+                    "operation": operation.to_string(),
+                })
+            })
+            .collect();
+
+        Request::new(
+            r#"
+            mutation TestPublishBatch($entries: [PublishBatchItem!]!) {
+                publishBatch(entries: $entries) {
+                    logId,
+                    seqNum,
+                    backlink
+                }
+            }"#,
+        )
+        .variables(Variables::from_json(json!({ "entries": entries })))
+    }
+
+    // Create a document and update it right away, signed by the given key pair.
+    fn create_and_update(key_pair: &KeyPair) -> Vec<(EncodedEntry, EncodedOperation)> {
+        let create_operation = OperationBuilder::new(test_schema().id())
+            .fields(&[("message", OperationValue::String("Hello!".to_string()))])
+            .build()
+            .unwrap();
+        let create_operation = encode_operation(&create_operation).unwrap();
+        let create_entry = EntryBuilder::new()
+            .sign(&create_operation, key_pair)
+            .unwrap();
+        let create_entry = encode_entry(&create_entry).unwrap();
+
+        let update_operation = OperationBuilder::new(test_schema().id())
+            .action(OperationAction::Update)
+            .previous(&DocumentViewId::new(&[create_entry.hash().into()]))
+            .fields(&[(
+                "message",
+                OperationValue::String("Hello again!".to_string()),
+            )])
+            .build()
+            .unwrap();
+        let update_operation = encode_operation(&update_operation).unwrap();
+        let update_entry = EntryBuilder::new()
+            .seq_num(&SeqNum::new(2).unwrap())
+            .backlink(&create_entry.hash())
+            .sign(&update_operation, key_pair)
+            .unwrap();
+        let update_entry = encode_entry(&update_entry).unwrap();
+
+        vec![
+            (create_entry, create_operation),
+            (update_entry, update_operation),
+        ]
+    }
+
+    #[rstest]
+    fn publish_batch(
+        #[from(populate_store_config)]
+        #[with(0, 0, vec![], false, test_schema())]
+        config: PopulateStoreConfig,
+        key_pair: KeyPair,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Adds the test_schema to the store and schema provider.
+            populate_and_materialize(&mut node, &config).await;
+
+            let (tx, mut rx) = broadcast::channel(120);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+            let context = HttpServiceContext::new(
+                node.context.store.clone(),
+                manager,
+                node.context.config.blobs_base_path.to_path_buf(),
+                node.context.status.clone(),
+                node.context.metrics.clone(),
+                node.context.config.api_tokens.clone(),
+                node.context.limits.clone(),
+            );
+
+            // The update depends on the create operation from the same batch
+            let items = create_and_update(&key_pair);
+            let response = context.schema.execute(publish_batch_request(&items)).await;
+
+            let create_hash = items[0].0.hash();
+            let update_hash = items[1].0.hash();
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "publishBatch": [
+                        {
+                            "logId": "0",
+                            "seqNum": "2",
+                            "backlink": create_hash.to_string(),
+                        },
+                        {
+                            "logId": "0",
+                            "seqNum": "3",
+                            "backlink": update_hash.to_string(),
+                        }
+                    ]
+                }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Both entries got persisted
+            for (entry, _) in &items {
+                let stored = node.context.store.get_entry(&entry.hash()).await.unwrap();
+                assert!(stored.is_some());
+            }
+
+            // Operations are announced on the bus in the same order
+            assert_eq!(
+                rx.recv().await.unwrap(),
+                ServiceMessage::NewOperation(create_hash.into())
+            );
+            assert_eq!(
+                rx.recv().await.unwrap(),
+                ServiceMessage::NewOperation(update_hash.into())
+            );
+        });
+    }
+
+    #[rstest]
+    fn publish_batch_is_atomic(
+        #[from(populate_store_config)]
+        #[with(0, 0, vec![], false, test_schema())]
+        config: PopulateStoreConfig,
+        key_pair: KeyPair,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Adds the test_schema to the store and schema provider.
+            populate_and_materialize(&mut node, &config).await;
+
+            let client = http_test_client(&node).await;
+
+            // The second entry claims to be in another log than the first one, which is invalid
+            let mut items = create_and_update(&key_pair);
+            let (_, update_operation) = items.pop().unwrap();
+            let invalid_entry = EntryBuilder::new()
+                .log_id(&LogId::new(1))
+                .seq_num(&SeqNum::new(2).unwrap())
+                .backlink(&items[0].0.hash())
+                .sign(&update_operation, &key_pair)
+                .unwrap();
+            items.push((encode_entry(&invalid_entry).unwrap(), update_operation));
+
+            let request = publish_batch_request(&items);
+            let response = client
+                .post("/graphql")
+                .json(&json!({
+                  "query": request.query,
+                  "variables": request.variables
+                }))
+                .send()
+                .await
+                .json::<serde_json::Value>()
+                .await;
+
+            let errors = response.get("errors").unwrap().as_array().unwrap();
+            assert!(errors[0]
+                .get("message")
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid entry at index 1"));
+
+            // The valid first entry was not persisted either
+            let stored = node
+                .context
+                .store
+                .get_entry(&items[0].0.hash())
+                .await
+                .unwrap();
+            assert!(stored.is_none());
+        });
+    }
+
+    #[rstest]
+    fn publish_batch_too_large(
+        #[from(populate_store_config)]
+        #[with(0, 0, vec![], false, test_schema())]
+        config: PopulateStoreConfig,
+        key_pair: KeyPair,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Adds the test_schema to the store and schema provider.
+            populate_and_materialize(&mut node, &config).await;
+
+            let (tx, _rx) = broadcast::channel(120);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+            let limits = Limits::new(
+                &Configuration {
+                    max_batch_size: 1,
+                    ..Configuration::default()
+                },
+                node.context.metrics.clone(),
+            );
+
+            let items = create_and_update(&key_pair);
+            let response = manager
+                .execute(publish_batch_request(&items).data(limits))
+                .await;
+
+            let code = response.errors[0]
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.get("code"))
+                .cloned();
+            assert_eq!(code, Some(Value::from("BATCH_TOO_LARGE")));
+
+            // None of the entries got published
+            let stored = node
+                .context
+                .store
+                .get_entry(&items[0].0.hash())
+                .await
+                .unwrap();
+            assert!(stored.is_none());
+        });
+    }
 }
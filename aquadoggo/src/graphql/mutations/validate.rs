@@ -0,0 +1,403 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use dynamic_graphql::{Context, Mutation, MutationFields, Result};
+use log::debug;
+use p2panda_rs::api::{publish, DomainError, ValidationError};
+use p2panda_rs::entry::decode::decode_entry;
+use p2panda_rs::entry::traits::{AsEncodedEntry, AsEntry};
+use p2panda_rs::entry::EncodedEntry;
+use p2panda_rs::operation::decode::decode_operation;
+use p2panda_rs::operation::error::ValidateOperationError;
+use p2panda_rs::operation::traits::Schematic;
+use p2panda_rs::operation::EncodedOperation;
+
+use crate::auth::Access;
+use crate::db::stores::BatchStore;
+use crate::db::SqlStore;
+use crate::graphql::mutations::MutationRoot;
+use crate::graphql::responses::{ValidationErrorKind, ValidationErrorResponse, ValidationResponse};
+use crate::graphql::scalars::{EncodedEntryScalar, EncodedOperationScalar};
+use crate::limits::Limits;
+use crate::schema::SchemaProvider;
+
+/// GraphQL "validate" mutation.
+#[derive(Mutation, Default, Debug, Copy, Clone)]
+pub struct Validate(MutationRoot);
+
+#[MutationFields]
+impl Validate {
+    /// Run all checks `publish` runs against an entry and operation without storing or
+    /// announcing them.
+    ///
+    /// Returns a list of all problems found, which is empty if the entry would be accepted.
+    async fn validate(
+        ctx: &Context<'_>,
+        // Signed and encoded entry to validate
+        entry: EncodedEntryScalar,
+        // p2panda operation representing the entry payload.
+        operation: EncodedOperationScalar,
+    ) -> Result<ValidationResponse> {
+        let store = ctx.data::<SqlStore>()?;
+        let schema_provider = ctx.data::<SchemaProvider>()?;
+
+        let encoded_entry: EncodedEntry = entry.into();
+        let encoded_operation: EncodedOperation = operation.into();
+
+        debug!(
+            "Query to validate received containing entry with hash {}",
+            encoded_entry.hash()
+        );
+
+        let mut errors = Vec::new();
+
+        if let Some(limits) = ctx.data_opt::<Limits>() {
+            if let Err(err) = limits.check_operation_size(encoded_operation.size()) {
+                errors.push(validation_error(ValidationErrorKind::Limit, err));
+            }
+        }
+
+        // Decode entry and operation independently to report problems with both of them at once
+        let entry = match decode_entry(&encoded_entry) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                errors.push(validation_error(ValidationErrorKind::Entry, err));
+                None
+            }
+        };
+
+        let operation = match decode_operation(&encoded_operation) {
+            Ok(operation) => Some(operation),
+            Err(err) => {
+                errors.push(validation_error(ValidationErrorKind::Operation, err));
+                None
+            }
+        };
+
+        // Rate limits are not checked here as validating does not use up any of the client's
+        // tokens, but the remaining restrictions of `publish` are
+        if let (Some(access), Some(entry), Some(operation)) =
+            (ctx.data_opt::<Access>(), &entry, &operation)
+        {
+            if let Err(err) = access.check_publish(operation.schema_id(), entry.public_key()) {
+                errors.push(validation_error(ValidationErrorKind::Access, err));
+            }
+        }
+
+        let schema = match &operation {
+            Some(operation) => {
+                let schema = schema_provider.get(operation.schema_id()).await;
+
+                if schema.is_none() {
+                    errors.push(validation_error(
+                        ValidationErrorKind::Schema,
+                        format!(
+                            "Schema {} not supported by this node",
+                            operation.schema_id()
+                        ),
+                    ));
+                }
+
+                schema
+            }
+            None => None,
+        };
+
+        // Run the actual publishing logic on top of a batch store which never gets committed,
+        // this covers log, sequence number, backlink and skiplink checks without writing anything
+        if let (true, Some(operation), Some(schema)) = (errors.is_empty(), operation, schema) {
+            let batch = BatchStore::new(store);
+
+            if let Err(err) = publish(
+                &batch,
+                &schema,
+                &encoded_entry,
+                &operation,
+                &encoded_operation,
+            )
+            .await
+            {
+                errors.push(validation_error(domain_error_kind(&err), err));
+            }
+        }
+
+        Ok(ValidationResponse {
+            valid: errors.is_empty(),
+            errors,
+        })
+    }
+}
+
+fn validation_error(kind: ValidationErrorKind, message: impl ToString) -> ValidationErrorResponse {
+    ValidationErrorResponse {
+        kind,
+        message: message.to_string(),
+    }
+}
+
+/// Determine which check failed from an error returned by `publish`.
+fn domain_error_kind(err: &DomainError) -> ValidationErrorKind {
+    match err {
+        DomainError::DecodeEntryError(_) => ValidationErrorKind::Entry,
+        DomainError::ValidateOperationError(ValidateOperationError::ValidateEntryError(_)) => {
+            ValidationErrorKind::Entry
+        }
+        DomainError::ValidateOperationError(_) => ValidationErrorKind::Operation,
+        DomainError::DeletedDocument => ValidationErrorKind::Document,
+        DomainError::MaxSeqNumReached(_, _) | DomainError::ExpectedLogIdNotFound(_) => {
+            ValidationErrorKind::Log
+        }
+        DomainError::ValidationError(err) => match err {
+            ValidationError::InvalidClaimedSchema(_, _, _)
+            | ValidationError::DocumentDeleted
+            | ValidationError::PreviousOperationNotFound(_)
+            | ValidationError::InvalidDocumentViewId => ValidationErrorKind::Document,
+            ValidationError::LogStoreError(_)
+            | ValidationError::EntryStoreError(_)
+            | ValidationError::OperationStoreError(_) => ValidationErrorKind::Store,
+            _ => ValidationErrorKind::Log,
+        },
+        DomainError::LogStoreError(_)
+        | DomainError::EntryStoreError(_)
+        | DomainError::OperationStoreError(_) => ValidationErrorKind::Store,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use async_graphql::{value, Request, Response};
+    use p2panda_rs::entry::encode::encode_entry;
+    use p2panda_rs::entry::traits::AsEncodedEntry;
+    use p2panda_rs::entry::{EncodedEntry, EntryBuilder, SeqNum};
+    use p2panda_rs::identity::{KeyPair, PublicKey};
+    use p2panda_rs::operation::encode::encode_operation;
+    use p2panda_rs::operation::{EncodedOperation, OperationBuilder, OperationValue};
+    use p2panda_rs::schema::{FieldType, SchemaId};
+    use p2panda_rs::storage_provider::traits::EntryStore;
+    use p2panda_rs::test_utils::fixtures::{key_pair, random_hash};
+    use rstest::rstest;
+    use serde_json::json;
+    use tokio::sync::broadcast;
+
+    use crate::auth::Access;
+    use crate::config::{AllowList, ApiScope, ApiToken};
+    use crate::graphql::GraphQLSchemaManager;
+    use crate::limits::{ClientAddress, Limits};
+    use crate::test_utils::{add_schema, http_test_client, test_runner, TestClient, TestNode};
+    use crate::Configuration;
+
+    // Create a signed entry and operation for the given schema.
+    fn create_entry(
+        schema_id: &SchemaId,
+        seq_num: u64,
+        key_pair: &KeyPair,
+    ) -> (EncodedEntry, EncodedOperation) {
+        let operation = OperationBuilder::new(schema_id)
+            .fields(&[("message", OperationValue::String("Hello!".to_string()))])
+            .build()
+            .unwrap();
+        let operation = encode_operation(&operation).unwrap();
+        let mut entry = EntryBuilder::new().seq_num(&SeqNum::new(seq_num).unwrap());
+        if seq_num > 1 {
+            entry = entry.backlink(&random_hash());
+        }
+        let entry = entry.sign(&operation, key_pair).unwrap();
+
+        (encode_entry(&entry).unwrap(), operation)
+    }
+
+    async fn validate(client: &TestClient, entry: &str, operation: &str) -> Response {
+        client
+            .post("/graphql")
+            .json(&json!({
+                "query": format!(
+                    r#"mutation {{
+                        validate(entry: "{entry}", operation: "{operation}") {{
+                            valid
+                            errors {{
+                                kind
+                            }}
+                        }}
+                    }}"#
+                )
+            }))
+            .send()
+            .await
+            .json()
+            .await
+    }
+
+    #[rstest]
+    fn validate_without_publishing(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let schema = add_schema(
+                &mut node,
+                "message",
+                vec![("message", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+
+            let client = http_test_client(&node).await;
+
+            // Use a different author than the one who created the schema, so we start a new log
+            let key_pair = KeyPair::new();
+
+            // Valid entries are reported as such but not persisted
+            let (entry, operation) = create_entry(schema.id(), 1, &key_pair);
+            let response = validate(&client, &entry.to_string(), &operation.to_string()).await;
+            assert_eq!(
+                response.data,
+                value!({ "validate": { "valid": true, "errors": [] } }),
+                "{:#?}",
+                response.errors
+            );
+            let stored = node.context.store.get_entry(&entry.hash()).await.unwrap();
+            assert!(stored.is_none());
+
+            // Entry claims to continue a log which does not exist yet
+            let (entry, operation) = create_entry(schema.id(), 2, &key_pair);
+            let response = validate(&client, &entry.to_string(), &operation.to_string()).await;
+            assert_eq!(
+                response.data,
+                value!({ "validate": { "valid": false, "errors": [{ "kind": "LOG" }] } }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+
+    #[rstest]
+    fn validate_reports_all_errors(key_pair: KeyPair) {
+        test_runner(|node: TestNode| async move {
+            let client = http_test_client(&node).await;
+
+            // Schema of the operation is not known to this node
+            let schema_id = SchemaId::from_str(
+                "message_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b",
+            )
+            .unwrap();
+            let (entry, operation) = create_entry(&schema_id, 1, &key_pair);
+            let response = validate(&client, &entry.to_string(), &operation.to_string()).await;
+            assert_eq!(
+                response.data,
+                value!({ "validate": { "valid": false, "errors": [{ "kind": "SCHEMA" }] } }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Both entry and operation are malformed
+            let response = validate(&client, "0042", "a1").await;
+            assert_eq!(
+                response.data,
+                value!({
+                    "validate": {
+                        "valid": false,
+                        "errors": [{ "kind": "ENTRY" }, { "kind": "OPERATION" }],
+                    }
+                }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+
+    fn access_token(
+        publish_schema_ids: AllowList<SchemaId>,
+        publish_public_keys: AllowList<PublicKey>,
+    ) -> Access {
+        Access::Token(Box::new(ApiToken {
+            token: "publisher".into(),
+            scopes: vec![ApiScope::Publish],
+            publish_schema_ids,
+            publish_public_keys,
+        }))
+    }
+
+    #[rstest]
+    #[case::operation_too_large(
+        Configuration { max_operation_size: 1, ..Configuration::default() },
+        Access::Unrestricted,
+        "LIMIT"
+    )]
+    #[case::other_schema(
+        Configuration::default(),
+        access_token(AllowList::Set(vec![SchemaId::Blob(1)]), AllowList::Wildcard),
+        "ACCESS"
+    )]
+    #[case::other_public_key(
+        Configuration::default(),
+        access_token(AllowList::Wildcard, AllowList::Set(vec![KeyPair::new().public_key()])),
+        "ACCESS"
+    )]
+    fn validate_limits_and_access(
+        key_pair: KeyPair,
+        #[case] config: Configuration,
+        #[case] access: Access,
+        #[case] expected_kind: &'static str,
+    ) {
+        test_runner(move |mut node: TestNode| async move {
+            let schema = add_schema(
+                &mut node,
+                "message",
+                vec![("message", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+
+            let (tx, _rx) = broadcast::channel(120);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+
+            let limits = Limits::new(
+                &Configuration {
+                    publish_rate_limit_per_ip: 1,
+                    ..config
+                },
+                node.context.metrics.clone(),
+            );
+            let client_address = ClientAddress(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+            let key_pair = KeyPair::new();
+            let (entry, operation) = create_entry(schema.id(), 1, &key_pair);
+            let request = Request::new(format!(
+                r#"mutation {{
+                    validate(entry: "{entry}", operation: "{operation}") {{
+                        valid
+                        errors {{
+                            kind
+                        }}
+                    }}
+                }}"#
+            ));
+
+            let response = manager
+                .execute(
+                    request
+                        .data(limits.clone())
+                        .data(access)
+                        .data(client_address),
+                )
+                .await;
+            assert_eq!(
+                response.data,
+                value!({ "validate": { "valid": false, "errors": [{ "kind": expected_kind }] } }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Validating did not use up any of the client's or author's rate limit tokens
+            assert!(limits
+                .try_publish(Some(&client_address), &[key_pair.public_key()])
+                .is_ok());
+        });
+    }
+}
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, Object, ResolverContext, TypeRef};
+use async_graphql::Value;
+use p2panda_rs::operation::OperationValue;
+use p2panda_rs::schema::{FieldType, Schema};
+
+use crate::db::stores::{AggregateGroup, FieldAggregate};
+use crate::graphql::constants;
+use crate::graphql::utils::{
+    aggregate_averages_name, aggregate_name, aggregate_values_name, gql_scalar, graphql_type,
+};
+
+/// Aggregated values of all numeric fields passed from an aggregation to its children.
+#[derive(Clone, Debug)]
+struct AggregatedValues(BTreeMap<String, Value>);
+
+/// Downcast aggregation which will have been passed up by the parent query node.
+fn downcast_group<'a>(ctx: &'a ResolverContext) -> &'a AggregateGroup {
+    ctx.parent_value
+        .downcast_ref::<AggregateGroup>()
+        .expect("Values passed from query parent should match expected")
+}
+
+/// Returns a field on an aggregation which resolves to one aggregated value of every numeric
+/// field.
+fn aggregated_values_field(
+    name: &str,
+    type_name: String,
+    aggregated_value: fn(&FieldAggregate) -> Value,
+) -> Field {
+    Field::new(name, TypeRef::named_nn(type_name), move |ctx| {
+        FieldFuture::new(async move {
+            let values = downcast_group(&ctx)
+                .fields
+                .iter()
+                .map(|(name, aggregate)| (name.to_owned(), aggregated_value(aggregate)))
+                .collect();
+
+            Ok(Some(FieldValue::owned_any(AggregatedValues(values))))
+        })
+    })
+}
+
+/// Returns a field resolving to the aggregated value of one numeric field.
+fn aggregated_value_field(name: &str, type_ref: TypeRef) -> Field {
+    let field_name = name.to_owned();
+
+    Field::new(name, type_ref, move |ctx| {
+        let field_name = field_name.clone();
+
+        FieldFuture::new(async move {
+            let values = ctx
+                .parent_value
+                .downcast_ref::<AggregatedValues>()
+                .expect("Values passed from query parent should match expected");
+
+            let value = values.0.get(&field_name).cloned().unwrap_or(Value::Null);
+            Ok(Some(FieldValue::value(value)))
+        })
+    })
+}
+
+/// Convert an optional aggregated operation value into a GraphQL value.
+fn optional_scalar(value: &Option<OperationValue>) -> Value {
+    value.as_ref().map(gql_scalar).unwrap_or(Value::Null)
+}
+
+/// Dynamically build GraphQL objects describing the aggregated values of all numeric fields of a
+/// p2panda schema.
+///
+/// Two objects are generated for each schema: `<schema_id>AggregateValues` keeps the type of every
+/// field and is used for sums, minimum and maximum values, `<schema_id>AggregateAverages` contains
+/// floats for the averages. No objects are returned when the schema doesn't contain any integer or
+/// float fields.
+pub fn build_aggregate_values_objects(schema: &Schema) -> Vec<Object> {
+    let mut values_object = Object::new(aggregate_values_name(schema.id())).description(format!(
+        "Aggregated values of all numeric fields of `{}` documents.",
+        schema.id().name()
+    ));
+    let mut averages_object =
+        Object::new(aggregate_averages_name(schema.id())).description(format!(
+            "Averages of all numeric fields of `{}` documents.",
+            schema.id().name()
+        ));
+
+    let mut has_numeric_fields = false;
+
+    for (name, field_type) in schema.fields().iter() {
+        if !matches!(field_type, FieldType::Integer | FieldType::Float) {
+            continue;
+        }
+
+        has_numeric_fields = true;
+
+        values_object = values_object.field(aggregated_value_field(name, graphql_type(field_type)));
+        averages_object =
+            averages_object.field(aggregated_value_field(name, TypeRef::named(TypeRef::FLOAT)));
+    }
+
+    if has_numeric_fields {
+        vec![values_object, averages_object]
+    } else {
+        vec![]
+    }
+}
+
+/// Dynamically build a GraphQL object describing aggregations over a collection of documents.
+///
+/// Each object contains `group` and `count` fields. Schemas with integer or float fields
+/// additionally get `sum`, `avg`, `min` and `max` fields.
+///
+/// Each generated object has a type name with the formatting `<schema_id>Aggregate`.
+pub fn build_aggregate_object(schema: &Schema) -> Object {
+    let mut object = Object::new(aggregate_name(schema.id()))
+        .field(
+            Field::new(
+                constants::GROUP_FIELD,
+                TypeRef::named(TypeRef::STRING),
+                move |ctx| {
+                    FieldFuture::new(async move {
+                        let group = downcast_group(&ctx).group.clone();
+                        Ok(Some(FieldValue::value(
+                            group.map_or(Value::Null, Value::from),
+                        )))
+                    })
+                },
+            )
+            .description(
+                "Value of the field the documents were grouped by, `null` when not grouped.",
+            ),
+        )
+        .field(
+            Field::new(
+                constants::COUNT_FIELD,
+                TypeRef::named_nn(TypeRef::INT),
+                move |ctx| {
+                    FieldFuture::new(async move {
+                        let count = downcast_group(&ctx).count;
+                        Ok(Some(FieldValue::value(Value::from(count))))
+                    })
+                },
+            )
+            .description("Number of documents in this group."),
+        )
+        .description(format!(
+            "Aggregated values over a group of `{}` documents.",
+            schema.id().name()
+        ));
+
+    let has_numeric_fields = schema
+        .fields()
+        .iter()
+        .any(|(_, field_type)| matches!(field_type, FieldType::Integer | FieldType::Float));
+
+    if has_numeric_fields {
+        object = object
+            .field(
+                aggregated_values_field(
+                    constants::SUM_FIELD,
+                    aggregate_values_name(schema.id()),
+                    |aggregate| optional_scalar(&aggregate.sum),
+                )
+                .description("Sum of the values of every numeric field."),
+            )
+            .field(
+                aggregated_values_field(
+                    constants::AVG_FIELD,
+                    aggregate_averages_name(schema.id()),
+                    |aggregate| aggregate.avg.map_or(Value::Null, Value::from),
+                )
+                .description("Average of the values of every numeric field."),
+            )
+            .field(
+                aggregated_values_field(
+                    constants::MIN_FIELD,
+                    aggregate_values_name(schema.id()),
+                    |aggregate| optional_scalar(&aggregate.min),
+                )
+                .description("Smallest value of every numeric field."),
+            )
+            .field(
+                aggregated_values_field(
+                    constants::MAX_FIELD,
+                    aggregate_values_name(schema.id()),
+                    |aggregate| optional_scalar(&aggregate.max),
+                )
+                .description("Largest value of every numeric field."),
+            );
+    }
+
+    object
+}
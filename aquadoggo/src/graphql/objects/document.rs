@@ -1,13 +1,15 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use async_graphql::dynamic::{Field, FieldFuture, FieldValue, Object, TypeRef};
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, Object, TypeRef};
 use async_graphql::Value;
 use p2panda_rs::schema::Schema;
 
 use crate::db::query::Cursor;
 use crate::graphql::constants;
-use crate::graphql::resolvers::{resolve_document_meta, Resolved};
-use crate::graphql::utils::{collection_item_name, fields_name};
+use crate::graphql::resolvers::{resolve_document_history, resolve_document_meta, Resolved};
+use crate::graphql::utils::{
+    collection_item_name, fields_name, referenced_by_name, reverse_relations,
+};
 
 /// Dynamically build GraphQL objects describing documents which conform to the shape of a p2panda
 /// schema.
@@ -15,9 +17,9 @@ use crate::graphql::utils::{collection_item_name, fields_name};
 /// Constructs resolvers for both `fields` and `meta` fields. The former simply passes up the query
 /// arguments to its children query fields. The latter calls the `resolve` method defined on
 /// `DocumentMeta` type.
-pub fn build_document_object(schema: &Schema) -> Object {
+pub fn build_document_object(schema: &Schema, all_schema: &[Schema]) -> Object {
     let fields = Object::new(schema.id().to_string());
-    with_document_fields(fields, schema)
+    with_document_fields(fields, schema, all_schema)
 }
 
 /// Dynamically build GraphQL objects describing documents which conform to the shape of a p2panda
@@ -26,10 +28,10 @@ pub fn build_document_object(schema: &Schema) -> Object {
 /// Contains resolvers for `cursor`, `fields` and `meta`. `fields` simply passes up the query
 /// arguments to its children query fields. `meta` calls the `resolve` method defined on
 /// `DocumentMeta` type.
-pub fn build_paginated_document_object(schema: &Schema) -> Object {
+pub fn build_paginated_document_object(schema: &Schema, all_schema: &[Schema]) -> Object {
     let fields = Object::new(collection_item_name(schema.id()));
 
-    with_document_fields(fields, schema).field(
+    with_document_fields(fields, schema, all_schema).field(
         Field::new(
             constants::CURSOR_FIELD,
             TypeRef::named(TypeRef::STRING),
@@ -54,7 +56,32 @@ pub fn build_paginated_document_object(schema: &Schema) -> Object {
 }
 
 /// Add application `fields` and `meta` fields to a GraphQL object.
-fn with_document_fields(fields: Object, schema: &Schema) -> Object {
+///
+/// A `referencedBy` field is added as well when documents of any known schema can relate to this
+/// document.
+fn with_document_fields(fields: Object, schema: &Schema, all_schema: &[Schema]) -> Object {
+    let fields = if reverse_relations(schema, all_schema).is_empty() {
+        fields
+    } else {
+        // The `referencedBy` field passes down the parent value to its children
+        fields.field(
+            Field::new(
+                constants::REFERENCED_BY_FIELD,
+                TypeRef::named_nn(referenced_by_name(schema.id())),
+                move |ctx| {
+                    FieldFuture::new(async move {
+                        let document = Resolved::downcast(&ctx);
+                        Ok(Some(FieldValue::owned_any(document)))
+                    })
+                },
+            )
+            .description(format!(
+                "Documents relating to this `{}` document.",
+                schema.id().name()
+            )),
+        )
+    };
+
     fields
         // The `fields` field passes down the parent value to its children
         .field(
@@ -89,5 +116,31 @@ fn with_document_fields(fields: Object, schema: &Schema) -> Object {
                 schema.id().name()
             )),
         )
+        // The `history` field of a document, resolves the `DocumentHistory` object
+        .field(
+            Field::new(
+                constants::HISTORY_FIELD,
+                TypeRef::named_nn(constants::DOCUMENT_HISTORY),
+                move |ctx| FieldFuture::new(async move { resolve_document_history(ctx).await }),
+            )
+            .argument(
+                InputValue::new(
+                    constants::PAGINATION_FIRST_ARG,
+                    TypeRef::named(TypeRef::INT),
+                )
+                .description("Number of paginated views we want from this request")
+                .default_value(25),
+            )
+            .argument(
+                InputValue::new(constants::PAGINATION_AFTER_ARG, TypeRef::named("Cursor"))
+                    .description(
+                        "The view we wish to start paginating from identified by a cursor",
+                    ),
+            )
+            .description(format!(
+                "All views of a `{}` document, starting with the oldest.",
+                schema.id().name()
+            )),
+        )
         .description(schema.description().to_string())
 }
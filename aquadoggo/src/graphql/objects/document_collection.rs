@@ -11,8 +11,8 @@ use crate::graphql::utils::{collection_item_name, collection_name};
 
 /// Dynamically build objects describing a paginated collection of documents.
 ///
-/// Each object contains `documents`, `totalCount` and `hasNextPage` fields and defines their
-/// resolution logic.
+/// Each object contains `documents`, `totalCount`, `hasNextPage`, `hasPreviousPage`,
+/// `startCursor` and `endCursor` fields and defines their resolution logic.
 ///
 /// Each generated object has a type name with the formatting `<schema_id>Collection`.
 pub fn build_document_collection_object(schema: &Schema) -> Object {
@@ -62,6 +62,30 @@ pub fn build_document_collection_object(schema: &Schema) -> Object {
             )
             .description("Cursor for the next page"),
         )
+        .field(
+            Field::new(
+                constants::START_CURSOR_FIELD,
+                TypeRef::named_nn(TypeRef::STRING),
+                move |ctx| {
+                    FieldFuture::new(async move {
+                        let collection = Resolved::downcast(&ctx);
+
+                        let start_cursor = match collection {
+                            Resolved::Collection(page_info, _) => page_info.start_cursor,
+                            _ => panic!("Expected document collection"),
+                        };
+
+                        match start_cursor {
+                            Some(cursor) => {
+                                Ok(Some(FieldValue::from(Value::from(cursor.encode()))))
+                            }
+                            None => Ok(Some(FieldValue::NULL)),
+                        }
+                    })
+                },
+            )
+            .description("Cursor for the previous page"),
+        )
         .field(
             Field::new(
                 constants::HAS_NEXT_PAGE_FIELD,
@@ -83,6 +107,27 @@ pub fn build_document_collection_object(schema: &Schema) -> Object {
                 "Boolean value denoting whether there is a next page available on this query.",
             ),
         )
+        .field(
+            Field::new(
+                constants::HAS_PREVIOUS_PAGE_FIELD,
+                TypeRef::named_nn(TypeRef::BOOLEAN),
+                move |ctx| {
+                    FieldFuture::new(async move {
+                        let collection = Resolved::downcast(&ctx);
+
+                        let has_previous_page = match collection {
+                            Resolved::Collection(page_info, _) => page_info.has_previous_page,
+                            _ => panic!("Expected document collection"),
+                        };
+
+                        Ok(Some(FieldValue::from(Value::from(has_previous_page))))
+                    })
+                },
+            )
+            .description(
+                "Boolean value denoting whether there is a previous page available on this query.",
+            ),
+        )
         .field(
             Field::new(
                 constants::DOCUMENTS_FIELD,
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use dynamic_graphql::{Enum, SimpleObject};
+
+use crate::graphql::scalars::{CursorScalar, DocumentViewIdScalar, PublicKeyScalar};
+
+/// Action of an operation which changed a document.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "OperationAction")]
+pub enum OperationActionEnum {
+    #[graphql(name = "CREATE")]
+    Create,
+
+    #[graphql(name = "UPDATE")]
+    Update,
+
+    #[graphql(name = "DELETE")]
+    Delete,
+}
+
+/// Single view of a document in its history and the operation which lead to it.
+#[derive(SimpleObject)]
+pub struct DocumentHistoryItem {
+    /// The pagination cursor for this item.
+    pub cursor: CursorScalar,
+
+    /// The document view id of the document after this operation was applied.
+    #[graphql(name = "viewId")]
+    pub document_view_id: DocumentViewIdScalar,
+
+    /// The action of the operation which lead to this view.
+    pub action: OperationActionEnum,
+
+    /// The public key of the author of the operation.
+    pub author: PublicKeyScalar,
+
+    /// The view id of the document this operation was applied on, not set for the first view.
+    pub previous: Option<DocumentViewIdScalar>,
+
+    /// Names of the fields which got changed by the operation.
+    pub fields: Vec<String>,
+
+    /// Flag indicating if this view is not materialized on this node (anymore), for example when
+    /// it got removed by the garbage collection. Pruned views can not be queried by their view id.
+    pub pruned: bool,
+}
+
+/// A single page of views from the history of a document, starting with the oldest.
+#[derive(SimpleObject)]
+pub struct DocumentHistory {
+    /// The total number of views in the history of this document.
+    #[graphql(name = "totalCount")]
+    pub total_count: u64,
+
+    /// Boolean value denoting whether there is a next page available on this query.
+    #[graphql(name = "hasNextPage")]
+    pub has_next_page: bool,
+
+    /// Cursor for the next page.
+    #[graphql(name = "endCursor")]
+    pub end_cursor: Option<CursorScalar>,
+
+    /// Views of the document on this page.
+    pub views: Vec<DocumentHistoryItem>,
+}
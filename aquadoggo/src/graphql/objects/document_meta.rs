@@ -17,4 +17,12 @@ pub struct DocumentMeta {
 
     /// The public key of the author who first created this document.
     pub owner: PublicKeyScalar,
+
+    /// Local time in seconds since UNIX epoch when this node received the operation which
+    /// created this document.
+    pub received_at: i64,
+
+    /// Local time in seconds since UNIX epoch when this node materialized this version of the
+    /// document.
+    pub updated_at: i64,
 }
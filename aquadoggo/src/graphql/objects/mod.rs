@@ -1,11 +1,17 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+mod aggregate;
 mod document;
 mod document_collection;
 mod document_fields;
+mod document_history;
 mod document_meta;
+mod referenced_by;
 
+pub use aggregate::{build_aggregate_object, build_aggregate_values_objects};
 pub use document::{build_document_object, build_paginated_document_object};
 pub use document_collection::build_document_collection_object;
 pub use document_fields::build_document_fields_object;
+pub use document_history::{DocumentHistory, DocumentHistoryItem, OperationActionEnum};
 pub use document_meta::DocumentMeta;
+pub use referenced_by::build_referenced_by_object;
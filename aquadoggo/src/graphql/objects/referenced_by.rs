@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_graphql::dynamic::{Field, FieldFuture, Object, TypeRef};
+use p2panda_rs::schema::Schema;
+
+use crate::graphql::resolvers::resolve_reverse_relation;
+use crate::graphql::utils::{
+    collection_name, referenced_by_name, reverse_relation_field_name, reverse_relations,
+    with_collection_arguments,
+};
+
+/// Dynamically build a GraphQL object listing all documents which relate to a document of a
+/// p2panda schema.
+///
+/// The object contains one paginated collection for every field of any known schema which holds a
+/// (pinned) relation or relation list to this schema. These fields follow the format
+/// `<schema_id>_<field_name>`. No object is returned when no such field exists.
+///
+/// Each generated object has a type name with the formatting `<schema_id>ReferencedBy`.
+pub fn build_referenced_by_object(schema: &Schema, all_schema: &[Schema]) -> Option<Object> {
+    let relations = reverse_relations(schema, all_schema);
+
+    if relations.is_empty() {
+        return None;
+    }
+
+    let mut object = Object::new(referenced_by_name(schema.id())).description(format!(
+        "Documents relating to a `{}` document.",
+        schema.id().name()
+    ));
+
+    for (related_schema, field_name) in relations {
+        let related_schema_id = related_schema.id().clone();
+        let name = reverse_relation_field_name(&related_schema_id, &field_name);
+        let description = format!(
+            "`{}` documents relating to this document in their `{}` field.",
+            related_schema_id.name(),
+            field_name
+        );
+
+        let field = Field::new(
+            name,
+            TypeRef::named_nn(collection_name(&related_schema_id)),
+            move |ctx| {
+                let schema = related_schema.clone();
+                let field_name = field_name.clone();
+
+                FieldFuture::new(
+                    async move { resolve_reverse_relation(ctx, schema, field_name).await },
+                )
+            },
+        );
+
+        object = object
+            .field(with_collection_arguments(field, &related_schema_id).description(description));
+    }
+
+    Some(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Response;
+    use p2panda_rs::document::DocumentId;
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::operation::{OperationValue, Relation};
+    use p2panda_rs::schema::FieldType;
+    use p2panda_rs::test_utils::fixtures::key_pair;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::test_utils::{
+        add_document, add_schema, http_test_client, test_runner, update_document, TestNode,
+    };
+
+    #[rstest]
+    fn referenced_by(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let posts_schema = add_schema(
+                &mut node,
+                "posts",
+                vec![("title", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+            let comments_schema = add_schema(
+                &mut node,
+                "comments",
+                vec![
+                    ("text", FieldType::String),
+                    ("post", FieldType::Relation(posts_schema.id().clone())),
+                ],
+                &key_pair,
+            )
+            .await;
+            let reactions_schema = add_schema(
+                &mut node,
+                "reactions",
+                vec![("post", FieldType::PinnedRelation(posts_schema.id().clone()))],
+                &key_pair,
+            )
+            .await;
+
+            let first_post_view_id = add_document(
+                &mut node,
+                posts_schema.id(),
+                vec![("title", "Bamboo".into())],
+                &key_pair,
+            )
+            .await;
+            let second_post_view_id = add_document(
+                &mut node,
+                posts_schema.id(),
+                vec![("title", "Leaves".into())],
+                &key_pair,
+            )
+            .await;
+            let first_post_id = first_post_view_id.graph_tips()[0].clone();
+
+            for (text, post_view_id) in [
+                ("So tasty!", &first_post_view_id),
+                ("Crunchy", &first_post_view_id),
+                ("Yuck", &second_post_view_id),
+            ] {
+                add_document(
+                    &mut node,
+                    comments_schema.id(),
+                    vec![
+                        ("text", text.into()),
+                        (
+                            "post",
+                            OperationValue::Relation(Relation::new(DocumentId::new(
+                                &post_view_id.graph_tips()[0],
+                            ))),
+                        ),
+                    ],
+                    &key_pair,
+                )
+                .await;
+            }
+
+            // Relations pinned to older views of the post are found as well
+            add_document(
+                &mut node,
+                reactions_schema.id(),
+                vec![("post", first_post_view_id.clone().into())],
+                &key_pair,
+            )
+            .await;
+            update_document(
+                &mut node,
+                posts_schema.id(),
+                vec![("title", "Bamboo shoots".into())],
+                &first_post_view_id,
+                &key_pair,
+            )
+            .await;
+
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            post: {posts}(id: "{first_post_id}") {{
+                                referencedBy {{
+                                    {comments}_post(orderBy: {{ field: text }}, orderDirection: DESC) {{
+                                        totalCount
+                                        documents {{ fields {{ text }} }}
+                                    }}
+                                    {reactions}_post {{
+                                        totalCount
+                                    }}
+                                }}
+                            }}
+                        }}"#,
+                        posts = posts_schema.id(),
+                        comments = comments_schema.id(),
+                        reactions = reactions_schema.id(),
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert!(response.is_ok(), "{:#?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            let referenced_by = &data["post"]["referencedBy"];
+            assert_eq!(
+                referenced_by[format!("{}_post", comments_schema.id())],
+                json!({
+                    "totalCount": 2,
+                    "documents": [
+                        { "fields": { "text": "So tasty!" } },
+                        { "fields": { "text": "Crunchy" } },
+                    ]
+                })
+            );
+            assert_eq!(
+                referenced_by[format!("{}_post", reactions_schema.id())],
+                json!({ "totalCount": 1 })
+            );
+        })
+    }
+}
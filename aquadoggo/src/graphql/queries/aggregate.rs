@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, Object, TypeRef};
+use log::debug;
+use p2panda_rs::schema::Schema;
+
+use crate::db::SqlStore;
+use crate::graphql::constants;
+use crate::graphql::utils::{
+    aggregate_name, filter_name, group_by_name, parse_aggregate_arguments,
+};
+use crate::schema::SchemaProvider;
+
+/// Adds a GraphQL query for aggregating the values of a filtered collection of documents by
+/// schema to the passed root query object.
+///
+/// The query follows the format `aggregate_<SCHEMA_ID>(<...ARGS>)`.
+pub fn build_aggregate_query(query: Object, schema: &Schema) -> Object {
+    let schema_id = schema.id().clone();
+    let schema = schema.clone();
+
+    query.field(
+        Field::new(
+            format!("{}{}", constants::QUERY_AGGREGATE_PREFIX, schema_id),
+            TypeRef::named_nn_list_nn(aggregate_name(&schema_id)),
+            move |ctx| {
+                let schema = schema.clone();
+                debug!(
+                    "Query to {}{} received",
+                    constants::QUERY_AGGREGATE_PREFIX,
+                    schema.id()
+                );
+
+                FieldFuture::new(async move {
+                    let store = ctx.data_unchecked::<SqlStore>();
+                    let all_schema = ctx.data_unchecked::<SchemaProvider>().all().await;
+
+                    // Populate query arguments with values from GraphQL query
+                    let (filter, group_by) = parse_aggregate_arguments(&ctx, &schema, &all_schema)?;
+
+                    // Aggregations are computed by the database
+                    let groups = store
+                        .aggregate(&schema, &filter, group_by.as_ref())
+                        .await?
+                        .into_iter()
+                        .map(FieldValue::owned_any)
+                        .collect::<Vec<FieldValue>>();
+
+                    Ok(Some(FieldValue::list(groups)))
+                })
+            },
+        )
+        .argument(
+            InputValue::new(
+                constants::FILTER_ARG,
+                TypeRef::named(filter_name(&schema_id)),
+            )
+            .description("Filter the aggregated documents based on field values"),
+        )
+        .argument(
+            InputValue::new(
+                constants::META_FILTER_ARG,
+                TypeRef::named("MetaFilterInputObject"),
+            )
+            .description("Filter the aggregated documents based on meta field values"),
+        )
+        .argument(
+            InputValue::new(
+                constants::GROUP_BY_ARG,
+                TypeRef::named(group_by_name(&schema_id)),
+            )
+            .description("Field by which the documents will be grouped before aggregating"),
+        )
+        .description(format!(
+            "Count `{}` documents and compute sums, averages, minimum and maximum values of their \
+            numeric fields. Documents can be filtered and grouped following parameters passed \
+            into the query via the available arguments.",
+            schema_id.name()
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Response;
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::test_utils::fixtures::key_pair;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::test_utils::{add_schema_and_documents, http_test_client, test_runner, TestNode};
+
+    #[rstest]
+    fn aggregate_query(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "votes",
+                vec![
+                    vec![
+                        ("option", "bamboo".into(), None),
+                        ("points", 3.into(), None),
+                    ],
+                    vec![
+                        ("option", "bamboo".into(), None),
+                        ("points", 5.into(), None),
+                    ],
+                    vec![
+                        ("option", "leaves".into(), None),
+                        ("points", 1.into(), None),
+                    ],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            total: aggregate_{type_name} {{
+                                group
+                                count
+                                sum {{ points }}
+                                avg {{ points }}
+                            }}
+                            perOption: aggregate_{type_name}(
+                                groupBy: option,
+                                filter: {{ points: {{ gt: 2 }} }}
+                            ) {{
+                                group
+                                count
+                                min {{ points }}
+                                max {{ points }}
+                            }}
+                        }}"#,
+                        type_name = schema.id(),
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert!(response.is_ok(), "{:#?}", response.errors);
+            assert_eq!(
+                response.data.into_json().unwrap(),
+                json!({
+                    "total": [{
+                        "group": null,
+                        "count": 3,
+                        "sum": { "points": 9 },
+                        "avg": { "points": 3.0 },
+                    }],
+                    "perOption": [{
+                        "group": "bamboo",
+                        "count": 2,
+                        "min": { "points": 3 },
+                        "max": { "points": 5 },
+                    }],
+                })
+            );
+        })
+    }
+}
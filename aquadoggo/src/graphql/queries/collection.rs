@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use async_graphql::dynamic::{Field, FieldFuture, Object, TypeRef};
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, TypeRef};
 use log::debug;
 use p2panda_rs::schema::Schema;
 
@@ -33,6 +33,23 @@ pub fn build_collection_query(query: Object, schema: &Schema) -> Object {
                         async move { resolve_document_collection(ctx, schema, None).await },
                     )
                 },
+            )
+            .argument(
+                InputValue::new(constants::AS_OF_ARG, TypeRef::named(TypeRef::INT)).description(
+                    "Query the document views which were current at this local time of the node, \
+                    in seconds since UNIX epoch. Views removed by the garbage collection get \
+                    materialized again. Related documents and relation lists always resolve to \
+                    their latest views",
+                ),
+            )
+            .argument(
+                InputValue::new(constants::AS_OF_OPERATION_ARG, TypeRef::named(TypeRef::INT))
+                    .description(
+                        "Query the document views which were current when the node received the \
+                        operation with this index, see `latestOperationIndex` of the node status. \
+                        Can not be combined with `asOf`, related documents and relation lists \
+                        always resolve to their latest views",
+                    ),
             ),
             &schema_id,
         ))
@@ -47,8 +64,8 @@ pub fn build_collection_query(query: Object, schema: &Schema) -> Object {
 #[cfg(test)]
 mod tests {
     use async_graphql::{value, Response, Value};
-    use p2panda_rs::document::DocumentViewId;
-    use p2panda_rs::operation::{PinnedRelationList, RelationList};
+    use p2panda_rs::document::{DocumentId, DocumentViewId};
+    use p2panda_rs::operation::{PinnedRelation, PinnedRelationList, Relation, RelationList};
     use p2panda_rs::schema::{FieldType, Schema, SchemaId};
     use p2panda_rs::test_utils::fixtures::key_pair;
     use p2panda_rs::{identity::KeyPair, operation::OperationValue};
@@ -56,8 +73,8 @@ mod tests {
     use serde_json::{json, Value as JsonValue};
 
     use crate::test_utils::{
-        add_document, add_schema, add_schema_and_documents, http_test_client, test_runner,
-        TestClient, TestNode,
+        add_document, add_schema, add_schema_and_documents, delete_document, http_test_client,
+        test_runner, TestClient, TestNode,
     };
 
     /// Make a GraphQL collection query for songs stored on the node.
@@ -591,7 +608,7 @@ mod tests {
             (
                 first: 1,
                 after: "27ECgmQpeuQwMKtCZxkbJUtCvkZDLuayQKv9zkV5uKx1xMHBeeSBasdhvoZwcgXLC5mnv7QR9HW11gjhH57D1mjs",
-                orderBy: DOCUMENT_ID,
+                orderBy: { field: DOCUMENT_ID },
                 orderDirection: ASC,
                 filter: {
                     bool: {
@@ -668,14 +685,14 @@ mod tests {
         vec!["Invalid value for argument \"after\", expected type \"Cursor\"".to_string()]
     )]
     #[case(
-        r#"(orderBy: HELLO)"#.to_string(),
+        r#"(orderBy: { field: HELLO })"#.to_string(),
         Value::Null,
-        vec!["Invalid value for argument \"orderBy\", enumeration type \"schema_name_00202356a8c4da41126b8098084c2a3b653be99d61bfd805f34e967b2970b9b1998dOrderBy\" does not contain the value \"HELLO\"".to_string()]
+        vec!["Invalid value for argument \"orderBy.field\", enumeration type \"schema_name_00202356a8c4da41126b8098084c2a3b653be99d61bfd805f34e967b2970b9b1998dOrderBy\" does not contain the value \"HELLO\"".to_string()]
     )]
     #[case(
-        r#"(orderBy: "hello")"#.to_string(),
+        r#"(orderBy: { field: "hello" })"#.to_string(),
         Value::Null,
-        vec!["Invalid value for argument \"orderBy\", enumeration type \"schema_name_00202356a8c4da41126b8098084c2a3b653be99d61bfd805f34e967b2970b9b1998dOrderBy\" does not contain the value \"hello\"".to_string()]
+        vec!["Invalid value for argument \"orderBy.field\", enumeration type \"schema_name_00202356a8c4da41126b8098084c2a3b653be99d61bfd805f34e967b2970b9b1998dOrderBy\" does not contain the value \"hello\"".to_string()]
     )]
     #[case(
         r#"(orderDirection: HELLO)"#.to_string(),
@@ -970,9 +987,9 @@ mod tests {
 
     #[rstest]
     #[case("", "")]
-    #[case("(orderDirection: ASC, orderBy: title)", "")]
-    #[case("(orderDirection: DESC, orderBy: DOCUMENT_ID)", "")]
-    #[case("(orderDirection: ASC, orderBy: DOCUMENT_VIEW_ID)", "")]
+    #[case("(orderDirection: ASC, orderBy: { field: title })", "")]
+    #[case("(orderDirection: DESC, orderBy: { field: DOCUMENT_ID })", "")]
+    #[case("(orderDirection: ASC, orderBy: { field: DOCUMENT_VIEW_ID })", "")]
     #[case("(meta: { owner: { eq: \"2f8e50c2ede6d936ecc3144187ff1c273808185cfbc5ff3d3748d1ff7353fc96\" } })", "")]
     #[case("(meta: { owner: { notEq: \"2f8e50c2ede6d936ecc3144187ff1c273808185cfbc5ff3d3748d1ff7353fc96\" } })", "")]
     #[case("(meta: { owner: { in: [ \"2f8e50c2ede6d936ecc3144187ff1c273808185cfbc5ff3d3748d1ff7353fc96\" ] } })", "")]
@@ -1010,16 +1027,16 @@ mod tests {
     #[case("(filter: { audio: { eq: \"E8\" } })", "")]
     #[case("(filter: { audio: { eq: \"\" } })", "")]
     #[case(
-        "(orderDirection: DESC, orderBy: title)",
-        "(orderDirection: ASC, orderBy: line)"
+        "(orderDirection: DESC, orderBy: { field: title })",
+        "(orderDirection: ASC, orderBy: { field: line })"
     )]
     #[case(
-        "(orderDirection: ASC, orderBy: title)",
-        "(orderDirection: DESC, orderBy: DOCUMENT_ID)"
+        "(orderDirection: ASC, orderBy: { field: title })",
+        "(orderDirection: DESC, orderBy: { field: DOCUMENT_ID })"
     )]
     #[case(
-        "(orderDirection: ASC, orderBy: title)",
-        "(orderDirection: ASC, orderBy: DOCUMENT_VIEW_ID)"
+        "(orderDirection: ASC, orderBy: { field: title })",
+        "(orderDirection: ASC, orderBy: { field: DOCUMENT_VIEW_ID })"
     )]
     #[case("", "(filter: { line: { gt: \"a\" } })")]
     #[case("", "(filter: { line: { lte: \"a\" } })")]
@@ -1083,7 +1100,7 @@ mod tests {
             let data = query_songs(
                 &client,
                 song_schema.id(),
-                "(first: 2, orderDirection: ASC, orderBy: title)",
+                "(first: 2, orderDirection: ASC, orderBy: { field: title })",
                 "",
             )
             .await;
@@ -1352,7 +1369,7 @@ mod tests {
                         "(
                             first: {first},
                             {after}
-                            orderBy: line,
+                            orderBy: {{ field: line }},
                             orderDirection: DESC,
                             filter: {{
                                 line: {{ contains: \"{filter_value}\" }}
@@ -1451,4 +1468,766 @@ mod tests {
             assert_eq!(data["query"]["documents"].as_array().unwrap().len(), 0);
         })
     }
+
+    #[rstest]
+    fn nested_filter_groups(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            // Publish some lyrics to the node.
+            let (lyric_schema, _) = here_be_some_lyrics(&mut node, &key_pair).await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            let data = query_lyrics(
+                &client,
+                lyric_schema.id(),
+                r#"(
+                    filter: {
+                        _or: [
+                            { line: { eq: "Oh bondage, up yours" } },
+                            { line: { contains: "chain" } }
+                        ],
+                        _not: { line: { contains: "wall" } }
+                    },
+                    orderBy: { field: line }
+                )"#,
+            )
+            .await;
+
+            assert_eq!(data["query"]["totalCount"], json!(3));
+            assert_eq!(
+                data["query"]["documents"][0]["fields"]["line"],
+                json!("Chain-gang chainmail, I don't think at all")
+            );
+            assert_eq!(
+                data["query"]["documents"][1]["fields"]["line"],
+                json!("Chain-store chainsmoke, I consume you all")
+            );
+            assert_eq!(
+                data["query"]["documents"][2]["fields"]["line"],
+                json!("Oh bondage, up yours")
+            );
+        })
+    }
+
+    #[rstest]
+    fn full_text_search(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            // Publish some lyrics to the node.
+            let (lyric_schema, _) = here_be_some_lyrics(&mut node, &key_pair).await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            // Only lyrics containing all words of the search are returned
+            let data = query_lyrics(&client, lyric_schema.id(), r#"(search: "oh BONDAGE")"#).await;
+            assert_eq!(data["query"]["totalCount"], json!(3));
+
+            // Search can be combined with other filters
+            let data = query_lyrics(
+                &client,
+                lyric_schema.id(),
+                r#"(search: "bondage", filter: { line: { contains: "up" } })"#,
+            )
+            .await;
+            assert_eq!(data["query"]["totalCount"], json!(1));
+            assert_eq!(
+                data["query"]["documents"][0]["fields"]["line"],
+                json!("Oh bondage, up yours")
+            );
+
+            // Words which are not in any lyrics don't return anything
+            let data = query_lyrics(&client, lyric_schema.id(), r#"(search: "panda")"#).await;
+            assert_eq!(data["query"]["totalCount"], json!(0));
+        })
+    }
+
+    #[rstest]
+    fn full_text_search_ordered_by_relevance(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "notes",
+                vec![
+                    vec![("line", "bamboo forest and one panda".into(), None)],
+                    vec![("line", "panda panda panda panda panda".into(), None)],
+                    vec![("line", "bamboo".into(), None)],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            // Paginate over search results, starting with the most relevant one
+            let mut lines = Vec::new();
+            let mut after = String::new();
+            loop {
+                let data = query_lyrics(
+                    &client,
+                    schema.id(),
+                    &format!(
+                        r#"(
+                            first: 1,
+                            {after}
+                            search: "panda",
+                            orderBy: {{ field: RELEVANCE }},
+                            orderDirection: DESC
+                        )"#
+                    ),
+                )
+                .await;
+
+                for document in data["query"]["documents"].as_array().unwrap() {
+                    lines.push(document["fields"]["line"].as_str().unwrap().to_string());
+                }
+
+                if !data["query"]["hasNextPage"].as_bool().unwrap() {
+                    break;
+                }
+                after = format!("after: {},", data["query"]["endCursor"]);
+            }
+
+            assert_eq!(
+                lines,
+                vec![
+                    "panda panda panda panda panda".to_string(),
+                    "bamboo forest and one panda".to_string()
+                ]
+            );
+
+            // Ordering by relevance is only possible when searching
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": lyrics_collection_query(schema.id(), "(orderBy: { field: RELEVANCE })")
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+            assert_eq!(
+                response.errors[0].message,
+                "Ordering by relevance requires a search query"
+            );
+        })
+    }
+
+    #[rstest]
+    fn backward_pagination(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "messages",
+                vec![
+                    vec![
+                        ("text", "Hello, Panda!".into(), None),
+                        ("timestamp", 1687265969.into(), None),
+                    ],
+                    vec![
+                        ("text", "Oh, howdy, Pengi!".into(), None),
+                        ("timestamp", 1687266014.into(), None),
+                    ],
+                    vec![
+                        ("text", "How are you?".into(), None),
+                        ("timestamp", 1687266032.into(), None),
+                    ],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            let query = |args: &str| {
+                format!(
+                    r#"{{
+                        query: all_{}({args}, orderBy: {{ field: timestamp }}) {{
+                            hasNextPage
+                            hasPreviousPage
+                            startCursor
+                            documents {{
+                                fields {{ text }}
+                            }}
+                        }}
+                    }}"#,
+                    schema.id()
+                )
+            };
+
+            // Page upwards from the newest message
+            let mut pages = Vec::new();
+            let mut before = String::new();
+            loop {
+                let response: Response = client
+                    .post("/graphql")
+                    .json(&json!({ "query": query(&format!("last: 2{before}")) }))
+                    .send()
+                    .await
+                    .json()
+                    .await;
+                assert!(response.is_ok(), "{:#?}", response.errors);
+                let data = response.data.into_json().unwrap();
+
+                let texts: Vec<String> = data["query"]["documents"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|document| document["fields"]["text"].as_str().unwrap().to_string())
+                    .collect();
+                pages.push(texts);
+
+                assert_eq!(
+                    data["query"]["hasNextPage"].as_bool().unwrap(),
+                    !before.is_empty()
+                );
+
+                if !data["query"]["hasPreviousPage"].as_bool().unwrap() {
+                    break;
+                }
+                before = format!(", before: {}", data["query"]["startCursor"]);
+            }
+
+            assert_eq!(
+                pages,
+                vec![
+                    vec!["Oh, howdy, Pengi!".to_string(), "How are you?".to_string()],
+                    vec!["Hello, Panda!".to_string()],
+                ]
+            );
+
+            // Forward and backward pagination can not be mixed
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({ "query": query("first: 2, last: 2") }))
+                .send()
+                .await
+                .json()
+                .await;
+            assert_eq!(
+                response.errors[0].message,
+                "Pagination arguments `first` and `after` can not be combined with `last`"
+            );
+        })
+    }
+
+    #[rstest]
+    fn ordering_by_multiple_fields(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "tasks",
+                vec![
+                    vec![
+                        ("title", "Feed the panda".into(), None),
+                        ("priority", 1.into(), None),
+                    ],
+                    vec![
+                        ("title", "Plant bamboo".into(), None),
+                        ("priority", 3.into(), None),
+                    ],
+                    vec![
+                        ("title", "Clean the enclosure".into(), None),
+                        ("priority", 1.into(), None),
+                    ],
+                    vec![
+                        ("title", "Buy bamboo".into(), None),
+                        ("priority", 3.into(), None),
+                    ],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            // Paginate over all tasks, one at a time
+            let mut titles = Vec::new();
+            let mut after = String::new();
+            loop {
+                let response: Response = client
+                    .post("/graphql")
+                    .json(&json!({
+                        "query": format!(
+                            r#"{{
+                                query: all_{}(
+                                    first: 1,
+                                    {after}
+                                    orderBy: [
+                                        {{ field: priority, direction: DESC }},
+                                        {{ field: title }}
+                                    ]
+                                ) {{
+                                    hasNextPage
+                                    endCursor
+                                    documents {{
+                                        fields {{ title }}
+                                    }}
+                                }}
+                            }}"#,
+                            schema.id()
+                        )
+                    }))
+                    .send()
+                    .await
+                    .json()
+                    .await;
+                assert!(response.is_ok(), "{:#?}", response.errors);
+                let data = response.data.into_json().unwrap();
+
+                for document in data["query"]["documents"].as_array().unwrap() {
+                    titles.push(document["fields"]["title"].as_str().unwrap().to_string());
+                }
+
+                if !data["query"]["hasNextPage"].as_bool().unwrap() {
+                    break;
+                }
+                after = format!("after: {},", data["query"]["endCursor"]);
+            }
+
+            assert_eq!(
+                titles,
+                vec![
+                    "Buy bamboo".to_string(),
+                    "Plant bamboo".to_string(),
+                    "Clean the enclosure".to_string(),
+                    "Feed the panda".to_string(),
+                ]
+            );
+        })
+    }
+
+    #[rstest]
+    fn filter_by_related_documents(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let posts_schema = add_schema(
+                &mut node,
+                "posts",
+                vec![("title", FieldType::String), ("author", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+            let comments_schema = add_schema(
+                &mut node,
+                "comments",
+                vec![
+                    ("text", FieldType::String),
+                    ("post", FieldType::Relation(posts_schema.id().to_owned())),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let panda_post_view_id = add_document(
+                &mut node,
+                posts_schema.id(),
+                vec![("title", "Bamboo".into()), ("author", "panda".into())],
+                &key_pair,
+            )
+            .await;
+            let penguin_post_view_id = add_document(
+                &mut node,
+                posts_schema.id(),
+                vec![("title", "Fish".into()), ("author", "penguin".into())],
+                &key_pair,
+            )
+            .await;
+
+            for (text, post_view_id) in [
+                ("So tasty!", &panda_post_view_id),
+                ("Crunchy", &panda_post_view_id),
+                ("Yuck", &penguin_post_view_id),
+            ] {
+                add_document(
+                    &mut node,
+                    comments_schema.id(),
+                    vec![
+                        ("text", text.into()),
+                        (
+                            "post",
+                            OperationValue::Relation(Relation::new(DocumentId::new(
+                                &post_view_id.graph_tips()[0],
+                            ))),
+                        ),
+                    ],
+                    &key_pair,
+                )
+                .await;
+            }
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            query: all_{}(
+                                filter: {{ _related: {{ post: {{ author: {{ eq: "panda" }} }} }} }},
+                                orderBy: {{ field: text }}
+                            ) {{
+                                totalCount
+                                documents {{
+                                    fields {{ text }}
+                                }}
+                            }}
+                        }}"#,
+                        comments_schema.id()
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+            assert!(response.is_ok(), "{:#?}", response.errors);
+
+            assert_eq!(
+                response.data.into_json().unwrap()["query"],
+                json!({
+                    "totalCount": 2,
+                    "documents": [
+                        { "fields": { "text": "Crunchy" } },
+                        { "fields": { "text": "So tasty!" } },
+                    ]
+                })
+            );
+        })
+    }
+
+    #[rstest]
+    fn filter_by_deleted_related_documents(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let posts_schema = add_schema(
+                &mut node,
+                "posts",
+                vec![("author", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+            let comments_schema = add_schema(
+                &mut node,
+                "comments",
+                vec![
+                    ("text", FieldType::String),
+                    ("post", FieldType::Relation(posts_schema.id().to_owned())),
+                    (
+                        "pinned_post",
+                        FieldType::PinnedRelation(posts_schema.id().to_owned()),
+                    ),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let post_view_id = add_document(
+                &mut node,
+                posts_schema.id(),
+                vec![("author", "panda".into())],
+                &key_pair,
+            )
+            .await;
+            add_document(
+                &mut node,
+                comments_schema.id(),
+                vec![
+                    ("text", "So tasty!".into()),
+                    (
+                        "post",
+                        OperationValue::Relation(Relation::new(DocumentId::new(
+                            &post_view_id.graph_tips()[0],
+                        ))),
+                    ),
+                    (
+                        "pinned_post",
+                        OperationValue::PinnedRelation(PinnedRelation::new(post_view_id.clone())),
+                    ),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            delete_document(&mut node, posts_schema.id(), &post_view_id, &key_pair).await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            // Deleted related documents do not match, neither through relations nor through
+            // pinned relations
+            for relation_field in ["post", "pinned_post"] {
+                let response: Response = client
+                    .post("/graphql")
+                    .json(&json!({
+                        "query": format!(
+                            r#"{{
+                                query: all_{}(
+                                    filter: {{
+                                        _related: {{ {}: {{ author: {{ eq: "panda" }} }} }}
+                                    }}
+                                ) {{
+                                    totalCount
+                                }}
+                            }}"#,
+                            comments_schema.id(),
+                            relation_field
+                        )
+                    }))
+                    .send()
+                    .await
+                    .json()
+                    .await;
+                assert!(response.is_ok(), "{:#?}", response.errors);
+
+                assert_eq!(
+                    response.data.into_json().unwrap()["query"],
+                    json!({ "totalCount": 0 }),
+                    "{relation_field}"
+                );
+            }
+        })
+    }
+
+    #[rstest]
+    fn filter_by_relation_list_membership(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let songs_schema = add_schema(
+                &mut node,
+                "songs",
+                vec![("title", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+            let playlists_schema = add_schema(
+                &mut node,
+                "playlists",
+                vec![
+                    ("name", FieldType::String),
+                    (
+                        "songs",
+                        FieldType::RelationList(songs_schema.id().to_owned()),
+                    ),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let mut song_ids = Vec::new();
+            for title in ["Ambush", "Bamboo", "Cactus"] {
+                let view_id = add_document(
+                    &mut node,
+                    songs_schema.id(),
+                    vec![("title", title.into())],
+                    &key_pair,
+                )
+                .await;
+                song_ids.push(DocumentId::new(&view_id.graph_tips()[0]));
+            }
+
+            for (name, indexes) in [
+                ("Chill", vec![1, 2]),
+                ("Mix", vec![0, 1]),
+                ("Solo", vec![2]),
+            ] {
+                let list = indexes
+                    .iter()
+                    .map(|index| song_ids[*index].clone())
+                    .collect();
+                add_document(
+                    &mut node,
+                    playlists_schema.id(),
+                    vec![
+                        ("name", name.into()),
+                        (
+                            "songs",
+                            OperationValue::RelationList(RelationList::new(list)),
+                        ),
+                    ],
+                    &key_pair,
+                )
+                .await;
+            }
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            contains: all_{schema_id}(
+                                filter: {{ songs: {{ contains: "{a}" }} }},
+                                orderBy: {{ field: name }}
+                            ) {{
+                                documents {{ fields {{ name }} }}
+                            }}
+                            containsAny: all_{schema_id}(
+                                filter: {{ songs: {{ containsAny: ["{a}", "{c}"] }} }},
+                                orderBy: {{ field: name }}
+                            ) {{
+                                documents {{ fields {{ name }} }}
+                            }}
+                            containsAll: all_{schema_id}(
+                                filter: {{ songs: {{ containsAll: ["{b}", "{c}"] }} }},
+                                orderBy: {{ field: name }}
+                            ) {{
+                                documents {{ fields {{ name }} }}
+                            }}
+                        }}"#,
+                        schema_id = playlists_schema.id(),
+                        a = song_ids[0],
+                        b = song_ids[1],
+                        c = song_ids[2],
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+            assert!(response.is_ok(), "{:#?}", response.errors);
+
+            let names = |names: &[&str]| {
+                json!({
+                    "documents": names
+                        .iter()
+                        .map(|name| json!({ "fields": { "name": name } }))
+                        .collect::<Vec<JsonValue>>()
+                })
+            };
+
+            let data = response.data.into_json().unwrap();
+            assert_eq!(data["contains"], names(&["Mix"]));
+            assert_eq!(data["containsAny"], names(&["Chill", "Mix", "Solo"]));
+            assert_eq!(data["containsAll"], names(&["Chill"]));
+        })
+    }
+
+    #[rstest]
+    fn timestamp_meta_fields(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "notes",
+                vec![
+                    vec![("text", "Bamboo is tasty".into(), None)],
+                    vec![("text", "Fish is tasty".into(), None)],
+                ],
+                &key_pair,
+            )
+            .await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            query: all_{}(
+                                meta: {{ receivedAt: {{ gt: 0 }}, updatedAt: {{ gt: 0 }} }},
+                                orderBy: {{ field: RECEIVED_AT, direction: DESC }}
+                            ) {{
+                                totalCount
+                                documents {{
+                                    meta {{ receivedAt updatedAt }}
+                                }}
+                            }}
+                        }}"#,
+                        schema.id()
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+            assert!(response.is_ok(), "{:#?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            assert_eq!(data["query"]["totalCount"], 2);
+            for document in data["query"]["documents"].as_array().unwrap() {
+                let received_at = document["meta"]["receivedAt"].as_i64().unwrap();
+                let updated_at = document["meta"]["updatedAt"].as_i64().unwrap();
+                assert!(received_at > 0);
+                assert!(updated_at >= received_at);
+            }
+        })
+    }
+
+    #[rstest]
+    fn collection_as_of(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let (schema, _) = add_schema_and_documents(
+                &mut node,
+                "notes",
+                vec![vec![("text", "Bamboo is tasty".into(), None)]],
+                &key_pair,
+            )
+            .await;
+
+            // Init a GraphQL client we'll use to query the node.
+            let client = http_test_client(&node).await;
+
+            let latest_operation_index = node
+                .context
+                .store
+                .get_latest_operation_index()
+                .await
+                .unwrap();
+
+            for (as_of, expected_count) in [
+                ("asOf: 0".to_string(), 0),
+                (format!("asOf: {}", i32::MAX), 1),
+                ("asOfOperation: 0".to_string(), 0),
+                (format!("asOfOperation: {latest_operation_index}"), 1),
+            ] {
+                let response: Response = client
+                    .post("/graphql")
+                    .json(&json!({
+                        "query": format!(
+                            r#"{{
+                                query: all_{}({as_of}) {{
+                                    totalCount
+                                    documents {{ fields {{ text }} }}
+                                }}
+                            }}"#,
+                            schema.id()
+                        )
+                    }))
+                    .send()
+                    .await
+                    .json()
+                    .await;
+                assert!(response.is_ok(), "{:#?}", response.errors);
+
+                let data = response.data.into_json().unwrap();
+                assert_eq!(data["query"]["totalCount"], expected_count);
+                assert_eq!(
+                    data["query"]["documents"].as_array().unwrap().len(),
+                    expected_count
+                );
+            }
+
+            // Both ways of stating the point in history can not be combined
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{ query: all_{}(asOf: 0, asOfOperation: 0) {{ totalCount }} }}"#,
+                        schema.id()
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+            assert_eq!(
+                response.errors[0].message,
+                "Arguments `asOf` and `asOfOperation` can not be combined"
+            );
+        })
+    }
 }
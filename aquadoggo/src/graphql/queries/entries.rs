@@ -0,0 +1,394 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, ResolverContext, TypeRef};
+use async_graphql::{Error, Value};
+use dynamic_graphql::{FieldValue, ScalarValue};
+use log::debug;
+use p2panda_rs::entry::{LogId, SeqNum};
+use p2panda_rs::hash::Hash;
+use p2panda_rs::identity::PublicKey;
+use p2panda_rs::storage_provider::traits::EntryStore;
+
+use crate::db::SqlStore;
+use crate::graphql::constants;
+use crate::graphql::responses::EntryResponse;
+use crate::graphql::scalars::{EntryHashScalar, LogIdScalar, PublicKeyScalar, SeqNumScalar};
+
+/// Maximum number of entries returned by one "entries" query.
+pub const MAX_ENTRIES_PAGE_SIZE: u64 = 100;
+
+/// Add "entry" query to the root query object.
+pub fn build_entry_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::ENTRY_QUERY,
+            TypeRef::named(constants::ENTRY),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let hash = parse_entry_arguments(&ctx)?;
+                    let store = ctx.data_unchecked::<SqlStore>();
+
+                    match store.get_entry(&hash).await? {
+                        Some(entry) => Ok(Some(FieldValue::owned_any(EntryResponse::from(entry)))),
+                        None => Ok(FieldValue::NONE),
+                    }
+                })
+            },
+        )
+        .argument(
+            InputValue::new(
+                constants::ENTRY_HASH_ARG,
+                TypeRef::named_nn(constants::ENTRY_HASH),
+            )
+            .description("The hash of the requested entry."),
+        )
+        .description("Return a single signed entry and its payload identified by its hash."),
+    )
+}
+
+/// Add "entries" query to the root query object.
+pub fn build_entries_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::ENTRIES_QUERY,
+            TypeRef::named_nn_list_nn(constants::ENTRY),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let (public_key, log_id, from, to) = parse_entries_arguments(&ctx)?;
+                    let store = ctx.data_unchecked::<SqlStore>();
+
+                    // Never return more than one page of entries, clients can request the
+                    // following ones by starting after the last returned sequence number
+                    let max_to =
+                        SeqNum::new(from.as_u64().saturating_add(MAX_ENTRIES_PAGE_SIZE - 1))
+                            .expect("Sequence number is greater than zero");
+                    let to = match to {
+                        Some(to) if to.as_u64() < max_to.as_u64() => to,
+                        _ => max_to,
+                    };
+
+                    let entries = store
+                        .get_entries_range(&public_key, &log_id, &from, &to)
+                        .await?;
+
+                    let entries: Vec<FieldValue> = entries
+                        .into_iter()
+                        .map(|entry| FieldValue::owned_any(EntryResponse::from(entry)))
+                        .collect();
+
+                    Ok(Some(FieldValue::list(entries)))
+                })
+            },
+        )
+        .argument(
+            InputValue::new(
+                constants::PUBLIC_KEY_ARG,
+                TypeRef::named_nn(constants::PUBLIC_KEY),
+            )
+            .description("The public key of the author of the log."),
+        )
+        .argument(
+            InputValue::new(constants::LOG_ID_ARG, TypeRef::named_nn(constants::LOG_ID))
+                .description("The id of the log."),
+        )
+        .argument(
+            InputValue::new(
+                constants::SEQ_NUM_FROM_ARG,
+                TypeRef::named(constants::SEQ_NUM),
+            )
+            .description("Sequence number of the first requested entry, defaults to 1."),
+        )
+        .argument(
+            InputValue::new(
+                constants::SEQ_NUM_TO_ARG,
+                TypeRef::named(constants::SEQ_NUM),
+            )
+            .description(
+                "Sequence number of the last requested entry, defaults to the latest entry.",
+            ),
+        )
+        .description(
+            "Return signed entries and their payloads of a log, ordered by sequence number. At \
+            most 100 entries are returned at once.",
+        ),
+    )
+}
+
+/// Parse and validate the arguments passed to entry.
+fn parse_entry_arguments(ctx: &ResolverContext) -> Result<Hash, Error> {
+    let mut hash = None;
+
+    for (name, value) in ctx.field().arguments()?.into_iter() {
+        if name.as_str() == constants::ENTRY_HASH_ARG {
+            hash = Some(EntryHashScalar::from_value(value)?);
+        }
+    }
+
+    let hash = hash.ok_or_else(|| Error::new("Must provide `hash` argument"))?;
+    debug!("Query to entry received for hash {}", hash);
+
+    Ok(hash.into())
+}
+
+/// Parse and validate the arguments passed to entries.
+fn parse_entries_arguments(
+    ctx: &ResolverContext,
+) -> Result<(PublicKey, LogId, SeqNum, Option<SeqNum>), Error> {
+    let mut public_key = None;
+    let mut log_id = None;
+    let mut from = SeqNum::default();
+    let mut to = None;
+
+    for (name, value) in ctx.field().arguments()?.into_iter() {
+        if value == Value::Null {
+            continue;
+        }
+
+        match name.as_str() {
+            constants::PUBLIC_KEY_ARG => {
+                public_key = Some(PublicKeyScalar::from_value(value)?);
+            }
+            constants::LOG_ID_ARG => {
+                log_id = Some(LogIdScalar::from_value(value)?);
+            }
+            constants::SEQ_NUM_FROM_ARG => {
+                from = SeqNumScalar::from_value(value)?.into();
+            }
+            constants::SEQ_NUM_TO_ARG => {
+                to = Some(SeqNumScalar::from_value(value)?.into());
+            }
+            _ => (),
+        }
+    }
+
+    let public_key = public_key.ok_or_else(|| Error::new("Must provide `publicKey` argument"))?;
+    let log_id = log_id.ok_or_else(|| Error::new("Must provide `logId` argument"))?;
+
+    debug!(
+        "Query to entries received for log {} of public key {}",
+        log_id, public_key
+    );
+
+    Ok((public_key.into(), log_id.into(), from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{value, Response, Value};
+    use p2panda_rs::entry::traits::{AsEncodedEntry, AsEntry};
+    use p2panda_rs::entry::{LogId, SeqNum};
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::storage_provider::traits::EntryStore;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::test_utils::{
+        http_test_client, populate_store, populate_store_config, test_runner, PopulateStoreConfig,
+        TestNode,
+    };
+
+    use super::MAX_ENTRIES_PAGE_SIZE;
+
+    #[rstest]
+    fn entries_query(
+        #[from(populate_store_config)]
+        #[with(10, 1, vec![KeyPair::new()])]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|node: TestNode| async move {
+            populate_store(&node.context.store, &config).await;
+            let public_key = config.authors[0].public_key();
+            let client = http_test_client(&node).await;
+
+            let query = |range: &str| {
+                format!(
+                    r#"{{
+                        entries(publicKey: "{public_key}", logId: "0"{range}) {{
+                            seqNum
+                        }}
+                    }}"#
+                )
+            };
+
+            // Request a range of entries
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({ "query": query(r#", from: "3", to: "5""#) }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "entries": [{ "seqNum": "3" }, { "seqNum": "4" }, { "seqNum": "5" }]
+                }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Request all entries starting at a sequence number
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({ "query": query(r#", from: "8""#) }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "entries": [{ "seqNum": "8" }, { "seqNum": "9" }, { "seqNum": "10" }]
+                }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Request all entries of the log
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({ "query": query("") }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            let data = response.data.into_json().unwrap();
+            assert_eq!(data["entries"].as_array().unwrap().len(), 10);
+        });
+    }
+
+    #[rstest]
+    fn entries_query_page_size(
+        #[from(populate_store_config)]
+        #[with(105, 1, vec![KeyPair::new()])]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|node: TestNode| async move {
+            populate_store(&node.context.store, &config).await;
+            let public_key = config.authors[0].public_key();
+            let client = http_test_client(&node).await;
+
+            for (range, expected) in [("", (1, 100)), (r#", from: "3", to: "200""#, (3, 102))] {
+                let response: Response = client
+                    .post("/graphql")
+                    .json(&json!({
+                        "query": format!(
+                            r#"{{
+                                entries(publicKey: "{public_key}", logId: "0"{range}) {{
+                                    seqNum
+                                }}
+                            }}"#
+                        )
+                    }))
+                    .send()
+                    .await
+                    .json()
+                    .await;
+
+                let data = response.data.into_json().unwrap();
+                let seq_nums: Vec<&str> = data["entries"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|entry| entry["seqNum"].as_str().unwrap())
+                    .collect();
+                assert_eq!(seq_nums.len(), MAX_ENTRIES_PAGE_SIZE as usize);
+                assert_eq!(seq_nums[0], expected.0.to_string());
+                assert_eq!(seq_nums[seq_nums.len() - 1], expected.1.to_string());
+            }
+        });
+    }
+
+    #[rstest]
+    fn entry_query(
+        #[from(populate_store_config)]
+        #[with(4, 1, vec![KeyPair::new()])]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|node: TestNode| async move {
+            populate_store(&node.context.store, &config).await;
+            let public_key = config.authors[0].public_key();
+
+            let entry = node
+                .context
+                .store
+                .get_entry_at_seq_num(&public_key, &LogId::default(), &SeqNum::new(4).unwrap())
+                .await
+                .unwrap()
+                .unwrap();
+
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            entry(hash: "{}") {{
+                                hash
+                                publicKey
+                                logId
+                                seqNum
+                                backlink
+                                skiplink
+                                encodedEntry
+                                encodedOperation
+                            }}
+                        }}"#,
+                        entry.hash()
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "entry": {
+                        "hash": entry.hash().to_string(),
+                        "publicKey": public_key.to_string(),
+                        "logId": "0",
+                        "seqNum": "4",
+                        "backlink": entry.backlink().unwrap().to_string(),
+                        "skiplink": entry.skiplink().unwrap().to_string(),
+                        "encodedEntry": entry.encoded_entry.to_string(),
+                        "encodedOperation": entry.payload().unwrap().to_string(),
+                    }
+                }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+
+    #[rstest]
+    fn unknown_entry() {
+        test_runner(|node: TestNode| async move {
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": r#"{
+                        entry(hash: "0020b177ec1bf26dfb3b7010d473e6d44713b29b765b99c6e60ecbfae742de496543") {
+                            seqNum
+                        }
+                    }"#
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({ "entry": Value::Null }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+}
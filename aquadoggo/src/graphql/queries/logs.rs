@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, ResolverContext, TypeRef};
+use async_graphql::Error;
+use dynamic_graphql::{FieldValue, ScalarValue};
+use log::debug;
+use p2panda_rs::entry::LogId;
+use p2panda_rs::identity::PublicKey;
+
+use crate::db::SqlStore;
+use crate::graphql::constants;
+use crate::graphql::responses::LogResponse;
+use crate::graphql::scalars::PublicKeyScalar;
+
+/// Add "logs" query to the root query object.
+pub fn build_logs_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::LOGS_QUERY,
+            TypeRef::named_nn_list_nn(constants::LOG),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let public_key = parse_arguments(&ctx)?;
+                    let store = ctx.data_unchecked::<SqlStore>();
+
+                    let author_logs = store.get_logs(&public_key).await?;
+
+                    // Look up the latest entries of all logs at once
+                    let log_keys: Vec<(PublicKey, LogId)> = author_logs
+                        .iter()
+                        .map(|(log_id, _, _)| (public_key, *log_id))
+                        .collect();
+                    let latest_seq_nums = store.get_latest_seq_nums(&log_keys).await?;
+
+                    let logs: Vec<FieldValue> = author_logs
+                        .into_iter()
+                        .map(|(log_id, document_id, schema_id)| {
+                            FieldValue::owned_any(LogResponse {
+                                public_key: public_key.into(),
+                                log_id: log_id.into(),
+                                document_id: (&document_id).into(),
+                                schema_id: schema_id.to_string(),
+                                latest_seq_num: latest_seq_nums
+                                    .get(&(public_key, log_id))
+                                    .map(|seq_num| seq_num.to_owned().into()),
+                            })
+                        })
+                        .collect();
+
+                    Ok(Some(FieldValue::list(logs)))
+                })
+            },
+        )
+        .argument(
+            InputValue::new(
+                constants::PUBLIC_KEY_ARG,
+                TypeRef::named_nn(constants::PUBLIC_KEY),
+            )
+            .description("The public key of the author logs are being requested for."),
+        )
+        .description("Return all logs of an author, ordered by log id."),
+    )
+}
+
+/// Parse and validate the arguments passed to logs.
+fn parse_arguments(ctx: &ResolverContext) -> Result<PublicKey, Error> {
+    let mut public_key = None;
+
+    for (name, value) in ctx.field().arguments()?.into_iter() {
+        if name.as_str() == constants::PUBLIC_KEY_ARG {
+            public_key = Some(PublicKeyScalar::from_value(value)?);
+        }
+    }
+
+    let public_key = public_key.ok_or_else(|| Error::new("Must provide `publicKey` argument"))?;
+    debug!("Query to logs received for public key {}", public_key);
+
+    Ok(public_key.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{value, Response};
+    use p2panda_rs::document::traits::AsDocument;
+    use p2panda_rs::identity::KeyPair;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::test_utils::{
+        http_test_client, populate_store, populate_store_config, test_runner, PopulateStoreConfig,
+        TestNode,
+    };
+
+    #[rstest]
+    fn logs_query(
+        #[from(populate_store_config)]
+        #[with(3, 2, vec![KeyPair::new()])]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|node: TestNode| async move {
+            let documents = populate_store(&node.context.store, &config).await;
+            let public_key = config.authors[0].public_key();
+            let schema_id = config.schema.id().to_string();
+
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            logs(publicKey: "{public_key}") {{
+                                publicKey
+                                logId
+                                documentId
+                                schemaId
+                                latestSeqNum
+                            }}
+                        }}"#
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "logs": [
+                        {
+                            "publicKey": public_key.to_string(),
+                            "logId": "0",
+                            "documentId": documents[0].id().to_string(),
+                            "schemaId": schema_id,
+                            "latestSeqNum": "3",
+                        },
+                        {
+                            "publicKey": public_key.to_string(),
+                            "logId": "1",
+                            "documentId": documents[1].id().to_string(),
+                            "schemaId": schema_id,
+                            "latestSeqNum": "3",
+                        }
+                    ]
+                }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+}
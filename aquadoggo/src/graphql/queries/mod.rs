@@ -1,9 +1,19 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+mod aggregate;
 mod collection;
 mod document;
+mod entries;
+mod logs;
 mod next_args;
+mod node;
+mod schemas;
 
+pub use aggregate::build_aggregate_query;
 pub use collection::build_collection_query;
 pub use document::build_document_query;
-pub use next_args::build_next_args_query;
+pub use entries::{build_entries_query, build_entry_query};
+pub use logs::build_logs_query;
+pub use next_args::{build_next_args_batch_query, build_next_args_query};
+pub use node::build_node_query;
+pub use schemas::{build_schema_query, build_schemas_query};
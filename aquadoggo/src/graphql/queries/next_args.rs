@@ -1,16 +1,25 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::{HashMap, HashSet};
+
 use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, ResolverContext, TypeRef};
 use async_graphql::Error;
 use dynamic_graphql::{FieldValue, ScalarValue};
 use log::debug;
-use p2panda_rs::api;
+use p2panda_rs::api::{self, DomainError, ValidationError};
+use p2panda_rs::document::{DocumentId, DocumentViewId};
+use p2panda_rs::entry::{LogId, SeqNum};
+use p2panda_rs::identity::PublicKey;
+use p2panda_rs::operation::OperationId;
 
 use crate::db::SqlStore;
 use crate::graphql::constants;
 use crate::graphql::responses::NextArguments;
 use crate::graphql::scalars::{DocumentViewIdScalar, PublicKeyScalar};
 
+/// Maximum number of items which can be passed to one nextArgsBatch query.
+const MAX_BATCH_ITEMS: usize = 1000;
+
 /// Add "nextArgs" query to the root query object.
 pub fn build_next_args_query(query: Object) -> Object {
     query.field(
@@ -55,6 +64,256 @@ pub fn build_next_args_query(query: Object) -> Object {
     )
 }
 
+/// Add "nextArgsBatch" query to the root query object.
+pub fn build_next_args_batch_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::NEXT_ARGS_BATCH_QUERY,
+            TypeRef::named_nn_list_nn(constants::NEXT_ARGS),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let items = parse_batch_arguments(&ctx)?;
+                    let store = ctx.data_unchecked::<SqlStore>();
+
+                    let next_args = next_args_batch(store, &items)
+                        .await?
+                        .into_iter()
+                        .map(FieldValue::owned_any);
+
+                    Ok(Some(FieldValue::list(next_args)))
+                })
+            },
+        )
+        .argument(
+            InputValue::new(
+                constants::NEXT_ARGS_BATCH_ARG,
+                TypeRef::named_nn_list_nn(constants::NEXT_ARGS_BATCH_ITEM),
+            )
+            .description("List of public keys and optional document view ids next args are being requested for, at most 1000 items."),
+        )
+        .description(
+            "Return required arguments for publishing entries for many documents at once, in \
+            the same order as the requested items. Every item without a document view id gets \
+            its own new log.",
+        ),
+    )
+}
+
+/// Calculate the arguments for publishing the next entry of every item in the given order, all of
+/// them are based on the same state of the store.
+///
+/// Logs and latest entries of all items are looked up together. Every item without a document
+/// view id is a new document and gets its own new log, items for the same author and document
+/// get the same arguments.
+async fn next_args_batch(
+    store: &SqlStore,
+    items: &[(PublicKey, Option<DocumentViewId>)],
+) -> Result<Vec<NextArguments>, Error> {
+    // Find the documents of all given document views
+    let operation_ids: Vec<OperationId> = items
+        .iter()
+        .filter_map(|(_, document_view_id)| document_view_id.as_ref())
+        .flat_map(|document_view_id| document_view_id.iter().cloned())
+        .collect();
+    let document_ids_by_operation = store
+        .get_document_ids_by_operation_ids(&operation_ids)
+        .await?;
+
+    let mut document_ids: Vec<Option<DocumentId>> = Vec::with_capacity(items.len());
+    for (_, document_view_id) in items {
+        let document_id = match document_view_id {
+            Some(document_view_id) => {
+                let mut found_document_ids = HashSet::new();
+                for operation_id in document_view_id.iter() {
+                    match document_ids_by_operation.get(operation_id) {
+                        Some(document_id) => found_document_ids.insert(document_id),
+                        None => {
+                            return Err(ValidationError::PreviousOperationNotFound(
+                                operation_id.to_owned(),
+                            )
+                            .into())
+                        }
+                    };
+                }
+
+                if found_document_ids.len() > 1 {
+                    return Err(ValidationError::InvalidDocumentViewId.into());
+                }
+
+                found_document_ids.into_iter().next().cloned()
+            }
+            None => None,
+        };
+
+        document_ids.push(document_id);
+    }
+
+    let existing_document_ids: Vec<DocumentId> = document_ids.iter().flatten().cloned().collect();
+    if !store
+        .get_deleted_document_ids(&existing_document_ids)
+        .await?
+        .is_empty()
+    {
+        return Err(ValidationError::DocumentDeleted.into());
+    }
+
+    // Determine the log of every item, new documents get the next free log ids of their author
+    let public_keys: Vec<PublicKey> = items
+        .iter()
+        .map(|(public_key, _)| *public_key)
+        .collect::<HashSet<PublicKey>>()
+        .into_iter()
+        .collect();
+    let log_ids = store
+        .get_log_ids(&public_keys, &existing_document_ids)
+        .await?;
+    let mut latest_log_ids = store.get_latest_log_ids(&public_keys).await?;
+    let mut new_log_ids: HashMap<(PublicKey, DocumentId), LogId> = HashMap::new();
+
+    let mut logs: Vec<(PublicKey, LogId, bool)> = Vec::with_capacity(items.len());
+    for ((public_key, _), document_id) in items.iter().zip(document_ids) {
+        let log_key = document_id.map(|document_id| (*public_key, document_id));
+
+        if let Some(log_id) = log_key.as_ref().and_then(|key| log_ids.get(key)) {
+            logs.push((*public_key, *log_id, true));
+            continue;
+        }
+
+        if let Some(log_id) = log_key.as_ref().and_then(|key| new_log_ids.get(key)) {
+            logs.push((*public_key, *log_id, false));
+            continue;
+        }
+
+        let log_id = match latest_log_ids.get(public_key) {
+            Some(latest_log_id) => {
+                let mut log_id = *latest_log_id;
+                log_id.next().ok_or(ValidationError::MaxLogId)?
+            }
+            None => LogId::default(),
+        };
+        latest_log_ids.insert(*public_key, log_id);
+        if let Some(log_key) = log_key {
+            new_log_ids.insert(log_key, log_id);
+        }
+
+        logs.push((*public_key, log_id, false));
+    }
+
+    // Continue existing logs after their latest entries
+    let existing_logs: Vec<(PublicKey, LogId)> = logs
+        .iter()
+        .filter(|(_, _, is_existing)| *is_existing)
+        .map(|(public_key, log_id, _)| (*public_key, *log_id))
+        .collect();
+    let latest_seq_nums = store.get_latest_seq_nums(&existing_logs).await?;
+
+    let mut seq_nums = Vec::with_capacity(logs.len());
+    let mut link_entries = Vec::new();
+    for (public_key, log_id, is_existing) in &logs {
+        if !is_existing {
+            seq_nums.push(None);
+            continue;
+        }
+
+        let latest_seq_num = latest_seq_nums
+            .get(&(*public_key, *log_id))
+            .ok_or_else(|| DomainError::ExpectedLogIdNotFound(log_id.as_u64()))?;
+        let seq_num = latest_seq_num.to_owned().next().ok_or_else(|| {
+            DomainError::MaxSeqNumReached(public_key.to_string(), log_id.as_u64())
+        })?;
+
+        // A skiplink is only required when it points at another entry than the backlink
+        let skiplink_seq_num = seq_num
+            .skiplink_seq_num()
+            .filter(|skiplink_seq_num| Some(*skiplink_seq_num) != seq_num.backlink_seq_num());
+
+        link_entries.push((*public_key, *log_id, *latest_seq_num));
+        if let Some(skiplink_seq_num) = skiplink_seq_num {
+            link_entries.push((*public_key, *log_id, skiplink_seq_num));
+        }
+
+        seq_nums.push(Some((seq_num, *latest_seq_num, skiplink_seq_num)));
+    }
+
+    let entry_hashes = store.get_entry_hashes(&link_entries).await?;
+
+    logs.into_iter()
+        .zip(seq_nums)
+        .map(|((public_key, log_id, _), seq_nums)| {
+            let (seq_num, backlink, skiplink) = match seq_nums {
+                Some((seq_num, backlink_seq_num, skiplink_seq_num)) => {
+                    let backlink = entry_hashes
+                        .get(&(public_key, log_id, backlink_seq_num))
+                        .cloned();
+                    let skiplink = match skiplink_seq_num {
+                        Some(skiplink_seq_num) => Some(
+                            entry_hashes
+                                .get(&(public_key, log_id, skiplink_seq_num))
+                                .cloned()
+                                .ok_or_else(|| {
+                                    ValidationError::ExpectedSkiplinkNotFound(
+                                        public_key.to_string(),
+                                        log_id.as_u64(),
+                                        skiplink_seq_num.as_u64(),
+                                    )
+                                })?,
+                        ),
+                        None => None,
+                    };
+                    (seq_num, backlink, skiplink)
+                }
+                None => (SeqNum::default(), None, None),
+            };
+
+            Ok(NextArguments {
+                log_id: log_id.into(),
+                seq_num: seq_num.into(),
+                backlink: backlink.map(|hash| hash.into()),
+                skiplink: skiplink.map(|hash| hash.into()),
+            })
+        })
+        .collect()
+}
+
+/// Parse the list of public keys and document view ids passed to nextArgsBatch.
+fn parse_batch_arguments(
+    ctx: &ResolverContext,
+) -> Result<Vec<(PublicKey, Option<DocumentViewId>)>, Error> {
+    let mut items = Vec::new();
+
+    for (name, value) in ctx.args.iter() {
+        if name.as_str() != constants::NEXT_ARGS_BATCH_ARG {
+            continue;
+        }
+
+        for item in value.list()?.iter() {
+            let item = item.object()?;
+
+            let public_key: PublicKey =
+                item.try_get(constants::PUBLIC_KEY_ARG)?.string()?.parse()?;
+
+            let document_view_id: Option<DocumentViewId> =
+                match item.get(constants::DOCUMENT_VIEW_ID_ARG) {
+                    Some(value) if !value.is_null() => Some(value.string()?.parse()?),
+                    _ => None,
+                };
+
+            items.push((public_key, document_view_id));
+        }
+    }
+
+    if items.len() > MAX_BATCH_ITEMS {
+        return Err(Error::new(format!(
+            "Too many items, at most {} are allowed",
+            MAX_BATCH_ITEMS
+        )));
+    }
+
+    debug!("Query to nextArgsBatch received for {} items", items.len());
+
+    Ok(items)
+}
+
 /// Parse and validate the arguments passed to next_args.
 fn parse_arguments(
     ctx: &ResolverContext,
@@ -92,6 +351,7 @@ fn parse_arguments(
 #[cfg(test)]
 mod tests {
     use async_graphql::{value, Response};
+    use p2panda_rs::api;
     use p2panda_rs::{document::traits::AsDocument, identity::KeyPair, test_utils::constants};
     use rstest::rstest;
     use serde_json::json;
@@ -101,6 +361,8 @@ mod tests {
         PopulateStoreConfig, TestNode,
     };
 
+    use super::next_args_batch;
+
     #[rstest]
     fn next_args_valid_query() {
         test_runner(|node: TestNode| async move {
@@ -196,6 +458,150 @@ mod tests {
         })
     }
 
+    #[rstest]
+    fn next_args_batch_query(
+        #[from(populate_store_config)]
+        #[with(1, 1, vec![KeyPair::from_private_key_str(constants::PRIVATE_KEY).unwrap()])]
+        config: PopulateStoreConfig,
+    ) {
+        test_runner(|mut node: TestNode| async move {
+            // Populates the store and materialises documents and schema.
+            let documents = populate_and_materialize(&mut node, &config).await;
+
+            let client = http_test_client(&node).await;
+            let document_id = documents[0].id();
+            let public_key = config.authors[0].public_key();
+            let other_public_key = KeyPair::new().public_key();
+
+            let response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            nextArgsBatch(items: [
+                                {{ publicKey: "{public_key}", viewId: "{}" }},
+                                {{ publicKey: "{public_key}" }},
+                                {{ publicKey: "{other_public_key}", viewId: null }},
+                                {{ publicKey: "{public_key}" }},
+                                {{ publicKey: "{public_key}", viewId: "{}" }}
+                            ]) {{
+                                logId,
+                                seqNum,
+                                backlink,
+                                skiplink
+                            }}
+                        }}"#,
+                        document_id.as_str(),
+                        document_id.as_str()
+                    )
+                }))
+                .send()
+                .await
+                .json::<Response>()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "nextArgsBatch": [
+                        {
+                            "logId": "0",
+                            "seqNum": "2",
+                            "backlink": "002015f3c7541991918e612be61ab58adf2316c9499a91dbfa012077de77c1620220",
+                            "skiplink": null,
+                        },
+                        {
+                            "logId": "1",
+                            "seqNum": "1",
+                            "backlink": null,
+                            "skiplink": null,
+                        },
+                        {
+                            "logId": "0",
+                            "seqNum": "1",
+                            "backlink": null,
+                            "skiplink": null,
+                        },
+                        {
+                            "logId": "2",
+                            "seqNum": "1",
+                            "backlink": null,
+                            "skiplink": null,
+                        },
+                        {
+                            "logId": "0",
+                            "seqNum": "2",
+                            "backlink": "002015f3c7541991918e612be61ab58adf2316c9499a91dbfa012077de77c1620220",
+                            "skiplink": null,
+                        }
+                    ]
+                }),
+                "{:#?}",
+                response.errors
+            );
+        })
+    }
+
+    #[rstest]
+    #[case::one_entry(1)]
+    #[case::with_skiplink(3)]
+    #[case::many_entries(12)]
+    fn next_args_batch_matches_next_args(#[case] entries: usize) {
+        test_runner(move |mut node: TestNode| async move {
+            let config = PopulateStoreConfig {
+                no_of_entries: entries,
+                no_of_logs: 2,
+                authors: vec![KeyPair::new(), KeyPair::new()],
+                ..PopulateStoreConfig::default()
+            };
+            let documents = populate_and_materialize(&mut node, &config).await;
+
+            // Ask for the logs of existing documents, new logs are handed out differently
+            let items: Vec<_> = documents
+                .iter()
+                .map(|document| (*document.author(), Some(document.view_id().to_owned())))
+                .collect();
+
+            let next_args = next_args_batch(&node.context.store, &items).await.unwrap();
+
+            for ((public_key, document_view_id), next_args) in items.iter().zip(next_args) {
+                let (backlink, skiplink, seq_num, log_id) =
+                    api::next_args(&node.context.store, public_key, document_view_id.as_ref())
+                        .await
+                        .unwrap();
+
+                assert_eq!(next_args.log_id, log_id.into());
+                assert_eq!(next_args.seq_num, seq_num.into());
+                assert_eq!(next_args.backlink, backlink.map(|hash| hash.into()));
+                assert_eq!(next_args.skiplink, skiplink.map(|hash| hash.into()));
+            }
+        })
+    }
+
+    #[rstest]
+    fn next_args_batch_too_many_items() {
+        test_runner(|node: TestNode| async move {
+            let client = http_test_client(&node).await;
+            let public_key = KeyPair::new().public_key();
+            let items = vec![format!(r#"{{ publicKey: "{public_key}" }}"#); 1001].join(", ");
+
+            let response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!("{{ nextArgsBatch(items: [{items}]) {{ logId }} }}")
+                }))
+                .send()
+                .await
+                .json::<Response>()
+                .await;
+
+            assert_eq!(
+                response.errors[0].message,
+                "Too many items, at most 1000 are allowed"
+            );
+        })
+    }
+
     #[rstest]
     fn next_args_error_response() {
         test_runner(|node: TestNode| async move {
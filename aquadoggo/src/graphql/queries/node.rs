@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use async_graphql::dynamic::{Field, FieldFuture, Object, TypeRef};
+use dynamic_graphql::FieldValue;
+use log::debug;
+
+use crate::db::SqlStore;
+use crate::graphql::constants;
+use crate::graphql::responses::{
+    ConnectedPeer, NodeStatusResponse, PendingTasks, ReplicationSession,
+};
+use crate::replication::SessionState;
+use crate::schema::SchemaProvider;
+use crate::status::NodeStatus;
+
+/// Add "node" query to the root query object.
+pub fn build_node_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::NODE_QUERY,
+            TypeRef::named_nn(constants::NODE_STATUS),
+            |ctx| {
+                FieldFuture::new(async move {
+                    debug!("Query to node received");
+
+                    let status = ctx.data_unchecked::<NodeStatus>();
+                    let store = ctx.data_unchecked::<SqlStore>();
+                    let schema_provider = ctx.data_unchecked::<SchemaProvider>();
+
+                    let connected_peers = status
+                        .connections()
+                        .await
+                        .into_iter()
+                        .map(|connection| ConnectedPeer {
+                            peer_id: connection.peer_id.to_string(),
+                            connection_id: connection.connection_id.to_string(),
+                            address: connection.address.to_string(),
+                            transport: connection.transport.as_str().to_string(),
+                        })
+                        .collect();
+
+                    let replication_sessions = status
+                        .sessions()
+                        .await
+                        .into_iter()
+                        .map(|session| ReplicationSession {
+                            peer_id: session.peer.id().to_string(),
+                            connection_id: session.peer.connection_id().to_string(),
+                            session_id: session.session_id,
+                            mode: session.mode.as_str().to_string(),
+                            target_set: session
+                                .target_set
+                                .iter()
+                                .map(|schema_id| schema_id.to_string())
+                                .collect(),
+                            state: match session.state {
+                                SessionState::Pending => "pending",
+                                SessionState::Established => "established",
+                                SessionState::Done => "done",
+                            }
+                            .to_string(),
+                            local: session.local,
+                            live_mode: session.live_mode,
+                        })
+                        .collect();
+
+                    let supported_schema_ids = schema_provider
+                        .supported_schema_ids()
+                        .await
+                        .iter()
+                        .map(|schema_id| schema_id.to_string())
+                        .collect();
+
+                    // Count pending tasks per worker, they are persisted in the database until
+                    // completed
+                    let mut task_counts: BTreeMap<String, u64> = BTreeMap::new();
+                    for task in store.get_tasks().await? {
+                        *task_counts
+                            .entry(task.worker_name().to_owned())
+                            .or_default() += 1;
+                    }
+                    let pending_tasks = task_counts
+                        .into_iter()
+                        .map(|(worker, count)| PendingTasks { worker, count })
+                        .collect();
+
+                    let node_status = NodeStatusResponse {
+                        peer_id: status.local_peer_id().to_string(),
+                        public_key: status.public_key().to_owned().into(),
+                        connected_peers,
+                        replication_sessions,
+                        supported_schema_ids,
+                        pending_tasks,
+                        query_cache: store.query_cache_metrics().into(),
+                        latest_operation_index: store.get_latest_operation_index().await?,
+                    };
+
+                    Ok(Some(FieldValue::owned_any(node_status)))
+                })
+            },
+        )
+        .description(
+            "Return the status of this node, its connections, ongoing work and query cache usage.",
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{value, Response};
+    use libp2p::swarm::ConnectionId;
+    use libp2p::{Multiaddr, PeerId};
+    use p2panda_rs::document::DocumentViewId;
+    use p2panda_rs::test_utils::fixtures::document_view_id;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::materializer::{Task, TaskInput};
+    use crate::test_utils::{http_test_client, test_runner, TestNode};
+
+    #[rstest]
+    fn node_query(document_view_id: DocumentViewId) {
+        test_runner(|node: TestNode| async move {
+            let remote_peer_id = PeerId::random();
+            let address: Multiaddr = "/ip4/127.0.0.1/udp/2022/quic-v1".parse().unwrap();
+            node.context
+                .status
+                .add_connection(remote_peer_id, ConnectionId::new_unchecked(1), &address)
+                .await;
+
+            let task = Task::new("reduce", TaskInput::DocumentViewId(document_view_id));
+            node.context.store.insert_task(&task).await.unwrap();
+
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": r#"{
+                        node {
+                            peerId
+                            publicKey
+                            connectedPeers {
+                                peerId
+                                address
+                                transport
+                            }
+                            replicationSessions {
+                                sessionId
+                            }
+                            pendingTasks {
+                                worker
+                                count
+                            }
+                            latestOperationIndex
+                        }
+                    }"#
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "node": {
+                        "peerId": node.context.status.local_peer_id().to_string(),
+                        "publicKey": node.context.key_pair.public_key().to_string(),
+                        "connectedPeers": [
+                            {
+                                "peerId": remote_peer_id.to_string(),
+                                "address": address.to_string(),
+                                "transport": "quic",
+                            }
+                        ],
+                        "replicationSessions": [],
+                        "pendingTasks": [
+                            {
+                                "worker": "reduce",
+                                "count": 1,
+                            }
+                        ],
+                        "latestOperationIndex": 0,
+                    }
+                }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+}
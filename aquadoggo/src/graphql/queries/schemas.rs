@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, ResolverContext, TypeRef};
+use async_graphql::{Error, Value};
+use dynamic_graphql::FieldValue;
+use log::debug;
+use p2panda_rs::schema::SchemaId;
+
+use crate::graphql::constants;
+use crate::graphql::responses::SchemaResponse;
+use crate::schema::SchemaProvider;
+
+/// Add "schemas" query to the root query object.
+pub fn build_schemas_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::SCHEMAS_QUERY,
+            TypeRef::named_nn_list_nn(constants::SCHEMA),
+            |ctx| {
+                FieldFuture::new(async move {
+                    debug!("Query to schemas received");
+
+                    let schema_provider = ctx.data_unchecked::<SchemaProvider>();
+
+                    let mut schemas = schema_provider.all().await;
+                    schemas.sort_by_key(|schema| schema.id().to_string());
+
+                    let schemas: Vec<FieldValue> = schemas
+                        .iter()
+                        .map(|schema| FieldValue::owned_any(SchemaResponse::from(schema)))
+                        .collect();
+
+                    Ok(Some(FieldValue::list(schemas)))
+                })
+            },
+        )
+        .description("Return all schemas supported by this node, ordered by their id."),
+    )
+}
+
+/// Add "schema" query to the root query object.
+pub fn build_schema_query(query: Object) -> Object {
+    query.field(
+        Field::new(
+            constants::SCHEMA_QUERY,
+            TypeRef::named(constants::SCHEMA),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let schema_id = parse_schema_arguments(&ctx)?;
+                    let schema_provider = ctx.data_unchecked::<SchemaProvider>();
+
+                    match schema_provider.get(&schema_id).await {
+                        Some(schema) => {
+                            Ok(Some(FieldValue::owned_any(SchemaResponse::from(&schema))))
+                        }
+                        None => Ok(FieldValue::NONE),
+                    }
+                })
+            },
+        )
+        .argument(
+            InputValue::new(constants::SCHEMA_ID_ARG, TypeRef::named_nn(TypeRef::STRING))
+                .description("The id of the requested schema."),
+        )
+        .description("Return a single schema supported by this node identified by its id."),
+    )
+}
+
+/// Parse and validate the arguments passed to schema.
+fn parse_schema_arguments(ctx: &ResolverContext) -> Result<SchemaId, Error> {
+    let mut schema_id = None;
+
+    for (name, value) in ctx.field().arguments()?.into_iter() {
+        if name.as_str() == constants::SCHEMA_ID_ARG {
+            if let Value::String(id) = value {
+                schema_id = Some(
+                    SchemaId::new(&id)
+                        .map_err(|err| Error::new(format!("Invalid schema id: {err}")))?,
+                );
+            }
+        }
+    }
+
+    let schema_id = schema_id.ok_or_else(|| Error::new("Must provide `id` argument"))?;
+    debug!("Query to schema received for id {}", schema_id);
+
+    Ok(schema_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{value, Response};
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::schema::{FieldType, SchemaId};
+    use p2panda_rs::test_utils::fixtures::key_pair;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::test_utils::{add_schema, http_test_client, test_runner, TestNode};
+
+    #[rstest]
+    fn schema_queries(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let venue_schema = add_schema(
+                &mut node,
+                "venue",
+                vec![("name", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+
+            let event_schema = add_schema(
+                &mut node,
+                "event",
+                vec![
+                    ("title", FieldType::String),
+                    ("venues", FieldType::RelationList(venue_schema.id().clone())),
+                ],
+                &key_pair,
+            )
+            .await;
+
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!(
+                        r#"{{
+                            schema(id: "{}") {{
+                                id
+                                name
+                                description
+                                version
+                                fields {{
+                                    name
+                                    type
+                                    relationSchemaId
+                                }}
+                            }}
+                            missing: schema(id: "unknown_0020c65567ae37efea293e34a9c7d13f8f2bf23dbdc3b5c7b9ab46293111c48fc78b") {{
+                                id
+                            }}
+                            schemas {{
+                                id
+                            }}
+                        }}"#,
+                        event_schema.id()
+                    )
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            let version = match event_schema.id() {
+                SchemaId::Application(_, view_id) => view_id.to_string(),
+                _ => panic!("Expected application schema"),
+            };
+
+            let mut schema_ids: Vec<String> = node
+                .context
+                .schema_provider
+                .all()
+                .await
+                .iter()
+                .map(|schema| schema.id().to_string())
+                .collect();
+            schema_ids.sort();
+            let schemas: Vec<async_graphql::Value> = schema_ids
+                .into_iter()
+                .map(|id| value!({ "id": id }))
+                .collect();
+
+            assert_eq!(
+                response.data,
+                value!({
+                    "schema": {
+                        "id": event_schema.id().to_string(),
+                        "name": "event",
+                        "description": event_schema.description().to_string(),
+                        "version": version,
+                        "fields": [
+                            {
+                                "name": "title",
+                                "type": "str",
+                                "relationSchemaId": null,
+                            },
+                            {
+                                "name": "venues",
+                                "type": "relation_list",
+                                "relationSchemaId": venue_schema.id().to_string(),
+                            },
+                        ],
+                    },
+                    "missing": null,
+                    "schemas": schemas,
+                }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+
+    #[rstest]
+    fn invalid_schema_id() {
+        test_runner(|node: TestNode| async move {
+            let client = http_test_client(&node).await;
+            let response: Response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": r#"{ schema(id: "not a schema id") { id } }"#
+                }))
+                .send()
+                .await
+                .json()
+                .await;
+
+            assert_eq!(response.errors.len(), 1);
+            assert!(response.errors[0].message.starts_with("Invalid schema id"));
+        });
+    }
+}
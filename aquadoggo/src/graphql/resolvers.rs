@@ -1,18 +1,30 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::convert::TryFrom;
+use std::num::NonZeroU64;
+
 use async_graphql::dynamic::ResolverContext;
 use async_graphql::Error;
 use dynamic_graphql::FieldValue;
 use p2panda_rs::document::traits::AsDocument;
-use p2panda_rs::operation::OperationValue;
+use p2panda_rs::document::DocumentViewId;
+use p2panda_rs::operation::traits::{AsOperation, WithPublicKey};
+use p2panda_rs::operation::{OperationAction, OperationId, OperationValue};
 use p2panda_rs::schema::{FieldType, Schema};
-use p2panda_rs::storage_provider::traits::DocumentStore;
+use p2panda_rs::storage_provider::traits::{DocumentStore, OperationStore};
+use p2panda_rs::WithId;
 
-use crate::db::stores::{PaginationCursor, PaginationData, RelationList};
+use crate::db::query::Pagination;
+use crate::db::stores::{
+    OperationCursor, PaginationCursor, PaginationData, RelationList, ReverseRelation,
+};
 use crate::db::types::StorageDocument;
 use crate::db::SqlStore;
-use crate::graphql::objects::DocumentMeta;
-use crate::graphql::scalars::{DocumentIdScalar, DocumentViewIdScalar};
+use crate::graphql::constants;
+use crate::graphql::objects::{
+    DocumentHistory, DocumentHistoryItem, DocumentMeta, OperationActionEnum,
+};
+use crate::graphql::scalars::{CursorScalar, DocumentIdScalar, DocumentViewIdScalar};
 use crate::graphql::utils::{get_document_from_params, gql_scalar, parse_collection_arguments};
 use crate::schema::SchemaProvider;
 
@@ -71,9 +83,10 @@ pub async fn resolve_document_collection(
     list: Option<RelationList>,
 ) -> Result<Option<FieldValue>, Error> {
     let store = ctx.data_unchecked::<SqlStore>();
+    let all_schema = ctx.data_unchecked::<SchemaProvider>().all().await;
 
     // Populate query arguments with values from GraphQL query
-    let query = parse_collection_arguments(&ctx, &schema, &list)?;
+    let query = parse_collection_arguments(&ctx, &schema, &all_schema, &list)?;
 
     // Fetch all queried documents and compose the value to be passed up the query tree
     let (pagination_data, documents) = store.query(&schema, &query, list.as_ref()).await?;
@@ -82,6 +95,32 @@ pub async fn resolve_document_collection(
     Ok(Some(FieldValue::owned_any(collection)))
 }
 
+/// Resolve a collection of documents which relate to the parent document in the given field.
+pub async fn resolve_reverse_relation(
+    ctx: ResolverContext<'_>,
+    schema: Schema,
+    field_name: String,
+) -> Result<Option<FieldValue<'_>>, Error> {
+    let store = ctx.data_unchecked::<SqlStore>();
+
+    let document = match Resolved::downcast(&ctx) {
+        Resolved::Document(document) => document,
+        Resolved::CollectionDocument(_, document) => document,
+        Resolved::Collection(_, _) => panic!("Expected list item or single document"),
+    };
+
+    // Populate query arguments with values from GraphQL query
+    let all_schema = ctx.data_unchecked::<SchemaProvider>().all().await;
+    let mut query = parse_collection_arguments(&ctx, &schema, &all_schema, &None)?;
+    query.reverse_relation = Some(ReverseRelation::new(document.id(), &field_name));
+
+    // Fetch all documents relating to the parent document
+    let (pagination_data, documents) = store.query(&schema, &query, None).await?;
+    let collection = Resolved::Collection(pagination_data, documents);
+
+    Ok(Some(FieldValue::owned_any(collection)))
+}
+
 /// Resolve meta fields of a single document.
 pub async fn resolve_document_meta(
     ctx: ResolverContext<'_>,
@@ -95,16 +134,121 @@ pub async fn resolve_document_meta(
         Resolved::Collection(_, _) => panic!("Expected list item or single document"),
     };
 
+    let store = ctx.data_unchecked::<SqlStore>();
+    let (received_at, updated_at) = store.get_document_timestamps(document.view_id()).await?;
+
     // We defined the document meta type and registered it in the GraphQL schema
     let document_meta = DocumentMeta {
         document_id: document.id().into(),
         document_view_id: document.view_id().into(),
         owner: document.author().to_owned().into(),
+        received_at,
+        updated_at,
     };
 
     Ok(Some(FieldValue::owned_any(document_meta)))
 }
 
+/// Resolve the history of a single document.
+///
+/// Every operation of the document is listed in the order they got applied, together with the
+/// view id of the document after this operation. Views which are not materialized in the store
+/// (anymore) are marked as pruned.
+pub async fn resolve_document_history(
+    ctx: ResolverContext<'_>,
+) -> Result<Option<FieldValue<'_>>, Error> {
+    let store = ctx.data_unchecked::<SqlStore>();
+
+    let document = match Resolved::downcast(&ctx) {
+        Resolved::Document(document) => document,
+        Resolved::CollectionDocument(_, document) => document,
+        Resolved::Collection(_, _) => panic!("Expected list item or single document"),
+    };
+
+    // Populate pagination arguments with values from GraphQL query
+    let mut pagination = Pagination::<PaginationCursor>::default();
+    for (name, value) in ctx.args.iter() {
+        match name.as_str() {
+            constants::PAGINATION_AFTER_ARG => {
+                let cursor: CursorScalar = value.string()?.parse()?;
+                pagination.after = Some(cursor.into());
+            }
+            constants::PAGINATION_FIRST_ARG => {
+                pagination.first = NonZeroU64::try_from(value.u64()?)?;
+            }
+            _ => (),
+        }
+    }
+
+    let operations = store.get_operations_by_document_id(document.id()).await?;
+    let materialized_view_ids = store.get_all_document_view_ids(document.id()).await?;
+
+    // Operations are returned in the order they were applied, we can reconstruct the view id
+    // after each operation by keeping track of the current graph tips
+    let mut graph_tips: Vec<OperationId> = Vec::new();
+    let mut views = Vec::with_capacity(operations.len());
+
+    for operation in operations {
+        let operation_id: &OperationId = operation.id();
+
+        if let Some(previous) = operation.previous() {
+            graph_tips.retain(|tip| !previous.iter().any(|id| id == tip));
+        }
+        graph_tips.push(operation_id.to_owned());
+        let view_id = DocumentViewId::new(&graph_tips);
+
+        let action = match operation.action() {
+            OperationAction::Create => OperationActionEnum::Create,
+            OperationAction::Update => OperationActionEnum::Update,
+            OperationAction::Delete => OperationActionEnum::Delete,
+        };
+
+        let cursor =
+            PaginationCursor::new(OperationCursor::from(operation_id.as_str()), None, None);
+
+        views.push(DocumentHistoryItem {
+            cursor: CursorScalar::from(&cursor),
+            pruned: !materialized_view_ids.contains(&view_id),
+            document_view_id: (&view_id).into(),
+            action,
+            author: operation.public_key().to_owned().into(),
+            previous: operation
+                .previous()
+                .as_ref()
+                .map(|previous| previous.into()),
+            fields: operation
+                .fields()
+                .map(|fields| fields.keys())
+                .unwrap_or_default(),
+        });
+    }
+
+    let total_count = views.len() as u64;
+
+    // Skip all views until the one identified by the passed cursor
+    if let Some(after) = pagination.after {
+        let after = CursorScalar::from(&after);
+        match views.iter().position(|view| view.cursor == after) {
+            Some(index) => {
+                views.drain(..=index);
+            }
+            None => return Err(Error::new("Unknown cursor passed into history")),
+        }
+    }
+
+    let has_next_page = views.len() as u64 > pagination.first.get();
+    views.truncate(pagination.first.get() as usize);
+
+    let history = DocumentHistory {
+        total_count,
+        has_next_page,
+        end_cursor: views.last().map(|view| view.cursor.clone()),
+        views,
+    };
+
+    Ok(Some(FieldValue::owned_any(history)))
+}
+
 /// Resolve a single document field value.
 ///
 /// If the value is a relation, then the relevant document id or document view id is determined and
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Return type for `entry` and `entries` queries.
+use dynamic_graphql::SimpleObject;
+use p2panda_rs::entry::traits::{AsEncodedEntry, AsEntry};
+
+use crate::db::types::StorageEntry;
+use crate::graphql::scalars::{
+    EncodedEntryScalar, EncodedOperationScalar, EntryHashScalar, LogIdScalar, PublicKeyScalar,
+    SeqNumScalar,
+};
+
+/// Signed bamboo entry and its encoded operation payload as stored on the node.
+#[derive(SimpleObject)]
+#[graphql(name = "Entry")]
+pub struct EntryResponse {
+    /// Hash of the entry.
+    pub hash: EntryHashScalar,
+
+    /// Public key of the author who signed the entry.
+    #[graphql(name = "publicKey")]
+    pub public_key: PublicKeyScalar,
+
+    /// Log id of the entry.
+    #[graphql(name = "logId")]
+    pub log_id: LogIdScalar,
+
+    /// Sequence number of the entry.
+    #[graphql(name = "seqNum")]
+    pub seq_num: SeqNumScalar,
+
+    /// Hash of the entry backlink.
+    pub backlink: Option<EntryHashScalar>,
+
+    /// Hash of the entry skiplink.
+    pub skiplink: Option<EntryHashScalar>,
+
+    /// Encoded and signed entry.
+    #[graphql(name = "encodedEntry")]
+    pub encoded_entry: EncodedEntryScalar,
+
+    /// Encoded operation payload of the entry, not set when it is not stored on this node.
+    #[graphql(name = "encodedOperation")]
+    pub encoded_operation: Option<EncodedOperationScalar>,
+}
+
+impl From<StorageEntry> for EntryResponse {
+    fn from(entry: StorageEntry) -> Self {
+        Self {
+            hash: entry.hash().into(),
+            public_key: entry.public_key().to_owned().into(),
+            log_id: entry.log_id().to_owned().into(),
+            seq_num: entry.seq_num().to_owned().into(),
+            backlink: entry.backlink().map(|hash| hash.to_owned().into()),
+            skiplink: entry.skiplink().map(|hash| hash.to_owned().into()),
+            encoded_entry: entry.encoded_entry.clone().into(),
+            encoded_operation: entry.payload.map(|payload| payload.into()),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Return type for `logs` query.
+use dynamic_graphql::SimpleObject;
+
+use crate::graphql::scalars::{DocumentIdScalar, LogIdScalar, PublicKeyScalar, SeqNumScalar};
+
+/// Bamboo log of an author, containing all entries for one document.
+#[derive(SimpleObject)]
+#[graphql(name = "Log")]
+pub struct LogResponse {
+    /// Public key of the author of this log.
+    #[graphql(name = "publicKey")]
+    pub public_key: PublicKeyScalar,
+
+    /// Log id of this log.
+    #[graphql(name = "logId")]
+    pub log_id: LogIdScalar,
+
+    /// Id of the document all entries in this log belong to.
+    #[graphql(name = "documentId")]
+    pub document_id: DocumentIdScalar,
+
+    /// Id of the schema of the document.
+    #[graphql(name = "schemaId")]
+    pub schema_id: String,
+
+    /// Sequence number of the latest entry in this log, not set when no entry is stored.
+    #[graphql(name = "latestSeqNum")]
+    pub latest_seq_num: Option<SeqNumScalar>,
+}
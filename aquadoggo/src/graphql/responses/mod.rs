@@ -1,5 +1,17 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+mod entry;
+mod log;
 mod next_arguments;
+mod node_status;
+mod schema;
+mod validation;
 
+pub use entry::EntryResponse;
+pub use log::LogResponse;
 pub use next_arguments::NextArguments;
+pub use node_status::{
+    ConnectedPeer, NodeStatusResponse, PendingTasks, QueryCacheStatus, ReplicationSession,
+};
+pub use schema::{SchemaFieldResponse, SchemaResponse};
+pub use validation::{ValidationErrorKind, ValidationErrorResponse, ValidationResponse};
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Return type for `node` query.
+use dynamic_graphql::SimpleObject;
+
+use crate::db::stores::QueryCacheMetrics;
+use crate::graphql::scalars::PublicKeyScalar;
+
+/// Status of the node, what it is connected to and what it is currently doing.
+#[derive(SimpleObject)]
+#[graphql(name = "NodeStatus")]
+pub struct NodeStatusResponse {
+    /// Peer id of this node in the p2p network.
+    #[graphql(name = "peerId")]
+    pub peer_id: String,
+
+    /// Public key of this node.
+    #[graphql(name = "publicKey")]
+    pub public_key: PublicKeyScalar,
+
+    /// Currently open connections to other peers.
+    #[graphql(name = "connectedPeers")]
+    pub connected_peers: Vec<ConnectedPeer>,
+
+    /// Currently active replication sessions with other peers.
+    #[graphql(name = "replicationSessions")]
+    pub replication_sessions: Vec<ReplicationSession>,
+
+    /// Schema ids which are supported by this node.
+    #[graphql(name = "supportedSchemaIds")]
+    pub supported_schema_ids: Vec<String>,
+
+    /// Number of pending tasks in the materializer, grouped by worker.
+    #[graphql(name = "pendingTasks")]
+    pub pending_tasks: Vec<PendingTasks>,
+
+    /// Usage of the in-memory cache for document query results.
+    #[graphql(name = "queryCache")]
+    pub query_cache: QueryCacheStatus,
+
+    /// Index of the latest operation received by this node, can be used to query collections as
+    /// of this point later.
+    #[graphql(name = "latestOperationIndex")]
+    pub latest_operation_index: i64,
+}
+
+/// Open connection to another peer.
+#[derive(SimpleObject)]
+pub struct ConnectedPeer {
+    /// Peer id of the remote peer.
+    #[graphql(name = "peerId")]
+    pub peer_id: String,
+
+    /// Id of this connection.
+    #[graphql(name = "connectionId")]
+    pub connection_id: String,
+
+    /// Address of the remote peer.
+    pub address: String,
+
+    /// Transport protocol of this connection, can be "quic", "tcp", "relay" or "unknown".
+    pub transport: String,
+}
+
+/// Active replication session with another peer.
+#[derive(SimpleObject)]
+pub struct ReplicationSession {
+    /// Peer id of the remote peer.
+    #[graphql(name = "peerId")]
+    pub peer_id: String,
+
+    /// Id of the connection this session is running on.
+    #[graphql(name = "connectionId")]
+    pub connection_id: String,
+
+    /// Id of this session.
+    #[graphql(name = "sessionId")]
+    pub session_id: u64,
+
+    /// Replication mode of this session.
+    pub mode: String,
+
+    /// Schema ids which get replicated in this session.
+    #[graphql(name = "targetSet")]
+    pub target_set: Vec<String>,
+
+    /// State of this session, can be "pending", "established" or "done".
+    pub state: String,
+
+    /// Flag indicating if this node initiated the session.
+    pub local: bool,
+
+    /// Flag indicating if the session is in live mode.
+    #[graphql(name = "liveMode")]
+    pub live_mode: bool,
+}
+
+/// Number of pending tasks of a materializer worker.
+#[derive(SimpleObject)]
+pub struct PendingTasks {
+    /// Name of the worker.
+    pub worker: String,
+
+    /// Number of pending tasks.
+    pub count: u64,
+}
+
+/// Usage of the query cache since the node started.
+#[derive(SimpleObject)]
+pub struct QueryCacheStatus {
+    /// Number of query results currently kept in the cache.
+    pub entries: u64,
+
+    /// Maximum number of query results the cache can hold, 0 if it is disabled.
+    pub capacity: u64,
+
+    /// Number of queries which were answered from the cache.
+    pub hits: u64,
+
+    /// Number of queries which needed to be sent to the database.
+    pub misses: u64,
+
+    /// Number of results which were removed to make space for newer ones.
+    pub evictions: u64,
+
+    /// Number of results which were removed because documents of their schemas changed.
+    pub invalidations: u64,
+}
+
+impl From<QueryCacheMetrics> for QueryCacheStatus {
+    fn from(metrics: QueryCacheMetrics) -> Self {
+        Self {
+            entries: metrics.entries as u64,
+            capacity: metrics.capacity as u64,
+            hits: metrics.hits,
+            misses: metrics.misses,
+            evictions: metrics.evictions,
+            invalidations: metrics.invalidations,
+        }
+    }
+}
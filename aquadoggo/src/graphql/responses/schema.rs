@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Return types for `schemas` and `schema` queries.
+use dynamic_graphql::SimpleObject;
+use p2panda_rs::schema::{FieldType, Schema, SchemaVersion};
+
+use crate::graphql::scalars::DocumentViewIdScalar;
+
+/// Definition of a schema supported by this node.
+#[derive(SimpleObject)]
+#[graphql(name = "Schema")]
+pub struct SchemaResponse {
+    /// Id of this schema.
+    pub id: String,
+
+    /// Name of this schema.
+    pub name: String,
+
+    /// Description of this schema.
+    pub description: String,
+
+    /// Document view id of the schema definition, not set for system schemas.
+    pub version: Option<DocumentViewIdScalar>,
+
+    /// Fields of this schema, ordered by name.
+    pub fields: Vec<SchemaFieldResponse>,
+}
+
+impl From<&Schema> for SchemaResponse {
+    fn from(schema: &Schema) -> Self {
+        Self {
+            id: schema.id().to_string(),
+            name: schema.id().name().to_string(),
+            description: schema.description().to_string(),
+            version: match schema.id().version() {
+                SchemaVersion::Application(view_id) => Some((&view_id).into()),
+                SchemaVersion::System(_) => None,
+            },
+            fields: schema
+                .fields()
+                .iter()
+                .map(|(name, field_type)| SchemaFieldResponse::new(name, field_type))
+                .collect(),
+        }
+    }
+}
+
+/// Definition of a field of a schema.
+#[derive(SimpleObject)]
+#[graphql(name = "SchemaField")]
+pub struct SchemaFieldResponse {
+    /// Name of this field.
+    pub name: String,
+
+    /// Type of this field, can be "bool", "int", "float", "str", "bytes", "relation",
+    /// "relation_list", "pinned_relation" or "pinned_relation_list".
+    #[graphql(name = "type")]
+    pub field_type: String,
+
+    /// Id of the schema of the documents this field relates to, only set for relation types.
+    #[graphql(name = "relationSchemaId")]
+    pub relation_schema_id: Option<String>,
+}
+
+impl SchemaFieldResponse {
+    fn new(name: &str, field_type: &FieldType) -> Self {
+        let (field_type, relation_schema_id) = match field_type {
+            FieldType::Boolean => ("bool", None),
+            FieldType::Integer => ("int", None),
+            FieldType::Float => ("float", None),
+            FieldType::String => ("str", None),
+            FieldType::Bytes => ("bytes", None),
+            FieldType::Relation(schema_id) => ("relation", Some(schema_id)),
+            FieldType::RelationList(schema_id) => ("relation_list", Some(schema_id)),
+            FieldType::PinnedRelation(schema_id) => ("pinned_relation", Some(schema_id)),
+            FieldType::PinnedRelationList(schema_id) => ("pinned_relation_list", Some(schema_id)),
+        };
+
+        Self {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+            relation_schema_id: relation_schema_id.map(|schema_id| schema_id.to_string()),
+        }
+    }
+}
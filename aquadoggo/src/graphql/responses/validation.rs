@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Return type for `validate` mutation.
+use dynamic_graphql::{Enum, SimpleObject};
+
+/// Result of validating an entry and operation without publishing them.
+#[derive(SimpleObject)]
+#[graphql(name = "ValidationResult")]
+pub struct ValidationResponse {
+    /// True if the entry and operation would be accepted by `publish`.
+    pub valid: bool,
+
+    /// All problems found with the entry and operation, empty if they are valid.
+    pub errors: Vec<ValidationErrorResponse>,
+}
+
+/// Problem found while validating an entry and operation.
+#[derive(SimpleObject)]
+#[graphql(name = "ValidationError")]
+pub struct ValidationErrorResponse {
+    /// Check which failed.
+    pub kind: ValidationErrorKind,
+
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+/// Checks which are run against an entry and operation before publishing them.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// Entry could not be decoded or its signature or payload hash is invalid.
+    #[graphql(name = "ENTRY")]
+    Entry,
+
+    /// Operation could not be decoded or does not match its schema.
+    #[graphql(name = "OPERATION")]
+    Operation,
+
+    /// Schema of the operation is not supported by this node.
+    #[graphql(name = "SCHEMA")]
+    Schema,
+
+    /// Log id, sequence number, backlink or skiplink of the entry are invalid.
+    #[graphql(name = "LOG")]
+    Log,
+
+    /// Operation refers to a deleted or unknown document, or to operations of another document.
+    #[graphql(name = "DOCUMENT")]
+    Document,
+
+    /// Node failed to look up data required for validation.
+    #[graphql(name = "STORE")]
+    Store,
+
+    /// Operation exceeds the size limit of this node.
+    #[graphql(name = "LIMIT")]
+    Limit,
+
+    /// Client is not allowed to publish operations of this schema or author.
+    #[graphql(name = "ACCESS")]
+    Access,
+}
@@ -45,6 +45,12 @@ impl Display for CursorScalar {
     }
 }
 
+impl From<&PaginationCursor> for CursorScalar {
+    fn from(cursor: &PaginationCursor) -> Self {
+        Self(cursor.clone())
+    }
+}
+
 impl From<CursorScalar> for PaginationCursor {
     fn from(cursor: CursorScalar) -> Self {
         cursor.0
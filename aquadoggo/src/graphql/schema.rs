@@ -3,35 +3,51 @@
 //! Dynamically create and manage GraphQL schemas.
 use std::sync::Arc;
 
-use async_graphql::dynamic::{Field, FieldFuture, Object, Schema, TypeRef};
-use async_graphql::{Request, Response, Value};
+use async_graphql::dynamic::{Field, FieldFuture, Object, Schema, Subscription, TypeRef};
+use async_graphql::{Data, Executor, Request, Response, Value};
 use dynamic_graphql::internal::Registry;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
 use log::{debug, info, warn};
 use p2panda_rs::Human;
 use tokio::sync::Mutex;
 
 use crate::bus::ServiceSender;
 use crate::db::SqlStore;
+use crate::graphql::auth::Authorization;
+use crate::graphql::constants;
 use crate::graphql::input_values::{
-    build_filter_input_object, build_order_enum_value, BooleanFilter, FloatFilter, HexBytesFilter,
-    IntegerFilter, MetaFilterInputObject, OrderDirection, PinnedRelationFilter,
-    PinnedRelationListFilter, RelationFilter, RelationListFilter, StringFilter,
+    build_filter_input_object, build_group_by_enum_value, build_order_enum_value,
+    build_order_input_object, build_related_filter_input_object, BooleanFilter, FloatFilter,
+    HexBytesFilter, IntegerFilter, MetaFilterInputObject, NextArgsBatchItem, OrderDirection,
+    PinnedRelationFilter, PinnedRelationListFilter, PublishBatchItem, RelationFilter,
+    RelationListFilter, StringFilter,
 };
-use crate::graphql::mutations::{MutationRoot, Publish};
+use crate::graphql::mutations::{MutationRoot, Publish, Validate};
 use crate::graphql::objects::{
-    build_document_collection_object, build_document_fields_object, build_document_object,
-    build_paginated_document_object, DocumentMeta,
+    build_aggregate_object, build_aggregate_values_objects, build_document_collection_object,
+    build_document_fields_object, build_document_object, build_paginated_document_object,
+    build_referenced_by_object, DocumentHistory, DocumentHistoryItem, DocumentMeta,
+    OperationActionEnum,
 };
 use crate::graphql::queries::{
-    build_collection_query, build_document_query, build_next_args_query,
+    build_aggregate_query, build_collection_query, build_document_query, build_entries_query,
+    build_entry_query, build_logs_query, build_next_args_batch_query, build_next_args_query,
+    build_node_query, build_schema_query, build_schemas_query,
+};
+use crate::graphql::responses::{
+    ConnectedPeer, EntryResponse, LogResponse, NextArguments, NodeStatusResponse, PendingTasks,
+    QueryCacheStatus, ReplicationSession, SchemaFieldResponse, SchemaResponse, ValidationErrorKind,
+    ValidationErrorResponse, ValidationResponse,
 };
-use crate::graphql::responses::NextArguments;
 use crate::graphql::scalars::{
     CursorScalar, DocumentIdScalar, DocumentViewIdScalar, EncodedEntryScalar,
     EncodedOperationScalar, EntryHashScalar, HexBytesScalar, LogIdScalar, PublicKeyScalar,
     SeqNumScalar,
 };
+use crate::graphql::subscriptions::{build_collection_subscription, build_document_subscription};
 use crate::schema::SchemaProvider;
+use crate::status::NodeStatus;
 
 /// Dynamically generates and returns a new GraphQL API root schema based on the currently
 /// registered p2panda schemas.
@@ -39,6 +55,7 @@ pub async fn build_root_schema(
     store: SqlStore,
     tx: ServiceSender,
     schema_provider: SchemaProvider,
+    status: NodeStatus,
 ) -> Result<Schema, async_graphql::dynamic::SchemaError> {
     let all_schema = schema_provider.all().await;
 
@@ -47,10 +64,26 @@ pub async fn build_root_schema(
         // Register mutation operations
         .register::<MutationRoot>()
         .register::<Publish>()
+        .register::<Validate>()
         // Register responses
         .register::<NextArguments>()
+        .register::<EntryResponse>()
+        .register::<LogResponse>()
+        .register::<NodeStatusResponse>()
+        .register::<ConnectedPeer>()
+        .register::<ReplicationSession>()
+        .register::<PendingTasks>()
+        .register::<QueryCacheStatus>()
+        .register::<SchemaResponse>()
+        .register::<SchemaFieldResponse>()
+        .register::<ValidationResponse>()
+        .register::<ValidationErrorResponse>()
+        .register::<ValidationErrorKind>()
         // Register objects
         .register::<DocumentMeta>()
+        .register::<DocumentHistory>()
+        .register::<DocumentHistoryItem>()
+        .register::<OperationActionEnum>()
         // Register input values
         .register::<BooleanFilter>()
         .register::<HexBytesFilter>()
@@ -59,6 +92,8 @@ pub async fn build_root_schema(
         .register::<MetaFilterInputObject>()
         .register::<OrderDirection>()
         .register::<PinnedRelationFilter>()
+        .register::<PublishBatchItem>()
+        .register::<NextArgsBatchItem>()
         .register::<PinnedRelationListFilter>()
         .register::<RelationFilter>()
         .register::<RelationListFilter>()
@@ -75,7 +110,11 @@ pub async fn build_root_schema(
         .register::<PublicKeyScalar>()
         .register::<SeqNumScalar>();
 
-    let mut schema_builder = Schema::build("Query", Some("MutationRoot"), None);
+    let mut schema_builder = Schema::build(
+        "Query",
+        Some("MutationRoot"),
+        Some(constants::SUBSCRIPTION_ROOT),
+    );
 
     // Populate it with the registered types. We can now use these in any following dynamically
     // created query object fields.
@@ -84,25 +123,34 @@ pub async fn build_root_schema(
     // Construct the root query object
     let mut root_query = Object::new("Query");
 
+    // Construct the root subscription object
+    let mut root_subscription = Subscription::new(constants::SUBSCRIPTION_ROOT);
+
     // Loop through all schema retrieved from the schema store, dynamically create GraphQL objects,
     // input values and a query for the documents they describe
-    for schema in all_schema {
+    for schema in all_schema.iter() {
         // Construct the fields type object which will be named `<schema_id>Field`
-        let document_fields_object = build_document_fields_object(&schema);
+        let document_fields_object = build_document_fields_object(schema);
 
         // Construct the document object which contains "fields" and "meta" fields
-        let document_object = build_document_object(&schema);
+        let document_object = build_document_object(schema, &all_schema);
 
         // Construct the paginated response wrapper for this document schema type
-        let document_collection_object = build_document_collection_object(&schema);
+        let document_collection_object = build_document_collection_object(schema);
 
         // Construct the document object which contains "fields" and "meta" fields as well as
         // "cursor" pagination field
-        let paginated_document_object = build_paginated_document_object(&schema);
+        let paginated_document_object = build_paginated_document_object(schema, &all_schema);
 
         // Construct the filter and ordering input values for this schema
-        let filter_input = build_filter_input_object(&schema);
-        let order_input = build_order_enum_value(&schema);
+        let filter_input = build_filter_input_object(schema);
+        let order_input = build_order_enum_value(schema);
+        let order_clause_input = build_order_input_object(schema);
+
+        // Construct the aggregation objects and grouping input value for this schema
+        let aggregate_object = build_aggregate_object(schema);
+        let aggregate_values_objects = build_aggregate_values_objects(schema);
+        let group_by_input = build_group_by_enum_value(schema);
 
         // Register a schema, schema fields and filter type for every schema
         schema_builder = schema_builder
@@ -111,27 +159,67 @@ pub async fn build_root_schema(
             .register(document_collection_object)
             .register(paginated_document_object)
             .register(order_input)
-            .register(filter_input);
+            .register(order_clause_input)
+            .register(filter_input)
+            .register(aggregate_object)
+            .register(group_by_input);
+
+        for object in aggregate_values_objects {
+            schema_builder = schema_builder.register(object);
+        }
+
+        // Construct the filter input value for fields of related documents
+        if let Some(related_filter_input) = build_related_filter_input_object(schema) {
+            schema_builder = schema_builder.register(related_filter_input);
+        }
+
+        // Construct the object listing documents relating to a document of this schema
+        if let Some(referenced_by_object) = build_referenced_by_object(schema, &all_schema) {
+            schema_builder = schema_builder.register(referenced_by_object);
+        }
 
         // Add a query for each schema. It offers an interface to retrieve a single document of
         // this schema by its document id or view id. Its resolver parses and validates the passed
         // parameters, then forwards them up to the children query fields
-        root_query = build_document_query(root_query, &schema);
+        root_query = build_document_query(root_query, schema);
 
         // Add a query for retrieving all documents of a certain schema
-        root_query = build_collection_query(root_query, &schema);
+        root_query = build_collection_query(root_query, schema);
+
+        // Add a query for aggregating the values of all documents of a certain schema
+        root_query = build_aggregate_query(root_query, schema);
+
+        // Add subscriptions for observing single documents or all documents of a certain schema
+        root_subscription = build_document_subscription(root_subscription, schema);
+        root_subscription = build_collection_subscription(root_subscription, schema);
     }
 
     // Add next args to the query object
     let root_query = build_next_args_query(root_query);
+    let root_query = build_next_args_batch_query(root_query);
+
+    // Add queries for raw entries and logs to the query object
+    let root_query = build_entry_query(root_query);
+    let root_query = build_entries_query(root_query);
+    let root_query = build_logs_query(root_query);
+
+    // Add node status to the query object
+    let root_query = build_node_query(root_query);
+
+    // Add schema definitions to the query object
+    let root_query = build_schemas_query(root_query);
+    let root_query = build_schema_query(root_query);
 
     // Build the GraphQL schema. We can unwrap here since it will only fail if we forgot to
     // register all required types above
     schema_builder
         .register(root_query)
+        .register(root_subscription)
         .data(store)
         .data(schema_provider)
         .data(tx)
+        .data(status)
+        .extension(Authorization)
         .finish()
 }
 
@@ -149,6 +237,9 @@ pub struct GraphQLSharedData {
 
     /// Schema provider giving us access to currently known schemas.
     schema_provider: SchemaProvider,
+
+    /// Status of the node reported by all services.
+    status: NodeStatus,
 }
 
 /// Builds new GraphQL schemas dynamically and executes the latest GraphQL schema for incoming
@@ -173,7 +264,12 @@ pub struct GraphQLSchemaManager {
 
 impl GraphQLSchemaManager {
     /// Returns a new instance of `GraphQLSchemaManager`.
-    pub async fn new(store: SqlStore, tx: ServiceSender, schema_provider: SchemaProvider) -> Self {
+    pub async fn new(
+        store: SqlStore,
+        tx: ServiceSender,
+        schema_provider: SchemaProvider,
+        status: NodeStatus,
+    ) -> Self {
         // Initialize a default GraphQL schema. Used as a fallback when a node has no supported schema configured.
         let root_query = Object::new("Query").field(Field::new(
             "hello",
@@ -190,6 +286,7 @@ impl GraphQLSchemaManager {
             store,
             tx,
             schema_provider,
+            status,
         };
 
         // Create manager instance and spawn internal watch task
@@ -212,7 +309,14 @@ impl GraphQLSchemaManager {
 
         // Create the new GraphQL based on the current state of known p2panda application schemas
         async fn rebuild(shared: GraphQLSharedData, schemas: GraphQLSchemas) {
-            match build_root_schema(shared.store, shared.tx, shared.schema_provider).await {
+            match build_root_schema(
+                shared.store,
+                shared.tx,
+                shared.schema_provider,
+                shared.status,
+            )
+            .await
+            {
                 Ok(schema) => schemas.lock().await.push(schema),
                 Err(err) => warn!("Can't re-build GraphQL schema: {}", err),
             }
@@ -254,6 +358,47 @@ impl GraphQLSchemaManager {
             .execute(request)
             .await
     }
+
+    /// Executes an incoming GraphQL subscription.
+    ///
+    /// Similar to `execute`, the subscription is handled by the latest given schema at the point
+    /// the subscription started.
+    pub fn execute_stream(
+        &self,
+        request: impl Into<Request>,
+        session_data: Option<Arc<Data>>,
+    ) -> BoxStream<'static, Response> {
+        let schemas = self.schemas.clone();
+        let request = request.into();
+
+        stream::once(async move {
+            schemas
+                .lock()
+                .await
+                .last()
+                .expect("No schema given yet")
+                .execute_stream_with_session_data(request, session_data.unwrap_or_default())
+        })
+        .flatten()
+        .boxed()
+    }
+}
+
+/// Allows using the manager with the GraphQL-over-WebSocket protocol implementation of
+/// `async_graphql_axum`.
+#[async_trait::async_trait]
+impl Executor for GraphQLSchemaManager {
+    async fn execute(&self, request: Request) -> Response {
+        GraphQLSchemaManager::execute(self, request).await
+    }
+
+    fn execute_stream(
+        &self,
+        request: Request,
+        session_data: Option<Arc<Data>>,
+    ) -> BoxStream<'static, Response> {
+        GraphQLSchemaManager::execute_stream(self, request, session_data)
+    }
 }
 
 impl std::fmt::Debug for GraphQLSchemaManager {
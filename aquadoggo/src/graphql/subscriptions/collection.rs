@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::num::NonZeroU64;
+
+use async_graphql::dynamic::{
+    FieldValue, Subscription, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+};
+use async_stream::try_stream;
+use log::debug;
+use p2panda_rs::operation::OperationValue;
+use p2panda_rs::schema::Schema;
+
+use crate::bus::ServiceSender;
+use crate::db::query::{Field, MetaField, Order, Pagination, Select};
+use crate::db::stores::{PaginationCursor, Query};
+use crate::db::SqlStore;
+use crate::graphql::constants;
+use crate::graphql::resolvers::Resolved;
+use crate::graphql::utils::{
+    next_materialized_document, parse_filter_arguments, with_filter_arguments,
+};
+use crate::schema::SchemaProvider;
+
+/// Adds a GraphQL subscription for observing all documents of a schema to the root subscription
+/// object.
+///
+/// The subscription follows the format `all_<SCHEMA_ID>(<...ARGS>)`. It emits every newly
+/// materialized document view which matches the passed filter arguments.
+pub fn build_collection_subscription(subscription: Subscription, schema: &Schema) -> Subscription {
+    let schema_id = schema.id().clone();
+    let schema = schema.clone();
+
+    subscription.field(with_filter_arguments(
+        SubscriptionField::new(
+            format!("{}{}", constants::QUERY_ALL_PREFIX, schema_id),
+            TypeRef::named_nn(schema_id.to_string()),
+            move |ctx| {
+                let schema = schema.clone();
+                debug!(
+                    "Subscription to {}{} received",
+                    constants::QUERY_ALL_PREFIX,
+                    schema.id()
+                );
+
+                SubscriptionFieldFuture::new(async move {
+                    let all_schema = ctx.data_unchecked::<SchemaProvider>().all().await;
+                    let filter = parse_filter_arguments(&ctx, &schema, &all_schema)?;
+                    let store = ctx.data_unchecked::<SqlStore>().clone();
+                    let mut rx = ctx.data_unchecked::<ServiceSender>().subscribe();
+
+                    // Select all fields of the document as we don't know yet which ones will be
+                    // resolved
+                    let mut fields: Vec<Field> = schema
+                        .fields()
+                        .keys()
+                        .iter()
+                        .map(|field_name| Field::new(field_name))
+                        .collect();
+                    fields.push(Field::Meta(MetaField::DocumentId));
+                    fields.push(Field::Meta(MetaField::DocumentViewId));
+                    fields.push(Field::Meta(MetaField::Owner));
+
+                    let pagination = Pagination::<PaginationCursor> {
+                        first: NonZeroU64::new(1).unwrap(),
+                        ..Pagination::default()
+                    };
+
+                    Ok(try_stream! {
+                        while let Some(document_id) =
+                            next_materialized_document(&mut rx, schema.id()).await
+                        {
+                            // Check if the materialized document matches the filters by
+                            // querying for it in the database
+                            let mut filter = filter.clone();
+                            filter.restrict(
+                                &Field::Meta(MetaField::DocumentId),
+                                &OperationValue::String(document_id.to_string()),
+                            );
+
+                            let query = Query::new(
+                                &pagination,
+                                &Select::new(&fields),
+                                &filter,
+                                &Order::default(),
+                            );
+
+                            let (_, mut documents) = store.query(&schema, &query, None).await?;
+
+                            if let Some((_, document)) = documents.pop() {
+                                yield FieldValue::owned_any(Resolved::Document(document));
+                            }
+                        }
+                    })
+                })
+            },
+        ),
+        &schema_id,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use async_graphql::value;
+    use futures::StreamExt;
+    use p2panda_rs::document::DocumentId;
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::schema::FieldType;
+    use p2panda_rs::test_utils::fixtures::random_key_pair;
+    use rstest::rstest;
+    use tokio::sync::broadcast;
+
+    use crate::bus::ServiceMessage;
+    use crate::graphql::GraphQLSchemaManager;
+    use crate::test_utils::{add_document, add_schema, test_runner, TestNode};
+
+    #[rstest]
+    fn filtered_collection_updates(#[from(random_key_pair)] key_pair: KeyPair) {
+        test_runner(move |mut node: TestNode| async move {
+            let schema = add_schema(
+                &mut node,
+                "schema_name",
+                vec![("bool", FieldType::Boolean), ("text", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+
+            let (tx, _) = broadcast::channel(16);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx.clone(),
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+
+            let mut stream = manager.execute_stream(
+                format!(
+                    r#"subscription {{
+                        documents: all_{type_name}(filter: {{ bool: {{ eq: true }} }}) {{
+                            fields {{ text }}
+                        }}
+                    }}"#,
+                    type_name = schema.id(),
+                ),
+                None,
+            );
+
+            // Publish two documents, only one of them matches the filter
+            let mut document_ids = Vec::new();
+            for (bool, text) in [(false, "no"), (true, "yes")] {
+                let view_id = add_document(
+                    &mut node,
+                    schema.id(),
+                    vec![("bool", bool.into()), ("text", text.into())],
+                    &key_pair,
+                )
+                .await;
+                document_ids.push(DocumentId::new(view_id.graph_tips().first().unwrap()));
+            }
+
+            let (response, _) = tokio::join!(stream.next(), async {
+                // Wait until the subscription is listening on the bus
+                while tx.receiver_count() == 0 {
+                    tokio::task::yield_now().await;
+                }
+
+                for document_id in document_ids {
+                    tx.send(ServiceMessage::DocumentMaterialized(
+                        schema.id().to_owned(),
+                        document_id,
+                    ))
+                    .unwrap();
+                }
+            });
+
+            let response = response.expect("Stream returns response");
+            assert_eq!(
+                response.data,
+                value!({ "documents": { "fields": { "text": "yes" } } }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+}
@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use async_graphql::dynamic::{
+    FieldValue, InputValue, ResolverContext, Subscription, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::Error;
+use async_stream::try_stream;
+use dynamic_graphql::ScalarValue;
+use log::debug;
+use p2panda_rs::document::traits::AsDocument;
+use p2panda_rs::document::DocumentId;
+use p2panda_rs::schema::Schema;
+use p2panda_rs::storage_provider::traits::DocumentStore;
+
+use crate::bus::ServiceSender;
+use crate::db::SqlStore;
+use crate::graphql::constants;
+use crate::graphql::resolvers::Resolved;
+use crate::graphql::scalars::DocumentIdScalar;
+use crate::graphql::utils::next_materialized_document;
+
+/// Adds a GraphQL subscription for observing a single document selected by its id to the root
+/// subscription object.
+///
+/// The subscription follows the format `document_<SCHEMA_ID>(id: <DOCUMENT_ID>)`. It emits the
+/// current view of the document first and then every newly materialized view. Nothing is emitted
+/// as long as the document does not exist (yet) or got deleted.
+pub fn build_document_subscription(subscription: Subscription, schema: &Schema) -> Subscription {
+    let schema_id = schema.id().clone();
+    subscription.field(
+        SubscriptionField::new(
+            format!("{}{}", constants::SUBSCRIPTION_DOCUMENT_PREFIX, schema_id),
+            TypeRef::named_nn(schema_id.to_string()),
+            move |ctx| {
+                let schema_id = schema_id.clone();
+
+                SubscriptionFieldFuture::new(async move {
+                    let document_id = parse_arguments(&ctx)?;
+                    let store = ctx.data_unchecked::<SqlStore>().clone();
+                    let mut rx = ctx.data_unchecked::<ServiceSender>().subscribe();
+
+                    Ok(try_stream! {
+                        let mut current_view_id = None;
+
+                        'subscription: loop {
+                            let document = store.get_document(&document_id).await?;
+                            let view_id = document.as_ref().map(|document| document.view_id().to_owned());
+
+                            // Only emit a value when the document changed since we've last seen it
+                            if view_id != current_view_id {
+                                current_view_id = view_id;
+
+                                if let Some(document) = document {
+                                    yield FieldValue::owned_any(Resolved::Document(document));
+                                }
+                            }
+
+                            // Wait until this document got materialized again
+                            loop {
+                                match next_materialized_document(&mut rx, &schema_id).await {
+                                    Some(materialized_id) if materialized_id == document_id => break,
+                                    Some(_) => continue,
+                                    None => break 'subscription,
+                                }
+                            }
+                        }
+                    })
+                })
+            },
+        )
+        .argument(
+            InputValue::new(
+                constants::DOCUMENT_ID_ARG,
+                TypeRef::named_nn(constants::DOCUMENT_ID),
+            )
+            .description("Specify the id of the document to be observed"),
+        )
+        .description(format!(
+            "Subscribe to the latest views of a {} document.",
+            schema.name()
+        )),
+    )
+}
+
+/// Parse and validate the arguments passed into this subscription.
+fn parse_arguments(ctx: &ResolverContext) -> Result<DocumentId, Error> {
+    let mut document_id = None;
+
+    for (name, value) in ctx.field().arguments()?.into_iter() {
+        if name.as_str() == constants::DOCUMENT_ID_ARG {
+            document_id = Some(DocumentIdScalar::from_value(value)?);
+        }
+    }
+
+    let document_id = document_id.ok_or_else(|| Error::new("Must provide `id` argument"))?;
+
+    debug!(
+        "Subscription to {} received for document {}",
+        ctx.field().name(),
+        document_id
+    );
+
+    Ok(DocumentId::from(&document_id))
+}
+
+#[cfg(test)]
+mod test {
+    use async_graphql::value;
+    use futures::StreamExt;
+    use p2panda_rs::document::DocumentId;
+    use p2panda_rs::identity::KeyPair;
+    use p2panda_rs::schema::FieldType;
+    use p2panda_rs::test_utils::fixtures::random_key_pair;
+    use rstest::rstest;
+    use tokio::sync::broadcast;
+
+    use crate::bus::ServiceMessage;
+    use crate::graphql::GraphQLSchemaManager;
+    use crate::test_utils::{add_document, add_schema, test_runner, update_document, TestNode};
+
+    #[rstest]
+    fn document_updates(#[from(random_key_pair)] key_pair: KeyPair) {
+        test_runner(move |mut node: TestNode| async move {
+            let schema = add_schema(
+                &mut node,
+                "schema_name",
+                vec![("bool", FieldType::Boolean)],
+                &key_pair,
+            )
+            .await;
+
+            let view_id = add_document(
+                &mut node,
+                schema.id(),
+                vec![("bool", true.into())],
+                &key_pair,
+            )
+            .await;
+            let document_id = DocumentId::new(view_id.graph_tips().first().unwrap());
+
+            let (tx, _) = broadcast::channel(16);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx.clone(),
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+
+            let mut stream = manager.execute_stream(
+                format!(
+                    r#"subscription {{
+                        document: document_{type_name}(id: "{document_id}") {{
+                            fields {{ bool }}
+                        }}
+                    }}"#,
+                    type_name = schema.id(),
+                    document_id = document_id,
+                ),
+                None,
+            );
+
+            // The current view of the document is emitted first
+            let response = stream.next().await.expect("Stream returns response");
+            assert_eq!(
+                response.data,
+                value!({ "document": { "fields": { "bool": true } } }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Update the document and inform the subscription about it
+            update_document(
+                &mut node,
+                schema.id(),
+                vec![("bool", false.into())],
+                &view_id,
+                &key_pair,
+            )
+            .await;
+            tx.send(ServiceMessage::DocumentMaterialized(
+                schema.id().to_owned(),
+                document_id.clone(),
+            ))
+            .unwrap();
+
+            let response = stream.next().await.expect("Stream returns response");
+            assert_eq!(
+                response.data,
+                value!({ "document": { "fields": { "bool": false } } }),
+                "{:#?}",
+                response.errors
+            );
+        });
+    }
+}
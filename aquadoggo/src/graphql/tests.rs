@@ -9,7 +9,9 @@ use p2panda_rs::{document::DocumentId, schema::FieldType};
 use rstest::rstest;
 use serde_json::json;
 
-use crate::test_utils::{add_document, add_schema, http_test_client, test_runner, TestNode};
+use crate::test_utils::{
+    add_document, add_schema, http_test_client, test_runner, update_document, TestNode,
+};
 
 // Test querying application documents with scalar fields (no relations) by document id and by view
 // id.
@@ -213,3 +215,141 @@ fn relation_fields() {
         assert_eq!(response.data, expected_data,);
     });
 }
+
+// Test querying the history of a document, including pagination and pruned views.
+#[test]
+fn document_history() {
+    test_runner(|mut node: TestNode| async move {
+        let key_pair = random_key_pair();
+        let public_key = key_pair.public_key().to_string();
+
+        // Add schema to node
+        let schema = add_schema(
+            &mut node,
+            "schema_name",
+            vec![("title", FieldType::String), ("year", FieldType::Integer)],
+            &key_pair,
+        )
+        .await;
+
+        // Publish a document and update it twice
+        let view_id_1 = add_document(
+            &mut node,
+            schema.id(),
+            vec![("title", "Old".into()), ("year", 1.into())],
+            &key_pair,
+        )
+        .await;
+        let view_id_2 = update_document(
+            &mut node,
+            schema.id(),
+            vec![("title", "New".into())],
+            &view_id_1,
+            &key_pair,
+        )
+        .await;
+        let view_id_3 = update_document(
+            &mut node,
+            schema.id(),
+            vec![("year", 2.into())],
+            &view_id_2,
+            &key_pair,
+        )
+        .await;
+
+        // Remove the first view from the store
+        assert!(node
+            .context
+            .store
+            .prune_document_view(&view_id_1)
+            .await
+            .unwrap());
+
+        let client = http_test_client(&node).await;
+
+        let query = |after: Option<String>| {
+            format!(
+                r#"{{
+                    document: {type_name}(viewId: "{view_id}") {{
+                        history(first: 2{after}) {{
+                            totalCount
+                            hasNextPage
+                            endCursor
+                            views {{
+                                viewId
+                                action
+                                author
+                                previous
+                                fields
+                                pruned
+                            }}
+                        }}
+                    }}
+                }}"#,
+                type_name = schema.id(),
+                view_id = view_id_3,
+                after = after.map_or("".to_string(), |cursor| format!(r#", after: "{cursor}""#)),
+            )
+        };
+
+        // Request first page
+        let response = client
+            .post("/graphql")
+            .json(&json!({ "query": query(None) }))
+            .send()
+            .await;
+        let response: Response = response.json().await;
+        let data = response.data.into_json().unwrap();
+        let history = &data["document"]["history"];
+
+        assert_eq!(history["totalCount"], 3, "{:#?}", response.errors);
+        assert_eq!(history["hasNextPage"], true);
+        assert_eq!(
+            history["views"],
+            json!([
+                {
+                    "viewId": view_id_1.to_string(),
+                    "action": "CREATE",
+                    "author": public_key,
+                    "previous": null,
+                    "fields": ["title", "year"],
+                    "pruned": true,
+                },
+                {
+                    "viewId": view_id_2.to_string(),
+                    "action": "UPDATE",
+                    "author": public_key,
+                    "previous": view_id_1.to_string(),
+                    "fields": ["title"],
+                    "pruned": false,
+                },
+            ])
+        );
+
+        // Request second page
+        let end_cursor = history["endCursor"].as_str().unwrap().to_string();
+        let response = client
+            .post("/graphql")
+            .json(&json!({ "query": query(Some(end_cursor)) }))
+            .send()
+            .await;
+        let response: Response = response.json().await;
+        let data = response.data.into_json().unwrap();
+        let history = &data["document"]["history"];
+
+        assert_eq!(history["hasNextPage"], false, "{:#?}", response.errors);
+        assert_eq!(
+            history["views"],
+            json!([
+                {
+                    "viewId": view_id_3.to_string(),
+                    "action": "UPDATE",
+                    "author": public_key,
+                    "previous": view_id_2.to_string(),
+                    "fields": ["year"],
+                    "pruned": false,
+                },
+            ])
+        );
+    });
+}
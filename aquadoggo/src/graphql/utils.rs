@@ -3,18 +3,26 @@
 use std::convert::{TryFrom, TryInto};
 use std::num::NonZeroU64;
 
-use async_graphql::dynamic::{InputValue, ObjectAccessor, ResolverContext, TypeRef, ValueAccessor};
-use async_graphql::{Error, Value};
+use async_graphql::dynamic::{
+    InputValue, ObjectAccessor, ResolverContext, SubscriptionField, TypeRef, ValueAccessor,
+};
+use async_graphql::indexmap::IndexMap;
+use async_graphql::{Error, Name, Value};
+use log::warn;
 use p2panda_rs::document::{DocumentId, DocumentViewId};
 use p2panda_rs::operation::OperationValue;
 use p2panda_rs::schema::{FieldType, Schema, SchemaId};
 use p2panda_rs::storage_provider::error::DocumentStorageError;
 use p2panda_rs::storage_provider::traits::DocumentStore;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+use crate::bus::ServiceMessage;
 
 use crate::db::query::{
-    Direction, Field, Filter, MetaField, Order, Pagination, PaginationField, Select,
+    Direction, Field, Filter, FilterGroup, MetaField, Order, Pagination, PaginationField, Select,
 };
-use crate::db::stores::{PaginationCursor, Query, RelationList};
+use crate::db::stores::{AsOf, PaginationCursor, Query, RelationList};
 use crate::db::types::StorageDocument;
 use crate::db::SqlStore;
 use crate::graphql::constants;
@@ -23,9 +31,16 @@ use crate::graphql::scalars::{CursorScalar, DocumentIdScalar, DocumentViewIdScal
 // Type name suffixes.
 const DOCUMENT_FIELDS_SUFFIX: &str = "Fields";
 const FILTER_INPUT_SUFFIX: &str = "Filter";
+const RELATED_FILTER_INPUT_SUFFIX: &str = "RelatedFilter";
 const ORDER_BY_SUFFIX: &str = "OrderBy";
+const ORDER_SUFFIX: &str = "Order";
 const COLLECTION_ITEM_SUFFIX: &str = "Item";
 const COLLECTION_SUFFIX: &str = "Collection";
+const AGGREGATE_SUFFIX: &str = "Aggregate";
+const AGGREGATE_VALUES_SUFFIX: &str = "AggregateValues";
+const AGGREGATE_AVERAGES_SUFFIX: &str = "AggregateAverages";
+const GROUP_BY_SUFFIX: &str = "GroupBy";
+const REFERENCED_BY_SUFFIX: &str = "ReferencedBy";
 
 /// Formats the name of a document collection type.
 pub fn collection_name(schema_id: &SchemaId) -> String {
@@ -47,11 +62,75 @@ pub fn filter_name(schema_id: &SchemaId) -> String {
     format!("{}{FILTER_INPUT_SUFFIX}", schema_id)
 }
 
+/// Formats the name of a filter type for related documents.
+pub fn related_filter_name(schema_id: &SchemaId) -> String {
+    format!("{}{RELATED_FILTER_INPUT_SUFFIX}", schema_id)
+}
+
 /// Formats the name of an order by type.
 pub fn order_by_name(schema_id: &SchemaId) -> String {
     format!("{}{ORDER_BY_SUFFIX}", schema_id)
 }
 
+/// Formats the name of an ordering clause type.
+pub fn order_name(schema_id: &SchemaId) -> String {
+    format!("{}{ORDER_SUFFIX}", schema_id)
+}
+
+/// Formats the name of an aggregation type.
+pub fn aggregate_name(schema_id: &SchemaId) -> String {
+    format!("{}{AGGREGATE_SUFFIX}", schema_id)
+}
+
+/// Formats the name of an aggregated values type.
+pub fn aggregate_values_name(schema_id: &SchemaId) -> String {
+    format!("{}{AGGREGATE_VALUES_SUFFIX}", schema_id)
+}
+
+/// Formats the name of an aggregated averages type.
+pub fn aggregate_averages_name(schema_id: &SchemaId) -> String {
+    format!("{}{AGGREGATE_AVERAGES_SUFFIX}", schema_id)
+}
+
+/// Formats the name of a group by type.
+pub fn group_by_name(schema_id: &SchemaId) -> String {
+    format!("{}{GROUP_BY_SUFFIX}", schema_id)
+}
+
+/// Formats the name of a type listing documents which relate to a document.
+pub fn referenced_by_name(schema_id: &SchemaId) -> String {
+    format!("{}{REFERENCED_BY_SUFFIX}", schema_id)
+}
+
+/// Formats the name of a field listing documents which relate to a document in the given field.
+pub fn reverse_relation_field_name(schema_id: &SchemaId, field_name: &str) -> String {
+    format!("{}_{}", schema_id, field_name)
+}
+
+/// Returns all schemas and their fields which contain (pinned) relations or relation lists to
+/// documents of the given schema.
+pub fn reverse_relations(schema: &Schema, all_schema: &[Schema]) -> Vec<(Schema, String)> {
+    let mut relations = Vec::new();
+
+    for other_schema in all_schema {
+        for (field_name, field_type) in other_schema.fields().iter() {
+            let related_schema_id = match field_type {
+                FieldType::Relation(schema_id)
+                | FieldType::RelationList(schema_id)
+                | FieldType::PinnedRelation(schema_id)
+                | FieldType::PinnedRelationList(schema_id) => schema_id,
+                _ => continue,
+            };
+
+            if related_schema_id == schema.id() {
+                relations.push((other_schema.to_owned(), field_name.to_owned()));
+            }
+        }
+    }
+
+    relations
+}
+
 /// Convert non-relation operation values into GraphQL values.
 ///
 /// Panics when given a relation field value.
@@ -118,37 +197,45 @@ pub fn filter_to_operation_value(
 pub fn parse_collection_arguments(
     ctx: &ResolverContext,
     schema: &Schema,
+    all_schema: &[Schema],
     list: &Option<RelationList>,
 ) -> Result<Query<PaginationCursor>, Error> {
     let mut pagination = Pagination::<PaginationCursor>::default();
-    let mut order = Order::default();
+    let mut order_by = Vec::new();
+    let mut order_direction = Direction::Ascending;
     let mut filter = Filter::default();
+    let mut search = None;
+    let mut as_of = None;
+    let mut is_forward = false;
 
     for (name, value) in ctx.args.iter() {
         match name.as_str() {
             constants::PAGINATION_AFTER_ARG => {
                 let cursor: CursorScalar = value.string()?.parse()?;
                 pagination.after = Some(cursor.into());
+                is_forward = true;
             }
             constants::PAGINATION_FIRST_ARG => {
                 pagination.first = NonZeroU64::try_from(value.u64()?)?;
             }
-            constants::ORDER_BY_ARG => {
-                let order_by = match value.enum_name()? {
-                    "OWNER" => Field::Meta(MetaField::Owner),
-                    "DOCUMENT_ID" => Field::Meta(MetaField::DocumentId),
-                    "DOCUMENT_VIEW_ID" => Field::Meta(MetaField::DocumentViewId),
-                    field_name => Field::new(field_name),
-                };
-                order.field = Some(order_by);
+            constants::PAGINATION_BEFORE_ARG => {
+                let cursor: CursorScalar = value.string()?.parse()?;
+                pagination.before = Some(cursor.into());
+            }
+            constants::PAGINATION_LAST_ARG => {
+                pagination.last = Some(NonZeroU64::try_from(value.u64()?)?);
             }
+            constants::ORDER_BY_ARG => match value.list() {
+                Ok(clauses) => {
+                    for clause in clauses.iter() {
+                        order_by.push(parse_order_clause(&clause)?);
+                    }
+                }
+                // A single clause is accepted in place of a list as well
+                Err(_) => order_by.push(parse_order_clause(&value)?),
+            },
             constants::ORDER_DIRECTION_ARG => {
-                let direction = match value.enum_name()? {
-                    "ASC" => Direction::Ascending,
-                    "DESC" => Direction::Descending,
-                    _ => panic!("Unknown order direction argument key received"),
-                };
-                order.direction = direction;
+                order_direction = parse_order_direction(&value)?;
             }
             constants::META_FILTER_ARG => {
                 let filter_object = value
@@ -160,36 +247,257 @@ pub fn parse_collection_arguments(
                 let filter_object = value
                     .object()
                     .map_err(|_| Error::new("internal: is not an object"))?;
-                parse_filter(&mut filter, schema, &filter_object)?;
+                parse_filter(&mut filter, schema, all_schema, &filter_object)?;
+            }
+            constants::SEARCH_ARG => {
+                let text = value.string()?.trim();
+                if text.is_empty() {
+                    return Err(Error::new("Search query can not be empty"));
+                }
+                search = Some(text.to_string());
+            }
+            constants::AS_OF_ARG => {
+                if as_of.is_some() {
+                    return Err(Error::new(
+                        "Arguments `asOf` and `asOfOperation` can not be combined",
+                    ));
+                }
+                as_of = Some(AsOf::Timestamp(value.i64()?));
+            }
+            constants::AS_OF_OPERATION_ARG => {
+                if as_of.is_some() {
+                    return Err(Error::new(
+                        "Arguments `asOf` and `asOfOperation` can not be combined",
+                    ));
+                }
+                as_of = Some(AsOf::OperationIndex(value.i64()?));
             }
             _ => panic!("Unknown argument key received"),
         }
     }
 
+    // The `first` argument has a default value, we can only tell if it was set by the client by
+    // looking at the raw arguments
+    is_forward = is_forward
+        || ctx
+            .field()
+            .arguments()?
+            .iter()
+            .any(|(name, _)| name.as_str() == constants::PAGINATION_FIRST_ARG);
+
+    if pagination.last.is_some() && is_forward {
+        return Err(Error::new(
+            "Pagination arguments `first` and `after` can not be combined with `last`",
+        ));
+    }
+
+    if pagination.before.is_some() && pagination.last.is_none() {
+        return Err(Error::new(
+            "Pagination argument `before` requires `last` to be set",
+        ));
+    }
+
+    // Fields without an explicitly set direction are ordered in the direction given by the
+    // separate argument
+    let mut order = Order::default();
+    for (field, direction) in order_by {
+        order = order.then(&field, direction.as_ref().unwrap_or(&order_direction));
+    }
+
+    if order.contains(&Field::Meta(MetaField::Relevance)) && search.is_none() {
+        return Err(Error::new("Ordering by relevance requires a search query"));
+    }
+
     // Parse selected fields in GraphQL query
     let (pagination_fields, fields) = look_ahead_selected_fields(ctx);
     let select = Select::new(fields.as_slice());
     pagination.fields = pagination_fields;
 
     // Set default ordering to document id as per specification, if we're in a root query
-    if list.is_none() && order.field.is_none() {
-        order.field = Some(Field::Meta(MetaField::DocumentId));
+    if list.is_none() && order.is_empty() {
+        order = Order::new(&Field::Meta(MetaField::DocumentId), &order_direction);
     }
 
     // Finally put it all together
-    let query = Query::new(&pagination, &select, &filter, &order);
+    let mut query = Query::new(&pagination, &select, &filter, &order);
+    query.search = search;
+    query.as_of = as_of;
 
     Ok(query)
 }
 
+/// Parse field and optional direction of one ordering clause.
+fn parse_order_clause(value: &ValueAccessor) -> Result<(Field, Option<Direction>), Error> {
+    let clause = value.object()?;
+
+    let field = match clause.try_get(constants::ORDER_FIELD_FIELD)?.enum_name()? {
+        "OWNER" => Field::Meta(MetaField::Owner),
+        "DOCUMENT_ID" => Field::Meta(MetaField::DocumentId),
+        "DOCUMENT_VIEW_ID" => Field::Meta(MetaField::DocumentViewId),
+        "RELEVANCE" => Field::Meta(MetaField::Relevance),
+        "RECEIVED_AT" => Field::Meta(MetaField::ReceivedAt),
+        "UPDATED_AT" => Field::Meta(MetaField::UpdatedAt),
+        field_name => Field::new(field_name),
+    };
+
+    let direction = match clause.get(constants::ORDER_DIRECTION_FIELD) {
+        Some(direction) => Some(parse_order_direction(&direction)?),
+        None => None,
+    };
+
+    Ok((field, direction))
+}
+
+/// Parse the direction of an ordering.
+fn parse_order_direction(value: &ValueAccessor) -> Result<Direction, Error> {
+    let direction = match value.enum_name()? {
+        "ASC" => Direction::Ascending,
+        "DESC" => Direction::Descending,
+        _ => panic!("Unknown order direction argument key received"),
+    };
+
+    Ok(direction)
+}
+
+/// Parse filter arguments of a collection subscription.
+pub fn parse_filter_arguments(
+    ctx: &ResolverContext,
+    schema: &Schema,
+    all_schema: &[Schema],
+) -> Result<Filter, Error> {
+    let mut filter = Filter::default();
+
+    for (name, value) in ctx.args.iter() {
+        match name.as_str() {
+            constants::META_FILTER_ARG => {
+                let filter_object = value
+                    .object()
+                    .map_err(|_| Error::new("internal: is not an object"))?;
+                parse_meta_filter(&mut filter, &filter_object)?;
+            }
+            constants::FILTER_ARG => {
+                let filter_object = value
+                    .object()
+                    .map_err(|_| Error::new("internal: is not an object"))?;
+                parse_filter(&mut filter, schema, all_schema, &filter_object)?;
+            }
+            _ => panic!("Unknown argument key received"),
+        }
+    }
+
+    Ok(filter)
+}
+
+/// Parse filter and grouping arguments of an aggregation query.
+pub fn parse_aggregate_arguments(
+    ctx: &ResolverContext,
+    schema: &Schema,
+    all_schema: &[Schema],
+) -> Result<(Filter, Option<Field>), Error> {
+    let mut filter = Filter::default();
+    let mut group_by = None;
+
+    for (name, value) in ctx.args.iter() {
+        match name.as_str() {
+            constants::META_FILTER_ARG => {
+                let filter_object = value
+                    .object()
+                    .map_err(|_| Error::new("internal: is not an object"))?;
+                parse_meta_filter(&mut filter, &filter_object)?;
+            }
+            constants::FILTER_ARG => {
+                let filter_object = value
+                    .object()
+                    .map_err(|_| Error::new("internal: is not an object"))?;
+                parse_filter(&mut filter, schema, all_schema, &filter_object)?;
+            }
+            constants::GROUP_BY_ARG => {
+                group_by = Some(match value.enum_name()? {
+                    "OWNER" => Field::Meta(MetaField::Owner),
+                    field_name => Field::new(field_name),
+                });
+            }
+            _ => panic!("Unknown argument key received"),
+        }
+    }
+
+    Ok((filter, group_by))
+}
+
 /// Parse a filter object received from the graphql api into an abstract filter type based on the
 /// schema of the documents being queried.
 fn parse_filter(
     filter: &mut Filter,
     schema: &Schema,
+    all_schema: &[Schema],
     filter_object: &ObjectAccessor,
 ) -> Result<(), Error> {
     for (field, filters) in filter_object.iter() {
+        // Parse nested filter groups first
+        match field.as_str() {
+            constants::FILTER_AND_FIELD | constants::FILTER_OR_FIELD => {
+                let mut nested_filters = Vec::new();
+                for nested_object in filters.list()?.iter() {
+                    let mut nested_filter = Filter::new();
+                    parse_filter(
+                        &mut nested_filter,
+                        schema,
+                        all_schema,
+                        &nested_object.object()?,
+                    )?;
+                    nested_filters.push(nested_filter);
+                }
+
+                let group = if field.as_str() == constants::FILTER_AND_FIELD {
+                    FilterGroup::And(nested_filters)
+                } else {
+                    FilterGroup::Or(nested_filters)
+                };
+                filter.add_group(group);
+                continue;
+            }
+            constants::FILTER_NOT_FIELD => {
+                let mut nested_filter = Filter::new();
+                parse_filter(&mut nested_filter, schema, all_schema, &filters.object()?)?;
+                filter.add_group(FilterGroup::Not(Box::new(nested_filter)));
+                continue;
+            }
+            constants::FILTER_RELATED_FIELD => {
+                for (relation_field, related_object) in filters.object()?.iter() {
+                    let related_schema = match schema.fields().get(relation_field.as_str()) {
+                        Some(FieldType::Relation(schema_id))
+                        | Some(FieldType::PinnedRelation(schema_id)) => all_schema
+                            .iter()
+                            .find(|schema| schema.id() == schema_id)
+                            .ok_or_else(|| {
+                                Error::new(format!(
+                                    "Unknown schema '{}' of related documents",
+                                    schema_id
+                                ))
+                            })?,
+                        _ => panic!("Expected relation or pinned relation field"),
+                    };
+
+                    // Deleted documents can not be related to, even through pinned relations
+                    let mut related_filter = Filter::default();
+                    parse_filter(
+                        &mut related_filter,
+                        related_schema,
+                        all_schema,
+                        &related_object.object()?,
+                    )?;
+
+                    filter.add_group(FilterGroup::Related(
+                        relation_field.to_string(),
+                        Box::new(related_schema.to_owned()),
+                        Box::new(related_filter),
+                    ));
+                }
+                continue;
+            }
+            _ => (),
+        }
+
         let filter_field = Field::new(field.as_str());
         let filters = filters.object()?;
         for (name, value) in filters.iter() {
@@ -235,8 +543,31 @@ fn parse_filter(
                     let value = filter_to_operation_value(&value, field_type)?;
                     filter.add_lte(&filter_field, &value);
                 }
-                "contains" => {
-                    filter.add_contains(&filter_field, value.string()?);
+                "contains" => match field_type {
+                    FieldType::RelationList(_) | FieldType::PinnedRelationList(_) => {
+                        let item = filter_to_operation_value(&value, field_type)?;
+                        filter.add_list_contains_all(&filter_field, &[item]);
+                    }
+                    _ => filter.add_contains(&filter_field, value.string()?),
+                },
+                "containsAny" => {
+                    let mut list_items: Vec<OperationValue> = vec![];
+                    for value in value.list()?.iter() {
+                        let item = filter_to_operation_value(&value, field_type)?;
+                        list_items.push(item);
+                    }
+                    if list_items.is_empty() {
+                        return Err(Error::new("containsAny filter can not be empty"));
+                    }
+                    filter.add_list_contains_any(&filter_field, &list_items);
+                }
+                "containsAll" => {
+                    let mut list_items: Vec<OperationValue> = vec![];
+                    for value in value.list()?.iter() {
+                        let item = filter_to_operation_value(&value, field_type)?;
+                        list_items.push(item);
+                    }
+                    filter.add_list_contains_all(&filter_field, &list_items);
                 }
                 "notContains" => {
                     filter.add_not_contains(&filter_field, value.string()?);
@@ -258,6 +589,7 @@ fn parse_meta_filter(filter: &mut Filter, filter_object: &ObjectAccessor) -> Res
         for (name, value) in filters.iter() {
             let field_type = match field.as_str() {
                 "edited" | "deleted" => FieldType::Boolean,
+                "receivedAt" | "updatedAt" => FieldType::Integer,
                 _ => FieldType::String,
             };
             match name.as_str() {
@@ -285,6 +617,22 @@ fn parse_meta_filter(filter: &mut Filter, filter_object: &ObjectAccessor) -> Res
                     let value = filter_to_operation_value(&value, &field_type)?;
                     filter.add_not(&filter_field, &value);
                 }
+                "gt" => {
+                    let value = filter_to_operation_value(&value, &field_type)?;
+                    filter.add_gt(&filter_field, &value);
+                }
+                "gte" => {
+                    let value = filter_to_operation_value(&value, &field_type)?;
+                    filter.add_gte(&filter_field, &value);
+                }
+                "lt" => {
+                    let value = filter_to_operation_value(&value, &field_type)?;
+                    filter.add_lt(&filter_field, &value);
+                }
+                "lte" => {
+                    let value = filter_to_operation_value(&value, &field_type)?;
+                    filter.add_lte(&filter_field, &value);
+                }
                 _ => panic!("Unknown meta filter type received"),
             }
         }
@@ -329,20 +677,34 @@ pub fn with_collection_arguments(
             )
             .description("Filter the query based on meta field values"),
         )
+        .argument(
+            InputValue::new(constants::SEARCH_ARG, TypeRef::named(TypeRef::STRING)).description(
+                "Full-text search over all string fields, only items containing every word are \
+                returned. Use `RELEVANCE` ordering to get the best matches first",
+            ),
+        )
         .argument(
             InputValue::new(
                 constants::ORDER_BY_ARG,
-                TypeRef::named(order_by_name(schema_id)),
+                TypeRef::named_nn_list(order_name(schema_id)),
+            )
+            .description(
+                "Fields by which items in the collection will be ordered, following fields are \
+                only considered when the values of all previous fields are equal",
             )
-            .description("Field by which items in the collection will be ordered")
-            .default_value("DOCUMENT_ID"),
+            .default_value(Value::List(vec![Value::Object(IndexMap::from([(
+                Name::new(constants::ORDER_FIELD_FIELD),
+                Value::Enum(Name::new("DOCUMENT_ID")),
+            )]))])),
         )
         .argument(
             InputValue::new(
                 constants::ORDER_DIRECTION_ARG,
                 TypeRef::named("OrderDirection"),
             )
-            .description("Direction which items in the collection will be ordered")
+            .description(
+                "Direction which items in the collection will be ordered, if not set for a field",
+            )
             .default_value("ASC"),
         )
         .argument(
@@ -357,12 +719,77 @@ pub fn with_collection_arguments(
             InputValue::new(constants::PAGINATION_AFTER_ARG, TypeRef::named("Cursor"))
                 .description("The item we wish to start paginating from identified by a cursor"),
         )
+        .argument(
+            InputValue::new(constants::PAGINATION_LAST_ARG, TypeRef::named(TypeRef::INT))
+                .description(
+                    "Number of paginated items we want from this request when paginating \
+                    backwards, can not be combined with `first` or `after`",
+                ),
+        )
+        .argument(
+            InputValue::new(constants::PAGINATION_BEFORE_ARG, TypeRef::named("Cursor"))
+                .description(
+                    "The item we wish to paginate backwards from identified by a cursor, \
+                    requires `last` to be set",
+                ),
+        )
         .description(format!(
             "Get all {} documents with pagination, ordering and filtering.",
             schema_id
         ))
 }
 
+/// Add filter arguments to a collection subscription field.
+pub fn with_filter_arguments(field: SubscriptionField, schema_id: &SchemaId) -> SubscriptionField {
+    field
+        .argument(
+            InputValue::new(
+                constants::FILTER_ARG,
+                TypeRef::named(filter_name(schema_id)),
+            )
+            .description("Filter the subscription based on field values"),
+        )
+        .argument(
+            InputValue::new(
+                constants::META_FILTER_ARG,
+                TypeRef::named("MetaFilterInputObject"),
+            )
+            .description("Filter the subscription based on meta field values"),
+        )
+        .description(format!(
+            "Subscribe to all {} documents matching the filters.",
+            schema_id
+        ))
+}
+
+/// Waits for the next document of the given schema being materialized on the node and returns its
+/// document id.
+///
+/// Returns `None` when the communication bus was closed.
+pub async fn next_materialized_document(
+    rx: &mut Receiver<ServiceMessage>,
+    schema_id: &SchemaId,
+) -> Option<DocumentId> {
+    loop {
+        match rx.recv().await {
+            Ok(ServiceMessage::DocumentMaterialized(materialized_schema_id, document_id))
+                if &materialized_schema_id == schema_id =>
+            {
+                return Some(document_id)
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(
+                    "Subscription missed {} messages on communication bus",
+                    skipped
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
 /// Helper method to extract selected pagination and application fields from query.
 pub fn look_ahead_selected_fields(ctx: &ResolverContext) -> (Vec<PaginationField>, Vec<Field>) {
     let selection_field = ctx
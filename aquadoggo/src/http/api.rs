@@ -1,40 +1,163 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use std::path::PathBuf;
+use std::io::SeekFrom;
+use std::ops::Bound;
 use std::str::FromStr;
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
-use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig, ALL_WEBSOCKET_PROTOCOLS};
+use async_graphql::Data;
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::body::StreamBody;
-use axum::extract::{Extension, Path};
-use axum::headers::{ETag, IfNoneMatch};
+use axum::extract::{Extension, Path, WebSocketUpgrade};
+use axum::headers::{
+    AcceptRanges, ContentLength, ContentRange, ETag, HeaderMapExt, IfNoneMatch, Range,
+};
 use axum::http::StatusCode;
 use axum::response::{self, IntoResponse, Response};
-use axum::TypedHeader;
-use http::header;
-use log::warn;
+use axum::{Json, TypedHeader};
+use http::{header, HeaderMap, HeaderValue};
 use p2panda_rs::document::traits::AsDocument;
 use p2panda_rs::document::{DocumentId, DocumentViewId};
+use p2panda_rs::operation::OperationValue;
 use p2panda_rs::schema::SchemaId;
 use p2panda_rs::storage_provider::traits::DocumentStore;
-use p2panda_rs::Human;
 use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_util::io::ReaderStream;
 
+use crate::auth::{authenticate, Access, AuthError};
+use crate::db::errors::BlobStoreError;
 use crate::http::context::HttpServiceContext;
+use crate::http::health::{check_health, HealthCheck};
+use crate::limits::ClientAddress;
+use crate::metrics::METRICS_CONTENT_TYPE;
 
 /// Handle GraphQL playground requests at the given path.
 pub async fn handle_graphql_playground(path: &str) -> impl IntoResponse {
-    response::Html(playground_source(GraphQLPlaygroundConfig::new(path)))
+    response::Html(playground_source(
+        GraphQLPlaygroundConfig::new(path).subscription_endpoint(path),
+    ))
+}
+
+/// Handle GraphQL subscriptions via the GraphQL-over-WebSocket protocol.
+///
+/// Requests which do not ask for a WebSocket connection are answered with the GraphQL playground
+/// instead.
+///
+/// As browsers can not set headers for WebSocket connections, clients can alternatively send their
+/// token with an "Authorization" field in the payload of the connection init message.
+///
+/// Messages exceeding the maximum request body size close the connection.
+pub async fn handle_graphql_subscription(
+    Extension(context): Extension<HttpServiceContext>,
+    access: Access,
+    client_address: ClientAddress,
+    protocol: Option<GraphQLProtocol>,
+    upgrade: Option<WebSocketUpgrade>,
+    path: &str,
+) -> Response {
+    match (protocol, upgrade) {
+        (Some(protocol), Some(upgrade)) => upgrade
+            .protocols(ALL_WEBSOCKET_PROTOCOLS)
+            .max_message_size(context.limits.max_request_body_size)
+            .on_upgrade(move |stream| {
+                let api_tokens = context.api_tokens;
+                let limits = context.limits;
+
+                GraphQLWebSocket::new(stream, context.schema, protocol)
+                    .on_connection_init(move |payload| async move {
+                        let access = match access {
+                            Access::Anonymous => authenticate(
+                                &api_tokens,
+                                payload
+                                    .get(header::AUTHORIZATION.as_str())
+                                    .or_else(|| payload.get("Authorization"))
+                                    .and_then(|value| value.as_str()),
+                            )?,
+                            access => access,
+                        };
+
+                        if let Access::Anonymous = access {
+                            return Err(AuthError::MissingToken.into());
+                        }
+
+                        let mut data = Data::default();
+                        data.insert(access);
+                        data.insert(limits);
+                        data.insert(client_address);
+                        Ok(data)
+                    })
+                    .serve()
+            })
+            .into_response(),
+        _ => handle_graphql_playground(path).await.into_response(),
+    }
 }
 
 /// Handle GraphQL requests.
+///
+/// Requests without a token are rejected when authentication is enabled, all others are checked
+/// against the scopes of the given token before they get executed. Published entries are checked
+/// against the rate limits of the client.
 pub async fn handle_graphql_query(
     Extension(context): Extension<HttpServiceContext>,
+    access: Access,
+    client_address: ClientAddress,
     req: GraphQLRequest,
-) -> GraphQLResponse {
-    context.schema.execute(req.into_inner()).await.into()
+) -> Result<GraphQLResponse, AuthError> {
+    if let Access::Anonymous = access {
+        return Err(AuthError::MissingToken);
+    }
+
+    let started_at = Instant::now();
+    let request = req
+        .into_inner()
+        .data(access)
+        .data(context.limits.clone())
+        .data(client_address);
+    let response = context.schema.execute(request).await;
+    context
+        .metrics
+        .graphql_request_completed(started_at.elapsed());
+
+    Ok(response.into())
+}
+
+/// Handle liveness checks, responds with "503 Service Unavailable" if a service stopped or failed
+/// or the database can not be reached.
+pub async fn handle_health(Extension(context): Extension<HttpServiceContext>) -> Response {
+    respond_with_health(&context, HealthCheck::Liveness).await
+}
+
+/// Handle readiness checks, responds with "503 Service Unavailable" until all services are ready,
+/// the database can be reached and no migration is running.
+pub async fn handle_ready(Extension(context): Extension<HttpServiceContext>) -> Response {
+    respond_with_health(&context, HealthCheck::Readiness).await
+}
+
+/// Returns HTTP response with a JSON report about the status of all services of the node.
+async fn respond_with_health(context: &HttpServiceContext, check: HealthCheck) -> Response {
+    let (passed, report) = check_health(context, check).await;
+
+    let status_code = if passed {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status_code, Json(report)).into_response()
+}
+
+/// Handle requests for metrics of the node in the Prometheus text-based exposition format.
+pub async fn handle_metrics(Extension(context): Extension<HttpServiceContext>) -> Response {
+    let body = context
+        .metrics
+        .encode(&context.status, &context.store)
+        .await;
+
+    ([(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)], body).into_response()
 }
 
 /// Handle requests for a blob document served via HTTP.
@@ -42,6 +165,7 @@ pub async fn handle_graphql_query(
 /// This method automatically returns the "latest" version of the document.
 pub async fn handle_blob_document(
     TypedHeader(if_none_match): TypedHeader<IfNoneMatch>,
+    range: Option<TypedHeader<Range>>,
     Extension(context): Extension<HttpServiceContext>,
     Path(document_id): Path<String>,
 ) -> Result<Response, BlobHttpError> {
@@ -60,7 +184,13 @@ pub async fn handle_blob_document(
         return Err(BlobHttpError::NotFound);
     }
 
-    respond_with_blob(if_none_match, context.blobs_base_path, document).await
+    respond_with_blob(
+        if_none_match,
+        range.map(|header| header.0),
+        context,
+        document,
+    )
+    .await
 }
 
 /// Handle requests for a blob document view served via HTTP.
@@ -68,6 +198,7 @@ pub async fn handle_blob_document(
 /// This method returns the version which was specified by the document view id.
 pub async fn handle_blob_view(
     TypedHeader(if_none_match): TypedHeader<IfNoneMatch>,
+    range: Option<TypedHeader<Range>>,
     Extension(context): Extension<HttpServiceContext>,
     Path((document_id, view_id)): Path<(String, String)>,
 ) -> Result<Response, BlobHttpError> {
@@ -87,15 +218,27 @@ pub async fn handle_blob_view(
         return Err(BlobHttpError::NotFound);
     }
 
-    respond_with_blob(if_none_match, context.blobs_base_path, document).await
+    respond_with_blob(
+        if_none_match,
+        range.map(|header| header.0),
+        context,
+        document,
+    )
+    .await
 }
 
 /// Returns HTTP response with the contents, ETag and given MIME type of a blob.
 ///
-/// Supports basic caching by handling "IfNoneMatch" headers matching the latest ETag.
+/// Supports basic caching by handling "IfNoneMatch" headers matching the latest ETag. Single byte
+/// ranges requested via "Range" headers are answered with "206 Partial Content", this allows
+/// browsers to seek in audio and video blobs.
+///
+/// The data is read from the materialized file on the file system. If the blob was not
+/// materialized yet it is read from its pieces in the database instead.
 async fn respond_with_blob(
     if_none_match: IfNoneMatch,
-    blobs_base_path: PathBuf,
+    range: Option<Range>,
+    context: HttpServiceContext,
     document: impl AsDocument,
 ) -> Result<Response, BlobHttpError> {
     let view_id = document.view_id();
@@ -113,39 +256,128 @@ async fn respond_with_blob(
 
     // Get MIME type of blob
     let mime_type_str = match document.get("mime_type") {
-        Some(p2panda_rs::operation::OperationValue::String(value)) => Ok(value),
+        Some(OperationValue::String(value)) => Ok(value),
         _ => Err(BlobHttpError::InternalError(anyhow!(
             "Blob document did not contain a valid 'mime_type' field"
         ))),
     }?;
 
-    // Get body from read-stream of stored file on file system
-    let mut file_path = blobs_base_path;
+    // Get length of blob, either from the stored file or as claimed by the document
+    let mut file_path = context.blobs_base_path;
     file_path.push(format!("{view_id}"));
-    match File::open(&file_path).await {
+    let (file, length) = match File::open(&file_path).await {
         Ok(file) => {
-            let headers = [
-                // MIME type to allow browsers to correctly handle this specific blob format
-                (header::CONTENT_TYPE, mime_type_str),
-                // ETag to allow browsers handle caching
-                (header::ETAG, &to_etag_str()),
-            ];
+            let metadata = file
+                .metadata()
+                .await
+                .map_err(|err| BlobHttpError::InternalError(err.into()))?;
+            (Some(file), metadata.len())
+        }
+        Err(_) => match document.get("length") {
+            Some(OperationValue::Integer(length)) => (None, *length as u64),
+            _ => {
+                return Err(BlobHttpError::InternalError(anyhow!(
+                    "Blob document did not contain a valid 'length' field"
+                )))
+            }
+        },
+    };
+
+    let mut headers = HeaderMap::new();
+    // MIME type to allow browsers to correctly handle this specific blob format
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(mime_type_str)
+            .map_err(|err| BlobHttpError::InternalError(err.into()))?,
+    );
+    // ETag to allow browsers handle caching
+    headers.typed_insert(etag);
+    // Inform browsers that they can request parts of the blob
+    headers.typed_insert(AcceptRanges::bytes());
+
+    let (status_code, start, end) = match byte_range(range, length) {
+        ByteRange::Full => (StatusCode::OK, 0, length),
+        ByteRange::Partial(start, end) => {
+            let content_range = ContentRange::bytes(start..end, length)
+                .map_err(|err| BlobHttpError::InternalError(err.into()))?;
+            headers.typed_insert(content_range);
+            (StatusCode::PARTIAL_CONTENT, start, end)
+        }
+        ByteRange::Unsatisfiable => {
+            headers.typed_insert(ContentRange::unsatisfied_bytes(length));
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response());
+        }
+    };
+    headers.typed_insert(ContentLength(end - start));
+
+    match file {
+        // Get body from read-stream of stored file on file system
+        Some(mut file) => {
+            file.seek(SeekFrom::Start(start))
+                .await
+                .map_err(|err| BlobHttpError::InternalError(err.into()))?;
+            let stream = ReaderStream::new(file.take(end - start));
+            Ok((status_code, headers, StreamBody::new(stream)).into_response())
+        }
+        // Blob was not materialized yet, get body from blob pieces in the database
+        None => {
+            let blob = context
+                .store
+                .get_blob_by_view_id(view_id)
+                .await
+                .map_err(|err| match err {
+                    BlobStoreError::MissingPieces => BlobHttpError::NotFound,
+                    err => BlobHttpError::InternalError(err.into()),
+                })?
+                .ok_or(BlobHttpError::NotFound)?;
+            let stream = blob.read_range(start as usize, end as usize);
+            Ok((status_code, headers, StreamBody::new(stream)).into_response())
+        }
+    }
+}
+
+/// Byte range of a blob which should be sent in a response.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// Send the whole blob.
+    Full,
 
-            let stream = ReaderStream::new(file);
-            let body = StreamBody::new(stream);
+    /// Send the blob data between the start and end position (end exclusive).
+    Partial(u64, u64),
+
+    /// Requested range lies outside of the blob.
+    Unsatisfiable,
+}
 
-            Ok((headers, body).into_response())
+/// Returns the byte range of a blob with the given length requested via the "Range" header.
+///
+/// Only single ranges are supported, requests for multiple ranges are answered with the whole
+/// blob as allowed by https://datatracker.ietf.org/doc/html/rfc7233#section-3.1
+fn byte_range(range: Option<Range>, length: u64) -> ByteRange {
+    let ranges: Vec<(Bound<u64>, Bound<u64>)> = match range {
+        Some(range) => range.iter().collect(),
+        None => return ByteRange::Full,
+    };
+
+    if ranges.len() != 1 {
+        return ByteRange::Full;
+    }
+
+    match ranges[0] {
+        // "bytes=<start>-<end>" and "bytes=<start>-"
+        (Bound::Included(start), end) if start < length => {
+            let end = match end {
+                Bound::Included(end) if end >= start => (end + 1).min(length),
+                Bound::Unbounded => length,
+                _ => return ByteRange::Unsatisfiable,
+            };
+            ByteRange::Partial(start, end)
         }
-        Err(_) => {
-            warn!(
-                "Data inconsistency detected: Blob document {} exists in database but not on file
-                system at path {}!",
-                view_id.display(),
-                file_path.display()
-            );
-
-            Err(BlobHttpError::NotFound)
+        // "bytes=-<suffix length>"
+        (Bound::Unbounded, Bound::Included(suffix)) if suffix > 0 && length > 0 => {
+            ByteRange::Partial(length - suffix.min(length), length)
         }
+        _ => ByteRange::Unsatisfiable,
     }
 }
 
@@ -178,7 +410,8 @@ impl IntoResponse for BlobHttpError {
 
 #[cfg(test)]
 mod tests {
-    use http::{header, StatusCode};
+    use axum::headers::{Header, Range};
+    use http::{header, HeaderValue, StatusCode};
     use p2panda_rs::document::DocumentId;
     use p2panda_rs::identity::KeyPair;
     use p2panda_rs::schema::validate::MAX_BLOB_PIECE_LENGTH;
@@ -189,6 +422,8 @@ mod tests {
     use crate::materializer::TaskInput;
     use crate::test_utils::{add_blob, http_test_client, test_runner, update_blob, TestNode};
 
+    use super::{byte_range, ByteRange};
+
     #[rstest]
     fn responds_with_blob_in_http_body(key_pair: KeyPair) {
         test_runner(|mut node: TestNode| async move {
@@ -398,4 +633,106 @@ mod tests {
             assert_eq!(response.status(), expected_status_code);
         })
     }
+
+    #[rstest]
+    fn responds_with_byte_ranges(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let blob_data = "Hello, World!".as_bytes();
+            let blob_view_id = add_blob(&mut node, blob_data, 6, "text/plain", &key_pair).await;
+            let document_id: DocumentId = blob_view_id.to_string().parse().unwrap();
+
+            let client = http_test_client(&node).await;
+
+            // Blob is read from the database as long as it was not materialized on the file
+            // system, afterwards it is read from the file
+            for materialized in [false, true] {
+                if materialized {
+                    blob_task(
+                        node.context.clone(),
+                        TaskInput::DocumentViewId(blob_view_id.clone()),
+                    )
+                    .await
+                    .unwrap();
+                }
+
+                // Whole blob
+                let response = client.get(&format!("/blobs/{}", document_id)).send().await;
+                let status_code = response.status();
+                let headers = response.headers();
+                assert_eq!(headers.get(header::ACCEPT_RANGES).unwrap(), "bytes");
+                assert_eq!(headers.get(header::CONTENT_LENGTH).unwrap(), "13");
+                assert_eq!(status_code, StatusCode::OK);
+                assert_eq!(response.text().await, "Hello, World!");
+
+                // Single range spanning multiple pieces
+                let response = client
+                    .get(&format!("/blobs/{}", document_id))
+                    .header(header::RANGE, "bytes=4-8")
+                    .send()
+                    .await;
+                let status_code = response.status();
+                let headers = response.headers();
+                assert_eq!(headers.get(header::CONTENT_RANGE).unwrap(), "bytes 4-8/13");
+                assert_eq!(headers.get(header::CONTENT_LENGTH).unwrap(), "5");
+                assert_eq!(status_code, StatusCode::PARTIAL_CONTENT);
+                assert_eq!(response.text().await, "o, Wo");
+
+                // Suffix range
+                let response = client
+                    .get(&format!("/blobs/{}/{}", document_id, blob_view_id))
+                    .header(header::RANGE, "bytes=-6")
+                    .send()
+                    .await;
+                assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+                assert_eq!(response.text().await, "World!");
+
+                // Range outside of the blob
+                let response = client
+                    .get(&format!("/blobs/{}", document_id))
+                    .header(header::RANGE, "bytes=13-")
+                    .send()
+                    .await;
+                let status_code = response.status();
+                let headers = response.headers();
+                assert_eq!(headers.get(header::CONTENT_RANGE).unwrap(), "bytes */13");
+                assert_eq!(status_code, StatusCode::RANGE_NOT_SATISFIABLE);
+            }
+        })
+    }
+
+    #[rstest]
+    fn responds_to_head_requests(key_pair: KeyPair) {
+        test_runner(|mut node: TestNode| async move {
+            let blob_data = "Hello, World!".as_bytes();
+            let blob_view_id = add_blob(&mut node, blob_data, 6, "text/plain", &key_pair).await;
+            let document_id: DocumentId = blob_view_id.to_string().parse().unwrap();
+
+            let client = http_test_client(&node).await;
+            let response = client.head(&format!("/blobs/{}", document_id)).send().await;
+            let status_code = response.status();
+            let headers = response.headers();
+            assert_eq!(headers.get(header::CONTENT_TYPE).unwrap(), "text/plain");
+            assert_eq!(headers.get(header::CONTENT_LENGTH).unwrap(), "13");
+            assert_eq!(headers.get(header::ACCEPT_RANGES).unwrap(), "bytes");
+            assert!(headers.get(header::ETAG).is_some());
+            assert_eq!(status_code, StatusCode::OK);
+            assert_eq!(response.text().await, "");
+        })
+    }
+
+    #[rstest]
+    #[case::no_range(None, ByteRange::Full)]
+    #[case::multiple_ranges(Some("bytes=0-1,4-5"), ByteRange::Full)]
+    #[case::from_to(Some("bytes=2-4"), ByteRange::Partial(2, 5))]
+    #[case::end_beyond_length(Some("bytes=8-20"), ByteRange::Partial(8, 10))]
+    #[case::open_end(Some("bytes=3-"), ByteRange::Partial(3, 10))]
+    #[case::suffix(Some("bytes=-3"), ByteRange::Partial(7, 10))]
+    #[case::start_beyond_length(Some("bytes=10-"), ByteRange::Unsatisfiable)]
+    #[case::end_before_start(Some("bytes=4-2"), ByteRange::Unsatisfiable)]
+    fn byte_ranges(#[case] range: Option<&str>, #[case] expected: ByteRange) {
+        let range = range.map(|value| {
+            Range::decode(&mut std::iter::once(&HeaderValue::from_str(value).unwrap())).unwrap()
+        });
+        assert_eq!(byte_range(range, 10), expected);
+    }
 }
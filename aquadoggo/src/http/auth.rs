@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+
+use crate::auth::{authenticate, Access, AuthError};
+use crate::config::ApiScope;
+use crate::http::context::HttpServiceContext;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Access
+where
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    /// Authenticates the client with the bearer token from its "Authorization" header.
+    ///
+    /// Requests with an invalid token get rejected right away, requests without any token are
+    /// treated as anonymous.
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let context = parts
+            .extensions
+            .get::<HttpServiceContext>()
+            .expect("HTTP service context needs to be added as an extension");
+
+        let authorization = parts
+            .headers
+            .get(AUTHORIZATION)
+            .map(|value| value.to_str().map_err(|_| AuthError::InvalidToken))
+            .transpose()?;
+
+        authenticate(&context.api_tokens, authorization)
+    }
+}
+
+/// Middleware rejecting all requests of clients which were not granted the "read" scope.
+pub async fn require_read_access<B>(
+    access: Access,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, AuthError> {
+    access.require(ApiScope::Read)?;
+    Ok(next.run(request).await)
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken => (
+                StatusCode::UNAUTHORIZED,
+                [(WWW_AUTHENTICATE, "Bearer")],
+                self.to_string(),
+            )
+                .into_response(),
+            AuthError::Forbidden | AuthError::PublishForbidden(_, _) => {
+                (StatusCode::FORBIDDEN, self.to_string()).into_response()
+            }
+        }
+    }
+}
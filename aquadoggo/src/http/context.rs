@@ -2,8 +2,12 @@
 
 use std::path::PathBuf;
 
+use crate::config::ApiToken;
 use crate::db::SqlStore;
 use crate::graphql::GraphQLSchemaManager;
+use crate::limits::Limits;
+use crate::metrics::Metrics;
+use crate::status::NodeStatus;
 
 #[derive(Clone)]
 pub struct HttpServiceContext {
@@ -15,14 +19,38 @@ pub struct HttpServiceContext {
 
     /// Path of the directory where blobs should be served from.
     pub blobs_base_path: PathBuf,
+
+    /// Status of the running node.
+    pub status: NodeStatus,
+
+    /// Metrics of the running node.
+    pub metrics: Metrics,
+
+    /// Tokens which give clients access to the API, authentication is disabled when empty.
+    pub api_tokens: Vec<ApiToken>,
+
+    /// Rate and size limits for requests of API clients.
+    pub limits: Limits,
 }
 
 impl HttpServiceContext {
-    pub fn new(store: SqlStore, schema: GraphQLSchemaManager, blobs_base_path: PathBuf) -> Self {
+    pub fn new(
+        store: SqlStore,
+        schema: GraphQLSchemaManager,
+        blobs_base_path: PathBuf,
+        status: NodeStatus,
+        metrics: Metrics,
+        api_tokens: Vec<ApiToken>,
+        limits: Limits,
+    ) -> Self {
         Self {
             store,
             schema,
             blobs_base_path,
+            status,
+            metrics,
+            api_tokens,
+            limits,
         }
     }
 }
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::query;
+use tokio::time::timeout;
+
+use crate::http::context::HttpServiceContext;
+use crate::manager::ServiceState;
+
+/// Maximum time we wait for the database to respond during a health check.
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks which can be run against the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthCheck {
+    /// Passes as long as none of the services stopped or failed and the database responds.
+    Liveness,
+
+    /// Passes when additionally all services signalled that they are ready and no migration is
+    /// running.
+    Readiness,
+}
+
+/// Status of the node and all its services, reported by the health and readiness endpoints.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// "ok" if the check passed, otherwise "unavailable".
+    pub status: &'static str,
+
+    /// State of every service, for example "ready" or "failed".
+    pub services: BTreeMap<String, String>,
+
+    /// "ok" if the database responded, otherwise "unreachable".
+    pub database: &'static str,
+
+    /// Flag indicating if a migration from a lock file is currently running.
+    pub migration_running: bool,
+}
+
+/// Runs a health check against the node, returns true if it passed next to a detailed report.
+pub async fn check_health(
+    context: &HttpServiceContext,
+    check: HealthCheck,
+) -> (bool, HealthReport) {
+    let services = context.status.services().all();
+    let migration_running = context.status.is_migration_running();
+    let database_reachable = matches!(
+        timeout(
+            DATABASE_TIMEOUT,
+            query("SELECT 1").execute(&context.store.pool)
+        )
+        .await,
+        Ok(Ok(_))
+    );
+
+    let is_alive = database_reachable
+        && services
+            .iter()
+            .all(|(_, state)| !matches!(state, ServiceState::Stopped | ServiceState::Failed));
+
+    let passed = match check {
+        HealthCheck::Liveness => is_alive,
+        HealthCheck::Readiness => {
+            is_alive
+                && !migration_running
+                && services
+                    .iter()
+                    .all(|(_, state)| state == &ServiceState::Ready)
+        }
+    };
+
+    let report = HealthReport {
+        status: if passed { "ok" } else { "unavailable" },
+        services: services
+            .iter()
+            .map(|(name, state)| (name.to_string(), state.as_str().to_string()))
+            .collect(),
+        database: if database_reachable {
+            "ok"
+        } else {
+            "unreachable"
+        },
+        migration_running,
+    };
+
+    (passed, report)
+}
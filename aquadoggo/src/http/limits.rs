@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::convert::Infallible;
+
+use async_graphql::{ErrorExtensions, ServerError};
+use async_graphql_axum::GraphQLResponse;
+use axum::async_trait;
+use axum::body::{Body, HttpBody};
+use axum::extract::connect_info::{ConnectInfo, Connected};
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::header::CONTENT_LENGTH;
+use hyper::server::conn::AddrStream;
+use tokio::net::TcpStream;
+use tokio_rustls::server::TlsStream;
+
+use crate::http::context::HttpServiceContext;
+use crate::limits::{ClientAddress, LimitError};
+
+impl Connected<&AddrStream> for ClientAddress {
+    fn connect_info(stream: &AddrStream) -> Self {
+        Self(Some(stream.remote_addr().ip()))
+    }
+}
+
+impl Connected<&TlsStream<TcpStream>> for ClientAddress {
+    fn connect_info(stream: &TlsStream<TcpStream>) -> Self {
+        let (tcp_stream, _) = stream.get_ref();
+        Self(tcp_stream.peer_addr().ok().map(|address| address.ip()))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientAddress
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    /// Returns the IP address of the client, if the server was started with connection info.
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<ConnectInfo<ClientAddress>>()
+            .map(|ConnectInfo(client_address)| *client_address)
+            .unwrap_or(ClientAddress(None)))
+    }
+}
+
+/// Middleware rejecting requests with bodies exceeding the configured maximum size.
+///
+/// The body gets buffered up to the limit, requests without or with a wrong "Content-Length"
+/// header are rejected as soon as they exceed it.
+pub async fn limit_request_body(
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response, Response> {
+    let context = request
+        .extensions()
+        .get::<HttpServiceContext>()
+        .expect("HTTP service context needs to be added as an extension")
+        .clone();
+
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    if let Some(content_length) = content_length {
+        context
+            .limits
+            .check_request_body(content_length)
+            .map_err(IntoResponse::into_response)?;
+    }
+
+    let (parts, mut body) = request.into_parts();
+    let mut buffer = Vec::with_capacity(content_length.unwrap_or_default());
+
+    while let Some(chunk) = body.data().await {
+        let chunk =
+            chunk.map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()).into_response())?;
+        buffer.extend_from_slice(&chunk);
+
+        context
+            .limits
+            .check_request_body(buffer.len())
+            .map_err(IntoResponse::into_response)?;
+    }
+
+    Ok(next
+        .run(Request::from_parts(parts, Body::from(buffer)))
+        .await)
+}
+
+impl IntoResponse for LimitError {
+    /// Responds with a GraphQL error containing the identifier of the exceeded limit.
+    fn into_response(self) -> Response {
+        let status_code = match self {
+            LimitError::RequestBodyTooLarge(_)
+            | LimitError::OperationTooLarge(_)
+            | LimitError::BatchTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            LimitError::TooManyRequestsFromClient | LimitError::TooManyRequestsFromAuthor => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+        };
+
+        let mut error = ServerError::new(self.to_string(), None);
+        error.extensions = self.extend().extensions;
+
+        let response = async_graphql::Response::from_errors(vec![error]);
+        (status_code, GraphQLResponse::from(response)).into_response()
+    }
+}
@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 mod api;
+mod auth;
 mod context;
+mod health;
+mod limits;
 mod service;
+mod tls;
 
 #[cfg(test)]
 pub use context::HttpServiceContext;
@@ -1,47 +1,108 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{SocketAddr, TcpListener};
 
-use anyhow::Result;
-use axum::extract::Extension;
+use anyhow::{anyhow, Result};
+use async_graphql_axum::GraphQLProtocol;
+use axum::extract::{Extension, WebSocketUpgrade};
+use axum::handler::Handler;
 use axum::http::Method;
+use axum::middleware;
 use axum::routing::get;
 use axum::Router;
-use http::header::CONTENT_TYPE;
+use http::header::{AUTHORIZATION, CONTENT_TYPE};
+use http::HeaderValue;
 use log::{debug, warn};
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
+use crate::auth::Access;
 use crate::bus::ServiceSender;
+use crate::config::AllowList;
 use crate::context::Context;
 use crate::graphql::GraphQLSchemaManager;
 use crate::http::api::{
-    handle_blob_document, handle_blob_view, handle_graphql_playground, handle_graphql_query,
+    handle_blob_document, handle_blob_view, handle_graphql_query, handle_graphql_subscription,
+    handle_health, handle_metrics, handle_ready,
 };
+use crate::http::auth::require_read_access;
 use crate::http::context::HttpServiceContext;
+use crate::http::limits::limit_request_body;
+use crate::http::tls::{tls_acceptor, tls_incoming};
 use crate::info_or_print;
+use crate::limits::ClientAddress;
 use crate::manager::{ServiceReadySender, Shutdown};
 
-/// Route to the GraphQL playground
+/// Route to the GraphQL playground, API and subscriptions
 const GRAPHQL_ROUTE: &str = "/graphql";
 
+/// Route to the Prometheus metrics of the node
+const METRICS_ROUTE: &str = "/metrics";
+
+/// Route to the liveness check of the node
+const HEALTH_ROUTE: &str = "/health";
+
+/// Route to the readiness check of the node
+const READY_ROUTE: &str = "/ready";
+
 /// Build HTTP server with GraphQL API.
-pub fn build_server(http_context: HttpServiceContext) -> Router {
+///
+/// Cross-origin requests are only allowed from the given origins.
+pub fn build_server(http_context: HttpServiceContext, allow_origins: &AllowList<String>) -> Router {
+    let allow_origin = match allow_origins {
+        AllowList::Wildcard => AllowOrigin::any(),
+        AllowList::Set(origins) => AllowOrigin::list(origins.iter().filter_map(|origin| {
+            match HeaderValue::from_str(origin) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    warn!("Ignoring invalid origin '{}' in CORS allow list", origin);
+                    None
+                }
+            }
+        })),
+    };
+
     // Configure CORS middleware
     let cors = CorsLayer::new()
         .allow_methods(vec![Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([CONTENT_TYPE])
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE])
         .allow_credentials(false)
-        .allow_origin(Any);
+        .allow_origin(allow_origin);
 
     Router::new()
         // Add GraphQL routes
         .route(
             GRAPHQL_ROUTE,
-            get(|| handle_graphql_playground(GRAPHQL_ROUTE)).post(handle_graphql_query),
+            get(
+                |context: Extension<HttpServiceContext>,
+                 access: Access,
+                 client_address: ClientAddress,
+                 protocol: Option<GraphQLProtocol>,
+                 upgrade: Option<WebSocketUpgrade>| {
+                    handle_graphql_subscription(
+                        context,
+                        access,
+                        client_address,
+                        protocol,
+                        upgrade,
+                        GRAPHQL_ROUTE,
+                    )
+                },
+            )
+            .post(handle_graphql_query.layer(middleware::from_fn(limit_request_body))),
         )
-        // Add blob routes
-        .route("/blobs/:document_id", get(handle_blob_document))
-        .route("/blobs/:document_id/:view_hash", get(handle_blob_view))
+        // Add routes which require the "read" scope
+        .merge(
+            Router::new()
+                // Add blob routes
+                .route("/blobs/:document_id", get(handle_blob_document))
+                .route("/blobs/:document_id/:view_hash", get(handle_blob_view))
+                // Add metrics route
+                .route(METRICS_ROUTE, get(handle_metrics))
+                .route_layer(middleware::from_fn(require_read_access)),
+        )
+        // Add health check routes
+        .route(HEALTH_ROUTE, get(handle_health))
+        .route(READY_ROUTE, get(handle_ready))
         // Add middlewares
         .layer(cors)
         // Add shared context
@@ -55,12 +116,16 @@ pub async fn http_service(
     tx: ServiceSender,
     tx_ready: ServiceReadySender,
 ) -> Result<()> {
-    let http_port = context.config.http_port;
-    let http_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), http_port);
+    let http_address = SocketAddr::new(context.config.http_bind_address, context.config.http_port);
 
     // Prepare GraphQL manager executing incoming GraphQL queries via HTTP
-    let graphql_schema_manager =
-        GraphQLSchemaManager::new(context.store.clone(), tx, context.schema_provider.clone()).await;
+    let graphql_schema_manager = GraphQLSchemaManager::new(
+        context.store.clone(),
+        tx,
+        context.schema_provider.clone(),
+        context.status.clone(),
+    )
+    .await;
 
     let blobs_base_path = &context.config.blobs_base_path;
 
@@ -69,66 +134,138 @@ pub async fn http_service(
         context.store.clone(),
         graphql_schema_manager,
         blobs_base_path.to_owned(),
+        context.status.clone(),
+        context.metrics.clone(),
+        context.config.api_tokens.clone(),
+        context.limits.clone(),
     );
 
-    // Start HTTP server with given port and re-attempt with random port if it was taken already
-    let builder = if let Ok(builder) = axum::Server::try_bind(&http_address) {
-        builder
-    } else {
-        info_or_print(&format!(
-            "HTTP port {http_port} was already taken, try random port instead .."
-        ));
-        axum::Server::try_bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0))?
-    };
+    // Load certificate and private key first to fail early when they are invalid
+    let acceptor = context
+        .config
+        .http_tls
+        .as_ref()
+        .map(tls_acceptor)
+        .transpose()?;
 
-    let builder = builder.serve(build_server(http_context).into_make_service());
+    let listener = bind_listener(http_address, context.config.http_strict_port)?;
+    let local_address = listener.local_addr()?;
 
-    let local_address = builder.local_addr();
-    info_or_print(&format!(
-        "Go to http://{}/graphql to use GraphQL playground",
-        local_address
-    ));
+    let app = build_server(http_context, &context.config.http_allow_origins)
+        .into_make_service_with_connect_info::<ClientAddress>();
 
-    builder
-        .with_graceful_shutdown(async {
-            debug!("HTTP service is ready");
-            if tx_ready.send(()).is_err() {
-                warn!("No subscriber informed about HTTP service being ready");
-            };
+    let shutdown = async {
+        debug!("HTTP service is ready");
+        if tx_ready.send(()).is_err() {
+            warn!("No subscriber informed about HTTP service being ready");
+        };
 
-            signal.await.ok();
-        })
-        .await?;
+        signal.await.ok();
+    };
+
+    match acceptor {
+        Some(acceptor) => {
+            info_or_print(&format!(
+                "Go to https://{}/graphql to use GraphQL playground",
+                local_address
+            ));
+
+            let incoming = tls_incoming(tokio::net::TcpListener::from_std(listener)?, acceptor);
+            axum::Server::builder(incoming)
+                .serve(app)
+                .with_graceful_shutdown(shutdown)
+                .await?;
+        }
+        None => {
+            info_or_print(&format!(
+                "Go to http://{}/graphql to use GraphQL playground",
+                local_address
+            ));
+
+            axum::Server::from_tcp(listener)?
+                .serve(app)
+                .with_graceful_shutdown(shutdown)
+                .await?;
+        }
+    }
 
     Ok(())
 }
 
+/// Binds a TCP listener to the given address.
+///
+/// Re-attempts with a random port if the given one was taken already, unless strict mode is
+/// enabled.
+fn bind_listener(address: SocketAddr, strict_port: bool) -> Result<TcpListener> {
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) if strict_port => {
+            return Err(anyhow!(
+                "Could not bind HTTP server to {}: {}",
+                address,
+                err
+            ))
+        }
+        Err(_) => {
+            info_or_print(&format!(
+                "HTTP port {} was already taken, try random port instead ..",
+                address.port()
+            ));
+            TcpListener::bind(SocketAddr::new(address.ip(), 0))?
+        }
+    };
+
+    // Required by tokio and hyper which take over the listener
+    listener.set_nonblocking(true)?;
+
+    Ok(listener)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::net::{Ipv6Addr, SocketAddr, TcpListener};
+
+    use http::header::{
+        ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, CONTENT_TYPE, ORIGIN, WWW_AUTHENTICATE,
+    };
+    use http::StatusCode;
     use serde_json::json;
     use tokio::sync::broadcast;
 
+    use crate::config::{AllowList, ApiScope, ApiToken, Configuration};
     use crate::graphql::GraphQLSchemaManager;
     use crate::http::context::HttpServiceContext;
+    use crate::limits::Limits;
+    use crate::manager::{ServiceManager, ServiceReadySender, Shutdown};
+    use crate::metrics::METRICS_CONTENT_TYPE;
     use crate::schema::SchemaProvider;
     use crate::test_utils::TestClient;
-    use crate::test_utils::{test_runner, TestNode};
+    use crate::test_utils::{configured_http_test_client, http_test_client, test_runner, TestNode};
 
-    use super::build_server;
+    use super::{bind_listener, build_server};
 
     #[test]
     fn graphql_endpoint() {
         test_runner(|node: TestNode| async move {
             let (tx, _) = broadcast::channel(120);
             let schema_provider = SchemaProvider::default();
-            let graphql_schema_manager =
-                GraphQLSchemaManager::new(node.context.store.clone(), tx, schema_provider).await;
+            let graphql_schema_manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx,
+                schema_provider,
+                node.context.status.clone(),
+            )
+            .await;
             let context = HttpServiceContext::new(
                 node.context.store.clone(),
                 graphql_schema_manager,
                 node.context.config.blobs_base_path.clone(),
+                node.context.status.clone(),
+                node.context.metrics.clone(),
+                node.context.config.api_tokens.clone(),
+                node.context.limits.clone(),
             );
-            let client = TestClient::new(build_server(context));
+            let client = TestClient::new(build_server(context, &AllowList::Wildcard));
 
             let response = client
                 .post("/graphql")
@@ -151,4 +288,307 @@ mod tests {
             );
         })
     }
+
+    #[test]
+    fn metrics_endpoint() {
+        test_runner(|node: TestNode| async move {
+            let client = http_test_client(&node).await;
+
+            client
+                .post("/graphql")
+                .json(&json!({
+                    "query": "{ __schema { __typename } }",
+                }))
+                .send()
+                .await;
+
+            let response = client.get("/metrics").send().await;
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .expect("ContentType to exist in header")
+                .to_owned();
+            let body = response.text().await;
+
+            assert_eq!(content_type, METRICS_CONTENT_TYPE);
+            assert!(body
+                .lines()
+                .any(|line| line == "aquadoggo_graphql_request_duration_seconds_count 1"));
+            assert!(body
+                .lines()
+                .any(|line| line.starts_with("aquadoggo_database_pool_connections ")));
+        })
+    }
+
+    #[test]
+    fn health_endpoints() {
+        test_runner(|node: TestNode| async move {
+            let client = http_test_client(&node).await;
+            let mut manager =
+                ServiceManager::<usize, usize>::new(120, 0, node.context.status.services().clone());
+
+            let service_ready = manager.add(
+                "http",
+                |_, signal: Shutdown, _, tx_ready: ServiceReadySender| async {
+                    tx_ready.send(()).unwrap();
+                    let _ = signal.await;
+                    Ok(())
+                },
+            );
+            assert!(service_ready.await.is_ok());
+
+            // All services are ready
+            let response = client.get("/ready").send().await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.json::<serde_json::Value>().await,
+                json!({
+                    "status": "ok",
+                    "services": { "http": "ready" },
+                    "database": "ok",
+                    "migrationRunning": false,
+                })
+            );
+
+            // Node is not ready while a migration is running
+            node.context.status.set_migration_running(true);
+            let response = client.get("/ready").send().await;
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let response = client.get("/health").send().await;
+            assert_eq!(response.status(), StatusCode::OK);
+            node.context.status.set_migration_running(false);
+
+            // Node is not ready while a service did not signal yet that it is ready
+            manager.add("network", |_, signal: Shutdown, _, _| async {
+                let _ = signal.await;
+                Ok(())
+            });
+            let response = client.get("/ready").send().await;
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let response = client.get("/health").send().await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Node is not alive anymore when a service failed
+            manager.add("replication", |_, _, _, _| async {
+                Err(anyhow::anyhow!("This went wrong"))
+            });
+            manager.on_exit().await;
+            let response = client.get("/health").send().await;
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(
+                response.json::<serde_json::Value>().await,
+                json!({
+                    "status": "unavailable",
+                    "services": {
+                        "http": "ready",
+                        "network": "starting",
+                        "replication": "failed",
+                    },
+                    "database": "ok",
+                    "migrationRunning": false,
+                })
+            );
+
+            manager.shutdown().await;
+        })
+    }
+
+    #[test]
+    fn api_authentication() {
+        test_runner(|node: TestNode| async move {
+            let api_token = |token: &str, scopes| ApiToken {
+                token: token.into(),
+                scopes,
+                publish_schema_ids: AllowList::Wildcard,
+                publish_public_keys: AllowList::Wildcard,
+            };
+            let client = configured_http_test_client(
+                &node,
+                vec![
+                    api_token("reader", vec![ApiScope::Read]),
+                    api_token("publisher", vec![ApiScope::Publish]),
+                ],
+                node.context.limits.clone(),
+                &AllowList::Wildcard,
+            )
+            .await;
+
+            let query = |authorization: Option<&str>, query: &str| {
+                let mut request = client.post("/graphql").json(&json!({ "query": query }));
+                if let Some(authorization) = authorization {
+                    request = request.header(AUTHORIZATION, authorization);
+                }
+                request.send()
+            };
+
+            // Requests without or with an invalid token are rejected
+            let response = query(None, "{ node { peerId } }").await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+            let response = query(Some("Bearer unknown"), "{ node { peerId } }").await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+            // Tokens can only be used for requests matching their scopes
+            let response: serde_json::Value = query(Some("Bearer reader"), "{ node { peerId } }")
+                .await
+                .json()
+                .await;
+            assert!(response.get("errors").is_none());
+
+            let response: serde_json::Value =
+                query(Some("Bearer publisher"), "{ node { peerId } }")
+                    .await
+                    .json()
+                    .await;
+            assert_eq!(
+                response["errors"][0]["message"],
+                "Token is not allowed to perform this action"
+            );
+
+            let response: serde_json::Value = query(
+                Some("Bearer reader"),
+                r#"mutation { publish(entry: "00", operation: "00") { logId } }"#,
+            )
+            .await
+            .json()
+            .await;
+            assert_eq!(
+                response["errors"][0]["message"],
+                "Token is not allowed to perform this action"
+            );
+
+            // Introspection is allowed for every token
+            let response: serde_json::Value =
+                query(Some("Bearer publisher"), "{ __schema { __typename } }")
+                    .await
+                    .json()
+                    .await;
+            assert!(response.get("errors").is_none());
+
+            // Metrics and blobs require the "read" scope
+            let response = client.get("/metrics").send().await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            let response = client
+                .get("/metrics")
+                .header(AUTHORIZATION, "Bearer publisher")
+                .send()
+                .await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            let response = client
+                .get("/metrics")
+                .header(AUTHORIZATION, "Bearer reader")
+                .send()
+                .await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Health checks and the GraphQL playground stay accessible
+            let response = client.get("/health").send().await;
+            assert_ne!(response.status(), StatusCode::UNAUTHORIZED);
+            let response = client.get("/graphql").send().await;
+            assert_eq!(response.status(), StatusCode::OK);
+        })
+    }
+
+    #[test]
+    fn strict_port() {
+        let address = SocketAddr::from((Ipv6Addr::LOCALHOST, 0));
+        let taken = TcpListener::bind(address).unwrap();
+        let taken_address = taken.local_addr().unwrap();
+
+        // Fail when port is taken in strict mode
+        assert!(bind_listener(taken_address, true).is_err());
+
+        // Otherwise fall back to a random port on the same interface
+        let listener = bind_listener(taken_address, false).unwrap();
+        let local_address = listener.local_addr().unwrap();
+        assert_eq!(local_address.ip(), taken_address.ip());
+        assert_ne!(local_address.port(), taken_address.port());
+    }
+
+    #[test]
+    fn cors_allow_origins() {
+        test_runner(|node: TestNode| async move {
+            let client = configured_http_test_client(
+                &node,
+                node.context.config.api_tokens.clone(),
+                node.context.limits.clone(),
+                &AllowList::Set(vec!["https://example.com".into()]),
+            )
+            .await;
+
+            let response = client
+                .get("/health")
+                .header(ORIGIN, "https://example.com")
+                .send()
+                .await;
+            assert_eq!(
+                response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+                "https://example.com"
+            );
+
+            let response = client
+                .get("/health")
+                .header(ORIGIN, "https://example.org")
+                .send()
+                .await;
+            assert!(response
+                .headers()
+                .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+                .is_none());
+        })
+    }
+
+    #[test]
+    fn request_body_limit() {
+        test_runner(|node: TestNode| async move {
+            let limits = Limits::new(
+                &Configuration {
+                    max_request_body_size: 64,
+                    ..Configuration::default()
+                },
+                node.context.metrics.clone(),
+            );
+            let client = configured_http_test_client(
+                &node,
+                node.context.config.api_tokens.clone(),
+                limits,
+                &AllowList::Wildcard,
+            )
+            .await;
+
+            let response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": "{ __schema { __typename } }",
+                }))
+                .send()
+                .await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let response = client
+                .post("/graphql")
+                .json(&json!({
+                    "query": format!("{{ __schema {{ __typename }} }} # {}", "a".repeat(64)),
+                }))
+                .send()
+                .await;
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+            assert_eq!(
+                response.json::<serde_json::Value>().await,
+                json!({
+                    "data": null,
+                    "errors": [{
+                        "message": "Request body exceeds maximum size of 64 bytes",
+                        "extensions": {
+                            "code": "REQUEST_BODY_TOO_LARGE"
+                        }
+                    }]
+                })
+            );
+
+            let response = client.get("/metrics").send().await;
+            assert!(response.text().await.lines().any(|line| line
+                == "aquadoggo_limits_exceeded_total{limit=\"REQUEST_BODY_TOO_LARGE\"} 1"));
+        })
+    }
 }
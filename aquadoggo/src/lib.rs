@@ -13,20 +13,24 @@
 )]
 #![allow(clippy::uninlined_format_args)]
 mod api;
+mod auth;
 mod bus;
 mod config;
 mod context;
 mod db;
 mod graphql;
 mod http;
+mod limits;
 mod manager;
 mod materializer;
+mod metrics;
 mod network;
 mod node;
 #[cfg(all(test, feature = "proptests"))]
 mod proptests;
 mod replication;
 mod schema;
+mod status;
 #[cfg(test)]
 mod test_utils;
 #[cfg(test)]
@@ -34,8 +38,8 @@ mod tests;
 
 use log::{info, log_enabled, Level};
 
-pub use crate::api::{ConfigFile, LockFile, NodeEvent};
-pub use crate::config::{AllowList, Configuration};
+pub use crate::api::{ConfigFile, LockFile, NodeEvent, UncheckedApiToken};
+pub use crate::config::{AllowList, ApiScope, ApiToken, Configuration, TlsConfiguration};
 pub use crate::network::{NetworkConfiguration, Transport};
 pub use node::Node;
 
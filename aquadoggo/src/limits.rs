@@ -0,0 +1,529 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Limits protecting the node against clients flooding it with requests or large payloads.
+//!
+//! Publishing entries is rate limited per client IP address and per author with token buckets,
+//! every bucket holds as many tokens as entries are allowed per minute and refills continuously.
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_graphql::{Error, ErrorExtensions};
+use p2panda_rs::identity::PublicKey;
+use thiserror::Error;
+
+use crate::config::Configuration;
+use crate::metrics::Metrics;
+
+/// Time it takes for an empty bucket to fill up completely again.
+const REFILL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of tracked clients, the buckets of the least recently seen ones get removed
+/// first.
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    /// Number of requests which can still be made.
+    tokens: f64,
+
+    /// Time when the tokens were last updated.
+    updated_at: Instant,
+
+    /// Value of the usage counter when the tokens were last updated.
+    last_used: u64,
+}
+
+#[derive(Debug)]
+struct Buckets<K> {
+    entries: HashMap<K, Bucket>,
+
+    /// Keys ordered by the usage counter of their buckets, used to find the least recently used
+    /// bucket.
+    usage: BTreeMap<u64, K>,
+
+    /// Counter increased every time a bucket gets updated.
+    tick: u64,
+}
+
+/// Limits the number of requests per minute for every key, for example IP addresses.
+#[derive(Debug, Clone)]
+pub struct RateLimiter<K> {
+    /// Maximum number of requests per minute, 0 disables the limit.
+    limit: u32,
+
+    /// Maximum number of keys for which buckets are kept.
+    max_keys: usize,
+
+    buckets: Arc<Mutex<Buckets<K>>>,
+}
+
+impl<K> RateLimiter<K>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Returns a new rate limiter allowing the given number of requests per minute.
+    pub fn new(limit: u32) -> Self {
+        Self::with_max_keys(limit, MAX_TRACKED_KEYS)
+    }
+
+    fn with_max_keys(limit: u32, max_keys: usize) -> Self {
+        Self {
+            limit,
+            max_keys,
+            buckets: Arc::new(Mutex::new(Buckets {
+                entries: HashMap::new(),
+                usage: BTreeMap::new(),
+                tick: 0,
+            })),
+        }
+    }
+
+    /// Takes the given number of tokens for this key, returns false when not enough of them are
+    /// left.
+    ///
+    /// Checking and taking the tokens happens at once, concurrent requests can never take more
+    /// tokens than the bucket holds.
+    pub fn try_consume(&self, key: &K, count: u32) -> bool {
+        self.try_consume_at(key, count, Instant::now())
+    }
+
+    /// Gives back tokens which were taken for requests which did not go through after all.
+    pub fn refund(&self, key: &K, count: u32) {
+        self.refund_at(key, count, Instant::now())
+    }
+
+    fn try_consume_at(&self, key: &K, count: u32, now: Instant) -> bool {
+        if self.limit == 0 {
+            return true;
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let tokens = self.tokens(&buckets, key, now);
+        let allowed = tokens >= f64::from(count);
+
+        // Rejected keys are remembered as well, otherwise clients could get rid of their empty
+        // buckets by flooding the node
+        let tokens = if allowed {
+            tokens - f64::from(count)
+        } else {
+            tokens
+        };
+        self.set_tokens(&mut buckets, key, tokens, now);
+
+        allowed
+    }
+
+    fn refund_at(&self, key: &K, count: u32, now: Instant) {
+        if self.limit == 0 {
+            return;
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let tokens =
+            (self.tokens(&buckets, key, now) + f64::from(count)).min(f64::from(self.limit));
+        self.set_tokens(&mut buckets, key, tokens, now);
+    }
+
+    /// Returns the number of tokens left for this key at the given time.
+    fn tokens(&self, buckets: &Buckets<K>, key: &K, now: Instant) -> f64 {
+        match buckets.entries.get(key) {
+            Some(bucket) => self.refill(bucket, now),
+            None => f64::from(self.limit),
+        }
+    }
+
+    fn set_tokens(&self, buckets: &mut Buckets<K>, key: &K, tokens: f64, now: Instant) {
+        buckets.tick += 1;
+        let tick = buckets.tick;
+
+        if let Some(bucket) = buckets.entries.remove(key) {
+            buckets.usage.remove(&bucket.last_used);
+        }
+
+        // Forget about the least recently seen keys to keep memory bounded, their buckets are
+        // the fullest ones
+        while buckets.entries.len() >= self.max_keys {
+            match buckets.usage.pop_first() {
+                Some((_, oldest_key)) => {
+                    buckets.entries.remove(&oldest_key);
+                }
+                None => break,
+            }
+        }
+
+        buckets.entries.insert(
+            key.clone(),
+            Bucket {
+                tokens,
+                updated_at: now,
+                last_used: tick,
+            },
+        );
+        buckets.usage.insert(tick, key.clone());
+    }
+
+    /// Returns the number of tokens in this bucket at the given time.
+    fn refill(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let capacity = f64::from(self.limit);
+        let refill_rate = capacity / REFILL_INTERVAL.as_secs_f64();
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        (bucket.tokens + elapsed * refill_rate).min(capacity)
+    }
+}
+
+/// IP address of the client which sent a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientAddress(pub Option<IpAddr>);
+
+/// Limits for requests to the GraphQL API and published entries, shared across all clients.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Maximum size in bytes of request bodies sent to the GraphQL API.
+    pub max_request_body_size: usize,
+
+    /// Maximum size in bytes of encoded operations which can be published.
+    pub max_operation_size: usize,
+
+    /// Maximum number of entries which can be published at once in a batch.
+    pub max_batch_size: usize,
+
+    publish_per_ip: RateLimiter<IpAddr>,
+
+    publish_per_public_key: RateLimiter<PublicKey>,
+
+    metrics: Metrics,
+}
+
+impl Limits {
+    /// Returns limits as defined in the node configuration, exceeded limits are counted in the
+    /// given metrics.
+    pub fn new(config: &Configuration, metrics: Metrics) -> Self {
+        Self {
+            max_request_body_size: config.max_request_body_size,
+            max_operation_size: config.max_operation_size,
+            max_batch_size: config.max_batch_size,
+            publish_per_ip: RateLimiter::new(config.publish_rate_limit_per_ip),
+            publish_per_public_key: RateLimiter::new(config.publish_rate_limit_per_public_key),
+            metrics,
+        }
+    }
+
+    /// Returns an error if a request body of the given size is too large.
+    pub fn check_request_body(&self, size: usize) -> Result<(), LimitError> {
+        if size > self.max_request_body_size {
+            return self.exceeded(LimitError::RequestBodyTooLarge(self.max_request_body_size));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if an operation of the given size is too large to be published.
+    pub fn check_operation_size(&self, operation_size: u64) -> Result<(), LimitError> {
+        if operation_size > self.max_operation_size as u64 {
+            return self.exceeded(LimitError::OperationTooLarge(self.max_operation_size));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if a batch with the given number of entries is too large to be published.
+    pub fn check_batch_size(&self, batch_size: usize) -> Result<(), LimitError> {
+        if batch_size > self.max_batch_size {
+            return self.exceeded(LimitError::BatchTooLarge(self.max_batch_size));
+        }
+
+        Ok(())
+    }
+
+    /// Counts entries by the given authors towards the limits of the client and the authors,
+    /// returns an error if they published too many entries recently.
+    ///
+    /// Nothing is counted when an error is returned. Entries which do not get published after all
+    /// need to be handed back with `refund_publish`.
+    pub fn try_publish(
+        &self,
+        client_address: Option<&ClientAddress>,
+        public_keys: &[PublicKey],
+    ) -> Result<(), LimitError> {
+        let ip_address = match client_address {
+            Some(ClientAddress(Some(ip_address))) => Some(ip_address),
+            _ => None,
+        };
+
+        if let Some(ip_address) = ip_address {
+            if !self
+                .publish_per_ip
+                .try_consume(ip_address, public_keys.len() as u32)
+            {
+                return self.exceeded(LimitError::TooManyRequestsFromClient);
+            }
+        }
+
+        let mut consumed = Vec::new();
+        for (public_key, count) in count_by_public_key(public_keys) {
+            if !self.publish_per_public_key.try_consume(public_key, count) {
+                // Hand back what was already counted for this request
+                if let Some(ip_address) = ip_address {
+                    self.publish_per_ip
+                        .refund(ip_address, public_keys.len() as u32);
+                }
+
+                for (public_key, count) in consumed {
+                    self.publish_per_public_key.refund(public_key, count);
+                }
+
+                return self.exceeded(LimitError::TooManyRequestsFromAuthor);
+            }
+
+            consumed.push((public_key, count));
+        }
+
+        Ok(())
+    }
+
+    /// Hands back entries counted with `try_publish` which did not get published after all.
+    pub fn refund_publish(
+        &self,
+        client_address: Option<&ClientAddress>,
+        public_keys: &[PublicKey],
+    ) {
+        if let Some(ClientAddress(Some(ip_address))) = client_address {
+            self.publish_per_ip
+                .refund(ip_address, public_keys.len() as u32);
+        }
+
+        for (public_key, count) in count_by_public_key(public_keys) {
+            self.publish_per_public_key.refund(public_key, count);
+        }
+    }
+
+    fn exceeded(&self, err: LimitError) -> Result<(), LimitError> {
+        self.metrics.limit_exceeded(err.code());
+        Err(err)
+    }
+}
+
+/// Returns the number of entries per author.
+fn count_by_public_key(public_keys: &[PublicKey]) -> HashMap<&PublicKey, u32> {
+    let mut counts = HashMap::new();
+    for public_key in public_keys {
+        *counts.entry(public_key).or_default() += 1;
+    }
+    counts
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    /// Request body exceeds the maximum size.
+    #[error("Request body exceeds maximum size of {0} bytes")]
+    RequestBodyTooLarge(usize),
+
+    /// Encoded operation exceeds the maximum size.
+    #[error("Operation exceeds maximum size of {0} bytes")]
+    OperationTooLarge(usize),
+
+    /// Batch contains more entries than allowed.
+    #[error("Batch exceeds maximum size of {0} entries")]
+    BatchTooLarge(usize),
+
+    /// Client published too many entries recently.
+    #[error("Too many entries published from this address, try again later")]
+    TooManyRequestsFromClient,
+
+    /// Author published too many entries recently.
+    #[error("Too many entries published by this author, try again later")]
+    TooManyRequestsFromAuthor,
+}
+
+impl LimitError {
+    /// Machine-readable identifier of the exceeded limit.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LimitError::RequestBodyTooLarge(_) => "REQUEST_BODY_TOO_LARGE",
+            LimitError::OperationTooLarge(_) => "OPERATION_TOO_LARGE",
+            LimitError::BatchTooLarge(_) => "BATCH_TOO_LARGE",
+            LimitError::TooManyRequestsFromClient => "RATE_LIMITED_CLIENT",
+            LimitError::TooManyRequestsFromAuthor => "RATE_LIMITED_AUTHOR",
+        }
+    }
+}
+
+impl ErrorExtensions for LimitError {
+    /// Returns a GraphQL error with the identifier of the exceeded limit as its "code" extension.
+    fn extend(&self) -> Error {
+        Error::new(self.to_string())
+            .extend_with(|_, extensions| extensions.set("code", self.code()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use p2panda_rs::identity::KeyPair;
+
+    use crate::config::Configuration;
+    use crate::metrics::Metrics;
+
+    use super::{ClientAddress, LimitError, Limits, RateLimiter};
+
+    #[test]
+    fn token_bucket() {
+        let rate_limiter = RateLimiter::new(2);
+        let now = Instant::now();
+
+        // Tokens are only taken when enough of them are left
+        assert!(!rate_limiter.try_consume_at(&"a", 3, now));
+        assert!(rate_limiter.try_consume_at(&"a", 2, now));
+        assert!(!rate_limiter.try_consume_at(&"a", 1, now));
+
+        // Refunded tokens can be taken again
+        rate_limiter.refund_at(&"a", 1, now);
+        assert!(rate_limiter.try_consume_at(&"a", 1, now));
+
+        // Other keys have their own bucket
+        assert!(rate_limiter.try_consume_at(&"b", 1, now));
+
+        // Bucket refills one token every 30 seconds
+        assert!(!rate_limiter.try_consume_at(&"a", 1, now + Duration::from_secs(20)));
+        assert!(rate_limiter.try_consume_at(&"a", 1, now + Duration::from_secs(31)));
+        assert!(!rate_limiter.try_consume_at(&"a", 1, now + Duration::from_secs(31)));
+
+        // Disabled rate limiter allows everything
+        let rate_limiter = RateLimiter::new(0);
+        for _ in 0..100 {
+            assert!(rate_limiter.try_consume_at(&"a", 1, now));
+        }
+    }
+
+    #[test]
+    fn bounded_number_of_keys() {
+        let rate_limiter = RateLimiter::with_max_keys(1, 3);
+        let now = Instant::now();
+
+        for key in [1, 2, 3] {
+            assert!(rate_limiter.try_consume_at(&key, 1, now));
+        }
+
+        // Key 1 was seen again, even though it got rejected, 2 is the least recently seen one now
+        // and gets forgotten
+        assert!(!rate_limiter.try_consume_at(&1, 1, now + Duration::from_secs(1)));
+        assert!(rate_limiter.try_consume_at(&4, 1, now + Duration::from_secs(2)));
+
+        {
+            let buckets = rate_limiter.buckets.lock().unwrap();
+            let mut keys: Vec<i32> = buckets.entries.keys().copied().collect();
+            keys.sort();
+            assert_eq!(keys, vec![1, 3, 4]);
+        }
+
+        // Any number of new keys never grows the buckets past the maximum
+        for key in 100..10_000 {
+            rate_limiter.try_consume_at(&key, 1, now);
+        }
+
+        let buckets = rate_limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.entries.len(), 3);
+        assert_eq!(buckets.usage.len(), 3);
+    }
+
+    #[test]
+    fn publish_limits() {
+        let limits = Limits::new(
+            &Configuration {
+                publish_rate_limit_per_ip: 2,
+                publish_rate_limit_per_public_key: 3,
+                max_operation_size: 100,
+                max_batch_size: 2,
+                ..Configuration::default()
+            },
+            Metrics::default(),
+        );
+
+        let client = ClientAddress(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        let other_client = ClientAddress(Some(IpAddr::V4(Ipv4Addr::BROADCAST)));
+        let public_key = KeyPair::new().public_key();
+
+        assert_eq!(
+            limits.check_operation_size(101),
+            Err(LimitError::OperationTooLarge(100))
+        );
+        assert!(limits.check_operation_size(100).is_ok());
+
+        assert_eq!(
+            limits.check_batch_size(3),
+            Err(LimitError::BatchTooLarge(2))
+        );
+        assert!(limits.check_batch_size(2).is_ok());
+
+        // Entries which did not get published are handed back and do not count
+        for _ in 0..5 {
+            assert!(limits.try_publish(Some(&client), &[public_key]).is_ok());
+            limits.refund_publish(Some(&client), &[public_key]);
+        }
+
+        assert!(limits
+            .try_publish(Some(&client), &[public_key, public_key])
+            .is_ok());
+        assert_eq!(
+            limits.try_publish(Some(&client), &[public_key]),
+            Err(LimitError::TooManyRequestsFromClient)
+        );
+
+        // Author can still publish from somewhere else until its own limit is reached
+        assert_eq!(
+            limits.try_publish(None, &[public_key, public_key]),
+            Err(LimitError::TooManyRequestsFromAuthor)
+        );
+        assert!(limits.try_publish(None, &[public_key]).is_ok());
+
+        // Rejected authors do not use up the limit of the client
+        assert_eq!(
+            limits.try_publish(Some(&other_client), &[public_key]),
+            Err(LimitError::TooManyRequestsFromAuthor)
+        );
+        let other_public_key = KeyPair::new().public_key();
+        assert!(limits
+            .try_publish(Some(&other_client), &[other_public_key, other_public_key])
+            .is_ok());
+    }
+
+    #[test]
+    fn concurrent_publish() {
+        let limits = Limits::new(
+            &Configuration {
+                publish_rate_limit_per_ip: 1,
+                ..Configuration::default()
+            },
+            Metrics::default(),
+        );
+        let client = ClientAddress(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+        // Many requests of the same client racing each other, only one of them gets through
+        let barrier = Arc::new(Barrier::new(8));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let limits = limits.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    let public_key = KeyPair::new().public_key();
+                    barrier.wait();
+                    limits.try_publish(Some(&client), &[public_key]).is_ok()
+                })
+            })
+            .collect();
+
+        let allowed = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|allowed| *allowed)
+            .count();
+        assert_eq!(allowed, 1);
+    }
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::collections::BTreeMap;
 use std::future::Future;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use log::{error, info};
@@ -71,6 +73,81 @@ where
     }
 }
 
+/// State of a service added to the service manager.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceState {
+    /// Service got started but did not send its ready signal yet.
+    Starting,
+
+    /// Service sent its ready signal and is handling messages on the communication bus.
+    Ready,
+
+    /// Service stopped.
+    Stopped,
+
+    /// Service returned an error or panicked.
+    Failed,
+}
+
+impl ServiceState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ServiceState::Starting => "starting",
+            ServiceState::Ready => "ready",
+            ServiceState::Stopped => "stopped",
+            ServiceState::Failed => "failed",
+        }
+    }
+}
+
+/// States of all services added to a service manager.
+///
+/// This can be cloned and shared to inspect the services from outside of the manager, for example
+/// to report them in health checks.
+#[derive(Clone, Debug, Default)]
+pub struct ServiceStates(Arc<Mutex<BTreeMap<&'static str, ServiceState>>>);
+
+impl ServiceStates {
+    /// Returns the names and states of all services, ordered by name.
+    pub fn all(&self) -> Vec<(&'static str, ServiceState)> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, state)| (*name, *state))
+            .collect()
+    }
+
+    fn set(&self, name: &'static str, state: ServiceState) {
+        self.0.lock().unwrap().insert(name, state);
+    }
+
+    /// Updates the state of a service unless it is already in one of the given states.
+    fn set_unless(&self, name: &'static str, state: ServiceState, unless: &[ServiceState]) {
+        let mut states = self.0.lock().unwrap();
+        match states.get(name) {
+            Some(current) if unless.contains(current) => (),
+            _ => {
+                states.insert(name, state);
+            }
+        }
+    }
+}
+
+/// Marks a service as failed when `PanicGuard` gets dropped because the service panicked.
+struct PanicGuard {
+    name: &'static str,
+    states: ServiceStates,
+}
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.states.set(self.name, ServiceState::Failed);
+        }
+    }
+}
+
 /// Wrapper around `Trigger` which sends a signal as soon as `Signal` gets dropped.
 #[derive(Clone)]
 struct Signal(Trigger);
@@ -125,6 +202,9 @@ where
     /// This needs to be a broadcast channel as we keep count of the subscribers and stop the
     /// service manager as soon as all of them have been dropped.
     shutdown_signal: broadcast::Sender<bool>,
+
+    /// States of all added services.
+    states: ServiceStates,
 }
 
 impl<D, M> ServiceManager<D, M>
@@ -135,8 +215,9 @@ where
     /// Returns a new instance of a service manager.
     ///
     /// The `capacity` argument defines the maximum bound of messages on the communication bus
-    /// which get broadcasted across all services.
-    pub fn new(capacity: usize, context: D) -> Self {
+    /// which get broadcasted across all services. The state of every added service gets reported
+    /// to `states`.
+    pub fn new(capacity: usize, context: D, states: ServiceStates) -> Self {
         let (tx, _) = broadcast::channel(capacity);
         let (shutdown_signal, _) = broadcast::channel(128);
         let (exit_signal, exit_handle) = triggered::trigger();
@@ -147,6 +228,7 @@ where
             exit_signal: Signal(exit_signal),
             exit_handle,
             shutdown_signal,
+            states,
         }
     }
 
@@ -176,17 +258,49 @@ where
 
         // Oneshot channel on which services send a message once they are listening on the
         // communication bus.
-        let (tx_ready, rx_ready) = oneshot::channel::<()>();
+        let (tx_ready, rx_service_ready) = oneshot::channel::<()>();
+
+        // Mark service as ready when it sent its signal and pass the signal on. If the service
+        // drops its sender without signalling we drop ours as well.
+        let (tx_service_ready, rx_ready) = oneshot::channel::<()>();
+        let states = self.states.clone();
+        states.set(name, ServiceState::Starting);
+        task::spawn(async move {
+            if rx_service_ready.await.is_ok() {
+                states.set_unless(
+                    name,
+                    ServiceState::Ready,
+                    &[ServiceState::Stopped, ServiceState::Failed],
+                );
+                let _ = tx_service_ready.send(());
+            }
+        });
 
         // Reference to shared context
         let context = self.context.clone();
 
+        // Reference to service states
+        let states = self.states.clone();
+
         task::spawn(async move {
             info!("Start {} service", name);
 
+            // Mark service as failed when it panics
+            let _panic_guard = PanicGuard {
+                name,
+                states: states.clone(),
+            };
+
             // Run the service!
             let handle = service.call(context, signal, tx, tx_ready).await;
 
+            // Update service state before signalling that it stopped
+            let state = match &handle {
+                Ok(_) => ServiceState::Stopped,
+                Err(_) => ServiceState::Failed,
+            };
+            states.set(name, state);
+
             // Drop the shutdown sender of this service when we're done, this signals the shutdown
             // process that this service has finally stopped
             drop(shutdown_tx);
@@ -247,13 +361,15 @@ mod tests {
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
-    use super::{Sender, ServiceManager, ServiceReadySender, Shutdown};
+    use super::{
+        Sender, ServiceManager, ServiceReadySender, ServiceState, ServiceStates, Shutdown,
+    };
 
     type Counter = Arc<AtomicUsize>;
 
     #[tokio::test]
     async fn service_manager() {
-        let mut manager = ServiceManager::<usize, usize>::new(120, 0);
+        let mut manager = ServiceManager::<usize, usize>::new(120, 0, ServiceStates::default());
 
         manager.add("test", |_, signal: Shutdown, _, _| async {
             let work = tokio::task::spawn(async {
@@ -286,7 +402,8 @@ mod tests {
         // Counter which is shared between services
         let counter: Counter = Arc::new(AtomicUsize::new(0));
 
-        let mut manager = ServiceManager::<Counter, Message>::new(120, counter.clone());
+        let mut manager =
+            ServiceManager::<Counter, Message>::new(120, counter.clone(), ServiceStates::default());
 
         // Create five services waiting for message
         for _ in 0..5 {
@@ -321,7 +438,8 @@ mod tests {
     #[tokio::test]
     async fn on_exit() {
         let counter: Counter = Arc::new(AtomicUsize::new(0));
-        let mut manager = ServiceManager::<Counter, usize>::new(120, counter.clone());
+        let mut manager =
+            ServiceManager::<Counter, usize>::new(120, counter.clone(), ServiceStates::default());
 
         manager.add(
             "one",
@@ -366,7 +484,7 @@ mod tests {
 
     #[tokio::test]
     async fn ready_signal() {
-        let mut manager = ServiceManager::<usize, usize>::new(120, 0);
+        let mut manager = ServiceManager::<usize, usize>::new(120, 0, ServiceStates::default());
 
         let service_ready = manager.add(
             "ready_signal",
@@ -383,7 +501,7 @@ mod tests {
 
     #[tokio::test]
     async fn ready_signal_error() {
-        let mut manager = ServiceManager::<usize, usize>::new(120, 0);
+        let mut manager = ServiceManager::<usize, usize>::new(120, 0, ServiceStates::default());
 
         let service_ready = manager.add(
             "ready_signal",
@@ -396,4 +514,48 @@ mod tests {
         // We panic when trying to wait for the service to become ready.
         assert!(service_ready.await.is_err());
     }
+
+    #[tokio::test]
+    async fn service_states() {
+        let states = ServiceStates::default();
+        let mut manager = ServiceManager::<usize, usize>::new(120, 0, states.clone());
+
+        let service_ready = manager.add(
+            "ready",
+            |_, signal: Shutdown, _, tx_ready: ServiceReadySender| async {
+                tx_ready.send(()).unwrap();
+                let _ = signal.await;
+                Ok(())
+            },
+        );
+        manager.add("starting", |_, signal: Shutdown, _, _| async {
+            let _ = signal.await;
+            Ok(())
+        });
+
+        assert!(service_ready.await.is_ok());
+        assert_eq!(
+            states.all(),
+            vec![
+                ("ready", ServiceState::Ready),
+                ("starting", ServiceState::Starting)
+            ]
+        );
+
+        manager.add("failing", |_, _, _, _| async {
+            Err(anyhow::anyhow!("This went wrong"))
+        });
+        manager.on_exit().await;
+        assert!(states.all().contains(&("failing", ServiceState::Failed)));
+
+        manager.shutdown().await;
+        assert_eq!(
+            states.all(),
+            vec![
+                ("failing", ServiceState::Failed),
+                ("ready", ServiceState::Stopped),
+                ("starting", ServiceState::Stopped)
+            ]
+        );
+    }
 }
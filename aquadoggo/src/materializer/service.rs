@@ -2,6 +2,9 @@
 
 use anyhow::Result;
 use log::{debug, warn};
+use p2panda_rs::hash::HashId;
+use p2panda_rs::operation::traits::AsOperation;
+use p2panda_rs::operation::OperationId;
 use p2panda_rs::storage_provider::traits::OperationStore;
 use tokio::task;
 
@@ -34,7 +37,11 @@ pub async fn materializer_service(
 ) -> Result<()> {
     // Create worker factory with task queue
     let pool_size = context.config.worker_pool_size as usize;
-    let mut factory = Factory::<TaskInput, Context>::new(context.clone(), CHANNEL_CAPACITY);
+    let mut factory = Factory::<TaskInput, Context>::new(
+        context.clone(),
+        CHANNEL_CAPACITY,
+        context.metrics.clone(),
+    );
 
     // Register worker functions in factory
     factory.register("reduce", pool_size, reduce_task);
@@ -49,6 +56,7 @@ pub async fn materializer_service(
     // Subscribe to status changes of tasks
     let mut on_task_status_change = factory.on_task_status_change();
     let store = context.store.clone();
+    let tx_status = tx.clone();
 
     // Keep track of status changes and persist it in the database. This allows us to pick up
     // uncompleted tasks next time we start the node.
@@ -66,6 +74,27 @@ pub async fn materializer_service(
                         .remove_task(&task)
                         .await
                         .expect("Failed removing completed task from database");
+
+                    // Inform other services that the latest view of a document might have
+                    // changed after a "reduce" task completed
+                    if let ("reduce", TaskInput::DocumentId(document_id)) =
+                        (task.worker_name().as_str(), task.input())
+                    {
+                        // The document id is the id of the initial "create" operation which tells
+                        // us the schema of this document, even when it got deleted
+                        let operation_id = OperationId::from(document_id.as_hash().to_owned());
+                        let operation = store
+                            .get_operation(&operation_id)
+                            .await
+                            .expect("Failed retrieving operation from database");
+
+                        if let Some(operation) = operation {
+                            let _ = tx_status.send(ServiceMessage::DocumentMaterialized(
+                                operation.schema_id(),
+                                document_id.to_owned(),
+                            ));
+                        }
+                    }
                 }
                 Err(err) => {
                     panic!("Failed receiving task status updates: {}", err)
@@ -159,6 +188,8 @@ pub async fn materializer_service(
 mod tests {
     use std::time::Duration;
 
+    use async_graphql::value;
+    use futures::StreamExt;
     use p2panda_rs::document::traits::AsDocument;
     use p2panda_rs::document::DocumentId;
     use p2panda_rs::entry::traits::AsEncodedEntry;
@@ -174,11 +205,12 @@ mod tests {
     use tokio::task;
 
     use crate::context::Context;
+    use crate::graphql::GraphQLSchemaManager;
     use crate::materializer::{Task, TaskInput};
     use crate::schema::SchemaProvider;
     use crate::test_utils::{
-        doggo_fields, doggo_schema, populate_store, populate_store_config, test_runner,
-        PopulateStoreConfig, TestNode,
+        add_document, add_schema, doggo_fields, doggo_schema, populate_store,
+        populate_store_config, test_runner, PopulateStoreConfig, TestNode,
     };
     use crate::Configuration;
 
@@ -518,6 +550,114 @@ mod tests {
         });
     }
 
+    #[rstest]
+    fn materialized_update_reaches_subscription(key_pair: KeyPair) {
+        test_runner(move |mut node: TestNode| async move {
+            let schema = add_schema(
+                &mut node,
+                "schema_name",
+                vec![("name", FieldType::String)],
+                &key_pair,
+            )
+            .await;
+            let view_id = add_document(
+                &mut node,
+                schema.id(),
+                vec![("name", "panda".into())],
+                &key_pair,
+            )
+            .await;
+            let document_id = DocumentId::new(view_id.graph_tips().first().unwrap());
+
+            let (tx, _) = broadcast::channel(128);
+            let manager = GraphQLSchemaManager::new(
+                node.context.store.clone(),
+                tx.clone(),
+                node.context.schema_provider.clone(),
+                node.context.status.clone(),
+            )
+            .await;
+
+            let mut stream = manager.execute_stream(
+                format!(
+                    r#"subscription {{
+                        document: document_{type_name}(id: "{document_id}") {{
+                            fields {{ name }}
+                        }}
+                    }}"#,
+                    type_name = schema.id(),
+                ),
+                None,
+            );
+
+            // The current view of the document is emitted first
+            let response = stream.next().await.expect("Stream returns response");
+            assert_eq!(
+                response.data,
+                value!({ "document": { "fields": { "name": "panda" } } }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Start materializer service
+            let shutdown = task::spawn(async {
+                loop {
+                    // Do this forever .. this means that the shutdown handler will never resolve
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            });
+            let (tx_ready, rx_ready) = oneshot::channel::<()>();
+            let context = node.context.clone();
+            let tx_clone = tx.clone();
+            let handle = tokio::spawn(async move {
+                materializer_service(context, shutdown, tx_clone, tx_ready)
+                    .await
+                    .unwrap();
+            });
+
+            if rx_ready.await.is_err() {
+                panic!("Service dropped");
+            }
+
+            // Publish an UPDATE on this document without materializing it and send it over the
+            // bus, the materializer informs the subscription once the new view was written
+            let (entry_encoded, _) = send_to_store(
+                &node.context.store,
+                &operation(
+                    Some(operation_fields(vec![(
+                        "name",
+                        OperationValue::String("panda123".into()),
+                    )])),
+                    Some(view_id),
+                    schema.id().to_owned(),
+                ),
+                &schema,
+                &key_pair,
+            )
+            .await
+            .expect("Publish entry");
+
+            tx.send(crate::bus::ServiceMessage::NewOperation(
+                entry_encoded.hash().into(),
+            ))
+            .unwrap();
+
+            let response = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("Subscription yields update")
+                .expect("Stream returns response");
+            assert_eq!(
+                response.data,
+                value!({ "document": { "fields": { "name": "panda123" } } }),
+                "{:#?}",
+                response.errors
+            );
+
+            // Make sure the service did not crash and is still running
+            assert!(!handle.is_finished());
+        });
+    }
+
     #[rstest]
     fn materialize_complex_documents(
         #[from(operation)]
@@ -87,6 +87,7 @@ use std::future::Future;
 use std::hash::Hash;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use deadqueue::unlimited::Queue;
 use log::{debug, error, info};
@@ -95,6 +96,8 @@ use tokio::sync::broadcast::{channel, Receiver, Sender};
 use tokio::task;
 use triggered::{Listener, Trigger};
 
+use crate::metrics::Metrics;
+
 /// A task holding a generic input value and the name of the worker which will process it
 /// eventually.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -293,6 +296,9 @@ where
     ///
     /// This can be used to react to factory errors, for example by quitting the program.
     error_handle: Listener,
+
+    /// Metrics where queue depths and task durations of all worker pools are reported.
+    metrics: Metrics,
 }
 
 impl<IN, D> Factory<IN, D>
@@ -308,7 +314,9 @@ where
     ///
     /// Factories will panic if the capacity limit was reached as it will cause the workers to miss
     /// incoming tasks.
-    pub fn new(context: D, capacity: usize) -> Self {
+    ///
+    /// Queue depths and task durations of all worker pools are reported to the given metrics.
+    pub fn new(context: D, capacity: usize, metrics: Metrics) -> Self {
         let (tx, _) = channel(capacity);
         let (tx_status, _) = channel(capacity);
         let (error_signal, error_handle) = triggered::trigger();
@@ -320,6 +328,7 @@ where
             tx_status,
             error_signal,
             error_handle,
+            metrics,
         }
     }
 
@@ -401,6 +410,7 @@ where
         let input_index = manager.input_index.clone();
         let name = String::from(name);
         let queue = manager.queue.clone();
+        let metrics = self.metrics.clone();
 
         // Create handle for error signal
         let error_signal = self.error_signal.clone();
@@ -437,6 +447,7 @@ where
                                         debug!("Sending materializer {} task with input {} to the task queue.", task.worker_name(), task.input());
                                         let next_id = counter.fetch_add(1, Ordering::Relaxed);
                                         queue.push(QueueItem::new(next_id, task.1.clone()));
+                                        metrics.task_queued(&name);
                                         index.insert(task.1, PostAction::Idle);
                                     }
                                     Some(PostAction::Idle) => {
@@ -493,6 +504,7 @@ where
             let input_index = manager.input_index.clone();
             let tx = self.tx.clone();
             let name = name.to_string();
+            let metrics = self.metrics.clone();
 
             // Create handle for error signal
             let error_signal = self.error_signal.clone();
@@ -513,9 +525,12 @@ where
                 loop {
                     // Wait until there is a new task arriving in the queue
                     let item = queue.pop().await;
+                    metrics.task_started(&name);
 
                     // Take this task and do work ..
+                    let started_at = Instant::now();
                     let result = work.call(context.clone(), item.input()).await;
+                    metrics.task_completed(&name, started_at.elapsed());
 
                     // Check the result
                     match result {
@@ -591,6 +606,8 @@ mod tests {
     use rand::seq::SliceRandom;
     use rand::Rng;
 
+    use crate::metrics::Metrics;
+
     use super::{Factory, Task, TaskError, TaskResult, TaskStatus};
 
     #[tokio::test]
@@ -602,7 +619,7 @@ mod tests {
         let database = Arc::new(Mutex::new(Vec::new()));
 
         // Initialise factory
-        let mut factory = Factory::<Input, Data>::new(database.clone(), 1024);
+        let mut factory = Factory::<Input, Data>::new(database.clone(), 1024, Metrics::default());
 
         // Define two workers
         async fn first(database: Data, input: Input) -> TaskResult<Input> {
@@ -645,7 +662,7 @@ mod tests {
         type Data = usize;
 
         // Initialise factory
-        let mut factory = Factory::<Input, Data>::new(1, 1024);
+        let mut factory = Factory::<Input, Data>::new(1, 1024, Metrics::default());
 
         // Record all status changes in this array
         let messages: Arc<Mutex<Vec<TaskStatus<Input>>>> = Arc::new(Mutex::new(Vec::new()));
@@ -729,7 +746,8 @@ mod tests {
             puzzles: HashMap::new(),
         }));
 
-        let mut factory = Factory::<JigsawPiece, Data>::new(database.clone(), 1024);
+        let mut factory =
+            Factory::<JigsawPiece, Data>::new(database.clone(), 1024, Metrics::default());
 
         // This tasks "picks" a single piece out of the box and sorts it into the database
         async fn pick(database: Data, input: JigsawPiece) -> TaskResult<JigsawPiece> {
@@ -0,0 +1,420 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Metrics about the running node, exported in the Prometheus text format.
+//!
+//! Services report events like finished materializer tasks or replication sessions here. Gauges
+//! like the number of connected peers or database connections are read from the node status and
+//! store when the metrics get exported.
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::db::SqlStore;
+use crate::status::NodeStatus;
+
+/// Content type of the Prometheus text-based exposition format.
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Upper bounds in seconds of the buckets used for all duration histograms.
+const DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Distribution of observed durations.
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Number of observations per bucket, the last one counts all observations exceeding the
+    /// largest bucket bound.
+    buckets: [u64; DURATION_BUCKETS.len() + 1],
+
+    /// Sum of all observed durations in seconds.
+    sum: f64,
+
+    /// Total number of observations.
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let index = DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+
+        self.buckets[index] += 1;
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsInner {
+    /// Number of tasks waiting in the queue of each materializer worker pool.
+    task_queue_depth: BTreeMap<String, u64>,
+
+    /// Durations of processed tasks for each materializer worker pool.
+    task_durations: BTreeMap<String, Histogram>,
+
+    replication_sessions_started: u64,
+    replication_sessions_completed: u64,
+    replication_sessions_failed: u64,
+    replication_entries_received: u64,
+    replication_entries_sent: u64,
+
+    /// Durations of answered GraphQL requests.
+    graphql_request_durations: Histogram,
+
+    /// Number of requests which got rejected for each exceeded limit.
+    limits_exceeded: BTreeMap<String, u64>,
+}
+
+/// Metrics of the running node, shared across services.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsInner>>,
+}
+
+impl Metrics {
+    /// Records that a task was added to the queue of a materializer worker pool.
+    pub fn task_queued(&self, worker_name: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner
+            .task_queue_depth
+            .entry(worker_name.to_owned())
+            .or_default() += 1;
+    }
+
+    /// Records that a worker took a task from the queue of its pool.
+    pub fn task_started(&self, worker_name: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(depth) = inner.task_queue_depth.get_mut(worker_name) {
+            *depth = depth.saturating_sub(1);
+        }
+    }
+
+    /// Records how long a worker took to process a task.
+    pub fn task_completed(&self, worker_name: &str, duration: Duration) {
+        self.inner
+            .lock()
+            .unwrap()
+            .task_durations
+            .entry(worker_name.to_owned())
+            .or_default()
+            .observe(duration);
+    }
+
+    /// Records that a replication session with another peer was started.
+    pub fn replication_session_started(&self) {
+        self.inner.lock().unwrap().replication_sessions_started += 1;
+    }
+
+    /// Records that a replication session with another peer completed successfully.
+    pub fn replication_session_completed(&self) {
+        self.inner.lock().unwrap().replication_sessions_completed += 1;
+    }
+
+    /// Records that a replication session with another peer failed.
+    pub fn replication_session_failed(&self) {
+        self.inner.lock().unwrap().replication_sessions_failed += 1;
+    }
+
+    /// Records that an entry was received from another peer during replication.
+    pub fn replication_entry_received(&self) {
+        self.inner.lock().unwrap().replication_entries_received += 1;
+    }
+
+    /// Records that an entry was sent to another peer during replication.
+    pub fn replication_entry_sent(&self) {
+        self.inner.lock().unwrap().replication_entries_sent += 1;
+    }
+
+    /// Records how long it took to answer a GraphQL request.
+    pub fn graphql_request_completed(&self, duration: Duration) {
+        self.inner
+            .lock()
+            .unwrap()
+            .graphql_request_durations
+            .observe(duration);
+    }
+
+    /// Records that a request got rejected because it exceeded a rate or size limit.
+    pub fn limit_exceeded(&self, limit: &str) {
+        *self
+            .inner
+            .lock()
+            .unwrap()
+            .limits_exceeded
+            .entry(limit.to_owned())
+            .or_default() += 1;
+    }
+
+    /// Returns all metrics in the Prometheus text-based exposition format.
+    pub async fn encode(&self, status: &NodeStatus, store: &SqlStore) -> String {
+        let connections = status.connections().await;
+        let connected_peers: HashSet<_> = connections
+            .iter()
+            .map(|connection| connection.peer_id)
+            .collect();
+
+        let mut buf = String::new();
+
+        {
+            let inner = self.inner.lock().unwrap();
+
+            write_header(
+                &mut buf,
+                "aquadoggo_materializer_queue_depth",
+                "gauge",
+                "Number of tasks waiting in the queue of a materializer worker pool.",
+            );
+            for (worker_name, depth) in &inner.task_queue_depth {
+                write_sample(
+                    &mut buf,
+                    "aquadoggo_materializer_queue_depth",
+                    &worker_label(worker_name),
+                    *depth,
+                );
+            }
+
+            write_header(
+                &mut buf,
+                "aquadoggo_materializer_task_duration_seconds",
+                "histogram",
+                "Time it took a materializer worker to process a task.",
+            );
+            for (worker_name, histogram) in &inner.task_durations {
+                write_histogram(
+                    &mut buf,
+                    "aquadoggo_materializer_task_duration_seconds",
+                    &worker_label(worker_name),
+                    histogram,
+                );
+            }
+
+            for (name, help, value) in [
+                (
+                    "aquadoggo_replication_sessions_started_total",
+                    "Number of replication sessions started with other peers.",
+                    inner.replication_sessions_started,
+                ),
+                (
+                    "aquadoggo_replication_sessions_completed_total",
+                    "Number of replication sessions which completed successfully.",
+                    inner.replication_sessions_completed,
+                ),
+                (
+                    "aquadoggo_replication_sessions_failed_total",
+                    "Number of replication sessions which failed.",
+                    inner.replication_sessions_failed,
+                ),
+                (
+                    "aquadoggo_replication_entries_received_total",
+                    "Number of entries received from other peers during replication.",
+                    inner.replication_entries_received,
+                ),
+                (
+                    "aquadoggo_replication_entries_sent_total",
+                    "Number of entries sent to other peers during replication.",
+                    inner.replication_entries_sent,
+                ),
+            ] {
+                write_header(&mut buf, name, "counter", help);
+                write_sample(&mut buf, name, "", value);
+            }
+
+            write_header(
+                &mut buf,
+                "aquadoggo_graphql_request_duration_seconds",
+                "histogram",
+                "Time it took to answer a GraphQL request.",
+            );
+            write_histogram(
+                &mut buf,
+                "aquadoggo_graphql_request_duration_seconds",
+                "",
+                &inner.graphql_request_durations,
+            );
+
+            write_header(
+                &mut buf,
+                "aquadoggo_limits_exceeded_total",
+                "counter",
+                "Number of requests which got rejected because they exceeded a rate or size limit.",
+            );
+            for (limit, count) in &inner.limits_exceeded {
+                write_sample(
+                    &mut buf,
+                    "aquadoggo_limits_exceeded_total",
+                    &format!("limit=\"{limit}\""),
+                    *count,
+                );
+            }
+        }
+
+        write_header(
+            &mut buf,
+            "aquadoggo_connected_peers",
+            "gauge",
+            "Number of peers this node is currently connected to.",
+        );
+        write_sample(
+            &mut buf,
+            "aquadoggo_connected_peers",
+            "",
+            connected_peers.len(),
+        );
+
+        write_header(
+            &mut buf,
+            "aquadoggo_connections",
+            "gauge",
+            "Number of open connections to other peers.",
+        );
+        write_sample(&mut buf, "aquadoggo_connections", "", connections.len());
+
+        let pool_size = store.pool.size() as usize;
+        let pool_idle = store.pool.num_idle();
+
+        write_header(
+            &mut buf,
+            "aquadoggo_database_pool_connections",
+            "gauge",
+            "Number of open connections in the database pool.",
+        );
+        write_sample(
+            &mut buf,
+            "aquadoggo_database_pool_connections",
+            "",
+            pool_size,
+        );
+
+        write_header(
+            &mut buf,
+            "aquadoggo_database_pool_connections_in_use",
+            "gauge",
+            "Number of database connections currently in use.",
+        );
+        write_sample(
+            &mut buf,
+            "aquadoggo_database_pool_connections_in_use",
+            "",
+            pool_size.saturating_sub(pool_idle),
+        );
+
+        buf
+    }
+}
+
+fn worker_label(worker_name: &str) -> String {
+    format!("worker=\"{worker_name}\"")
+}
+
+fn write_header(buf: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(buf, "# HELP {name} {help}").expect("Writing to string never fails");
+    writeln!(buf, "# TYPE {name} {kind}").expect("Writing to string never fails");
+}
+
+fn write_sample(buf: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    if labels.is_empty() {
+        writeln!(buf, "{name} {value}")
+    } else {
+        writeln!(buf, "{name}{{{labels}}} {value}")
+    }
+    .expect("Writing to string never fails");
+}
+
+fn write_histogram(buf: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    let bucket_name = format!("{name}_bucket");
+    let with_bound = |bound: &str| {
+        if labels.is_empty() {
+            format!("le=\"{bound}\"")
+        } else {
+            format!("{labels},le=\"{bound}\"")
+        }
+    };
+
+    // Buckets are cumulative, every bucket counts all observations less or equal its bound
+    let mut cumulative = 0;
+    for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets.iter()) {
+        cumulative += count;
+        write_sample(
+            buf,
+            &bucket_name,
+            &with_bound(&bound.to_string()),
+            cumulative,
+        );
+    }
+    write_sample(buf, &bucket_name, &with_bound("+Inf"), histogram.count);
+
+    write_sample(buf, &format!("{name}_sum"), labels, histogram.sum);
+    write_sample(buf, &format!("{name}_count"), labels, histogram.count);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use libp2p::swarm::ConnectionId;
+    use libp2p::{Multiaddr, PeerId};
+
+    use crate::test_utils::{test_runner, TestNode};
+
+    use super::Metrics;
+
+    #[test]
+    fn encode_metrics() {
+        test_runner(|node: TestNode| async move {
+            let metrics = Metrics::default();
+
+            metrics.task_queued("reduce");
+            metrics.task_queued("reduce");
+            metrics.task_started("reduce");
+            metrics.task_completed("reduce", Duration::from_millis(20));
+            metrics.task_completed("reduce", Duration::from_secs(20));
+            metrics.replication_session_started();
+            metrics.replication_entry_received();
+            metrics.replication_entry_received();
+            metrics.limit_exceeded("RATE_LIMITED_CLIENT");
+
+            let peer_id = PeerId::random();
+            let address: Multiaddr = "/ip4/127.0.0.1/tcp/2022".parse().unwrap();
+            for connection_id in [1, 2] {
+                node.context
+                    .status
+                    .add_connection(
+                        peer_id,
+                        ConnectionId::new_unchecked(connection_id),
+                        &address,
+                    )
+                    .await;
+            }
+
+            let encoded = metrics
+                .encode(&node.context.status, &node.context.store)
+                .await;
+            let lines: Vec<&str> = encoded.lines().collect();
+
+            for expected in [
+                "# TYPE aquadoggo_materializer_queue_depth gauge",
+                "aquadoggo_materializer_queue_depth{worker=\"reduce\"} 1",
+                "aquadoggo_materializer_task_duration_seconds_bucket{worker=\"reduce\",le=\"0.01\"} 0",
+                "aquadoggo_materializer_task_duration_seconds_bucket{worker=\"reduce\",le=\"0.025\"} 1",
+                "aquadoggo_materializer_task_duration_seconds_bucket{worker=\"reduce\",le=\"10\"} 1",
+                "aquadoggo_materializer_task_duration_seconds_bucket{worker=\"reduce\",le=\"+Inf\"} 2",
+                "aquadoggo_materializer_task_duration_seconds_sum{worker=\"reduce\"} 20.02",
+                "aquadoggo_materializer_task_duration_seconds_count{worker=\"reduce\"} 2",
+                "aquadoggo_replication_sessions_started_total 1",
+                "aquadoggo_replication_sessions_failed_total 0",
+                "aquadoggo_replication_entries_received_total 2",
+                "aquadoggo_graphql_request_duration_seconds_count 0",
+                "aquadoggo_limits_exceeded_total{limit=\"RATE_LIMITED_CLIENT\"} 1",
+                "aquadoggo_connected_peers 1",
+                "aquadoggo_connections 2",
+            ] {
+                assert!(lines.contains(&expected), "Missing line: {}", expected);
+            }
+        })
+    }
+}
@@ -26,6 +26,7 @@ use crate::network::relay::Relay;
 use crate::network::swarm::{build_quic_swarm, build_tcp_swarm};
 use crate::network::utils::{dial_known_peer, is_known_peer_address};
 use crate::network::{identity, peers, utils, ShutdownHandler};
+use crate::status::NodeStatus;
 use crate::{info_or_print, NetworkConfiguration};
 
 /// Interval at which we attempt to dial known peers and relays.
@@ -111,6 +112,7 @@ pub async fn network_service(
         swarm,
         network_config.to_owned(),
         local_peer_id,
+        context.status.clone(),
         shutdown,
         tx,
         tx_ready,
@@ -147,6 +149,9 @@ struct EventLoop {
     /// Shutdown handler.
     shutdown_handler: ShutdownHandler,
 
+    /// Status of the node where we keep track of all open connections.
+    status: NodeStatus,
+
     /// Did we learn our own port yet.
     learned_port: bool,
 
@@ -159,6 +164,7 @@ impl EventLoop {
         swarm: Swarm<P2pandaBehaviour>,
         network_config: NetworkConfiguration,
         local_peer_id: PeerId,
+        status: NodeStatus,
         tx: ServiceSender,
         shutdown_handler: ShutdownHandler,
     ) -> Self {
@@ -172,6 +178,7 @@ impl EventLoop {
             known_peers: HashMap::new(),
             relays: HashMap::new(),
             shutdown_handler,
+            status,
             learned_port: false,
             learned_observed_addr: false,
         }
@@ -510,6 +517,7 @@ impl EventLoop {
                 endpoint,
                 num_established,
                 peer_id,
+                connection_id,
                 ..
             } => {
                 debug!(
@@ -518,6 +526,10 @@ impl EventLoop {
                     num_established
                 );
 
+                self.status
+                    .add_connection(peer_id, connection_id, endpoint.get_remote_address())
+                    .await;
+
                 // Check if the connected peer is one of our relay addresses.
                 if let Some(addr) = is_known_peer_address(
                     &mut self.network_config.relay_addresses,
@@ -562,6 +574,8 @@ impl EventLoop {
                         .unwrap_or("No cause given".to_string())
                 );
 
+                self.status.remove_connection(&connection_id).await;
+
                 // Remove this peer address from our known peers.
                 self.known_peers.remove(endpoint.get_remote_address());
             }
@@ -574,6 +588,7 @@ pub async fn spawn_event_loop(
     swarm: Swarm<P2pandaBehaviour>,
     network_config: NetworkConfiguration,
     local_peer_id: PeerId,
+    status: NodeStatus,
     shutdown: Shutdown,
     tx: ServiceSender,
     tx_ready: ServiceReadySender,
@@ -585,6 +600,7 @@ pub async fn spawn_event_loop(
         swarm,
         network_config,
         local_peer_id,
+        status,
         tx,
         shutdown_handler.clone(),
     );
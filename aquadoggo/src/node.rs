@@ -56,7 +56,7 @@ impl Node {
             .expect("Could not initialize database");
 
         // Prepare storage and schema providers using connection pool
-        let store = SqlStore::new(pool.clone());
+        let store = SqlStore::new(pool.clone(), config.query_cache_size);
 
         // Initiate the SchemaProvider with all currently known schema from the store.
         //
@@ -68,8 +68,11 @@ impl Node {
 
         // Create service manager with shared data between services
         let context = Context::new(store, key_pair, config, schema_provider);
-        let mut manager =
-            ServiceManager::<Context, ServiceMessage>::new(SERVICE_BUS_CAPACITY, context.clone());
+        let mut manager = ServiceManager::<Context, ServiceMessage>::new(
+            SERVICE_BUS_CAPACITY,
+            context.clone(),
+            context.status.services().clone(),
+        );
 
         // Start materializer service
         if manager
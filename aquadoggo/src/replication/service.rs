@@ -18,6 +18,7 @@ use crate::bus::{ServiceMessage, ServiceSender};
 use crate::context::Context;
 use crate::db::SqlStore;
 use crate::manager::{ServiceReadySender, Shutdown};
+use crate::metrics::Metrics;
 use crate::network::identity::to_libp2p_peer_id;
 use crate::network::{Peer, PeerMessage};
 use crate::replication::errors::ReplicationError;
@@ -26,6 +27,7 @@ use crate::replication::{
     SyncIngest, SyncManager, SyncMessage,
 };
 use crate::schema::SchemaProvider;
+use crate::status::{NodeStatus, SessionStatus};
 
 /// Maximum number of peers we replicate with at one a time.
 const MAX_PEER_SAMPLE: usize = 3;
@@ -47,6 +49,8 @@ pub async fn replication_service(
     let manager = ConnectionManager::new(
         &context.schema_provider,
         &context.store,
+        &context.status,
+        &context.metrics,
         &tx,
         to_libp2p_peer_id(&context.key_pair.public_key()),
     );
@@ -130,6 +134,12 @@ struct ConnectionManager {
     /// Our latest announcement state we want to propagate to all current and future peers. It
     /// contains a list of schema ids we're supporting as a node.
     announcement: Option<Announcement>,
+
+    /// Status of the node where we report all active replication sessions.
+    status: NodeStatus,
+
+    /// Metrics of the node where we report started, completed and failed replication sessions.
+    metrics: Metrics,
 }
 
 impl ConnectionManager {
@@ -137,6 +147,8 @@ impl ConnectionManager {
     pub fn new(
         schema_provider: &SchemaProvider,
         store: &SqlStore,
+        status: &NodeStatus,
+        metrics: &Metrics,
         tx: &ServiceSender,
         local_peer_id: PeerId,
     ) -> Self {
@@ -153,6 +165,8 @@ impl ConnectionManager {
             rx: BroadcastStream::new(tx.subscribe()),
             schema_provider: schema_provider.clone(),
             announcement: None,
+            status: status.clone(),
+            metrics: metrics.clone(),
         }
     }
 
@@ -184,6 +198,29 @@ impl ConnectionManager {
 
         // Check if we can establish replication sessions with peers
         self.update_sessions().await;
+
+        self.update_status().await;
+    }
+
+    /// Report all currently active replication sessions to the node status.
+    async fn update_status(&self) {
+        let mut sessions = Vec::new();
+
+        for peer in self.peers.keys() {
+            for session in self.sync_manager.get_sessions(peer) {
+                sessions.push(SessionStatus {
+                    peer: *peer,
+                    session_id: session.id,
+                    mode: session.mode(),
+                    target_set: session.target_set(),
+                    state: session.state.clone(),
+                    local: session.local,
+                    live_mode: session.is_live_mode(),
+                });
+            }
+        }
+
+        self.status.set_sessions(sessions).await;
     }
 
     /// Handle a peer connection closing.
@@ -264,15 +301,19 @@ impl ConnectionManager {
             }
         }
 
+        if let Message::Entry(_, _) = message.message() {
+            self.metrics.replication_entry_received();
+        }
+
         match self.sync_manager.handle_message(&peer, &message).await {
             Ok(result) => {
-                for message in result.messages {
-                    self.send_service_message(ServiceMessage::SentMessage(
-                        peer,
-                        PeerMessage::SyncMessage(message),
-                    ));
+                // Remote peer initiated a new replication session with us
+                if let Message::SyncRequest(_, _) = message.message() {
+                    self.metrics.replication_session_started();
                 }
 
+                self.send_sync_messages(&peer, result.messages);
+
                 if result.is_done {
                     self.on_replication_finished(peer, session_id).await;
                 }
@@ -286,6 +327,7 @@ impl ConnectionManager {
     /// Handle successful replication sessions.
     async fn on_replication_finished(&mut self, peer: Peer, _session_id: SessionId) {
         debug!("Finished replication with peer {}", peer.display());
+        self.metrics.replication_session_completed();
 
         match self.peers.get_mut(&peer) {
             Some(status) => {
@@ -310,6 +352,8 @@ impl ConnectionManager {
             warn!("Replication failed: {}", error);
         }
 
+        self.metrics.replication_session_failed();
+
         match self.peers.get_mut(&peer) {
             Some(status) => {
                 status.failed_count += 1;
@@ -437,12 +481,8 @@ impl ConnectionManager {
             .await
         {
             Ok(messages) => {
-                for message in messages {
-                    self.send_service_message(ServiceMessage::SentMessage(
-                        *peer,
-                        PeerMessage::SyncMessage(message),
-                    ));
-                }
+                self.metrics.replication_session_started();
+                self.send_sync_messages(peer, messages);
             }
             Err(err) => {
                 warn!("Replication error: {}", err)
@@ -467,7 +507,23 @@ impl ConnectionManager {
                     self.on_announcement_message(peer, message).await;
                 }
             },
-            _ => (), // Ignore all other messages
+            _ => return, // Ignore all other messages
+        }
+
+        self.update_status().await;
+    }
+
+    /// Sends replication messages to a remote peer via the network service.
+    fn send_sync_messages(&self, peer: &Peer, messages: Vec<SyncMessage>) {
+        for message in messages {
+            if let Message::Entry(_, _) = message.message() {
+                self.metrics.replication_entry_sent();
+            }
+
+            self.send_service_message(ServiceMessage::SentMessage(
+                *peer,
+                PeerMessage::SyncMessage(message),
+            ));
         }
     }
 
@@ -552,6 +608,8 @@ mod tests {
             let mut manager = ConnectionManager::new(
                 &node.context.schema_provider,
                 &node.context.store,
+                &node.context.status,
+                &node.context.metrics,
                 &tx,
                 local_peer_id,
             );
@@ -626,8 +684,14 @@ mod tests {
             let (tx, mut rx) = broadcast::channel::<ServiceMessage>(10);
 
             let schema_provider = SchemaProvider::new(vec![], AllowList::Set(vec![]));
-            let mut manager =
-                ConnectionManager::new(&schema_provider, &node.context.store, &tx, local_peer_id);
+            let mut manager = ConnectionManager::new(
+                &schema_provider,
+                &node.context.store,
+                &node.context.status,
+                &node.context.metrics,
+                &tx,
+                local_peer_id,
+            );
             manager.update_announcement().await;
 
             let remote_peer = Peer::new(remote_peer_id, ConnectionId::new_unchecked(1));
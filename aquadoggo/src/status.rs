@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::ConnectionId;
+use libp2p::{Multiaddr, PeerId};
+use p2panda_rs::identity::PublicKey;
+use tokio::sync::Mutex;
+
+use crate::manager::ServiceStates;
+use crate::network::identity::to_libp2p_peer_id;
+use crate::network::Peer;
+use crate::replication::{Mode, SchemaIdSet, SessionId, SessionState};
+
+/// Transport protocol used for a connection to another peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionTransport {
+    /// Direct connection via QUIC.
+    Quic,
+
+    /// Direct connection via TCP.
+    Tcp,
+
+    /// Connection relayed via another peer.
+    Relay,
+
+    /// Any other transport.
+    Unknown,
+}
+
+impl ConnectionTransport {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ConnectionTransport::Quic => "quic",
+            ConnectionTransport::Tcp => "tcp",
+            ConnectionTransport::Relay => "relay",
+            ConnectionTransport::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<&Multiaddr> for ConnectionTransport {
+    fn from(address: &Multiaddr) -> Self {
+        // Relayed addresses contain the transport to the relay as well, so we check them first
+        if address
+            .iter()
+            .any(|protocol| matches!(protocol, Protocol::P2pCircuit))
+        {
+            return ConnectionTransport::Relay;
+        }
+
+        for protocol in address.iter() {
+            match protocol {
+                Protocol::QuicV1 | Protocol::Quic => return ConnectionTransport::Quic,
+                Protocol::Tcp(_) => return ConnectionTransport::Tcp,
+                _ => continue,
+            }
+        }
+
+        ConnectionTransport::Unknown
+    }
+}
+
+/// Connection to another peer, established by the network service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionStatus {
+    /// Id of the remote peer.
+    pub peer_id: PeerId,
+
+    /// Id of this connection.
+    pub connection_id: ConnectionId,
+
+    /// Address of the remote peer.
+    pub address: Multiaddr,
+
+    /// Transport protocol of this connection.
+    pub transport: ConnectionTransport,
+}
+
+/// Replication session with another peer, managed by the replication service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionStatus {
+    /// Remote peer we're replicating with.
+    pub peer: Peer,
+
+    /// Id of this session.
+    pub session_id: SessionId,
+
+    /// Replication mode of this session.
+    pub mode: Mode,
+
+    /// Set of schema ids which get replicated in this session.
+    pub target_set: SchemaIdSet,
+
+    /// Current state of this session.
+    pub state: SessionState,
+
+    /// Flag indicating if we've initiated this session.
+    pub local: bool,
+
+    /// Flag indicating if the session is in live mode.
+    pub live_mode: bool,
+}
+
+#[derive(Debug, Default)]
+struct NodeStatusInner {
+    connections: HashMap<ConnectionId, ConnectionStatus>,
+    sessions: Vec<SessionStatus>,
+}
+
+/// Status of the running node, shared across services.
+///
+/// Services report what they are currently doing here, for example the network service keeps
+/// track of all open connections, so it can be inspected via the node API.
+#[derive(Clone, Debug)]
+pub struct NodeStatus {
+    /// Our own local peer id.
+    local_peer_id: PeerId,
+
+    /// Public key of this node.
+    public_key: PublicKey,
+
+    /// States of all services of this node, reported by the service manager.
+    services: ServiceStates,
+
+    /// Flag indicating if a migration of schemas or data from a lock file is currently running.
+    migration_running: Arc<AtomicBool>,
+
+    inner: Arc<Mutex<NodeStatusInner>>,
+}
+
+impl NodeStatus {
+    /// Returns a new instance of `NodeStatus` for the node identified by this public key.
+    pub fn new(public_key: &PublicKey) -> Self {
+        Self {
+            local_peer_id: to_libp2p_peer_id(public_key),
+            public_key: public_key.to_owned(),
+            services: ServiceStates::default(),
+            migration_running: Arc::new(AtomicBool::new(false)),
+            inner: Arc::new(Mutex::new(NodeStatusInner::default())),
+        }
+    }
+
+    /// Returns our own local peer id.
+    pub fn local_peer_id(&self) -> PeerId {
+        self.local_peer_id
+    }
+
+    /// Returns the public key of this node.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Returns the states of all services of this node.
+    pub fn services(&self) -> &ServiceStates {
+        &self.services
+    }
+
+    /// Sets the flag indicating if a migration from a lock file is currently running.
+    pub fn set_migration_running(&self, running: bool) {
+        self.migration_running.store(running, Ordering::SeqCst);
+    }
+
+    /// Returns true if a migration from a lock file is currently running.
+    pub fn is_migration_running(&self) -> bool {
+        self.migration_running.load(Ordering::SeqCst)
+    }
+
+    /// Registers a newly established connection to another peer.
+    pub async fn add_connection(
+        &self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        address: &Multiaddr,
+    ) {
+        let connection = ConnectionStatus {
+            peer_id,
+            connection_id,
+            address: address.to_owned(),
+            transport: address.into(),
+        };
+
+        self.inner
+            .lock()
+            .await
+            .connections
+            .insert(connection_id, connection);
+    }
+
+    /// Removes a closed connection.
+    pub async fn remove_connection(&self, connection_id: &ConnectionId) {
+        self.inner.lock().await.connections.remove(connection_id);
+    }
+
+    /// Returns all currently open connections to other peers.
+    pub async fn connections(&self) -> Vec<ConnectionStatus> {
+        let mut connections: Vec<ConnectionStatus> = self
+            .inner
+            .lock()
+            .await
+            .connections
+            .values()
+            .cloned()
+            .collect();
+        connections.sort_by_key(|connection| connection.connection_id);
+        connections
+    }
+
+    /// Replaces the list of currently active replication sessions.
+    pub async fn set_sessions(&self, sessions: Vec<SessionStatus>) {
+        self.inner.lock().await.sessions = sessions;
+    }
+
+    /// Returns all currently active replication sessions.
+    pub async fn sessions(&self) -> Vec<SessionStatus> {
+        self.inner.lock().await.sessions.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::swarm::ConnectionId;
+    use libp2p::{Multiaddr, PeerId};
+    use p2panda_rs::identity::KeyPair;
+    use rstest::rstest;
+
+    use super::{ConnectionTransport, NodeStatus};
+
+    #[rstest]
+    #[case("/ip4/127.0.0.1/udp/2022/quic-v1", ConnectionTransport::Quic)]
+    #[case("/ip4/127.0.0.1/tcp/2022", ConnectionTransport::Tcp)]
+    #[case(
+        "/ip4/127.0.0.1/udp/2022/quic-v1/p2p/12D3KooWJbr5Cj6qcyxXf5GRQymnb8ay3GHtmgSJcwGBZEWcbmsq/p2p-circuit",
+        ConnectionTransport::Relay
+    )]
+    #[case("/ip4/127.0.0.1", ConnectionTransport::Unknown)]
+    fn transport_from_address(#[case] address: &str, #[case] expected: ConnectionTransport) {
+        let address: Multiaddr = address.parse().unwrap();
+        assert_eq!(ConnectionTransport::from(&address), expected);
+    }
+
+    #[tokio::test]
+    async fn track_connections() {
+        let status = NodeStatus::new(&KeyPair::new().public_key());
+        let peer_id = PeerId::random();
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/2022".parse().unwrap();
+
+        status
+            .add_connection(peer_id, ConnectionId::new_unchecked(1), &address)
+            .await;
+        status
+            .add_connection(peer_id, ConnectionId::new_unchecked(2), &address)
+            .await;
+        assert_eq!(status.connections().await.len(), 2);
+
+        status
+            .remove_connection(&ConnectionId::new_unchecked(1))
+            .await;
+        let connections = status.connections().await;
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].peer_id, peer_id);
+        assert_eq!(connections[0].connection_id, ConnectionId::new_unchecked(2));
+        assert_eq!(connections[0].transport, ConnectionTransport::Tcp);
+    }
+}
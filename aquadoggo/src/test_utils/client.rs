@@ -4,17 +4,16 @@ use std::convert::TryFrom;
 use std::net::{SocketAddr, TcpListener};
 use std::time::Duration;
 
-use axum::body::HttpBody;
-use axum::BoxError;
+use axum::Router;
 use http::header::{HeaderName, HeaderValue};
-use http::{HeaderMap, Request, StatusCode};
-use hyper::{Body, Server};
+use http::{HeaderMap, StatusCode};
+use hyper::Server;
 use tokio::sync::broadcast;
-use tower::make::Shared;
-use tower_service::Service;
 
+use crate::config::{AllowList, ApiToken};
 use crate::graphql::GraphQLSchemaManager;
 use crate::http::{build_server, HttpServiceContext};
+use crate::limits::{ClientAddress, Limits};
 use crate::test_utils::TestNode;
 
 /// HTTP client for testing request and responses.
@@ -24,15 +23,7 @@ pub struct TestClient {
 }
 
 impl TestClient {
-    pub(crate) fn new<S, ResBody>(service: S) -> Self
-    where
-        S: Service<Request<Body>, Response = http::Response<ResBody>> + Clone + Send + 'static,
-        ResBody: HttpBody + Send + 'static,
-        ResBody::Data: Send,
-        ResBody::Error: Into<BoxError>,
-        S::Future: Send,
-        S::Error: Into<BoxError>,
-    {
+    pub(crate) fn new(router: Router) -> Self {
         // Setting the port to zero asks the operating system to find one for us
         let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind ephemeral socket");
         let addr = listener.local_addr().unwrap();
@@ -40,7 +31,7 @@ impl TestClient {
         tokio::spawn(async move {
             let server = Server::from_tcp(listener)
                 .unwrap()
-                .serve(Shared::new(service));
+                .serve(router.into_make_service_with_connect_info::<ClientAddress>());
             server.await.expect("server error");
         });
 
@@ -59,6 +50,12 @@ impl TestClient {
         }
     }
 
+    pub(crate) fn head(&self, url: &str) -> RequestBuilder {
+        RequestBuilder {
+            builder: self.client.head(format!("http://{}{}", self.addr, url)),
+        }
+    }
+
     pub(crate) fn post(&self, url: &str) -> RequestBuilder {
         RequestBuilder {
             builder: self.client.post(format!("http://{}{}", self.addr, url)),
@@ -68,12 +65,30 @@ impl TestClient {
 
 /// Configures a test client that can be used for HTTP API testing.
 pub async fn http_test_client(node: &TestNode) -> TestClient {
+    configured_http_test_client(
+        node,
+        node.context.config.api_tokens.clone(),
+        node.context.limits.clone(),
+        &node.context.config.http_allow_origins,
+    )
+    .await
+}
+
+/// Configures a test client for HTTP API testing with the given API tokens, limits and allowed
+/// CORS origins instead of the ones of the node.
+pub async fn configured_http_test_client(
+    node: &TestNode,
+    api_tokens: Vec<ApiToken>,
+    limits: Limits,
+    allow_origins: &AllowList<String>,
+) -> TestClient {
     let (tx, _) = broadcast::channel(120);
 
     let manager = GraphQLSchemaManager::new(
         node.context.store.clone(),
         tx,
         node.context.schema_provider.clone(),
+        node.context.status.clone(),
     )
     .await;
 
@@ -81,9 +96,13 @@ pub async fn http_test_client(node: &TestNode) -> TestClient {
         node.context.store.clone(),
         manager,
         node.context.config.blobs_base_path.to_path_buf(),
+        node.context.status.clone(),
+        node.context.metrics.clone(),
+        api_tokens,
+        limits,
     );
 
-    TestClient::new(build_server(http_context))
+    TestClient::new(build_server(http_context, allow_origins))
 }
 
 pub(crate) struct RequestBuilder {
@@ -7,7 +7,7 @@ pub mod helpers;
 mod node;
 mod runner;
 
-pub use client::{http_test_client, TestClient};
+pub use client::{configured_http_test_client, http_test_client, TestClient};
 pub use config::TestConfiguration;
 pub use db::{initialize_db, initialize_sqlite_db};
 pub use helpers::{doggo_fields, doggo_schema, generate_key_pairs, schema_from_fields};
@@ -66,7 +66,7 @@ impl TestNodeManager {
         let (_config, pool) = initialize_sqlite_db().await;
 
         // Initialise test store using pool.
-        let store = SqlStore::new(pool.clone());
+        let store = SqlStore::new(pool.clone(), config.query_cache_size);
 
         let schema_provider = SchemaProvider::new(vec![], config.allow_schema_ids.clone());
 
@@ -96,7 +96,7 @@ pub fn test_runner<F: AsyncTestFn + Send + Sync + 'static>(test: F) {
     runtime.block_on(async {
         // Initialise store
         let (_config, pool) = initialize_db().await;
-        let store = SqlStore::new(pool);
+        let store = SqlStore::new(pool, Configuration::default().query_cache_size);
 
         // Construct temporary blobs directory for the test runner
         let temp_dir = tempfile::TempDir::new()
@@ -1,9 +1,10 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use std::net::IpAddr;
 use std::path::PathBuf;
 
 use anyhow::{bail, Result};
-use aquadoggo::{AllowList, ConfigFile, Configuration};
+use aquadoggo::{AllowList, ConfigFile, Configuration, UncheckedApiToken};
 use clap::{crate_version, Parser};
 use colored::Colorize;
 use directories::ProjectDirs;
@@ -48,11 +49,31 @@ pub fn load_config() -> Result<(ConfigFilePath, ConfigFile)> {
         figment = figment.merge(Toml::file(path));
     }
 
-    let config = figment
+    let mut config: ConfigFile = figment
         .merge(Env::raw())
         .merge(Serialized::defaults(cli))
         .extract()?;
 
+    // Add api tokens from an optional separate file, this allows keeping secrets out of the main
+    // config file. Relative paths are resolved from the folder of the config file
+    if let Some(path) = config.api_tokens_path.take() {
+        let path = match config_file_path
+            .as_ref()
+            .and_then(|config_path| config_path.parent())
+        {
+            Some(config_dir) if path.is_relative() => config_dir.join(path),
+            _ => path,
+        };
+
+        if !path.exists() {
+            bail!("API tokens file '{}' does not exist", path.display());
+        }
+
+        let api_tokens: Vec<UncheckedApiToken> =
+            Figment::from(Toml::file(&path)).extract_inner("api_tokens")?;
+        config.api_tokens.extend(api_tokens);
+    }
+
     Ok((config_file_path, config))
 }
 
@@ -112,6 +133,45 @@ struct Cli {
     #[serde(skip_serializing_if = "Option::is_none")]
     http_port: Option<u16>,
 
+    /// IP address the HTTP server binds to, for example "::" to listen on IPv6 interfaces.
+    /// Defaults to "0.0.0.0".
+    #[arg(long, value_name = "IP_ADDRESS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_bind_address: Option<IpAddr>,
+
+    /// Fail when the HTTP port is already taken instead of falling back to a random, free port.
+    /// Disabled by default.
+    #[arg(
+        long,
+        value_name = "BOOL",
+        default_missing_value = "true",
+        num_args = 0..=1,
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_strict_port: Option<bool>,
+
+    /// List of origins which are allowed to make cross-origin requests to the HTTP API, for
+    /// example "https://example.com". Separate multiple values with a whitespace. Defaults to
+    /// allow requests from _any_ origin ("*").
+    #[arg(long, value_name = "ORIGIN", num_args = 0..)]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_with_wildcard"
+    )]
+    http_allow_origins: Option<Vec<String>>,
+
+    /// Path to PEM file containing the certificate chain to serve the HTTP API via TLS. Needs to be
+    /// set together with the private key path.
+    #[arg(long, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_tls_certificate_path: Option<PathBuf>,
+
+    /// Path to PEM file containing the private key to serve the HTTP API via TLS. Needs to be set
+    /// together with the certificate path.
+    #[arg(long, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_tls_key_path: Option<PathBuf>,
+
     /// Protocol (TCP/QUIC) used for node-node communication and data replication. Defaults to QUIC.
     #[arg(short = 'q', long, value_name = "TRANSPORT")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -397,6 +457,18 @@ pub fn print_config(
         "disabled"
     };
 
+    let http_tls = if config.http_tls.is_some() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+
+    let api_authentication = if config.api_tokens.is_empty() {
+        "disabled".to_string()
+    } else {
+        format!("enabled ({} tokens)", config.api_tokens.len())
+    };
+
     format!(
         r"Allow schema IDs: {}
 Database URL: {}
@@ -404,6 +476,8 @@ mDNS: {}
 Private key: {}
 Relay mode: {}
 Private Net: {}
+HTTP TLS: {}
+API authentication: {}
 
 Node is ready!
 ",
@@ -412,6 +486,8 @@ Node is ready!
         mdns.blue(),
         private_key.blue(),
         relay_mode.blue(),
-        pnet.blue()
+        pnet.blue(),
+        http_tls.blue(),
+        api_authentication.blue()
     )
 }